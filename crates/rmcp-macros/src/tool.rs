@@ -3,28 +3,53 @@ use std::collections::HashSet;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use syn::{
-    Expr, FnArg, Ident, ItemFn, ItemImpl, MetaList, PatType, Token, Type, Visibility, parse::Parse,
-    parse_quote,
+    Attribute, Expr, FnArg, Ident, ItemFn, ItemImpl, MetaList, PatType, ReturnType, Token, Type,
+    Visibility, parse::Parse, parse_quote,
 };
 
 #[derive(Default)]
 struct ToolImplItemAttrs {
     tool_box: Option<Option<Ident>>,
+    before_call: Vec<Expr>,
+    order: Option<Expr>,
+    schema_draft: Option<Expr>,
+    inline_subschemas: Option<Expr>,
 }
 
 impl Parse for ToolImplItemAttrs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut tool_box = None;
+        let mut before_call = Vec::new();
+        let mut order = None;
+        let mut schema_draft = None;
+        let mut inline_subschemas = None;
         while !input.is_empty() {
             let key: Ident = input.parse()?;
             match key.to_string().as_str() {
                 "tool_box" => {
                     tool_box = Some(None);
                     if input.lookahead1().peek(Token![=]) {
+                        input.parse::<Token![=]>()?;
                         let value: Ident = input.parse()?;
                         tool_box = Some(Some(value));
                     }
                 }
+                "before_call" => {
+                    input.parse::<Token![=]>()?;
+                    before_call.push(input.parse()?);
+                }
+                "order" => {
+                    input.parse::<Token![=]>()?;
+                    order = Some(input.parse()?);
+                }
+                "schema_draft" => {
+                    input.parse::<Token![=]>()?;
+                    schema_draft = Some(input.parse()?);
+                }
+                "inline_subschemas" => {
+                    input.parse::<Token![=]>()?;
+                    inline_subschemas = Some(input.parse()?);
+                }
                 _ => {
                     return Err(syn::Error::new(key.span(), "unknown attribute"));
                 }
@@ -35,30 +60,95 @@ impl Parse for ToolImplItemAttrs {
             input.parse::<Token![,]>()?;
         }
 
-        Ok(ToolImplItemAttrs { tool_box })
+        Ok(ToolImplItemAttrs {
+            tool_box,
+            before_call,
+            order,
+            schema_draft,
+            inline_subschemas,
+        })
     }
 }
 
 #[derive(Default)]
 struct ToolFnItemAttrs {
     name: Option<Expr>,
+    title: Option<Expr>,
     description: Option<Expr>,
     vis: Option<Visibility>,
+    strict: bool,
+    annotations: Option<ToolAnnotationsAttrs>,
+}
+
+/// The hints of `#[tool(annotations(idempotent_hint = true, ..))]`, named like the fields of `ToolAnnotations`
+struct ToolAnnotationsAttrs {
+    fields: Vec<(Ident, Expr)>,
+}
+
+impl Parse for ToolAnnotationsAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        const FIELDS: [&str; 5] = [
+            "title",
+            "read_only_hint",
+            "destructive_hint",
+            "idempotent_hint",
+            "open_world_hint",
+        ];
+        let mut fields = Vec::new();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if !FIELDS.contains(&key.to_string().as_str()) {
+                return Err(syn::Error::new(key.span(), "unknown annotation"));
+            }
+            input.parse::<Token![=]>()?;
+            fields.push((key, input.parse()?));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(ToolAnnotationsAttrs { fields })
+    }
 }
 
 impl Parse for ToolFnItemAttrs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut name = None;
+        let mut title = None;
         let mut description = None;
         let mut vis = None;
+        let mut strict = false;
+        let mut annotations = None;
         while !input.is_empty() {
             let key: Ident = input.parse()?;
+            if key == "annotations" {
+                let content;
+                syn::parenthesized!(content in input);
+                annotations = Some(content.parse()?);
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+                continue;
+            }
+            if key == "strict" {
+                strict = true;
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+                continue;
+            }
             input.parse::<Token![=]>()?;
             match key.to_string().as_str() {
                 "name" => {
                     let value: Expr = input.parse()?;
                     name = Some(value);
                 }
+                "title" => {
+                    let value: Expr = input.parse()?;
+                    title = Some(value);
+                }
                 "description" => {
                     let value: Expr = input.parse()?;
                     description = Some(value);
@@ -79,8 +169,11 @@ impl Parse for ToolFnItemAttrs {
 
         Ok(ToolFnItemAttrs {
             name,
+            title,
             description,
             vis,
+            strict,
+            annotations,
         })
     }
 }
@@ -88,6 +181,7 @@ impl Parse for ToolFnItemAttrs {
 struct ToolFnParamAttrs {
     serde_meta: Vec<MetaList>,
     schemars_meta: Vec<MetaList>,
+    doc_attrs: Vec<Attribute>,
     ident: Ident,
     rust_type: Box<Type>,
 }
@@ -98,7 +192,9 @@ impl ToTokens for ToolFnParamAttrs {
         let rust_type = &self.rust_type;
         let serde_meta = &self.serde_meta;
         let schemars_meta = &self.schemars_meta;
+        let doc_attrs = &self.doc_attrs;
         tokens.extend(quote! {
+            #(#doc_attrs)*
             #(#[#serde_meta])*
             #(#[#schemars_meta])*
             pub #ident: #rust_type,
@@ -130,6 +226,7 @@ const SCHEMARS_IDENT: &str = "schemars";
 const PARAM_IDENT: &str = "param";
 const AGGREGATED_IDENT: &str = "aggr";
 const REQ_IDENT: &str = "req";
+const DOC_IDENT: &str = "doc";
 
 pub enum ParamMarker {
     Param,
@@ -177,6 +274,33 @@ pub(crate) fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenSt
 pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Result<TokenStream> {
     let tool_impl_attr: ToolImplItemAttrs = syn::parse2(attr)?;
     let tool_box_ident = tool_impl_attr.tool_box;
+    let before_call = tool_impl_attr.before_call;
+    let order = tool_impl_attr.order;
+    let schema_draft = tool_impl_attr.schema_draft;
+    let inline_subschemas = tool_impl_attr.inline_subschemas;
+    let misplaced = input.trait_.is_some() || tool_box_ident.is_none();
+    if let Some(hook) = before_call.first().filter(|_| misplaced) {
+        return Err(syn::Error::new_spanned(
+            hook,
+            "before_call should be set with tool_box on the impl block of the tools",
+        ));
+    }
+    if let Some(order) = order.as_ref().filter(|_| misplaced) {
+        return Err(syn::Error::new_spanned(
+            order,
+            "order should be set with tool_box on the impl block of the tools",
+        ));
+    }
+    if let Some(schema_option) = schema_draft
+        .as_ref()
+        .or(inline_subschemas.as_ref())
+        .filter(|_| misplaced)
+    {
+        return Err(syn::Error::new_spanned(
+            schema_option,
+            "schema options should be set with tool_box on the impl block of the tools",
+        ));
+    }
     if input.trait_.is_some() {
         if let Some(ident) = tool_box_ident {
             input.items.push(parse_quote!(
@@ -184,6 +308,7 @@ pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Res
             ));
         }
     } else if let Some(ident) = tool_box_ident {
+        let ident: Ident = ident.unwrap_or_else(|| parse_quote!(tool_box));
         let mut tool_fn_idents = Vec::new();
         for item in &input.items {
             if let syn::ImplItem::Fn(method) = item {
@@ -195,10 +320,13 @@ pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Res
             }
         }
         let this_type_ident = &input.self_ty;
+        let order = order.iter();
+        let schema_draft = schema_draft.iter();
+        let inline_subschemas = inline_subschemas.iter();
         input.items.push(parse_quote!(
             rmcp::tool_box!(#this_type_ident {
                 #(#tool_fn_idents),*
-            } #ident);
+            } #ident #(, before_call = #before_call)* #(, order = #order)* #(, schema_draft = #schema_draft)* #(, inline_subschemas = #inline_subschemas)*);
         ));
     }
     Ok(quote! {
@@ -225,6 +353,8 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
             FnArg::Typed(pat_type) => {
                 let mut serde_metas = Vec::new();
                 let mut schemars_metas = Vec::new();
+                // moved to the field of the param, where schemars reads the description
+                let mut doc_attrs = Vec::new();
                 let mut arg_ident = match pat_type.pat.as_ref() {
                     syn::Pat::Ident(pat_ident) => Some(pat_ident.ident.clone()),
                     _ => None,
@@ -247,6 +377,7 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                                         catched.replace(Catched::Param(ToolFnParamAttrs {
                                             serde_meta: Vec::new(),
                                             schemars_meta: Vec::new(),
+                                            doc_attrs: Vec::new(),
                                             ident: arg_ident,
                                             rust_type: pat_type.ty.clone(),
                                         }));
@@ -263,6 +394,9 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                                 pat_type.attrs.push(attr);
                             }
                         }
+                        _ if attr.path().is_ident(DOC_IDENT) => {
+                            doc_attrs.push(attr);
+                        }
                         _ => {
                             pat_type.attrs.push(attr);
                         }
@@ -272,6 +406,7 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                     Some(Catched::Param(mut param)) => {
                         param.serde_meta = serde_metas;
                         param.schemars_meta = schemars_metas;
+                        param.doc_attrs = doc_attrs;
                         match &mut tool_macro_attrs.params {
                             ToolParams::Params { attrs } => {
                                 attrs.push(param);
@@ -305,21 +440,31 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
         &format!("{}_tool_attr", input_fn.sig.ident),
         proc_macro2::Span::call_site(),
     );
+    let tool_attr_with_options_fn_ident = Ident::new(
+        &format!("{}_tool_attr_with_options", input_fn.sig.ident),
+        proc_macro2::Span::call_site(),
+    );
 
     // generate get tool attr function
     let tool_attr_fn = {
         let description = if let Some(expr) = tool_macro_attrs.fn_item.description {
             expr
         } else {
+            let doc = doc_comment(&input_fn.attrs);
             parse_quote! {
-                ""
+                #doc
             }
         };
+        let title = if let Some(expr) = tool_macro_attrs.fn_item.title {
+            quote! { Some(#expr.into()) }
+        } else {
+            quote! { None }
+        };
         let schema = match &tool_macro_attrs.params {
             ToolParams::Aggregated { rust_type } => {
                 let ty = &rust_type.ty;
                 let schema = quote! {
-                    rmcp::handler::server::tool::cached_schema_for_type::<#ty>()
+                    rmcp::handler::server::tool::cached_schema_for_type_with_options::<#ty>(schema_options)
                 };
                 schema
             }
@@ -329,27 +474,58 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                 let schema = quote! {
                     {
                         #param_type
-                        rmcp::handler::server::tool::cached_schema_for_type::<#temp_param_type_name>()
+                        rmcp::handler::server::tool::cached_schema_for_type_with_options::<#temp_param_type_name>(schema_options)
                     }
                 };
                 schema
             }
             ToolParams::NoParam => {
                 quote! {
-                    rmcp::handler::server::tool::cached_schema_for_type::<rmcp::model::EmptyObject>()
+                    rmcp::handler::server::tool::cached_schema_for_type_with_options::<rmcp::model::EmptyObject>(schema_options)
                 }
             }
         };
+        let output_schema = match structured_output_type(&input_fn.sig.output) {
+            Some(ty) => quote! {
+                Some(rmcp::handler::server::tool::cached_schema_for_type_with_options::<#ty>(schema_options))
+            },
+            None => quote! { None },
+        };
+        let annotations = match &tool_macro_attrs.fn_item.annotations {
+            Some(ToolAnnotationsAttrs { fields }) => {
+                let (keys, values): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+                quote! {
+                    Some(rmcp::model::ToolAnnotations {
+                        #(#keys: Some(#values.into()),)*
+                        ..Default::default()
+                    })
+                }
+            }
+            None => quote! { None },
+        };
         let input_fn_attrs = &input_fn.attrs;
         let input_fn_vis = &input_fn.vis;
+        let tool = quote! {
+            rmcp::model::Tool {
+                name: #name.into(),
+                title: #title,
+                description: #description.into(),
+                input_schema: #schema.into(),
+                output_schema: #output_schema,
+                annotations: #annotations,
+            }
+        };
         quote! {
             #(#input_fn_attrs)*
             #input_fn_vis fn #tool_attr_fn_ident() -> rmcp::model::Tool {
-                rmcp::model::Tool {
-                    name: #name.into(),
-                    description: #description.into(),
-                    input_schema: #schema.into(),
-                }
+                let schema_options = rmcp::handler::server::tool::SchemaOptions::default();
+                #tool
+            }
+            #(#input_fn_attrs)*
+            #input_fn_vis fn #tool_attr_with_options_fn_ident(
+                schema_options: rmcp::handler::server::tool::SchemaOptions,
+            ) -> rmcp::model::Tool {
+                #tool
             }
         }
     };
@@ -357,22 +533,22 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
     // generate wrapped tool function
     let tool_call_fn = {
         // wrapper function have the same sig:
-        // async fn #tool_tool_call(context: rmcp::handler::server::tool::ToolCallContext<'_, Self>)
+        // async fn #tool_tool_call(__rmcp_tool_context: rmcp::handler::server::tool::ToolCallContext<'_, Self>)
         //      -> std::result::Result<rmcp::model::CallToolResult, rmcp::Error>
         //
         // and the block part shoule be like:
         // {
         //      use rmcp::handler::server::tool::*;
-        //      let (t0, context) = <T0>::from_tool_call_context_part(context)?;
-        //      let (t1, context) = <T1>::from_tool_call_context_part(context)?;
+        //      let (t0, __rmcp_tool_context) = <T0>::from_tool_call_context_part(__rmcp_tool_context)?;
+        //      let (t1, __rmcp_tool_context) = <T1>::from_tool_call_context_part(__rmcp_tool_context)?;
         //      ...
-        //      let (tn, context) = <Tn>::from_tool_call_context_part(context)?;
+        //      let (tn, __rmcp_tool_context) = <Tn>::from_tool_call_context_part(__rmcp_tool_context)?;
         //      // for params
         //      ... expand helper types here
-        //      let (__rmcp_tool_req, context) = rmcp::model::JsonObject::from_tool_call_context_part(context)?;
+        //      let (__rmcp_tool_req, __rmcp_tool_context) = rmcp::model::JsonObject::from_tool_call_context_part(__rmcp_tool_context)?;
         //      let __#TOOL_ToolCallParam { param_0, param_1, param_2, .. } = parse_json_object(__rmcp_tool_req)?;
         //      // for aggr
-        //      let (Parameters(aggr), context) = <Parameters<AggrType>>::from_tool_call_context_part(context)?;
+        //      let (Parameters(aggr), __rmcp_tool_context) = <Parameters<AggrType>>::from_tool_call_context_part(__rmcp_tool_context)?;
         //      Self::#tool_ident(to, param_0, t1, param_1, ..., param_2, tn, aggr).await.into_call_tool_result()
         //
         // }
@@ -399,14 +575,14 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                             let pat = &pat_type.pat;
                             let ty = &pat_type.ty;
                             quote! {
-                                let (#pat, context) = <#ty>::from_tool_call_context_part(context)?;
+                                let (#pat, __rmcp_tool_context) = <#ty>::from_tool_call_context_part(__rmcp_tool_context)?;
                             }
                         }
                         FnArg::Receiver(r) => {
                             let ty = r.ty.clone();
                             let pat = receiver_ident();
                             quote! {
-                                let  (#pat, context) = <#ty>::from_tool_call_context_part(context)?;
+                                let  (#pat, __rmcp_tool_context) = <#ty>::from_tool_call_context_part(__rmcp_tool_context)?;
                             }
                         }
                     };
@@ -416,11 +592,24 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
         let trival_argrextraction_part = quote! {
             #(#trival_args)*
         };
+        // reject the unknown arguments before the extraction
+        let strict = tool_macro_attrs.fn_item.strict;
+        let deny_unknown_arguments = |ty: &dyn ToTokens| {
+            if strict {
+                quote! {
+                    __rmcp_tool_context.deny_unknown_arguments::<#ty>()?;
+                }
+            } else {
+                quote! {}
+            }
+        };
         let processed_argrextraction_part = match &mut tool_macro_attrs.params {
             ToolParams::Aggregated { rust_type } => {
                 let PatType { pat, ty, .. } = rust_type;
+                let deny_unknown_arguments = deny_unknown_arguments(ty);
                 quote! {
-                    let (Parameters(#pat), context) = <Parameters<#ty>>::from_tool_call_context_part(context)?;
+                    #deny_unknown_arguments
+                    let (Parameters(#pat), __rmcp_tool_context) = <Parameters<#ty>>::from_tool_call_context_part(__rmcp_tool_context)?;
                 }
             }
             ToolParams::Params { attrs } => {
@@ -428,17 +617,17 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                     create_request_type(attrs, input_fn.sig.ident.to_string());
 
                 let params_ident = attrs.iter().map(|attr| &attr.ident).collect::<Vec<_>>();
+                let deny_unknown_arguments = deny_unknown_arguments(&temp_param_type_name);
                 quote! {
                     #param_type
-                    let (__rmcp_tool_req, context) = rmcp::model::JsonObject::from_tool_call_context_part(context)?;
+                    #deny_unknown_arguments
+                    let (__rmcp_tool_req, __rmcp_tool_context) = rmcp::model::JsonObject::from_tool_call_context_part(__rmcp_tool_context)?;
                     let #temp_param_type_name {
                         #(#params_ident,)*
                     } = parse_json_object(__rmcp_tool_req)?;
                 }
             }
-            ToolParams::NoParam => {
-                quote! {}
-            }
+            ToolParams::NoParam => deny_unknown_arguments(&quote! { rmcp::model::EmptyObject }),
         };
         // generate the execution part
         // has reveiver?
@@ -484,7 +673,7 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
             .collect::<Vec<_>>();
         quote! {
             #(#raw_fn_attr)*
-            #raw_fn_vis async fn #tool_call_fn_ident(context: rmcp::handler::server::tool::ToolCallContext<'_, Self>)
+            #raw_fn_vis async fn #tool_call_fn_ident(__rmcp_tool_context: rmcp::handler::server::tool::ToolCallContext<'_, Self>)
                 -> std::result::Result<rmcp::model::CallToolResult, rmcp::Error> {
                 use rmcp::handler::server::tool::*;
                 #trival_argrextraction_part
@@ -500,6 +689,52 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
     })
 }
 
+/// Get `T` from a return type of `Structured<T>` or `Result<Structured<T>, E>`
+fn structured_output_type(output: &ReturnType) -> Option<&Type> {
+    fn generic_argument<'a>(ty: &'a Type, ident: &str) -> Option<&'a Type> {
+        let Type::Path(type_path) = ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last()?;
+        if segment.ident != ident {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return None;
+        };
+        match arguments.args.first()? {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }
+    }
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let ty = generic_argument(ty, "Result").unwrap_or(ty);
+    generic_argument(ty, "Structured")
+}
+
+/// The text of the `///` comments, without the space after the slashes
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(DOC_IDENT))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(line),
+                        ..
+                    }),
+                ..
+            }) => Some(line.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect::<Vec<_>>();
+    lines.join("\n").trim().to_owned()
+}
+
 fn create_request_type(attrs: &[ToolFnParamAttrs], tool_name: String) -> (TokenStream, Ident) {
     let pascal_case_tool_name = tool_name.to_ascii_uppercase();
     let temp_param_type_name = Ident::new(
@@ -0,0 +1,60 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rmcp::model::{
+    Base64Alphabet, Content, ResourceContents, decode_base64, encode_base64, validate_base64,
+};
+
+const SIZES: [usize; 3] = [4 << 10, 256 << 10, 4 << 20];
+
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base64");
+    for size in SIZES {
+        let data = noise(size);
+        let encoded = encode_base64(&data, Base64Alphabet::Standard);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("encode", size), &data, |b, data| {
+            b.iter(|| encode_base64(data, Base64Alphabet::Standard))
+        });
+        group.bench_with_input(BenchmarkId::new("decode", size), &encoded, |b, encoded| {
+            b.iter(|| decode_base64(encoded).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("validate", size),
+            &encoded,
+            |b, encoded| b.iter(|| validate_base64(encoded).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn content(c: &mut Criterion) {
+    let mut group = c.benchmark_group("blob_content");
+    for size in SIZES {
+        let content = Content::resource(ResourceContents::blob(noise(size), "file:///noise"));
+        let json = serde_json::to_string(&content).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(
+            BenchmarkId::new("serialize", size),
+            &content,
+            |b, content| b.iter(|| serde_json::to_string(content).unwrap()),
+        );
+        group.bench_with_input(BenchmarkId::new("deserialize", size), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<Content>(json).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, codec, content);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use futures::{SinkExt, StreamExt};
+use rmcp::{
+    model::{
+        CallToolResult, Content, JsonRpcResponse, JsonRpcVersion2_0, NumberOrString,
+        ServerJsonRpcMessage, ServerResult,
+    },
+    transport::io::{
+        RawMessage, from_async_read, from_async_write, raw_from_async_read, raw_from_async_write,
+    },
+};
+
+const SIZES: [usize; 3] = [1 << 10, 64 << 10, 1 << 20];
+
+/// The newline delimited messages of a server, each a tool result with `size` bytes of text
+fn messages(size: usize, count: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for id in 0..count {
+        let message = ServerJsonRpcMessage::Response(JsonRpcResponse {
+            jsonrpc: JsonRpcVersion2_0,
+            id: NumberOrString::Number(id as i64),
+            result: ServerResult::CallToolResult(CallToolResult::success(vec![Content::text(
+                "lorem ipsum ".repeat(size / 12),
+            )])),
+        });
+        serde_json::to_writer(&mut bytes, &message).unwrap();
+        bytes.push(b'\n');
+    }
+    bytes
+}
+
+/// Forward the messages read from `input` to a buffer, deserializing and serializing them
+async fn relay_typed(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut sink = from_async_write::<ServerJsonRpcMessage, _>(&mut output);
+    let mut stream = std::pin::pin!(from_async_read::<ServerJsonRpcMessage, _>(input));
+    while let Some(message) = stream.next().await {
+        sink.send(message).await.unwrap();
+    }
+    drop(sink);
+    output
+}
+
+/// Forward the messages read from `input` to a buffer verbatim
+async fn relay_raw(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut sink = raw_from_async_write(&mut output);
+    let mut stream = std::pin::pin!(raw_from_async_read(input));
+    while let Some(message) = stream.next().await {
+        sink.send(message).await.unwrap();
+    }
+    drop(sink);
+    output
+}
+
+fn relay(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("relay");
+    for size in SIZES {
+        let input = messages(size, 16);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("typed", size), &input, |b, input| {
+            b.iter(|| runtime.block_on(relay_typed(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("raw", size), &input, |b, input| {
+            b.iter(|| runtime.block_on(relay_raw(input)))
+        });
+    }
+    group.finish();
+}
+
+fn validate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("raw_message");
+    for size in SIZES {
+        let input = messages(size, 1);
+        let line = &input[..input.len() - 1];
+        group.throughput(Throughput::Bytes(line.len() as u64));
+        group.bench_with_input(BenchmarkId::new("validate", size), line, |b, line| {
+            b.iter(|| RawMessage::new(line.to_vec()).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("parse", size), line, |b, line| {
+            b.iter(|| serde_json::from_slice::<ServerJsonRpcMessage>(line).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, relay, validate);
+criterion_main!(benches);
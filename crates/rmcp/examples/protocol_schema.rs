@@ -0,0 +1,6 @@
+//! Print the JSON Schema of the protocol, see `rmcp::model::protocol_schema`
+fn main() -> serde_json::Result<()> {
+    let schema = rmcp::model::protocol_schema();
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! Delays between the attempts of an operation, e.g. to reconnect a transport or retry a request
+use std::{
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// An endless iterator of exponentially growing delays, with jitter.
+///
+/// The `n`th delay is `base * 2^n`, capped at `max`. A jitter `j` in `0.0..=1.0` scales
+/// each delay by a random factor in `1 - j..=1`, so that clients failing together don't retry together.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use rmcp::backoff::ExponentialBackoff;
+/// let mut backoff = ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(10))
+///     .with_jitter(0.0);
+/// assert_eq!(backoff.next(), Some(Duration::from_millis(100)));
+/// assert_eq!(backoff.next(), Some(Duration::from_millis(200)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    base: Duration,
+    max: Duration,
+    jitter: f64,
+    attempt: u32,
+    rng: u64,
+}
+
+impl Default for ExponentialBackoff {
+    /// From 100ms up to 30s, with a jitter of 0.5
+    fn default() -> Self {
+        Self::new(Duration::from_millis(100), Duration::from_secs(30))
+    }
+}
+
+impl ExponentialBackoff {
+    /// The jitter is 0.5 by default
+    pub fn new(base: Duration, max: Duration) -> Self {
+        let seed = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        Self {
+            base,
+            max,
+            jitter: 0.5,
+            attempt: 0,
+            rng: 0,
+        }
+        .with_seed(seed)
+    }
+
+    /// Set the jitter, clamped to `0.0..=1.0`
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = if jitter.is_nan() {
+            0.0
+        } else {
+            jitter.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Seed the jitter, to get the same delays every time, e.g. in tests
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // xorshift gets stuck at 0
+        self.rng = seed | 1;
+        self
+    }
+
+    pub fn base(&self) -> Duration {
+        self.base
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// Start again from the base delay, e.g. after a successful attempt
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// A uniform sample in `0.0..1.0`, from xorshift64*
+    fn sample(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let bits = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+        bits as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for ExponentialBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = self
+            .base
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+        if self.jitter == 0.0 {
+            return Some(delay);
+        }
+        let factor = 1.0 - self.jitter * self.sample();
+        Some(delay.mul_f64(factor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_jitter() {
+        let delays = ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(1))
+            .with_jitter(0.0)
+            .take(6)
+            .collect::<Vec<_>>();
+        let expected = [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis);
+        assert_eq!(delays, expected);
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        let mut backoff = ExponentialBackoff::new(Duration::MAX / 4, Duration::MAX)
+            .with_jitter(0.0)
+            .skip(200);
+        assert_eq!(backoff.next(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut backoff =
+            ExponentialBackoff::new(Duration::from_millis(10), Duration::from_secs(1))
+                .with_jitter(0.0);
+        backoff.nth(3);
+        backoff.reset();
+        assert_eq!(backoff.next(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let backoff = ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(5))
+            .with_jitter(0.3);
+        let capped = ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(5))
+            .with_jitter(0.0);
+        for (delay, capped) in backoff.zip(capped).take(64) {
+            assert!(delay <= capped, "{delay:?} > {capped:?}");
+            assert!(delay >= capped.mul_f64(0.7), "{delay:?} < 0.7 * {capped:?}");
+        }
+    }
+
+    #[test]
+    fn test_jitter_distribution() {
+        const SAMPLES: usize = 10_000;
+        let backoff =
+            ExponentialBackoff::new(Duration::from_secs(1), Duration::from_secs(1)).with_seed(42);
+        let factors = backoff
+            .take(SAMPLES)
+            .map(|delay| delay.as_secs_f64())
+            .collect::<Vec<_>>();
+        // uniform in 0.5..=1.0
+        let mean = factors.iter().sum::<f64>() / SAMPLES as f64;
+        assert!((mean - 0.75).abs() < 0.01, "mean {mean}");
+        let mut buckets = [0usize; 5];
+        for factor in &factors {
+            let bucket = ((factor - 0.5) * 10.0) as usize;
+            buckets[bucket.min(4)] += 1;
+        }
+        for count in buckets {
+            assert!(
+                count.abs_diff(SAMPLES / 5) < SAMPLES / 50,
+                "buckets {buckets:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_seeded_delays_repeat() {
+        let backoff = || {
+            ExponentialBackoff::new(Duration::from_millis(10), Duration::from_secs(1)).with_seed(7)
+        };
+        assert!(backoff().take(10).eq(backoff().take(10)));
+        let unseeded = || ExponentialBackoff::new(Duration::from_secs(1), Duration::from_secs(1));
+        assert_ne!(unseeded().next(), unseeded().next());
+    }
+}
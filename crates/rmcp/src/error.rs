@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::model::ErrorData;
+use crate::model::{ErrorCode, ErrorData};
 
 pub type Error = ErrorData;
 
@@ -15,3 +15,54 @@ impl Display for ErrorData {
 }
 
 impl std::error::Error for ErrorData {}
+
+/// The request was cancelled by the peer, e.g. while a tool waited with `ToolContext::sleep`.
+///
+/// Return it with `?` from a handler, it's converted into an error with the code [`Cancelled::CODE`],
+/// even when it's wrapped in an `anyhow::Error`. The peer doesn't await the response anymore,
+/// so it only tells the logs and the middlewares why the request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, thiserror::Error)]
+#[error("request cancelled")]
+pub struct Cancelled;
+
+impl Cancelled {
+    /// The code of `RequestCancelled` in the language server protocol, as MCP doesn't define one
+    pub const CODE: ErrorCode = ErrorCode(-32800);
+}
+
+impl From<Cancelled> for ErrorData {
+    fn from(cancelled: Cancelled) -> Self {
+        ErrorData::new(Cancelled::CODE, cancelled.to_string(), None)
+    }
+}
+
+/// An internal error whose message is the ones of the error and its sources, joined with `: `.
+///
+/// It may carry paths or queries, return an [`ErrorSanitizer`](crate::handler::server::sanitize::ErrorSanitizer)
+/// from the server to redact it.
+fn internal_error(error: &(dyn std::error::Error + 'static)) -> ErrorData {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+    ErrorData::internal_error(message, None)
+}
+
+impl From<std::io::Error> for ErrorData {
+    fn from(error: std::io::Error) -> Self {
+        internal_error(&error)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for ErrorData {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<Cancelled>() {
+            Some(cancelled) => (*cancelled).into(),
+            None => internal_error(error.as_ref()),
+        }
+    }
+}
@@ -2,6 +2,19 @@ use crate::error::Error as McpError;
 use crate::model::*;
 use crate::service::{Peer, RequestContext, RoleClient, Service, ServiceRole};
 
+pub mod limit;
+pub mod logging;
+pub mod refetch;
+pub mod roots;
+#[cfg(feature = "experimental")]
+pub mod sampling;
+pub use limit::{LimitPolicy, RequestLimit};
+pub use logging::LoggingClientHandler;
+pub use refetch::ResourceRefetcher;
+pub use roots::Roots;
+#[cfg(feature = "experimental")]
+pub use sampling::PartialMessageStream;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct ClientHandlerService<H = Option<Peer<RoleClient>>> {
     pub handler: H,
@@ -27,20 +40,44 @@ impl<H: ClientHandler> Service for ClientHandlerService<H> {
         request: <Self::Role as ServiceRole>::PeerReq,
         context: RequestContext<Self::Role>,
     ) -> Result<<Self::Role as ServiceRole>::Resp, McpError> {
+        // held until the response is ready
+        let _permit = match (&request, self.handler.request_limit()) {
+            (ServerRequest::PingRequest(_), _) | (_, None) => None,
+            (_, Some(limit)) => Some(limit.acquire(&context.ct).await?),
+        };
         match request {
             ServerRequest::PingRequest(_) => {
                 self.handler.ping(context).await.map(ClientResult::empty)
             }
-            ServerRequest::CreateMessageRequest(request) => self
-                .handler
-                .create_message(request.params, context)
-                .await
-                .map(ClientResult::CreateMessageResult),
-            ServerRequest::ListRootsRequest(_) => self
-                .handler
-                .list_roots(context)
-                .await
-                .map(ClientResult::ListRootsResult),
+            ServerRequest::CreateMessageRequest(request) => {
+                if self.handler.can_create_message() {
+                    request.params.validate()?;
+                }
+                self.handler
+                    .create_message(request.params, context)
+                    .await
+                    .map(ClientResult::CreateMessageResult)
+            }
+            ServerRequest::ListRootsRequest(_) => match self.handler.roots() {
+                Some(roots) => Ok(ClientResult::ListRootsResult(roots.list())),
+                None => {
+                    let result = self.handler.list_roots(context).await?;
+                    // the server's request was fine, the roots of this client are wrong
+                    result
+                        .validate()
+                        .map_err(|error| McpError::internal_error(error.message, error.data))?;
+                    Ok(ClientResult::ListRootsResult(result))
+                }
+            },
+            ServerRequest::Unknown(request) => {
+                if let Some(error) = ServerRequest::invalid_params_error(&request) {
+                    return Err(McpError::invalid_params(
+                        format!("invalid params of {}: {error}", request.method),
+                        None,
+                    ));
+                }
+                self.handler.unknown_request(request, context).await
+            }
         }
     }
 
@@ -59,7 +96,12 @@ impl<H: ClientHandler> Service for ClientHandlerService<H> {
                 self.handler.on_logging_message(notification.params).await
             }
             ServerNotification::ResourceUpdatedNotification(notification) => {
-                self.handler.on_resource_updated(notification.params).await
+                let refetched = self.handler.resource_refetcher().is_some_and(|refetcher| {
+                    refetcher.on_resource_updated(&notification.params.uri)
+                });
+                if !refetched {
+                    self.handler.on_resource_updated(notification.params).await
+                }
             }
             ServerNotification::ResourceListChangedNotification(_notification_no_param) => {
                 self.handler.on_resource_list_changed().await
@@ -70,6 +112,9 @@ impl<H: ClientHandler> Service for ClientHandlerService<H> {
             ServerNotification::PromptListChangedNotification(_notification_no_param) => {
                 self.handler.on_prompt_list_changed().await
             }
+            ServerNotification::Unknown(notification) => {
+                self.handler.on_unknown_notification(notification).await
+            }
         };
         Ok(())
     }
@@ -89,6 +134,8 @@ impl<H: ClientHandler> Service for ClientHandlerService<H> {
 
 #[allow(unused_variables)]
 pub trait ClientHandler: Sized + Send + Sync + 'static {
+    /// The serve loop answers the pings itself, so it's only called when
+    /// [`Service::handle_request`](crate::Service::handle_request) is called directly
     fn ping(
         &self,
         context: RequestContext<RoleClient>,
@@ -96,6 +143,13 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
         std::future::ready(Ok(()))
     }
 
+    /// Implement it with [`ClientHandler::create_message`]: the `sampling` capability is only
+    /// advertised if so
+    fn can_create_message(&self) -> bool {
+        false
+    }
+    /// Answer `sampling/createMessage`, e.g. with a LLM. The params are checked with
+    /// [`CreateMessageRequestParam::validate`] before it's called.
     fn create_message(
         &self,
         params: CreateMessageRequestParam,
@@ -105,12 +159,43 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
             McpError::method_not_found::<CreateMessageRequestMethod>(),
         ))
     }
+    /// Answer `roots/list`, the roots are checked with [`ListRootsResult::validate`]. After a change,
+    /// tell the server with [`Peer::notify_roots_list_changed`].
+    ///
+    /// Override [`ClientHandler::can_list_roots`] too, so that the `roots` capability is advertised.
     fn list_roots(
         &self,
         context: RequestContext<RoleClient>,
     ) -> impl Future<Output = Result<ListRootsResult, McpError>> + Send + '_ {
         std::future::ready(Ok(ListRootsResult::default()))
     }
+    /// Answer `roots/list` with them instead of calling [`ClientHandler::list_roots`], the `roots`
+    /// capability is advertised if there are some, see [`Roots`]
+    fn roots(&self) -> Option<&Roots> {
+        None
+    }
+    /// Implement it with [`ClientHandler::roots`] or [`ClientHandler::list_roots`]: the `roots` capability
+    /// is only advertised if so. By default it's whether [`ClientHandler::roots`] returns some.
+    fn can_list_roots(&self) -> bool {
+        self.roots().is_some()
+    }
+    /// Handle a request with a method this crate doesn't know, e.g. to pass it through in a proxy
+    fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        context: RequestContext<RoleClient>,
+    ) -> impl Future<Output = Result<ClientResult, McpError>> + Send + '_ {
+        std::future::ready(Err(McpError::new(
+            ErrorCode::METHOD_NOT_FOUND,
+            request.method,
+            None,
+        )))
+    }
+
+    /// Cap the number of requests of the server handled at the same time, see [`RequestLimit`]
+    fn request_limit(&self) -> Option<&RequestLimit> {
+        None
+    }
 
     fn on_cancelled(
         &self,
@@ -136,6 +221,11 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Re-read the resources subscribed with it when they're updated, instead of calling
+    /// [`ClientHandler::on_resource_updated`], see [`ResourceRefetcher`]
+    fn resource_refetcher(&self) -> Option<&ResourceRefetcher> {
+        None
+    }
     fn on_resource_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
@@ -145,13 +235,36 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     fn on_prompt_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Called for the notifications with a method this crate doesn't know
+    fn on_unknown_notification(
+        &self,
+        notification: UnknownNotification,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        tracing::debug!(method = notification.method, "unknown notification");
+        std::future::ready(())
+    }
 
     fn get_peer(&self) -> Option<Peer<RoleClient>>;
 
     fn set_peer(&mut self, peer: Peer<RoleClient>);
 
+    /// The capabilities of what the handler can answer, see [`ClientHandler::can_create_message`]
+    /// and [`ClientHandler::can_list_roots`]
+    fn capabilities(&self) -> ClientCapabilities {
+        ClientCapabilities {
+            roots: self.can_list_roots().then_some(RootsCapabilities {
+                list_changed: Some(true),
+            }),
+            sampling: self.can_create_message().then(JsonObject::default),
+            ..Default::default()
+        }
+    }
+
     fn get_info(&self) -> ClientInfo {
-        ClientInfo::default()
+        ClientInfo {
+            capabilities: self.capabilities(),
+            ..Default::default()
+        }
     }
 }
 
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+use crate::model::{ErrorCode, ErrorData};
+
+/// What happens to a request arriving while the limit is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitPolicy {
+    /// Wait for a running request to finish, in the order of arrival
+    #[default]
+    Queue,
+    /// Answer with a [`RequestLimit::BUSY`] error at once
+    Reject,
+}
+
+/// Cap the number of requests of the server the client handles at the same time, e.g. `sampling/createMessage`.
+///
+/// Return it from [`ClientHandler::request_limit`](super::ClientHandler::request_limit) to enable it. The pings
+/// and the notifications don't count, a ping is answered even when the client is busy.
+///
+/// ```rust,ignore
+/// struct Client {
+///     peer: Option<Peer<RoleClient>>,
+///     limit: RequestLimit,
+/// }
+///
+/// impl ClientHandler for Client {
+///     fn request_limit(&self) -> Option<&RequestLimit> {
+///         Some(&self.limit)
+///     }
+///     // get_peer, set_peer...
+/// }
+///
+/// let limit = RequestLimit::new(4, LimitPolicy::Reject);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestLimit {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    policy: LimitPolicy,
+}
+
+impl RequestLimit {
+    /// The code of the error sent when a request is rejected, in the range of the server errors of json-rpc
+    pub const BUSY: ErrorCode = ErrorCode(-32000);
+
+    /// Handle at most `max` requests at once, it panics if `max` is 0
+    pub fn new(max: usize, policy: LimitPolicy) -> Self {
+        assert!(max > 0, "the request limit must be positive");
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+            max,
+            policy,
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn policy(&self) -> LimitPolicy {
+        self.policy
+    }
+
+    /// The number of requests being handled, without the queued ones
+    pub fn in_flight(&self) -> usize {
+        self.max - self.semaphore.available_permits()
+    }
+
+    /// A permit to handle a request, held until its response is ready.
+    ///
+    /// A queued request leaves the queue with an error when it's cancelled.
+    pub(crate) async fn acquire(
+        &self,
+        ct: &CancellationToken,
+    ) -> Result<OwnedSemaphorePermit, ErrorData> {
+        let permit = match self.policy {
+            LimitPolicy::Reject => self.semaphore.clone().try_acquire_owned().ok(),
+            LimitPolicy::Queue => tokio::select! {
+                permit = self.semaphore.clone().acquire_owned() => permit.ok(),
+                _ = ct.cancelled() => {
+                    return Err(ErrorData::new(Self::BUSY, "request cancelled while queued", None));
+                }
+            },
+        };
+        permit.ok_or_else(|| {
+            tracing::warn!(
+                max = self.max,
+                "request rejected, too many requests in flight"
+            );
+            ErrorData::new(
+                Self::BUSY,
+                format!("client busy, {} requests in flight", self.max),
+                None,
+            )
+        })
+    }
+}
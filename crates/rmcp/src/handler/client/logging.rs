@@ -0,0 +1,92 @@
+use crate::{
+    model::{
+        CancelledNotificationParam, LoggingLevel, LoggingMessageNotificationParam,
+        ProgressNotificationParam, ResourceUpdatedNotificationParam, UnknownNotification,
+    },
+    service::{Peer, RoleClient},
+};
+
+use super::ClientHandler;
+
+/// A client handler which logs every notification of the server with `tracing`, and answers no request.
+///
+/// The log messages of the server are logged at their level, `notice` at `INFO` and from `critical` to
+/// `emergency` at `ERROR`, the other notifications at `INFO`, or `DEBUG` for the cancellations and the unknown ones.
+///
+/// ```rust,ignore
+/// let client = serve_client(ClientHandlerService::new(LoggingClientHandler::default()), transport).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LoggingClientHandler {
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl ClientHandler for LoggingClientHandler {
+    async fn on_cancelled(&self, params: CancelledNotificationParam) {
+        tracing::debug!(request_id = %params.request_id, reason = params.reason, "server cancelled a request");
+    }
+
+    async fn on_progress(&self, params: ProgressNotificationParam) {
+        tracing::info!(
+            progress_token = ?params.progress_token,
+            progress = params.progress,
+            total = params.total,
+            description = params.message,
+            "server progress"
+        );
+    }
+
+    async fn on_logging_message(&self, params: LoggingMessageNotificationParam) {
+        let logger = params.logger.as_deref();
+        let data = &params.data;
+        macro_rules! log {
+            ($macro: ident) => {
+                match params.text_data() {
+                    Some(text) => tracing::$macro!(logger, "{text}"),
+                    None => tracing::$macro!(logger, %data, "server log"),
+                }
+            };
+        }
+        match params.level {
+            LoggingLevel::Debug => log!(debug),
+            LoggingLevel::Info | LoggingLevel::Notice => log!(info),
+            LoggingLevel::Warning => log!(warn),
+            LoggingLevel::Error
+            | LoggingLevel::Critical
+            | LoggingLevel::Alert
+            | LoggingLevel::Emergency => log!(error),
+        }
+    }
+
+    async fn on_resource_updated(&self, params: ResourceUpdatedNotificationParam) {
+        tracing::info!(uri = params.uri, "server resource updated");
+    }
+
+    async fn on_resource_list_changed(&self) {
+        tracing::info!("server resource list changed");
+    }
+
+    async fn on_tool_list_changed(&self) {
+        tracing::info!("server tool list changed");
+    }
+
+    async fn on_prompt_list_changed(&self) {
+        tracing::info!("server prompt list changed");
+    }
+
+    async fn on_unknown_notification(&self, notification: UnknownNotification) {
+        tracing::debug!(
+            method = notification.method,
+            params = ?notification.params,
+            "server unknown notification"
+        );
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, SubscribeRequestParam,
+        UnsubscribeRequestParam,
+    },
+    service::{Peer, RoleClient, ServiceError},
+};
+
+type RefetchCallback = dyn Fn(Result<ReadResourceResult, ServiceError>) + Send + Sync;
+
+/// Re-read the subscribed resources when the server says they're updated.
+///
+/// Only the resources subscribed with [`ResourceRefetcher::subscribe`] are re-read, those subscribed
+/// with [`Peer::subscribe`] are left to [`ClientHandler::on_resource_updated`](super::ClientHandler::on_resource_updated).
+/// Return it from [`ClientHandler::resource_refetcher`](super::ClientHandler::resource_refetcher) to enable it.
+///
+/// The updates are debounced: the resource is read once the debounce window has passed since the first update,
+/// all the updates arriving within the window are collapsed into this read. An update arriving while the resource
+/// is read opens a new window, so the last delivered result is never older than the last update.
+/// When the reads complete out of order, the result of a read superseded by a later one is dropped.
+///
+/// ```rust,ignore
+/// struct Client {
+///     peer: Option<Peer<RoleClient>>,
+///     refetcher: ResourceRefetcher,
+/// }
+///
+/// impl ClientHandler for Client {
+///     fn resource_refetcher(&self) -> Option<&ResourceRefetcher> {
+///         Some(&self.refetcher)
+///     }
+///     // get_peer, set_peer...
+/// }
+///
+/// refetcher.subscribe(client.peer(), "file:///log.txt", |result| println!("{result:?}")).await?;
+/// ```
+#[derive(Clone)]
+pub struct ResourceRefetcher {
+    debounce: Duration,
+    subscriptions: Arc<Mutex<HashMap<String, Subscription>>>,
+}
+
+struct Subscription {
+    /// Tells a subscription from a later one of the same uri, whose refetch is scheduled separately
+    id: u64,
+    peer: Peer<RoleClient>,
+    callback: Arc<RefetchCallback>,
+    pending: bool,
+    /// The number of the last read started, the result of an earlier one is stale
+    generation: u64,
+}
+
+impl std::fmt::Debug for ResourceRefetcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceRefetcher")
+            .field("debounce", &self.debounce)
+            .field(
+                "subscriptions",
+                &self.subscriptions().keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for ResourceRefetcher {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DEBOUNCE)
+    }
+}
+
+impl ResourceRefetcher {
+    pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            subscriptions: Default::default(),
+        }
+    }
+
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Subscribe to the resource, and deliver its contents to the callback each time it's updated
+    pub async fn subscribe<F>(
+        &self,
+        peer: &Peer<RoleClient>,
+        uri: impl Into<String>,
+        callback: F,
+    ) -> Result<(), ServiceError>
+    where
+        F: Fn(Result<ReadResourceResult, ServiceError>) + Send + Sync + 'static,
+    {
+        let uri = uri.into();
+        peer.subscribe(SubscribeRequestParam { uri: uri.clone() })
+            .await?;
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let subscription = Subscription {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            peer: peer.clone(),
+            callback: Arc::new(callback),
+            pending: false,
+            generation: 0,
+        };
+        self.subscriptions().insert(uri, subscription);
+        Ok(())
+    }
+
+    /// Stop re-reading the resource and unsubscribe from it, a pending refetch is dropped
+    pub async fn unsubscribe(&self, uri: &str) -> Result<(), ServiceError> {
+        let Some(subscription) = self.subscriptions().remove(uri) else {
+            return Ok(());
+        };
+        subscription
+            .peer
+            .unsubscribe(UnsubscribeRequestParam {
+                uri: uri.to_owned(),
+            })
+            .await
+    }
+
+    pub fn is_subscribed(&self, uri: &str) -> bool {
+        self.subscriptions().contains_key(uri)
+    }
+
+    /// Schedule the refetch of an updated resource, returns `false` if it's not subscribed here
+    pub fn on_resource_updated(&self, uri: &str) -> bool {
+        let id = {
+            let mut subscriptions = self.subscriptions();
+            let Some(subscription) = subscriptions.get_mut(uri) else {
+                return false;
+            };
+            if subscription.pending {
+                return true;
+            }
+            subscription.pending = true;
+            subscription.id
+        };
+        let subscriptions = self.subscriptions.clone();
+        let debounce = self.debounce;
+        let uri = uri.to_owned();
+        tokio::spawn(async move {
+            tokio::time::sleep(debounce).await;
+            let lock = || {
+                subscriptions
+                    .lock()
+                    .expect("resource refetcher lock poisoned")
+            };
+            let (peer, callback, generation) = {
+                let mut subscriptions = lock();
+                match subscriptions.get_mut(&uri) {
+                    Some(subscription) if subscription.id == id => {
+                        subscription.pending = false;
+                        subscription.generation += 1;
+                        (
+                            subscription.peer.clone(),
+                            subscription.callback.clone(),
+                            subscription.generation,
+                        )
+                    }
+                    _ => return,
+                }
+            };
+            let result = peer
+                .read_resource(ReadResourceRequestParam::new(uri.clone()))
+                .await;
+            let latest = lock().get(&uri).is_some_and(|subscription| {
+                subscription.id == id && subscription.generation == generation
+            });
+            if latest {
+                callback(result);
+            }
+        });
+        true
+    }
+
+    fn subscriptions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Subscription>> {
+        self.subscriptions
+            .lock()
+            .expect("resource refetcher lock poisoned")
+    }
+}
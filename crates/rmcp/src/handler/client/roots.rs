@@ -0,0 +1,69 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    model::{ListRootsResult, Root},
+    service::{Peer, RoleClient, ServiceError},
+};
+
+/// The roots the client exposes to the server.
+///
+/// Return it from [`ClientHandler::roots`](super::ClientHandler::roots) to answer `roots/list` with them
+/// and to advertise the `roots` capability, with `listChanged` since [`Roots::update`] notifies the server.
+///
+/// ```rust,ignore
+/// struct Client {
+///     peer: Option<Peer<RoleClient>>,
+///     roots: Roots,
+/// }
+///
+/// impl ClientHandler for Client {
+///     fn roots(&self) -> Option<&Roots> {
+///         Some(&self.roots)
+///     }
+///     // get_peer, set_peer...
+/// }
+///
+/// roots.update(client.peer(), [Root::new("file:///workspace")]).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Roots {
+    roots: Arc<RwLock<Vec<Root>>>,
+}
+
+impl Roots {
+    /// It fails if a root isn't a `file://` URL, see [`Root::validate`]
+    pub fn new(roots: impl IntoIterator<Item = Root>) -> Result<Self, crate::Error> {
+        let roots = ListRootsResult {
+            roots: roots.into_iter().collect(),
+            meta: None,
+        };
+        roots.validate()?;
+        Ok(Self {
+            roots: Arc::new(RwLock::new(roots.roots)),
+        })
+    }
+
+    pub fn list(&self) -> ListRootsResult {
+        ListRootsResult {
+            roots: self.roots.read().expect("roots lock poisoned").clone(),
+            meta: None,
+        }
+    }
+
+    /// Replace the roots, and tell the server with `notifications/roots/list_changed`.
+    ///
+    /// The roots are left unchanged if one isn't a `file://` URL.
+    pub async fn update(
+        &self,
+        peer: &Peer<RoleClient>,
+        roots: impl IntoIterator<Item = Root>,
+    ) -> Result<(), ServiceError> {
+        let roots = ListRootsResult {
+            roots: roots.into_iter().collect(),
+            meta: None,
+        };
+        roots.validate().map_err(ServiceError::McpError)?;
+        *self.roots.write().expect("roots lock poisoned") = roots.roots;
+        peer.notify_roots_list_changed().await
+    }
+}
@@ -0,0 +1,68 @@
+//! Stream the partial text of a sampling, a non-standard extension of `sampling/createMessage`.
+//!
+//! `sampling/createMessage` is a request and a response, so the server only sees the text once it's
+//! complete. With this extension, the server sets a progress token in the `_meta` of the request, and the
+//! client sends the text generated so far as the `message` of `notifications/progress` with this token.
+//! Both sides must opt in, a server unaware of it just sees progress notifications.
+use crate::{
+    model::{ClientNotification, CreateMessageRequestParam, ProgressTracker},
+    service::{Peer, RequestContext, RoleClient, ServiceError},
+};
+
+/// Send the partial text of a sampling while answering [`ClientHandler::create_message`](super::ClientHandler::create_message).
+///
+/// ```rust,ignore
+/// async fn create_message(&self, params: CreateMessageRequestParam, context: RequestContext<RoleClient>) -> Result<CreateMessageResult, McpError> {
+///     let mut partial = context.partial_message_stream(&params);
+///     while let Some(token) = llm.next_token().await {
+///         if let Some(partial) = &mut partial {
+///             partial.stream_partial(token).await?;
+///         }
+///     }
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PartialMessageStream {
+    peer: Peer<RoleClient>,
+    tracker: ProgressTracker,
+}
+
+impl PartialMessageStream {
+    /// `None` if the request has no progress token, the server doesn't expect partial text then
+    pub fn new(
+        params: &CreateMessageRequestParam,
+        context: &RequestContext<RoleClient>,
+    ) -> Option<Self> {
+        let meta = params.meta.as_ref()?;
+        Some(Self {
+            peer: context.peer.clone(),
+            tracker: ProgressTracker::new(meta.progress_token.clone()),
+        })
+    }
+
+    /// Send a chunk of text, the `progress` of the notification is the count of chunks sent so far.
+    ///
+    /// The server handles the notifications concurrently, so it orders the chunks by their `progress`.
+    pub async fn stream_partial(&mut self, text: impl Into<String>) -> Result<(), ServiceError> {
+        let progress = self.tracker.last().map_or(1, |last| last.saturating_add(1));
+        let mut notification = self
+            .tracker
+            .advance(progress, None)
+            .map_err(ServiceError::McpError)?;
+        notification.params.message = Some(text.into());
+        self.peer
+            .send_notification(ClientNotification::ProgressNotification(notification))
+            .await
+    }
+}
+
+impl RequestContext<RoleClient> {
+    /// See [`PartialMessageStream::new`]
+    pub fn partial_message_stream(
+        &self,
+        params: &CreateMessageRequestParam,
+    ) -> Option<PartialMessageStream> {
+        PartialMessageStream::new(params, self)
+    }
+}
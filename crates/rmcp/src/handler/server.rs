@@ -1,9 +1,15 @@
 use crate::error::Error as McpError;
 use crate::model::*;
 use crate::service::{Peer, RequestContext, RoleServer, Service, ServiceRole};
+use futures::future::BoxFuture;
 
+pub mod composite;
+pub mod idempotency;
+pub mod logging;
 mod resource;
+pub mod sanitize;
 pub mod tool;
+mod validation;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct ServerHandlerService<H> {
     pub handler: H,
@@ -13,16 +19,12 @@ impl<H: ServerHandler> ServerHandlerService<H> {
     pub fn new(handler: H) -> Self {
         Self { handler }
     }
-}
 
-impl<H: ServerHandler> Service for ServerHandlerService<H> {
-    type Role = RoleServer;
-
-    async fn handle_request(
+    async fn dispatch_request(
         &self,
-        request: <Self::Role as ServiceRole>::PeerReq,
-        context: RequestContext<Self::Role>,
-    ) -> Result<<Self::Role as ServiceRole>::Resp, McpError> {
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, McpError> {
         match request {
             ClientRequest::InitializeRequest(request) => self
                 .handler
@@ -37,11 +39,12 @@ impl<H: ServerHandler> Service for ServerHandlerService<H> {
                 .complete(request.params, context)
                 .await
                 .map(ServerResult::CompleteResult),
-            ClientRequest::SetLevelRequest(request) => self
-                .handler
-                .set_level(request.params, context)
-                .await
-                .map(ServerResult::empty),
+            ClientRequest::SetLevelRequest(request) => {
+                let level = request.params.level;
+                self.handler.set_level(request.params, context).await?;
+                self.handler.on_set_level(level).await;
+                Ok(ServerResult::empty(()))
+            }
             ClientRequest::GetPromptRequest(request) => self
                 .handler
                 .get_prompt(request.params, context)
@@ -77,16 +80,56 @@ impl<H: ServerHandler> Service for ServerHandlerService<H> {
                 .unsubscribe(request.params, context)
                 .await
                 .map(ServerResult::empty),
-            ClientRequest::CallToolRequest(request) => self
-                .handler
-                .call_tool(request.params, context)
-                .await
-                .map(ServerResult::CallToolResult),
-            ClientRequest::ListToolsRequest(request) => self
-                .handler
-                .list_tools(request.params, context)
-                .await
-                .map(ServerResult::ListToolsResult),
+            ClientRequest::CallToolRequest(request) => {
+                if !self.handler.filter_tool(&request.params.name, &context) {
+                    return Err(McpError::invalid_params("tool not found", None));
+                }
+                let result = self.handler.call_tool(request.params, context).await?;
+                if cfg!(debug_assertions)
+                    && let Err(error) = result.validate()
+                {
+                    tracing::warn!(%error, "suspicious tool call result");
+                }
+                Ok(ServerResult::CallToolResult(result))
+            }
+            ClientRequest::ListToolsRequest(request) => {
+                let mut result = self
+                    .handler
+                    .list_tools(request.params, context.clone())
+                    .await?;
+                result
+                    .tools
+                    .retain(|tool| self.handler.filter_tool(&tool.name, &context));
+                for tool in &mut result.tools {
+                    self.handler.post_process_tool(tool);
+                }
+                Ok(ServerResult::ListToolsResult(result))
+            }
+            ClientRequest::Unknown(request) => {
+                if let Some(error) = ClientRequest::invalid_params_error(&request) {
+                    return Err(McpError::invalid_params(
+                        format!("invalid params of {}: {error}", request.method),
+                        None,
+                    ));
+                }
+                self.handler.unknown_request(request, context).await
+            }
+        }
+    }
+}
+
+impl<H: ServerHandler> Service for ServerHandlerService<H> {
+    type Role = RoleServer;
+
+    async fn handle_request(
+        &self,
+        request: <Self::Role as ServiceRole>::PeerReq,
+        context: RequestContext<Self::Role>,
+    ) -> Result<<Self::Role as ServiceRole>::Resp, McpError> {
+        let result = self.dispatch_request(request, context).await;
+        match self.handler.error_sanitizer() {
+            Some(sanitizer) => result.map_err(|error| sanitizer.sanitize(error)),
+            None => result,
         }
     }
 
@@ -107,6 +150,9 @@ impl<H: ServerHandler> Service for ServerHandlerService<H> {
             ClientNotification::RootsListChangedNotification(_notification) => {
                 self.handler.on_roots_list_changed().await
             }
+            ClientNotification::Unknown(notification) => {
+                self.handler.on_unknown_notification(notification).await
+            }
         };
         Ok(())
     }
@@ -126,6 +172,8 @@ impl<H: ServerHandler> Service for ServerHandlerService<H> {
 
 #[allow(unused_variables)]
 pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
+    /// The serve loop answers the pings itself, so it's only called when
+    /// [`Service::handle_request`](crate::Service::handle_request) is called directly
     fn ping(
         &self,
         context: RequestContext<RoleServer>,
@@ -147,12 +195,18 @@ pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
     ) -> impl Future<Output = Result<CompleteResult, McpError>> + Send + '_ {
         std::future::ready(Err(McpError::method_not_found::<CompleteRequestMethod>()))
     }
+    /// Accept the level by default if the server advertises the `logging` capability,
+    /// the accepted level is then passed to [`ServerHandler::on_set_level`]
     fn set_level(
         &self,
         request: SetLevelRequestParam,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
-        std::future::ready(Err(McpError::method_not_found::<SetLevelRequestMethod>()))
+        if self.get_info().capabilities.logging.is_some() {
+            std::future::ready(Ok(()))
+        } else {
+            std::future::ready(Err(McpError::method_not_found::<SetLevelRequestMethod>()))
+        }
     }
     fn get_prompt(
         &self,
@@ -182,6 +236,8 @@ pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
     ) -> impl Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_ {
         std::future::ready(Ok(ListResourceTemplatesResult::default()))
     }
+    /// The result is a single response, so the whole resource is held in memory while it's sent.
+    /// With the `experimental` feature, a client reads a large resource in parts with `ReadResourceRequestParam::with_range`.
     fn read_resource(
         &self,
         request: ReadResourceRequestParam,
@@ -219,6 +275,32 @@ pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
     ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
         std::future::ready(Ok(ListToolsResult::default()))
     }
+    /// Hide tools from a client, e.g. the admin tools from an unauthenticated client, or the tools
+    /// using sampling from a client without the `sampling` capability.
+    ///
+    /// A tool is listed by `tools/list` only if this returns true for its name, and a call to a hidden tool
+    /// is rejected with `tool not found`, so it's never dispatched to [`ServerHandler::call_tool`]. The client
+    /// info is `context.peer.peer_info()`, and the state of the connection `context.session()`.
+    ///
+    /// A call only checks the called tool, the tools aren't listed for it.
+    fn filter_tool(&self, name: &str, context: &RequestContext<RoleServer>) -> bool {
+        true
+    }
+    /// Modify each tool before it's listed, after [`ServerHandler::filter_tool`], e.g. to add `examples`
+    /// to the generated input schema, or to tighten a type for a client.
+    fn post_process_tool(&self, tool: &mut Tool) {}
+    /// Handle a request with a method this crate doesn't know, e.g. to pass it through in a proxy
+    fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ServerResult, McpError>> + Send + '_ {
+        std::future::ready(Err(McpError::new(
+            ErrorCode::METHOD_NOT_FOUND,
+            request.method,
+            None,
+        )))
+    }
 
     fn on_cancelled(
         &self,
@@ -239,6 +321,19 @@ pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
     fn on_roots_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Called once a `logging/setLevel` is accepted by [`ServerHandler::set_level`], e.g. to reload the
+    /// tracing filter with a [`TracingLevelReload`](logging::TracingLevelReload)
+    fn on_set_level(&self, level: LoggingLevel) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
+    /// Called for the notifications with a method this crate doesn't know
+    fn on_unknown_notification(
+        &self,
+        notification: UnknownNotification,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        tracing::debug!(method = notification.method, "unknown notification");
+        std::future::ready(())
+    }
 
     fn get_peer(&self) -> Option<Peer<RoleServer>> {
         None
@@ -251,4 +346,385 @@ pub trait ServerHandler: Sized + Clone + Send + Sync + 'static {
     fn get_info(&self) -> ServerInfo {
         ServerInfo::default()
     }
+
+    /// The sanitizer applied to the errors of all the requests before they're sent to the client,
+    /// `None` sends them unchanged
+    fn error_sanitizer(&self) -> Option<&sanitize::ErrorSanitizer> {
+        None
+    }
+}
+
+/// An object safe version of [`ServerHandler`], implemented for all the server handlers.
+///
+/// It makes it possible to store different handlers in a collection, e.g. `Vec<Box<dyn DynServerHandler>>`.
+/// `Box<dyn DynServerHandler>` is a [`ServerHandler`] itself, so it can be served directly.
+pub trait DynServerHandler: Send + Sync + 'static {
+    fn ping(&self, context: RequestContext<RoleServer>) -> BoxFuture<'_, Result<(), McpError>>;
+    fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<InitializeResult, McpError>>;
+    fn complete(
+        &self,
+        request: CompleteRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<CompleteResult, McpError>>;
+    fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>>;
+    fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<GetPromptResult, McpError>>;
+    fn list_prompts(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListPromptsResult, McpError>>;
+    fn list_resources(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListResourcesResult, McpError>>;
+    fn list_resource_templates(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListResourceTemplatesResult, McpError>>;
+    fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ReadResourceResult, McpError>>;
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>>;
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>>;
+    fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<CallToolResult, McpError>>;
+    fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListToolsResult, McpError>>;
+    fn filter_tool(&self, name: &str, context: &RequestContext<RoleServer>) -> bool;
+    fn post_process_tool(&self, tool: &mut Tool);
+    fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ServerResult, McpError>>;
+    fn on_cancelled(&self, notification: CancelledNotificationParam) -> BoxFuture<'_, ()>;
+    fn on_progress(&self, notification: ProgressNotificationParam) -> BoxFuture<'_, ()>;
+    fn on_initialized(&self) -> BoxFuture<'_, ()>;
+    fn on_roots_list_changed(&self) -> BoxFuture<'_, ()>;
+    fn on_set_level(&self, level: LoggingLevel) -> BoxFuture<'_, ()>;
+    fn on_unknown_notification(&self, notification: UnknownNotification) -> BoxFuture<'_, ()>;
+    fn get_peer(&self) -> Option<Peer<RoleServer>>;
+    fn set_peer(&mut self, peer: Peer<RoleServer>);
+    fn get_info(&self) -> ServerInfo;
+    fn error_sanitizer(&self) -> Option<&sanitize::ErrorSanitizer>;
+    fn clone_box(&self) -> Box<dyn DynServerHandler>;
+}
+
+impl<H: ServerHandler> DynServerHandler for H {
+    fn ping(&self, context: RequestContext<RoleServer>) -> BoxFuture<'_, Result<(), McpError>> {
+        Box::pin(ServerHandler::ping(self, context))
+    }
+    fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<InitializeResult, McpError>> {
+        Box::pin(ServerHandler::initialize(self, request, context))
+    }
+    fn complete(
+        &self,
+        request: CompleteRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<CompleteResult, McpError>> {
+        Box::pin(ServerHandler::complete(self, request, context))
+    }
+    fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>> {
+        Box::pin(ServerHandler::set_level(self, request, context))
+    }
+    fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<GetPromptResult, McpError>> {
+        Box::pin(ServerHandler::get_prompt(self, request, context))
+    }
+    fn list_prompts(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListPromptsResult, McpError>> {
+        Box::pin(ServerHandler::list_prompts(self, request, context))
+    }
+    fn list_resources(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListResourcesResult, McpError>> {
+        Box::pin(ServerHandler::list_resources(self, request, context))
+    }
+    fn list_resource_templates(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListResourceTemplatesResult, McpError>> {
+        Box::pin(ServerHandler::list_resource_templates(
+            self, request, context,
+        ))
+    }
+    fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ReadResourceResult, McpError>> {
+        Box::pin(ServerHandler::read_resource(self, request, context))
+    }
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>> {
+        Box::pin(ServerHandler::subscribe(self, request, context))
+    }
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<(), McpError>> {
+        Box::pin(ServerHandler::unsubscribe(self, request, context))
+    }
+    fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<CallToolResult, McpError>> {
+        Box::pin(ServerHandler::call_tool(self, request, context))
+    }
+    fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ListToolsResult, McpError>> {
+        Box::pin(ServerHandler::list_tools(self, request, context))
+    }
+    fn filter_tool(&self, name: &str, context: &RequestContext<RoleServer>) -> bool {
+        ServerHandler::filter_tool(self, name, context)
+    }
+    fn post_process_tool(&self, tool: &mut Tool) {
+        ServerHandler::post_process_tool(self, tool)
+    }
+    fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        context: RequestContext<RoleServer>,
+    ) -> BoxFuture<'_, Result<ServerResult, McpError>> {
+        Box::pin(ServerHandler::unknown_request(self, request, context))
+    }
+    fn on_cancelled(&self, notification: CancelledNotificationParam) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_cancelled(self, notification))
+    }
+    fn on_progress(&self, notification: ProgressNotificationParam) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_progress(self, notification))
+    }
+    fn on_initialized(&self) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_initialized(self))
+    }
+    fn on_roots_list_changed(&self) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_roots_list_changed(self))
+    }
+    fn on_set_level(&self, level: LoggingLevel) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_set_level(self, level))
+    }
+    fn on_unknown_notification(&self, notification: UnknownNotification) -> BoxFuture<'_, ()> {
+        Box::pin(ServerHandler::on_unknown_notification(self, notification))
+    }
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        ServerHandler::get_peer(self)
+    }
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        ServerHandler::set_peer(self, peer)
+    }
+    fn get_info(&self) -> ServerInfo {
+        ServerHandler::get_info(self)
+    }
+    fn error_sanitizer(&self) -> Option<&sanitize::ErrorSanitizer> {
+        ServerHandler::error_sanitizer(self)
+    }
+    fn clone_box(&self) -> Box<dyn DynServerHandler> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn DynServerHandler> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone_box()
+    }
+}
+
+impl ServerHandler for Box<dyn DynServerHandler> {
+    fn ping(
+        &self,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        DynServerHandler::ping(self.as_ref(), context)
+    }
+    fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<InitializeResult, McpError>> + Send + '_ {
+        DynServerHandler::initialize(self.as_ref(), request, context)
+    }
+    fn complete(
+        &self,
+        request: CompleteRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<CompleteResult, McpError>> + Send + '_ {
+        DynServerHandler::complete(self.as_ref(), request, context)
+    }
+    fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        DynServerHandler::set_level(self.as_ref(), request, context)
+    }
+    fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<GetPromptResult, McpError>> + Send + '_ {
+        DynServerHandler::get_prompt(self.as_ref(), request, context)
+    }
+    fn list_prompts(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListPromptsResult, McpError>> + Send + '_ {
+        DynServerHandler::list_prompts(self.as_ref(), request, context)
+    }
+    fn list_resources(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListResourcesResult, McpError>> + Send + '_ {
+        DynServerHandler::list_resources(self.as_ref(), request, context)
+    }
+    fn list_resource_templates(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_ {
+        DynServerHandler::list_resource_templates(self.as_ref(), request, context)
+    }
+    fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ReadResourceResult, McpError>> + Send + '_ {
+        DynServerHandler::read_resource(self.as_ref(), request, context)
+    }
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        DynServerHandler::subscribe(self.as_ref(), request, context)
+    }
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        DynServerHandler::unsubscribe(self.as_ref(), request, context)
+    }
+    fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
+        DynServerHandler::call_tool(self.as_ref(), request, context)
+    }
+    fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+        DynServerHandler::list_tools(self.as_ref(), request, context)
+    }
+    fn filter_tool(&self, name: &str, context: &RequestContext<RoleServer>) -> bool {
+        DynServerHandler::filter_tool(self.as_ref(), name, context)
+    }
+    fn post_process_tool(&self, tool: &mut Tool) {
+        DynServerHandler::post_process_tool(self.as_ref(), tool)
+    }
+    fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ServerResult, McpError>> + Send + '_ {
+        DynServerHandler::unknown_request(self.as_ref(), request, context)
+    }
+    fn on_cancelled(
+        &self,
+        notification: CancelledNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_cancelled(self.as_ref(), notification)
+    }
+    fn on_progress(
+        &self,
+        notification: ProgressNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_progress(self.as_ref(), notification)
+    }
+    fn on_initialized(&self) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_initialized(self.as_ref())
+    }
+    fn on_roots_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_roots_list_changed(self.as_ref())
+    }
+    fn on_set_level(&self, level: LoggingLevel) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_set_level(self.as_ref(), level)
+    }
+    fn on_unknown_notification(
+        &self,
+        notification: UnknownNotification,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        DynServerHandler::on_unknown_notification(self.as_ref(), notification)
+    }
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        DynServerHandler::get_peer(self.as_ref())
+    }
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        DynServerHandler::set_peer(self.as_mut(), peer)
+    }
+    fn get_info(&self) -> ServerInfo {
+        DynServerHandler::get_info(self.as_ref())
+    }
+    fn error_sanitizer(&self) -> Option<&sanitize::ErrorSanitizer> {
+        DynServerHandler::error_sanitizer(self.as_ref())
+    }
 }
@@ -0,0 +1,265 @@
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use futures::future::BoxFuture;
+use thiserror::Error;
+
+use super::{
+    ServerHandler,
+    sanitize::ErrorSanitizer,
+    tool::{ExclusiveToolRouter, ToolBox, ToolCallContext},
+};
+use crate::{
+    model::{
+        CallToolRequestParam, CallToolResult, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo, Tool,
+    },
+    service::{RequestContext, RoleServer},
+};
+
+type DynToolCall = dyn Fn(
+        CallToolRequestParam,
+        RequestContext<RoleServer>,
+    ) -> BoxFuture<'static, Result<CallToolResult, crate::Error>>
+    + Send
+    + Sync;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("tool {name} is already provided by another tool box")]
+pub struct ToolNameCollision {
+    pub name: Cow<'static, str>,
+}
+
+/// A [`ServerHandler`] combining the tools of several tool boxes, which may belong to different services.
+///
+/// This makes it possible to build tool sets in separate modules and serve them together.
+/// The tool names must be unique across the tool boxes, a collision is reported when the tool box is added.
+/// To let tool boxes with the same tool names coexist, add them with a prefix.
+/// The tools taking `&mut self` are added with the [`ExclusiveToolRouter`] owning their state.
+///
+/// ```rust,ignore
+/// let handler = CompositeHandler::new()
+///     .with_tool_box(Calculator, Calculator::tool_box())?
+///     .with_tool_box(Counter::new(), Counter::tool_box())?
+///     // listed and called as `math.sum`
+///     .with_prefixed_tool_box("math", Math, Math::tool_box())?
+///     .with_exclusive_router(ExclusiveToolRouter::new(Notes::default(), Notes::tool_box()))?;
+/// ```
+#[derive(Clone)]
+pub struct CompositeHandler {
+    info: ServerInfo,
+    tools: Vec<Tool>,
+    routes: HashMap<Cow<'static, str>, Route>,
+    error_sanitizer: Option<ErrorSanitizer>,
+}
+
+/// The tool box of a listed tool, and the name of the tool in the tool box
+#[derive(Clone)]
+struct Route {
+    name: Cow<'static, str>,
+    call: Arc<DynToolCall>,
+}
+
+impl Default for CompositeHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompositeHandler {
+    pub fn new() -> Self {
+        Self {
+            info: ServerInfo {
+                capabilities: ServerCapabilities::builder().enable_tools().build(),
+                ..Default::default()
+            },
+            tools: Vec::new(),
+            routes: HashMap::new(),
+            error_sanitizer: None,
+        }
+    }
+
+    /// Set the info returned by [`ServerHandler::get_info`], the tools capability is enabled by default
+    pub fn with_info(mut self, info: ServerInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Sanitize the errors of the tool boxes before they're sent to the client
+    pub fn with_error_sanitizer(mut self, sanitizer: ErrorSanitizer) -> Self {
+        self.error_sanitizer = Some(sanitizer);
+        self
+    }
+
+    /// Add the tools of a tool box, called on the service.
+    ///
+    /// If a tool name is already taken, no tool of this tool box is added.
+    pub fn with_tool_box<S>(
+        self,
+        service: S,
+        tool_box: &'static ToolBox<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        self.add_tool_box(None, service, tool_box)
+    }
+
+    /// Like [`CompositeHandler::with_tool_box`], but the tools are listed as `{prefix}.{name}`.
+    ///
+    /// The prefix is stripped before the call is dispatched, so the tool box sees its own tool names.
+    pub fn with_prefixed_tool_box<S>(
+        self,
+        prefix: &str,
+        service: S,
+        tool_box: &'static ToolBox<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        self.add_tool_box(Some(prefix), service, tool_box)
+    }
+
+    /// Add the tools of an exclusive router, so the tools taking `&mut self` can be called.
+    ///
+    /// As with the router alone, the calls of its tools are serialized, the other tool boxes aren't blocked.
+    pub fn with_exclusive_router<S>(
+        self,
+        router: ExclusiveToolRouter<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        self.add_exclusive_router(None, router)
+    }
+
+    /// Like [`CompositeHandler::with_exclusive_router`], but the tools are listed as `{prefix}.{name}`
+    pub fn with_prefixed_exclusive_router<S>(
+        self,
+        prefix: &str,
+        router: ExclusiveToolRouter<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        self.add_exclusive_router(Some(prefix), router)
+    }
+
+    fn add_tool_box<S>(
+        self,
+        prefix: Option<&str>,
+        service: S,
+        tool_box: &'static ToolBox<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        let service = Arc::new(service);
+        let call: Arc<DynToolCall> = Arc::new(move |request, context| {
+            let service = service.clone();
+            Box::pin(async move {
+                let context = ToolCallContext::new(service.as_ref(), request, context);
+                tool_box.call(context).await
+            })
+        });
+        self.add_tools(prefix, tool_box.list(), call)
+    }
+
+    fn add_exclusive_router<S>(
+        self,
+        prefix: Option<&str>,
+        router: ExclusiveToolRouter<S>,
+    ) -> Result<Self, ToolNameCollision>
+    where
+        S: Send + Sync + 'static,
+    {
+        let tools = router.list();
+        let call: Arc<DynToolCall> = Arc::new(move |request, context| {
+            let router = router.clone();
+            Box::pin(async move { router.call(request, context).await })
+        });
+        self.add_tools(prefix, tools, call)
+    }
+
+    /// Route the tools to the call of their tool box, unless a tool name is already taken
+    fn add_tools(
+        mut self,
+        prefix: Option<&str>,
+        tools: Vec<Tool>,
+        call: Arc<DynToolCall>,
+    ) -> Result<Self, ToolNameCollision> {
+        let tools = tools
+            .into_iter()
+            .map(|mut tool| {
+                let name = tool.name.clone();
+                if let Some(prefix) = prefix {
+                    tool.name = format!("{prefix}.{name}").into();
+                }
+                (name, tool)
+            })
+            .collect::<Vec<_>>();
+        if let Some((_, tool)) = tools
+            .iter()
+            .find(|(_, tool)| self.routes.contains_key(&tool.name))
+        {
+            return Err(ToolNameCollision {
+                name: tool.name.clone(),
+            });
+        }
+        for (name, tool) in tools {
+            let route = Route {
+                name,
+                call: call.clone(),
+            };
+            self.routes.insert(tool.name.clone(), route);
+            self.tools.push(tool);
+        }
+        Ok(self)
+    }
+
+    pub fn list(&self) -> &[Tool] {
+        &self.tools
+    }
+}
+
+impl ServerHandler for CompositeHandler {
+    fn get_info(&self) -> ServerInfo {
+        self.info.clone()
+    }
+
+    fn error_sanitizer(&self) -> Option<&ErrorSanitizer> {
+        self.error_sanitizer.as_ref()
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, crate::Error> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: self.tools.clone(),
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        CallToolRequestParam {
+            name,
+            arguments,
+            meta,
+        }: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, crate::Error> {
+        let route = self
+            .routes
+            .get(&name)
+            .ok_or_else(|| crate::Error::invalid_params("tool not found", None))?;
+        let request = CallToolRequestParam {
+            name: route.name.clone(),
+            arguments,
+            meta,
+        };
+        (route.call)(request, context).await
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::watch;
+
+use crate::model::{CallToolResult, JsonObject};
+
+/// The key in the `_meta` of a tool call, a client sets it to the same string when it retries the call,
+/// see [`CallToolRequestParam::with_idempotency_key`](crate::model::CallToolRequestParam::with_idempotency_key).
+pub const IDEMPOTENCY_KEY_META: &str = "idempotencyKey";
+
+/// A cache of tool call results, used to make client retries of idempotent tools safe.
+///
+/// Results are keyed on the [`IDEMPOTENCY_KEY_META`] key of the call, within the session of the call:
+/// a duplicate is a call of the same session with the same key, tool name and arguments within the ttl.
+/// The calls without a key are never cached. Only tools annotated with `idempotentHint` are cached, see
+/// [`ToolBox::call_with_idempotency_cache`](crate::handler::server::tool::ToolBox::call_with_idempotency_cache).
+/// A duplicate of a call still running waits for its result instead of executing the tool again.
+///
+/// When the cache is full, the expired entries are evicted first, then the ones closest to expiration.
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+/// The id of the session, and the idempotency key
+type CacheKey = (u64, String);
+
+#[derive(Debug)]
+struct CacheEntry {
+    name: Cow<'static, str>,
+    arguments: Option<Arc<JsonObject>>,
+    result: CacheResult,
+    expires_at: Instant,
+}
+
+#[derive(Debug)]
+enum CacheResult {
+    /// The result is sent once the first call finishes, the sender is dropped if it fails
+    Running(watch::Receiver<Option<CallToolResult>>),
+    Done(CallToolResult),
+}
+
+/// What to do with a call, see [`IdempotencyCache::begin`]
+pub(crate) enum Begin<'a> {
+    Cached(CallToolResult),
+    /// A duplicate is running, wait for its result, and begin again if it fails
+    Wait(watch::Receiver<Option<CallToolResult>>),
+    /// Execute the tool, and complete the call with its result
+    Run(InFlight<'a>),
+}
+
+/// A call being executed, a failed or cancelled call is dropped without a result, so the duplicates run again
+pub(crate) struct InFlight<'a> {
+    cache: &'a IdempotencyCache,
+    key: CacheKey,
+    sender: Option<watch::Sender<Option<CallToolResult>>>,
+}
+
+impl InFlight<'_> {
+    pub(crate) fn complete(mut self, result: CallToolResult) {
+        let mut entries = self.cache.entries();
+        if let Some(entry) = entries.get_mut(&self.key)
+            && self.is_running(entry)
+        {
+            entry.result = CacheResult::Done(result.clone());
+        }
+        drop(entries);
+        if let Some(sender) = self.sender.take() {
+            sender.send_replace(Some(result));
+        }
+    }
+
+    /// If the entry is this call, and not a call registered after this one was evicted
+    fn is_running(&self, entry: &CacheEntry) -> bool {
+        match (&entry.result, &self.sender) {
+            (CacheResult::Running(receiver), Some(sender)) => {
+                receiver.same_channel(&sender.subscribe())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.sender.is_none() {
+            return;
+        }
+        let mut entries = self.cache.entries();
+        if entries
+            .get(&self.key)
+            .is_some_and(|entry| self.is_running(entry))
+        {
+            entries.remove(&self.key);
+        }
+    }
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TTL, Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl IdempotencyCache {
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of cached results, including the expired ones not evicted yet
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the expired results
+    pub fn evict_expired(&self) {
+        let now = Instant::now();
+        self.entries().retain(|_, entry| entry.expires_at > now);
+    }
+
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// Look up a call, and register it as running if it isn't a duplicate
+    pub(crate) fn begin(
+        &self,
+        key: CacheKey,
+        name: Cow<'static, str>,
+        arguments: Option<Arc<JsonObject>>,
+    ) -> Begin<'_> {
+        let now = Instant::now();
+        let mut entries = self.entries();
+        let duplicate = entries.get(&key).filter(|entry| {
+            entry.expires_at > now && entry.name == name && entry.arguments == arguments
+        });
+        match duplicate.map(|entry| &entry.result) {
+            Some(CacheResult::Done(result)) => return Begin::Cached(result.clone()),
+            Some(CacheResult::Running(receiver)) => return Begin::Wait(receiver.clone()),
+            None => {}
+        }
+        if self.capacity == 0 {
+            return Begin::Run(InFlight {
+                cache: self,
+                key,
+                sender: None,
+            });
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+            while entries.len() >= self.capacity {
+                let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone())
+                else {
+                    break;
+                };
+                entries.remove(&oldest);
+            }
+        }
+        let (sender, receiver) = watch::channel(None);
+        entries.insert(
+            key.clone(),
+            CacheEntry {
+                name,
+                arguments,
+                result: CacheResult::Running(receiver),
+                expires_at: now + self.ttl,
+            },
+        );
+        Begin::Run(InFlight {
+            cache: self,
+            key,
+            sender: Some(sender),
+        })
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, CacheEntry>> {
+        self.entries
+            .lock()
+            .expect("idempotency cache lock poisoned")
+    }
+}
@@ -0,0 +1,124 @@
+//! Map the logging levels of MCP to `tracing`, e.g. to change the tracing filter on `logging/setLevel`
+use tracing::{Level, level_filters::LevelFilter};
+
+use crate::model::LoggingLevel;
+
+/// The tracing level of a logging level.
+///
+/// Tracing has no `notice` level, nor any level above `error`: `notice` is `INFO`, and from `critical`
+/// to `emergency` are `ERROR`.
+pub fn tracing_level(level: LoggingLevel) -> Level {
+    match level {
+        LoggingLevel::Debug => Level::DEBUG,
+        LoggingLevel::Info | LoggingLevel::Notice => Level::INFO,
+        LoggingLevel::Warning => Level::WARN,
+        LoggingLevel::Error
+        | LoggingLevel::Critical
+        | LoggingLevel::Alert
+        | LoggingLevel::Emergency => Level::ERROR,
+    }
+}
+
+/// The filter enabling the events of a logging level and above
+pub fn level_filter(level: LoggingLevel) -> LevelFilter {
+    LevelFilter::from_level(tracing_level(level))
+}
+
+/// Reload a tracing filter with the level the client sets.
+///
+/// The filter is a [`reload::Layer`](tracing_subscriber::reload::Layer) of the subscriber,
+/// of a [`LevelFilter`], or of any filter built from one:
+///
+/// ```rust,ignore
+/// let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+/// tracing_subscriber::registry().with(filter).with(fmt::layer()).init();
+/// let reload = TracingLevelReload::new(handle);
+///
+/// impl ServerHandler for Server {
+///     async fn on_set_level(&self, level: LoggingLevel) {
+///         if let Err(error) = self.reload.reload(level) {
+///             tracing::warn!(%error, "fail to reload the tracing filter");
+///         }
+///     }
+///     // get_info advertises the logging capability...
+/// }
+/// ```
+#[cfg(feature = "tracing-subscriber")]
+pub struct TracingLevelReload<L, S> {
+    handle: tracing_subscriber::reload::Handle<L, S>,
+}
+
+#[cfg(feature = "tracing-subscriber")]
+impl<L, S> Clone for TracingLevelReload<L, S> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing-subscriber")]
+impl<L, S> std::fmt::Debug for TracingLevelReload<L, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TracingLevelReload").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tracing-subscriber")]
+impl<L, S> TracingLevelReload<L, S>
+where
+    LevelFilter: Into<L>,
+{
+    pub fn new(handle: tracing_subscriber::reload::Handle<L, S>) -> Self {
+        Self { handle }
+    }
+
+    /// Replace the filter with the one of the level, it fails if the subscriber is gone
+    pub fn reload(&self, level: LoggingLevel) -> Result<(), tracing_subscriber::reload::Error> {
+        self.handle.reload(level_filter(level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_are_ordered() {
+        let levels = [
+            LoggingLevel::Debug,
+            LoggingLevel::Info,
+            LoggingLevel::Notice,
+            LoggingLevel::Warning,
+            LoggingLevel::Error,
+            LoggingLevel::Critical,
+            LoggingLevel::Alert,
+            LoggingLevel::Emergency,
+        ];
+        // the more severe, the fewer events pass the filter
+        for pair in levels.windows(2) {
+            assert!(level_filter(pair[0]) >= level_filter(pair[1]), "{pair:?}");
+        }
+        assert_eq!(level_filter(LoggingLevel::Notice), LevelFilter::INFO);
+        assert_eq!(level_filter(LoggingLevel::Emergency), LevelFilter::ERROR);
+    }
+
+    #[cfg(feature = "tracing-subscriber")]
+    #[test]
+    fn test_reload() {
+        use tracing_subscriber::{layer::SubscriberExt, reload};
+
+        let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+        let subscriber = tracing_subscriber::registry().with(filter);
+        let reload = TracingLevelReload::new(handle.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!tracing::enabled!(Level::DEBUG));
+            reload.reload(LoggingLevel::Debug).expect("reload");
+            assert!(tracing::enabled!(Level::DEBUG));
+            reload.reload(LoggingLevel::Critical).expect("reload");
+            assert!(!tracing::enabled!(Level::WARN));
+            assert!(tracing::enabled!(Level::ERROR));
+            assert_eq!(handle.clone_current(), Some(LevelFilter::ERROR));
+        });
+    }
+}
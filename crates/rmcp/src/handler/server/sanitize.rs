@@ -0,0 +1,118 @@
+//! Hide the details of the errors sent to the client, e.g. the paths or the queries in an internal error
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::model::{ErrorCode, ErrorData};
+
+/// What is sent to the client for an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Send the error unchanged
+    Forward,
+    /// Replace the message with this one, and drop the data
+    Redact(Cow<'static, str>),
+    /// Keep the first characters of the message, and drop the data
+    Truncate(usize),
+}
+
+impl ErrorPolicy {
+    /// Redact with the message `internal error`
+    pub const fn redact() -> Self {
+        Self::Redact(Cow::Borrowed(ErrorSanitizer::GENERIC_MESSAGE))
+    }
+}
+
+/// Sanitize the errors of a server before they're sent to the client.
+///
+/// Return it from [`ServerHandler::error_sanitizer`](super::ServerHandler::error_sanitizer). The policy is
+/// picked by the error code, the full error is logged with `tracing` when it's changed. By default the
+/// internal errors are redacted and the other errors are forwarded:
+///
+/// ```rust,ignore
+/// let sanitizer = ErrorSanitizer::default()
+///     .with_policy(ErrorCode::INVALID_PARAMS, ErrorPolicy::Truncate(80));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorSanitizer {
+    policies: HashMap<ErrorCode, ErrorPolicy>,
+    default_policy: ErrorPolicy,
+}
+
+impl Default for ErrorSanitizer {
+    fn default() -> Self {
+        Self::new(ErrorPolicy::Forward)
+            .with_policy(ErrorCode::INTERNAL_ERROR, ErrorPolicy::redact())
+    }
+}
+
+impl ErrorSanitizer {
+    /// The message of the errors redacted by [`ErrorPolicy::redact`]
+    pub const GENERIC_MESSAGE: &str = "internal error";
+
+    /// A sanitizer applying `default_policy` to all the error codes
+    pub fn new(default_policy: ErrorPolicy) -> Self {
+        Self {
+            policies: HashMap::new(),
+            default_policy,
+        }
+    }
+
+    /// Use `policy` for the errors with `code`
+    pub fn with_policy(mut self, code: ErrorCode, policy: ErrorPolicy) -> Self {
+        self.policies.insert(code, policy);
+        self
+    }
+
+    pub fn policy(&self, code: ErrorCode) -> &ErrorPolicy {
+        self.policies.get(&code).unwrap_or(&self.default_policy)
+    }
+
+    /// The error to send to the client
+    pub fn sanitize(&self, error: ErrorData) -> ErrorData {
+        let message = match self.policy(error.code) {
+            ErrorPolicy::Forward => return error,
+            ErrorPolicy::Redact(message) => message.clone(),
+            ErrorPolicy::Truncate(length) => match error.message.char_indices().nth(*length) {
+                Some((end, _)) => format!("{}…", &error.message[..end]).into(),
+                None if error.data.is_none() => return error,
+                None => error.message.clone(),
+            },
+        };
+        tracing::warn!(
+            code = error.code.0,
+            message = %error.message,
+            data = ?error.data,
+            "error sanitized before being sent to the client"
+        );
+        ErrorData::new(error.code, message, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        let sanitizer = ErrorSanitizer::default()
+            .with_policy(ErrorCode::INVALID_PARAMS, ErrorPolicy::Truncate(5));
+
+        let internal = ErrorData::internal_error("open /etc/secret: denied", Some(json!("trace")));
+        assert_eq!(
+            sanitizer.sanitize(internal),
+            ErrorData::internal_error(ErrorSanitizer::GENERIC_MESSAGE, None)
+        );
+
+        let invalid = ErrorData::invalid_params("épée tranchante", Some(json!(1)));
+        assert_eq!(
+            sanitizer.sanitize(invalid),
+            ErrorData::invalid_params("épée …", None)
+        );
+        let short = ErrorData::invalid_params("short", None);
+        assert_eq!(sanitizer.sanitize(short.clone()), short);
+
+        let not_found = ErrorData::resource_not_found("no file", Some(json!("uri")));
+        assert_eq!(sanitizer.sanitize(not_found.clone()), not_found);
+    }
+}
@@ -7,40 +7,130 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio_util::sync::CancellationToken;
 
+use super::{
+    idempotency::{Begin, IDEMPOTENCY_KEY_META, IdempotencyCache},
+    validation,
+};
 use crate::{
     RoleServer,
-    model::{CallToolRequestParam, CallToolResult, ConstString, IntoContents, JsonObject},
-    service::RequestContext,
+    error::Cancelled,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientCapabilities, ConstString, Implementation,
+        IntoContents, JsonObject, RequestId,
+    },
+    service::{Peer, RequestContext},
 };
-/// A shortcut for generating a JSON schema for a type.
+/// The JSON Schema draft used to generate tool input schemas.
+///
+/// Defaults to [`SchemaDraft::Draft07`], which is what most MCP clients expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SchemaDraft {
+    /// `http://json-schema.org/draft-07/schema#`, subschemas are referenced in `#/definitions/`
+    #[default]
+    Draft07,
+    /// `https://json-schema.org/draft/2019-09/schema`, subschemas are referenced in `#/$defs/`
+    Draft2019_09,
+    /// `https://json-schema.org/draft/2020-12/schema`, subschemas are referenced in `#/$defs/`
+    Draft2020_12,
+}
+
+impl SchemaDraft {
+    pub const fn meta_schema(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft07 => "http://json-schema.org/draft-07/schema#",
+            SchemaDraft::Draft2019_09 => "https://json-schema.org/draft/2019-09/schema",
+            SchemaDraft::Draft2020_12 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+    /// The key of the referenceable subschemas in the root schema
+    pub const fn definitions_key(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft07 => "definitions",
+            SchemaDraft::Draft2019_09 | SchemaDraft::Draft2020_12 => "$defs",
+        }
+    }
+    fn settings(&self) -> schemars::r#gen::SchemaSettings {
+        let mut settings = match self {
+            SchemaDraft::Draft07 => schemars::r#gen::SchemaSettings::draft07(),
+            SchemaDraft::Draft2019_09 | SchemaDraft::Draft2020_12 => {
+                schemars::r#gen::SchemaSettings::draft2019_09()
+            }
+        };
+        settings.meta_schema = Some(self.meta_schema().to_owned());
+        settings.definitions_path = format!("#/{}/", self.definitions_key());
+        settings
+    }
+}
+
+/// The options of the generated JSON schemas, set for a tool box with `#[tool(tool_box, schema_draft = .., inline_subschemas = ..)]`.
+///
+/// The tool box passes them to the `{tool}_tool_attr_with_options` function generated by `#[tool]`,
+/// `{tool}_tool_attr` uses the default ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SchemaOptions {
+    pub draft: SchemaDraft,
+    /// Inline the subschemas of nested types instead of referencing them in the definitions.
+    ///
+    /// Some clients don't resolve `$ref`, enable this to ship self-contained schemas to them.
+    /// Recursive types still have a `$ref` in the cyclic position.
+    pub inline_subschemas: bool,
+}
+
+/// A shortcut for generating a JSON schema for a type, with the default [`SchemaOptions`].
 pub fn schema_for_type<T: JsonSchema>() -> JsonObject {
-    let schema = T::json_schema(&mut schemars::SchemaGenerator::default());
+    schema_for_type_with_options::<T>(SchemaOptions::default())
+}
+
+/// Generate a root JSON schema for a type in the given draft, the subschemas are referenced.
+pub fn schema_for_type_with_draft<T: JsonSchema>(draft: SchemaDraft) -> JsonObject {
+    schema_for_type_with_options::<T>(SchemaOptions {
+        draft,
+        ..Default::default()
+    })
+}
+
+/// Generate a root JSON schema for a type with the options.
+pub fn schema_for_type_with_options<T: JsonSchema>(options: SchemaOptions) -> JsonObject {
+    let mut settings = options.draft.settings();
+    settings.inline_subschemas = options.inline_subschemas;
+    let schema = settings.into_generator().into_root_schema_for::<T>();
     let object = serde_json::to_value(schema).expect("failed to serialize schema");
     match object {
-        serde_json::Value::Object(object) => object,
+        serde_json::Value::Object(mut object) => {
+            if let Some(definitions) = object.remove("definitions") {
+                object.insert(options.draft.definitions_key().to_owned(), definitions);
+            }
+            object
+        }
         _ => panic!("unexpected schema value"),
     }
 }
 
 /// Call [`schema_for_type`] with a cache
 pub fn cached_schema_for_type<T: JsonSchema + std::any::Any>() -> Arc<JsonObject> {
+    cached_schema_for_type_with_options::<T>(SchemaOptions::default())
+}
+
+/// Call [`schema_for_type_with_options`] with a cache
+pub fn cached_schema_for_type_with_options<T: JsonSchema + std::any::Any>(
+    options: SchemaOptions,
+) -> Arc<JsonObject> {
     thread_local! {
-        static CACHE_FOR_TYPE: std::sync::RwLock<HashMap<TypeId, Arc<JsonObject>>> = Default::default();
+        static CACHE_FOR_TYPE: std::sync::RwLock<HashMap<(TypeId, SchemaOptions), Arc<JsonObject>>> = Default::default();
     };
     CACHE_FOR_TYPE.with(|cache| {
         if let Some(x) = cache
             .read()
             .expect("schema cache lock poisoned")
-            .get(&TypeId::of::<T>())
+            .get(&(TypeId::of::<T>(), options))
         {
             x.clone()
         } else {
-            let schema = schema_for_type::<T>();
-            let schema = Arc::new(schema);
+            let schema = Arc::new(schema_for_type_with_options::<T>(options));
             cache
                 .write()
                 .expect("schema cache lock poisoned")
-                .insert(TypeId::of::<T>(), schema.clone());
+                .insert((TypeId::of::<T>(), options), schema.clone());
             schema
         }
     })
@@ -57,27 +147,147 @@ pub fn parse_json_object<T: DeserializeOwned>(input: JsonObject) -> Result<T, cr
 }
 pub struct ToolCallContext<'service, S> {
     request_context: RequestContext<RoleServer>,
-    service: &'service S,
+    service: ToolCallService<'service, S>,
     name: Cow<'static, str>,
-    arguments: Option<JsonObject>,
+    /// Shared with the [`ToolContext`] and the idempotency cache rather than copied
+    arguments: Option<Arc<JsonObject>>,
+    meta: Option<JsonObject>,
+}
+
+enum ToolCallService<'service, S> {
+    Shared(&'service S),
+    Exclusive(&'service mut S),
+    Taken,
 }
 
 impl<'service, S> ToolCallContext<'service, S> {
     pub fn new(
         service: &'service S,
-        CallToolRequestParam { name, arguments }: CallToolRequestParam,
+        CallToolRequestParam {
+            name,
+            arguments,
+            meta,
+        }: CallToolRequestParam,
         request_context: RequestContext<RoleServer>,
     ) -> Self {
         Self {
             request_context,
-            service,
+            service: ToolCallService::Shared(service),
+            name,
+            arguments: arguments.map(Arc::new),
+            meta,
+        }
+    }
+    /// Create a context with exclusive access to the service, so tools taking `&mut self` can be called.
+    pub fn new_exclusive(
+        service: &'service mut S,
+        CallToolRequestParam {
             name,
             arguments,
+            meta,
+        }: CallToolRequestParam,
+        request_context: RequestContext<RoleServer>,
+    ) -> Self {
+        Self {
+            request_context,
+            service: ToolCallService::Exclusive(service),
+            name,
+            arguments: arguments.map(Arc::new),
+            meta,
         }
     }
     pub fn name(&self) -> &str {
         &self.name
     }
+    pub fn arguments(&self) -> Option<&JsonObject> {
+        self.arguments.as_deref()
+    }
+    /// The `_meta` of the call
+    pub fn meta(&self) -> Option<&JsonObject> {
+        self.meta.as_ref()
+    }
+    /// Reject the arguments which are not properties of the schema of `P`, this is how `#[tool(strict)]` is applied.
+    ///
+    /// Types accepting any property, like maps, accept all the arguments.
+    pub fn deny_unknown_arguments<P: JsonSchema + std::any::Any>(
+        &self,
+    ) -> Result<(), crate::Error> {
+        let schema = cached_schema_for_type::<P>();
+        let is_object = schema.get("type").and_then(|ty| ty.as_str()) == Some("object");
+        let open = schema
+            .get("additionalProperties")
+            .is_some_and(|additional| additional != &serde_json::Value::Bool(false));
+        if !is_object || open {
+            return Ok(());
+        }
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        let is_known = |key: &String| properties.is_some_and(|p| p.contains_key(key));
+        let Some(unknown) = self
+            .arguments()
+            .into_iter()
+            .flat_map(JsonObject::keys)
+            .find(|key| !is_known(key))
+        else {
+            return Ok(());
+        };
+        let expected = properties
+            .into_iter()
+            .flat_map(|p| p.keys())
+            .map(|key| format!("`{key}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(crate::Error::invalid_params(
+            format!("unknown field `{unknown}`, expected one of {expected}"),
+            Some(serde_json::json!({ "field": unknown })),
+        ))
+    }
+    fn before_call(&mut self, hooks: &[Box<BeforeCallHook>]) -> Result<(), crate::Error> {
+        if hooks.is_empty() {
+            return Ok(());
+        }
+        let request = CallToolRequestParam {
+            name: std::mem::take(&mut self.name),
+            // not shared yet, so this doesn't copy
+            arguments: self.arguments.take().map(Arc::unwrap_or_clone),
+            meta: self.meta.take(),
+        };
+        let result = hooks
+            .iter()
+            .try_for_each(|hook| hook(&request, &self.request_context));
+        self.name = request.name;
+        self.arguments = request.arguments.map(Arc::new);
+        self.meta = request.meta;
+        result
+    }
+    fn shared_service(&mut self) -> Result<&'service S, crate::Error> {
+        match std::mem::replace(&mut self.service, ToolCallService::Taken) {
+            ToolCallService::Shared(service) => {
+                self.service = ToolCallService::Shared(service);
+                Ok(service)
+            }
+            ToolCallService::Exclusive(service) => {
+                let service: &'service S = service;
+                self.service = ToolCallService::Shared(service);
+                Ok(service)
+            }
+            ToolCallService::Taken => Err(crate::Error::internal_error(
+                "service has been borrowed exclusively by the tool",
+                None,
+            )),
+        }
+    }
+    fn exclusive_service(&mut self) -> Result<&'service mut S, crate::Error> {
+        match std::mem::replace(&mut self.service, ToolCallService::Taken) {
+            ToolCallService::Exclusive(service) => Ok(service),
+            service => {
+                self.service = service;
+                Err(crate::Error::internal_error(
+                    "tool requires exclusive access, serve it with an `ExclusiveToolRouter`",
+                    None,
+                ))
+            }
+        }
+    }
 }
 
 pub trait FromToolCallContextPart<'a, S>: Sized {
@@ -110,6 +320,58 @@ impl<T: IntoContents, E: IntoContents> IntoCallToolResult for Result<T, E> {
     }
 }
 
+/// The error is returned as is, as the JSON-RPC error of the call, so a tool can choose its error code,
+/// like [`ErrorCode::RESOURCE_NOT_FOUND`](crate::model::ErrorCode::RESOURCE_NOT_FOUND).
+impl<T: IntoContents> IntoCallToolResult for Result<T, crate::Error> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        self.map(|value| CallToolResult::success(value.into_contents()))
+    }
+}
+
+/// The error is converted with [`crate::error::map_error`], an internal error by default,
+/// so a tool can use `?` on any error wrapped in an [`anyhow::Error`].
+#[cfg(feature = "anyhow")]
+impl<T: IntoContents> IntoCallToolResult for Result<T, anyhow::Error> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        self.map(|value| CallToolResult::success(value.into_contents()))
+            .map_err(crate::Error::from)
+    }
+}
+
+/// A tool output returned as the structured content of the result, with its JSON as the text content.
+///
+/// The `#[tool]` macro declares the schema of `T` as the output schema of the tool, so `T` should be
+/// a struct, as the output schema must be an object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structured<T>(pub T);
+
+impl<T: Serialize> IntoCallToolResult for Structured<T> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        let value = serde_json::to_value(self.0).map_err(|e| {
+            crate::Error::internal_error(
+                format!("failed to serialize structured content: {error}", error = e),
+                None,
+            )
+        })?;
+        Ok(CallToolResult::structured(value))
+    }
+}
+
+impl<T: Serialize, E: IntoContents> IntoCallToolResult for Result<Structured<T>, E> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        match self {
+            Ok(value) => value.into_call_tool_result(),
+            Err(error) => Ok(CallToolResult::error(error.into_contents())),
+        }
+    }
+}
+
+impl<T: Serialize> IntoCallToolResult for Result<Structured<T>, crate::Error> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        self?.into_call_tool_result()
+    }
+}
+
 pin_project_lite::pin_project! {
     #[project = IntoCallToolResultFutProj]
     pub enum IntoCallToolResultFut<F, R> {
@@ -156,6 +418,13 @@ pub trait CallToolHandler<'a, S, A> {
     fn call(self, context: ToolCallContext<'a, S>) -> Self::Fut;
 }
 
+/// A hook called before a tool is dispatched, return an error to reject the call.
+///
+/// The negotiated client info can be read from `context.peer.peer_info()`.
+pub type BeforeCallHook = dyn Fn(&CallToolRequestParam, &RequestContext<RoleServer>) -> Result<(), crate::Error>
+    + Send
+    + Sync;
+
 pub type DynCallToolHandler<S> = dyn Fn(ToolCallContext<'_, S>) -> BoxFuture<'_, Result<CallToolResult, crate::Error>>
     + Send
     + Sync;
@@ -164,6 +433,8 @@ pub struct Parameter<K: ConstString, V>(pub K, pub V);
 
 /// Parameter Extractor
 ///
+/// The fields unknown to `P` are ignored, mark `P` with `#[serde(deny_unknown_fields)]` to reject them instead.
+/// The raw arguments stay available to the tool through a `JsonObject` parameter or [`ToolContext::raw_arguments`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Parameters<P>(pub P);
@@ -191,9 +462,9 @@ impl<'a, S> FromToolCallContextPart<'a, S> for CancellationToken {
 
 impl<'a, S> FromToolCallContextPart<'a, S> for Callee<'a, S> {
     fn from_tool_call_context_part(
-        context: ToolCallContext<'a, S>,
+        mut context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        Ok((Callee(context.service), context))
+        Ok((Callee(context.shared_service()?), context))
     }
 }
 
@@ -207,11 +478,99 @@ impl<'a, S> FromToolCallContextPart<'a, S> for ToolName {
     }
 }
 
-impl<'a, S> FromToolCallContextPart<'a, S> for &'a S {
+/// The context of a tool call, add a parameter of this type to the tool to extract it.
+///
+/// ```rust,ignore
+/// #[tool(description = "Say hello to the client")]
+/// fn say_hello(&self, context: ToolContext) -> String {
+///     format!("hello, {}", context.client_info().name)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ToolContext {
+    pub id: RequestId,
+    pub peer: Peer<RoleServer>,
+    /// Cancelled when the client cancels the call
+    pub ct: CancellationToken,
+    arguments: Option<Arc<JsonObject>>,
+    meta: Option<JsonObject>,
+}
+
+impl ToolContext {
+    /// The JSON-RPC id of the call as sent by the client, a number or a string, e.g. to correlate the logs
+    pub fn request_id(&self) -> &RequestId {
+        &self.id
+    }
+    /// The implementation of the client, negotiated during initialization
+    pub fn client_info(&self) -> &Implementation {
+        &self.peer.peer_info().client_info
+    }
+    /// The capabilities of the client, negotiated during initialization
+    pub fn client_capabilities(&self) -> &ClientCapabilities {
+        &self.peer.peer_info().capabilities
+    }
+    /// The arguments of the call as sent by the client, including the fields unknown to the typed parameters
+    pub fn raw_arguments(&self) -> Option<&JsonObject> {
+        self.arguments.as_deref()
+    }
+    /// The `_meta` of the call, where the extensions of the protocol are set
+    pub fn meta(&self) -> Option<&JsonObject> {
+        self.meta.as_ref()
+    }
+    /// The page of the content asked by the client, `None` if it wants all the content,
+    /// see [`tool_page`](crate::model::CallToolResult::paginate)
+    #[cfg(feature = "experimental")]
+    pub fn cursor(&self) -> Result<Option<crate::model::Cursor>, crate::Error> {
+        crate::model::cursor_of(&self.meta)
+    }
+    /// Sleep for `duration`, or fail as soon as the client cancels the call, e.g. between the steps of a long operation:
+    ///
+    /// ```rust,ignore
+    /// #[tool(description = "Count down from 10")]
+    /// async fn countdown(&self, context: ToolContext) -> Result<String, McpError> {
+    ///     for _ in 0..10 {
+    ///         context.sleep(Duration::from_secs(1)).await?;
+    ///     }
+    ///     Ok("liftoff".into())
+    /// }
+    /// ```
+    pub async fn sleep(&self, duration: std::time::Duration) -> Result<(), Cancelled> {
+        tokio::select! {
+            biased;
+            _ = self.ct.cancelled() => Err(Cancelled),
+            _ = tokio::time::sleep(duration) => Ok(()),
+        }
+    }
+}
+
+impl<'a, S> FromToolCallContextPart<'a, S> for ToolContext {
     fn from_tool_call_context_part(
         context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        Ok((context.service, context))
+        let tool_context = ToolContext {
+            id: context.request_context.id.clone(),
+            peer: context.request_context.peer.clone(),
+            ct: context.request_context.ct.clone(),
+            arguments: context.arguments.clone(),
+            meta: context.meta.clone(),
+        };
+        Ok((tool_context, context))
+    }
+}
+
+impl<'a, S> FromToolCallContextPart<'a, S> for &'a S {
+    fn from_tool_call_context_part(
+        mut context: ToolCallContext<'a, S>,
+    ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
+        Ok((context.shared_service()?, context))
+    }
+}
+
+impl<'a, S> FromToolCallContextPart<'a, S> for &'a mut S {
+    fn from_tool_call_context_part(
+        mut context: ToolCallContext<'a, S>,
+    ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
+        Ok((context.exclusive_service()?, context))
     }
 }
 
@@ -234,7 +593,7 @@ where
             format!("missing parameter {field}", field = K::VALUE),
             None,
         ))?;
-        let value: V = serde_json::from_value(value.clone()).map_err(|e| {
+        let value = V::deserialize(value).map_err(|e| {
             crate::Error::invalid_params(
                 format!(
                     "failed to deserialize parameter {field}: {error}",
@@ -253,25 +612,26 @@ where
     P: DeserializeOwned,
 {
     fn from_tool_call_context_part(
-        mut context: ToolCallContext<'a, S>,
+        context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        let arguments = context.arguments.take().unwrap_or_default();
-        let value: P =
-            serde_json::from_value(serde_json::Value::Object(arguments)).map_err(|e| {
-                crate::Error::invalid_params(
-                    format!("failed to deserialize parameters: {error}", error = e),
-                    None,
-                )
-            })?;
+        let empty = JsonObject::new();
+        let arguments = context.arguments.as_deref().unwrap_or(&empty);
+        let value = P::deserialize(arguments).map_err(|e| {
+            crate::Error::invalid_params(
+                format!("failed to deserialize parameters: {error}", error = e),
+                None,
+            )
+        })?;
         Ok((Parameters(value), context))
     }
 }
 
 impl<'a, S> FromToolCallContextPart<'a, S> for JsonObject {
     fn from_tool_call_context_part(
-        mut context: ToolCallContext<'a, S>,
+        context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        let object = context.arguments.take().unwrap_or_default();
+        // the raw access is the only one copying the arguments
+        let object = context.arguments.as_deref().cloned().unwrap_or_default();
         Ok((object, context))
     }
 }
@@ -368,7 +728,7 @@ pub struct ToolBoxItem<S> {
     pub attr: crate::model::Tool,
 }
 
-impl<S: Send + Sync + 'static + Clone> ToolBoxItem<S> {
+impl<S: Send + Sync + 'static> ToolBoxItem<S> {
     pub fn new<C>(attr: crate::model::Tool, call: C) -> Self
     where
         C: Fn(ToolCallContext<'_, S>) -> BoxFuture<'_, Result<CallToolResult, crate::Error>>
@@ -381,44 +741,271 @@ impl<S: Send + Sync + 'static + Clone> ToolBoxItem<S> {
             attr,
         }
     }
+}
+
+impl<S> ToolBoxItem<S> {
     pub fn name(&self) -> &str {
         &self.attr.name
     }
+
+    /// Call the tool and validate its output
+    async fn dispatch(
+        &self,
+        context: ToolCallContext<'_, S>,
+    ) -> Result<CallToolResult, crate::Error> {
+        let result = (self.call)(context).await?;
+        self.validate_output(&result)?;
+        Ok(result)
+    }
+
+    /// Check the structured content against the output schema, if the tool declared one
+    fn validate_output(&self, result: &CallToolResult) -> Result<(), crate::Error> {
+        let Some(output_schema) = &self.attr.output_schema else {
+            return Ok(());
+        };
+        if result.is_error == Some(true) {
+            return Ok(());
+        }
+        let Some(structured_content) = &result.structured_content else {
+            return Err(crate::Error::internal_error(
+                "tool with an output schema must return structured content",
+                Some(serde_json::json!({ "tool": self.name() })),
+            ));
+        };
+        validation::validate(output_schema, structured_content).map_err(|reason| {
+            crate::Error::internal_error(
+                "structured content doesn't match the output schema",
+                Some(serde_json::json!({ "tool": self.name(), "reason": reason })),
+            )
+        })
+    }
+}
+
+/// The order of the tools listed by [`ToolBox::list`], so the clients and the snapshot tests see a stable order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ToolOrder {
+    /// The order the tools are added in, for `#[tool(tool_box)]` the order they're declared in the impl block
+    #[default]
+    Declaration,
+    /// Sorted by name
+    Alphabetical,
 }
 
 #[derive(Default)]
 pub struct ToolBox<S> {
     #[allow(clippy::type_complexity)]
     pub map: std::collections::HashMap<Cow<'static, str>, ToolBoxItem<S>>,
+    /// The names in the order they're added, a tool replaced keeps its place
+    names: Vec<Cow<'static, str>>,
+    order: ToolOrder,
+    before_call: Vec<Box<BeforeCallHook>>,
+    idempotency_cache: IdempotencyCache,
 }
 
 impl<S> ToolBox<S> {
     pub fn new() -> Self {
         Self {
             map: std::collections::HashMap::new(),
+            names: Vec::new(),
+            order: ToolOrder::default(),
+            before_call: Vec::new(),
+            idempotency_cache: IdempotencyCache::default(),
         }
     }
     pub fn add(&mut self, item: ToolBoxItem<S>) {
-        self.map.insert(item.attr.name.clone(), item);
+        let name = item.attr.name.clone();
+        if self.map.insert(name.clone(), item).is_none() {
+            self.names.push(name);
+        }
+    }
+
+    /// Set the order of [`ToolBox::list`], it's [`ToolOrder::Declaration`] by default.
+    ///
+    /// With `#[tool(tool_box)]`, set it with `#[tool(tool_box, order = ToolOrder::Alphabetical)]`.
+    pub fn set_order(&mut self, order: ToolOrder) {
+        self.order = order;
+    }
+
+    pub fn order(&self) -> ToolOrder {
+        self.order
+    }
+
+    /// Replace the cache of the results of the idempotent tools used by [`ToolBox::call`],
+    /// a cache with a capacity of 0 disables it
+    pub fn set_idempotency_cache(&mut self, cache: IdempotencyCache) {
+        self.idempotency_cache = cache;
+    }
+
+    pub fn idempotency_cache(&self) -> &IdempotencyCache {
+        &self.idempotency_cache
+    }
+
+    /// Add a hook called before every tool call, in the order they are added.
+    ///
+    /// The hooks run after the tool is found, and the first error rejects the call.
+    pub fn add_before_call<H>(&mut self, hook: H)
+    where
+        H: Fn(&CallToolRequestParam, &RequestContext<RoleServer>) -> Result<(), crate::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.before_call.push(Box::new(hook));
     }
 
     pub fn remove<H, A>(&mut self, name: &str) {
         self.map.remove(name);
+        self.names.retain(|added| added != name);
     }
 
+    /// Call a tool, a duplicate call of a tool annotated with `idempotentHint` gets the result
+    /// of the first call from the [`ToolBox::idempotency_cache`], see [`ToolBox::call_with_idempotency_cache`]
     pub async fn call(
         &self,
         context: ToolCallContext<'_, S>,
+    ) -> Result<CallToolResult, crate::Error> {
+        self.call_with_idempotency_cache(context, &self.idempotency_cache)
+            .await
+    }
+
+    /// Like [`ToolBox::call`], with another cache.
+    ///
+    /// A duplicate call of a tool annotated with `idempotentHint` returns the result cached in the
+    /// [`IdempotencyCache`] instead of executing the tool again, or waits for the result if the first call
+    /// is still running. A duplicate has the same [`IDEMPOTENCY_KEY_META`] key in its `_meta`, and comes from
+    /// the same session.
+    pub async fn call_with_idempotency_cache(
+        &self,
+        mut context: ToolCallContext<'_, S>,
+        cache: &IdempotencyCache,
     ) -> Result<CallToolResult, crate::Error> {
         let item = self
             .map
             .get(context.name())
             .ok_or_else(|| crate::Error::invalid_params("tool not found", None))?;
-        (item.call)(context).await
+        context.before_call(&self.before_call)?;
+        let idempotent = item
+            .attr
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.idempotent_hint)
+            .unwrap_or_default();
+        let idempotency_key = context
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get(IDEMPOTENCY_KEY_META))
+            .and_then(serde_json::Value::as_str);
+        let Some(idempotency_key) = idempotency_key.filter(|_| idempotent) else {
+            return item.dispatch(context).await;
+        };
+        let key = (
+            context.request_context.session().id(),
+            idempotency_key.to_owned(),
+        );
+        loop {
+            let begin = cache.begin(key.clone(), context.name.clone(), context.arguments.clone());
+            match begin {
+                Begin::Cached(result) => return Ok(result),
+                Begin::Wait(mut receiver) => {
+                    // the first call failed or was cancelled if there's no result, begin again
+                    if let Ok(result) = receiver.wait_for(Option::is_some).await {
+                        return Ok(result.clone().expect("a result"));
+                    }
+                }
+                Begin::Run(in_flight) => {
+                    let result = item.dispatch(context).await?;
+                    in_flight.complete(result.clone());
+                    return Ok(result);
+                }
+            }
+        }
+    }
+
+    /// The tools in the [`ToolOrder`] of the tool box.
+    ///
+    /// The tools inserted in `map` directly have no declaration order, they're listed after the others by name.
+    pub fn list(&self) -> Vec<crate::model::Tool> {
+        let mut names = self
+            .names
+            .iter()
+            .filter(|name| self.map.contains_key(*name))
+            .collect::<Vec<_>>();
+        let mut inserted = self
+            .map
+            .keys()
+            .filter(|name| !self.names.contains(name))
+            .collect::<Vec<_>>();
+        inserted.sort();
+        names.extend(inserted);
+        if self.order == ToolOrder::Alphabetical {
+            names.sort();
+        }
+        names
+            .into_iter()
+            .map(|name| self.map[name].attr.clone())
+            .collect()
+    }
+}
+
+/// A tool router owning the state of tools which take `&mut self`.
+///
+/// The state is guarded by a mutex, and the lock is held during the whole execution of a tool,
+/// so **all the calls through this router are serialized**. Tools taking `&self` work as well.
+///
+/// ```rust,ignore
+/// #[derive(Clone)]
+/// pub struct Server {
+///     router: ExclusiveToolRouter<Counter>,
+/// }
+///
+/// impl ServerHandler for Server {
+///     async fn list_tools(&self, _: PaginatedRequestParam, _: RequestContext<RoleServer>) -> Result<ListToolsResult, McpError> {
+///         Ok(ListToolsResult { next_cursor: None, tools: self.router.list(), meta: None })
+///     }
+///     async fn call_tool(&self, request: CallToolRequestParam, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+///         self.router.call(request, context).await
+///     }
+/// }
+/// ```
+pub struct ExclusiveToolRouter<S: 'static> {
+    state: Arc<tokio::sync::Mutex<S>>,
+    tool_box: &'static ToolBox<S>,
+}
+
+impl<S> Clone for ExclusiveToolRouter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            tool_box: self.tool_box,
+        }
+    }
+}
+
+impl<S> ExclusiveToolRouter<S> {
+    pub fn new(state: S, tool_box: &'static ToolBox<S>) -> Self {
+        Self {
+            state: Arc::new(tokio::sync::Mutex::new(state)),
+            tool_box,
+        }
+    }
+
+    /// The shared state, lock it to read or modify the state outside of a tool call
+    pub fn state(&self) -> &Arc<tokio::sync::Mutex<S>> {
+        &self.state
     }
 
     pub fn list(&self) -> Vec<crate::model::Tool> {
-        self.map.values().map(|item| item.attr.clone()).collect()
+        self.tool_box.list()
+    }
+
+    pub async fn call(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, crate::Error> {
+        let mut state = self.state.lock().await;
+        let context = ToolCallContext::new_exclusive(&mut *state, request, context);
+        self.tool_box.call(context).await
     }
 }
 
@@ -428,23 +1015,77 @@ macro_rules! tool_box {
     (@pin_add $callee: ident, $attr: expr, $f: expr) => {
         $callee.add(ToolBoxItem::new($attr, |context| Box::pin($f(context))));
     };
+    (@catalog tool_box) => {
+        /// The tools of the tool box as listed by `tools/list`, available without a connection,
+        /// e.g. to publish them from a build script
+        #[allow(dead_code)]
+        pub fn tool_catalog() -> Vec<$crate::model::Tool> {
+            Self::tool_box().list()
+        }
+    };
+    (@catalog $tool_box: ident) => {
+        $crate::paste! {
+            /// The tools of the tool box as listed by `tools/list`, available without a connection
+            #[allow(dead_code)]
+            pub fn [<$tool_box _catalog>]() -> Vec<$crate::model::Tool> {
+                Self::$tool_box().list()
+            }
+        }
+    };
     ($server: ident { $($tool: ident),* $(,)?} ) => {
         $crate::tool_box!($server { $($tool),* }  tool_box);
     };
-    ($server: ident { $($tool: ident),* $(,)?} $tool_box: ident) => {
+    // the options, in any order, are applied in two passes: those of the schemas before the tools are added
+    (@schema_options $schema_options: ident,) => {};
+    (@schema_options $schema_options: ident, schema_draft = $draft: expr $(, $($rest: tt)*)?) => {
+        $schema_options.draft = $draft;
+        $crate::tool_box!(@schema_options $schema_options, $($($rest)*)?);
+    };
+    (@schema_options $schema_options: ident, inline_subschemas = $inline: expr $(, $($rest: tt)*)?) => {
+        $schema_options.inline_subschemas = $inline;
+        $crate::tool_box!(@schema_options $schema_options, $($($rest)*)?);
+    };
+    (@schema_options $schema_options: ident, before_call = $hook: expr $(, $($rest: tt)*)?) => {
+        $crate::tool_box!(@schema_options $schema_options, $($($rest)*)?);
+    };
+    (@schema_options $schema_options: ident, order = $order: expr $(, $($rest: tt)*)?) => {
+        $crate::tool_box!(@schema_options $schema_options, $($($rest)*)?);
+    };
+    // and those of the tool box once they're added
+    (@tool_box_options $tool_box: ident,) => {};
+    (@tool_box_options $tool_box: ident, before_call = $hook: expr $(, $($rest: tt)*)?) => {
+        $tool_box.add_before_call($hook);
+        $crate::tool_box!(@tool_box_options $tool_box, $($($rest)*)?);
+    };
+    (@tool_box_options $tool_box: ident, order = $order: expr $(, $($rest: tt)*)?) => {
+        $tool_box.set_order($order);
+        $crate::tool_box!(@tool_box_options $tool_box, $($($rest)*)?);
+    };
+    (@tool_box_options $tool_box: ident, schema_draft = $draft: expr $(, $($rest: tt)*)?) => {
+        $crate::tool_box!(@tool_box_options $tool_box, $($($rest)*)?);
+    };
+    (@tool_box_options $tool_box: ident, inline_subschemas = $inline: expr $(, $($rest: tt)*)?) => {
+        $crate::tool_box!(@tool_box_options $tool_box, $($($rest)*)?);
+    };
+    ($server: ident { $($tool: ident),* $(,)?} $tool_box: ident $(, $($option: tt)*)?) => {
         fn $tool_box() -> &'static $crate::handler::server::tool::ToolBox<$server> {
-            use $crate::handler::server::tool::{ToolBox, ToolBoxItem};
+            use $crate::handler::server::tool::{SchemaOptions, ToolBox, ToolBoxItem};
             static TOOL_BOX: std::sync::OnceLock<ToolBox<$server>> = std::sync::OnceLock::new();
             TOOL_BOX.get_or_init(|| {
                 let mut tool_box = ToolBox::new();
+                #[allow(unused_mut)]
+                let mut schema_options = SchemaOptions::default();
+                $crate::tool_box!(@schema_options schema_options, $($($option)*)?);
                 $crate::paste!{
                     $(
-                        $crate::tool_box!(@pin_add tool_box, $server::[< $tool _tool_attr>](), $server::[<$tool _tool_call>]);
+                        $crate::tool_box!(@pin_add tool_box, $server::[< $tool _tool_attr_with_options>](schema_options), $server::[<$tool _tool_call>]);
                     )*
                 }
+                $crate::tool_box!(@tool_box_options tool_box, $($($option)*)?);
                 tool_box
             })
         }
+        $crate::tool_box!(@catalog $tool_box);
     };
     (@derive) => {
         $crate::tool_box!(@derive tool_box);
@@ -458,7 +1099,8 @@ macro_rules! tool_box {
         ) -> Result<$crate::model::ListToolsResult, $crate::Error> {
             Ok($crate::model::ListToolsResult {
                 next_cursor: None,
-                tools: Self::tool_box().list(),
+                tools: Self::$tool_box().list(),
+                meta: None,
             })
         }
 
@@ -0,0 +1,199 @@
+//! Validate the structured content of tool calls against their output schemas.
+//!
+//! Only the keywords generated by `schemars` are checked: `$ref` to the local definitions, `type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`, `allOf`, `anyOf` and `oneOf`.
+//! Other keywords, like `format` or `minimum`, are ignored.
+use serde_json::Value;
+
+use crate::model::JsonObject;
+
+/// Validate the value against the schema, the error describes the first violation found.
+pub(crate) fn validate(schema: &JsonObject, value: &Value) -> Result<(), String> {
+    Validator { root: schema }.validate_object(schema, value, "#")
+}
+
+struct Validator<'a> {
+    root: &'a JsonObject,
+}
+
+impl Validator<'_> {
+    fn validate(&self, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        match schema {
+            Value::Bool(true) => Ok(()),
+            Value::Bool(false) => Err(format!("{path}: no value is allowed")),
+            Value::Object(schema) => self.validate_object(schema, value, path),
+            // not a valid schema, nothing to check
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_object(
+        &self,
+        schema: &JsonObject,
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        if let Some(schema) = reference.and_then(|reference| self.resolve(reference)) {
+            self.validate(schema, value, path)?;
+        }
+        if let Some(expected) = schema.get("type") {
+            let matched = match expected {
+                Value::String(expected) => type_matches(expected, value),
+                Value::Array(expected) => expected
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|expected| type_matches(expected, value)),
+                _ => true,
+            };
+            if !matched {
+                return Err(format!("{path}: expect type {expected}, got {value}"));
+            }
+        }
+        let variants = schema.get("enum").and_then(Value::as_array);
+        if let Some(variants) = variants.filter(|variants| !variants.contains(value)) {
+            return Err(format!("{path}: {value} is not one of {variants:?}"));
+        }
+        if let Some(constant) = schema.get("const").filter(|constant| *constant != value) {
+            return Err(format!("{path}: expect {constant}, got {value}"));
+        }
+        if let Value::Object(object) = value {
+            self.validate_properties(schema, object, path)?;
+        }
+        if let Value::Array(array) = value {
+            match schema.get("items") {
+                Some(Value::Array(items)) => {
+                    for (index, (item, value)) in items.iter().zip(array).enumerate() {
+                        self.validate(item, value, &format!("{path}/{index}"))?;
+                    }
+                }
+                Some(items) => {
+                    for (index, value) in array.iter().enumerate() {
+                        self.validate(items, value, &format!("{path}/{index}"))?;
+                    }
+                }
+                None => {}
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("allOf") {
+            for schema in schemas {
+                self.validate(schema, value, path)?;
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+            let matched = schemas
+                .iter()
+                .any(|schema| self.validate(schema, value, path).is_ok());
+            if !matched {
+                return Err(format!("{path}: {value} matches none of the anyOf schemas"));
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+            let matched = schemas
+                .iter()
+                .filter(|schema| self.validate(schema, value, path).is_ok())
+                .count();
+            if matched != 1 {
+                return Err(format!(
+                    "{path}: {value} matches {matched} of the oneOf schemas, expect exactly 1"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_properties(
+        &self,
+        schema: &JsonObject,
+        object: &JsonObject,
+        path: &str,
+    ) -> Result<(), String> {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{path}: missing required property {key}"));
+                }
+            }
+        }
+        for (key, value) in object {
+            let property = properties
+                .and_then(|properties| properties.get(key))
+                .or_else(|| schema.get("additionalProperties"));
+            if let Some(property) = property {
+                self.validate(property, value, &format!("{path}/{key}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a reference to `#/definitions/{name}` or `#/$defs/{name}`
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        let (key, name) = reference.strip_prefix("#/")?.split_once('/')?;
+        self.root.get(key)?.get(name)
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        // unknown type, don't reject the value
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_structured_content() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "temperature": { "type": "number" },
+                "unit": { "$ref": "#/$defs/Unit" },
+                "forecast": { "type": "array", "items": { "type": ["integer", "null"] } }
+            },
+            "required": ["city", "temperature"],
+            "additionalProperties": false,
+            "$defs": {
+                "Unit": { "type": "string", "enum": ["celsius", "fahrenheit"] }
+            }
+        });
+        let schema = schema.as_object().expect("object schema");
+        let valid = json!({
+            "city": "Paris",
+            "temperature": 21.5,
+            "unit": "celsius",
+            "forecast": [20, null, 23]
+        });
+        assert_eq!(validate(schema, &valid), Ok(()));
+
+        let invalid = [
+            json!({ "city": "Paris" }),
+            json!({ "city": "Paris", "temperature": "hot" }),
+            json!({ "city": "Paris", "temperature": 21, "unit": "kelvin" }),
+            json!({ "city": "Paris", "temperature": 21, "forecast": [20.5] }),
+            json!({ "city": "Paris", "temperature": 21, "humidity": 40 }),
+            json!([]),
+        ];
+        for value in invalid {
+            assert!(
+                validate(schema, &value).is_err(),
+                "{value} should be invalid"
+            );
+        }
+    }
+}
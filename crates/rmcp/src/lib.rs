@@ -1,5 +1,6 @@
 #![doc = include_str!("../../../README.md")]
-mod error;
+/// The error sent to the peer, and how the other errors are converted into it
+pub mod error;
 pub use error::Error;
 
 /// Basic data types in MCP specification
@@ -9,16 +10,21 @@ pub mod service;
 #[cfg(any(feature = "client", feature = "server"))]
 pub use service::{Peer, Service, ServiceError};
 #[cfg(feature = "client")]
-pub use service::{RoleClient, serve_client};
+pub use service::{
+    RoleClient, serve_client, serve_client_no_init, serve_client_resumed, serve_client_with_options,
+};
 #[cfg(feature = "server")]
-pub use service::{RoleServer, serve_server};
+pub use service::{RoleServer, serve_server, serve_server_with_config};
 
 #[cfg(feature = "client")]
 pub use handler::client::{ClientHandler, ClientHandlerService};
 #[cfg(feature = "server")]
 pub use handler::server::{ServerHandler, ServerHandlerService};
 
+pub mod backoff;
 pub mod handler;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod transport;
 
 #[cfg(all(feature = "macros", feature = "server"))]
@@ -27,7 +33,7 @@ pub use rmcp_macros::tool;
 // re-export
 #[cfg(all(feature = "macros", feature = "server"))]
 pub use paste::paste;
-#[cfg(all(feature = "macros", feature = "server"))]
+#[cfg(any(all(feature = "macros", feature = "server"), feature = "schemars"))]
 pub use schemars;
 #[cfg(feature = "macros")]
 pub use serde;
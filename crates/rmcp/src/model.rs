@@ -1,16 +1,38 @@
 use std::{borrow::Cow, sync::Arc};
 mod annotaded;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "base64")]
+mod binary;
 mod capabilities;
 mod content;
+mod cursor;
+mod mime;
 mod prompt;
+#[cfg(feature = "experimental")]
+mod range;
 mod resource;
+#[cfg(feature = "schemars")]
+mod schema;
 mod tool;
+#[cfg(feature = "experimental")]
+mod tool_page;
 
 pub use annotaded::*;
+#[cfg(feature = "base64")]
+pub use binary::*;
 pub use capabilities::*;
 pub use content::*;
+pub use cursor::*;
+pub use mime::*;
 pub use prompt::*;
+#[cfg(feature = "experimental")]
+pub use range::*;
 pub use resource::*;
+#[cfg(feature = "schemars")]
+pub use schema::{const_string_schema, protocol_schema};
+#[cfg(feature = "experimental")]
+pub use tool_page::*;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -39,7 +61,10 @@ macro_rules! object {
     };
 }
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy, Eq)]
-#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    any(feature = "server", feature = "schemars"),
+    derive(schemars::JsonSchema)
+)]
 pub struct EmptyObject {}
 
 pub trait ConstString: Default {
@@ -55,6 +80,8 @@ macro_rules! const_string {
             const VALUE: &str = $value;
         }
 
+        $crate::const_string_schema!($name = $value);
+
         impl serde::Serialize for $name {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where
@@ -84,6 +111,36 @@ macro_rules! const_string {
     };
 }
 
+#[cfg(feature = "schemars")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! const_string_schema {
+    ($name:ident = $value:literal) => {
+        impl $crate::schemars::JsonSchema for $name {
+            fn is_referenceable() -> bool {
+                false
+            }
+
+            fn schema_name() -> String {
+                stringify!($name).to_owned()
+            }
+
+            fn json_schema(
+                _: &mut $crate::schemars::r#gen::SchemaGenerator,
+            ) -> $crate::schemars::schema::Schema {
+                $crate::model::const_string_schema($value)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "schemars"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! const_string_schema {
+    ($name:ident = $value:literal) => {};
+}
+
 const_string!(JsonRpcVersion2_0 = "2.0");
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -97,6 +154,26 @@ impl Default for ProtocolVersion {
 impl ProtocolVersion {
     pub const LATEST: Self = Self(Cow::Borrowed("2024-11-05"));
     pub const V_2024_11_05: Self = Self::LATEST;
+    pub const V_2025_03_26: Self = Self(Cow::Borrowed("2025-03-26"));
+}
+
+/// The versions are dates, so they're ordered as strings
+impl PartialOrd for ProtocolVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ProtocolVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
 impl Serialize for ProtocolVersion {
@@ -117,6 +194,7 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
         #[allow(clippy::single_match)]
         match s.as_str() {
             "2024-11-05" => return Ok(ProtocolVersion::V_2024_11_05),
+            "2025-03-26" => return Ok(ProtocolVersion::V_2025_03_26),
             _ => {}
         }
         Ok(ProtocolVersion(Cow::Owned(s)))
@@ -124,7 +202,8 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
 }
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum NumberOrString {
-    Number(u32),
+    /// Any integer JSON-RPC allows, as long as it fits in an `i64`
+    Number(i64),
     String(Arc<str>),
 }
 
@@ -156,10 +235,9 @@ impl<'de> Deserialize<'de> for NumberOrString {
     {
         let value: Value = Deserialize::deserialize(deserializer)?;
         match value {
-            Value::Number(n) => Ok(NumberOrString::Number(
-                n.as_u64()
-                    .ok_or(serde::de::Error::custom("Expect an integer"))? as u32,
-            )),
+            Value::Number(n) => Ok(NumberOrString::Number(n.as_i64().ok_or(
+                serde::de::Error::custom("Expect an integer in the i64 range"),
+            )?)),
             Value::String(s) => Ok(NumberOrString::String(s.into())),
             _ => Err(serde::de::Error::custom("Expect number or string")),
         }
@@ -169,6 +247,7 @@ impl<'de> Deserialize<'de> for NumberOrString {
 pub type RequestId = NumberOrString;
 pub type ProgressToken = NumberOrString;
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct WithMeta<P = JsonObject, M = ()> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub _meta: Option<M>,
@@ -176,35 +255,164 @@ pub struct WithMeta<P = JsonObject, M = ()> {
     pub inner: P,
 }
 
+/// The `_meta` of a request
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RequestMeta {
-    progress_token: ProgressToken,
+    /// Ask the peer to send `notifications/progress` with this token while it handles the request
+    pub progress_token: ProgressToken,
+}
+
+impl RequestMeta {
+    pub fn new(progress_token: ProgressToken) -> Self {
+        Self { progress_token }
+    }
+}
+
+/// Get the progress token of a request, set in its `_meta` when the peer asks for progress notifications
+pub trait GetProgressToken {
+    fn progress_token(&self) -> Option<ProgressToken>;
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Request<M = String, P = Option<WithMeta<JsonObject, RequestMeta>>> {
     pub method: M,
     // #[serde(skip_serializing_if = "Option::is_none")]
     pub params: P,
 }
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RequestNoParam<M = String> {
     pub method: M,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Notification<M = String, P = Option<WithMeta<JsonObject, JsonObject>>> {
     pub method: M,
     pub params: P,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NotificationNoParam<M = String> {
     pub method: M,
 }
 
+/// Get the method of a request or a notification
+pub trait GetMethod {
+    fn method(&self) -> &str;
+    /// The method if it's a known one, so that it can be kept without copying it, `None` for an unknown method
+    fn static_method(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<M: ConstString, P> GetMethod for Request<M, P> {
+    fn method(&self) -> &str {
+        M::VALUE
+    }
+    fn static_method(&self) -> Option<&'static str> {
+        Some(M::VALUE)
+    }
+}
+
+impl<M: ConstString> GetMethod for RequestNoParam<M> {
+    fn method(&self) -> &str {
+        M::VALUE
+    }
+    fn static_method(&self) -> Option<&'static str> {
+        Some(M::VALUE)
+    }
+}
+
+impl<M: ConstString, P> GetMethod for Notification<M, P> {
+    fn method(&self) -> &str {
+        M::VALUE
+    }
+    fn static_method(&self) -> Option<&'static str> {
+        Some(M::VALUE)
+    }
+}
+
+impl<M: ConstString> GetMethod for NotificationNoParam<M> {
+    fn method(&self) -> &str {
+        M::VALUE
+    }
+    fn static_method(&self) -> Option<&'static str> {
+        Some(M::VALUE)
+    }
+}
+
+/// The method of a request or a notification type
+pub trait ConstMethod {
+    const METHOD: &str;
+}
+
+impl<M: ConstString, P> ConstMethod for Request<M, P> {
+    const METHOD: &str = M::VALUE;
+}
+
+impl<M: ConstString> ConstMethod for RequestNoParam<M> {
+    const METHOD: &str = M::VALUE;
+}
+
+impl<M: ConstString, P> ConstMethod for Notification<M, P> {
+    const METHOD: &str = M::VALUE;
+}
+
+impl<M: ConstString> ConstMethod for NotificationNoParam<M> {
+    const METHOD: &str = M::VALUE;
+}
+
+/// Get the `_meta` of a result, where some servers attach e.g. a trace id
+pub trait GetMeta {
+    fn meta(&self) -> Option<&JsonObject>;
+    fn meta_mut(&mut self) -> &mut Option<JsonObject>;
+    /// Set a field of the `_meta`, replacing its previous value
+    fn with_meta_field(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self
+    where
+        Self: Sized,
+    {
+        self.meta_mut()
+            .get_or_insert_with(JsonObject::new)
+            .insert(key.into(), value.into());
+        self
+    }
+    /// Get a field of the `_meta`
+    fn meta_field(&self, key: &str) -> Option<&Value> {
+        self.meta().and_then(|meta| meta.get(key))
+    }
+}
+
+macro_rules! impl_get_meta {
+    ($($t: ty),* $(,)?) => {
+        $(
+            impl GetMeta for $t {
+                fn meta(&self) -> Option<&JsonObject> {
+                    self.meta.as_ref()
+                }
+                fn meta_mut(&mut self) -> &mut Option<JsonObject> {
+                    &mut self.meta
+                }
+            }
+        )*
+    };
+}
+
+impl GetMeta for DefaultResponse {
+    fn meta(&self) -> Option<&JsonObject> {
+        self._meta.as_ref()
+    }
+    fn meta_mut(&mut self) -> &mut Option<JsonObject> {
+        &mut self._meta
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JsonRpcRequest<R = Request> {
     pub jsonrpc: JsonRpcVersion2_0,
     pub id: RequestId,
@@ -213,6 +421,7 @@ pub struct JsonRpcRequest<R = Request> {
 }
 type DefaultResponse = WithMeta<JsonObject, JsonObject>;
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JsonRpcResponse<R = DefaultResponse> {
     pub jsonrpc: JsonRpcVersion2_0,
     pub id: RequestId,
@@ -220,21 +429,54 @@ pub struct JsonRpcResponse<R = DefaultResponse> {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JsonRpcError {
     pub jsonrpc: JsonRpcVersion2_0,
     pub id: RequestId,
     pub error: ErrorData,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JsonRpcNotification<N = Notification> {
     pub jsonrpc: JsonRpcVersion2_0,
     #[serde(flatten)]
     pub notification: N,
 }
 
+impl<'de, N: Deserialize<'de>> Deserialize<'de> for JsonRpcNotification<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(bound = "N: Deserialize<'de>")]
+        struct Raw<N> {
+            jsonrpc: JsonRpcVersion2_0,
+            #[serde(default)]
+            id: Option<serde::de::IgnoredAny>,
+            #[serde(flatten)]
+            notification: N,
+        }
+        let Raw {
+            jsonrpc,
+            id,
+            notification,
+        } = Raw::deserialize(deserializer)?;
+        // a message with an id is a request, even if it's not a valid one
+        if id.is_some() {
+            return Err(serde::de::Error::custom("Expect no id in a notification"));
+        }
+        Ok(JsonRpcNotification {
+            jsonrpc,
+            notification,
+        })
+    }
+}
+
 // Standard JSON-RPC error codes
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(transparent)]
 pub struct ErrorCode(pub i32);
 
@@ -249,6 +491,7 @@ impl ErrorCode {
 
 /// Error information for JSON-RPC error responses.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ErrorData {
     /// The error type that occurred.
     pub code: ErrorCode,
@@ -295,6 +538,7 @@ impl ErrorData {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum JsonRpcMessage<Req = Request, Resp = DefaultResponse, Noti = Notification> {
     Request(JsonRpcRequest<Req>),
@@ -402,6 +646,7 @@ impl From<EmptyResult> for () {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CancelledNotificationParam {
     pub request_id: RequestId,
@@ -430,6 +675,7 @@ const_string!(InitializedNotificationMethod = "notifications/initialized");
 /// This notification is sent from the client to the server after initialization has finished.
 pub type InitializedNotification = NotificationNoParam<InitializedNotificationMethod>;
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct InitializeRequestParam {
     pub protocol_version: ProtocolVersion,
@@ -438,6 +684,7 @@ pub struct InitializeRequestParam {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct InitializeResult {
     pub protocol_version: ProtocolVersion,
@@ -445,6 +692,8 @@ pub struct InitializeResult {
     pub server_info: Implementation,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
 }
 
 pub type ServerInfo = InitializeResult;
@@ -457,6 +706,7 @@ impl Default for ServerInfo {
             capabilities: ServerCapabilities::default(),
             server_info: Implementation::from_build_env(),
             instructions: None,
+            meta: None,
         }
     }
 }
@@ -472,6 +722,7 @@ impl Default for ClientInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Implementation {
     pub name: String,
     pub version: String,
@@ -493,10 +744,11 @@ impl Implementation {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PaginatedRequestParam {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor: Option<String>,
+    pub cursor: Option<Cursor>,
 }
 
 const_string!(PingRequestMethod = "ping");
@@ -504,6 +756,7 @@ pub type PingRequest = RequestNoParam<PingRequestMethod>;
 
 const_string!(ProgressNotificationMethod = "notifications/progress");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressNotificationParam {
     pub progress_token: ProgressToken,
@@ -512,22 +765,99 @@ pub struct ProgressNotificationParam {
     /// Total number of items to process (or total progress required), if known
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total: Option<u32>,
+    /// A human readable description of the progress
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 pub type ProgressNotification = Notification<ProgressNotificationMethod, ProgressNotificationParam>;
 
-pub type Cursor = String;
+impl ProgressNotification {
+    pub fn new(progress_token: ProgressToken, progress: u32, total: Option<u32>) -> Self {
+        Self {
+            method: ProgressNotificationMethod,
+            params: ProgressNotificationParam {
+                progress_token,
+                progress,
+                total,
+                message: None,
+            },
+        }
+    }
+}
+
+impl ServerNotification {
+    /// Create a [`ProgressNotification`]
+    pub fn progress(progress_token: ProgressToken, progress: u32, total: Option<u32>) -> Self {
+        ProgressNotification::new(progress_token, progress, total).into()
+    }
+}
+
+impl ClientNotification {
+    /// Create a [`ProgressNotification`]
+    pub fn progress(progress_token: ProgressToken, progress: u32, total: Option<u32>) -> Self {
+        ProgressNotification::new(progress_token, progress, total).into()
+    }
+}
+
+/// Track the progress sent for a token, to make sure it never goes backwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressTracker {
+    progress_token: ProgressToken,
+    last: Option<u32>,
+}
+
+impl ProgressTracker {
+    pub fn new(progress_token: ProgressToken) -> Self {
+        Self {
+            progress_token,
+            last: None,
+        }
+    }
+
+    pub fn progress_token(&self) -> &ProgressToken {
+        &self.progress_token
+    }
+
+    /// The last progress sent
+    pub fn last(&self) -> Option<u32> {
+        self.last
+    }
+
+    /// Create the notification of a new progress, an `invalid_params` error is returned if it's less than the last one
+    pub fn advance(
+        &mut self,
+        progress: u32,
+        total: Option<u32>,
+    ) -> Result<ProgressNotification, crate::Error> {
+        if let Some(last) = self.last.filter(|last| progress < *last) {
+            return Err(crate::Error::invalid_params(
+                "progress must not decrease",
+                Some(serde_json::json!({ "last": last, "progress": progress })),
+            ));
+        }
+        self.last = Some(progress);
+        Ok(ProgressNotification::new(
+            self.progress_token.clone(),
+            progress,
+            total,
+        ))
+    }
+}
 
 macro_rules! paginated_result {
     ($t:ident {
         $i_item: ident: $t_item: ty
     }) => {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+        #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
         #[serde(rename_all = "camelCase")]
         pub struct $t {
             #[serde(skip_serializing_if = "Option::is_none")]
             pub next_cursor: Option<Cursor>,
             pub $i_item: $t_item,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
         }
     };
 }
@@ -547,14 +877,29 @@ paginated_result!(ListResourceTemplatesResult {
 
 const_string!(ReadResourceRequestMethod = "resources/read");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ReadResourceRequestParam {
     pub uri: String,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
+}
+
+impl ReadResourceRequestParam {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            meta: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ReadResourceResult {
     pub contents: Vec<ResourceContents>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
 }
 
 pub type ReadResourceRequest = Request<ReadResourceRequestMethod, ReadResourceRequestParam>;
@@ -565,6 +910,7 @@ pub type ResourceListChangedNotification =
 
 const_string!(SubscribeRequestMethod = "resources/subscribe");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SubscribeRequestParam {
     pub uri: String,
@@ -573,6 +919,7 @@ pub type SubscribeRequest = Request<SubscribeRequestMethod, SubscribeRequestPara
 
 const_string!(UnsubscribeRequestMethod = "resources/unsubscribe");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct UnsubscribeRequestParam {
     pub uri: String,
@@ -581,6 +928,7 @@ pub type UnsubscribeRequest = Request<UnsubscribeRequestMethod, UnsubscribeReque
 
 const_string!(ResourceUpdatedNotificationMethod = "notifications/resources/updated");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceUpdatedNotificationParam {
     pub uri: String,
@@ -596,6 +944,7 @@ paginated_result!(ListPromptsResult {
 
 const_string!(GetPromptRequestMethod = "prompts/get");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct GetPromptRequestParam {
     pub name: String,
@@ -610,7 +959,8 @@ pub type PromptListChangedNotification = NotificationNoParam<PromptListChangedNo
 const_string!(ToolListChangedNotificationMethod = "notifications/tools/list_changed");
 pub type ToolListChangedNotification = NotificationNoParam<ToolListChangedNotificationMethod>;
 // 日志相关
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum LoggingLevel {
     Debug,
@@ -625,6 +975,7 @@ pub enum LoggingLevel {
 
 const_string!(SetLevelRequestMethod = "logging/setLevel");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct SetLevelRequestParam {
     pub level: LoggingLevel,
@@ -633,6 +984,7 @@ pub type SetLevelRequest = Request<SetLevelRequestMethod, SetLevelRequestParam>;
 
 const_string!(LoggingMessageNotificationMethod = "notifications/message");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct LoggingMessageNotificationParam {
     pub level: LoggingLevel,
@@ -640,6 +992,41 @@ pub struct LoggingMessageNotificationParam {
     pub logger: Option<String>,
     pub data: Value,
 }
+
+impl LoggingMessageNotificationParam {
+    /// A log message of a text
+    pub fn text(level: LoggingLevel, logger: Option<&str>, text: impl Into<String>) -> Self {
+        Self {
+            level,
+            logger: logger.map(str::to_owned),
+            data: Value::String(text.into()),
+        }
+    }
+
+    /// A log message of structured data, e.g. the fields of an event
+    pub fn structured<T: Serialize>(
+        level: LoggingLevel,
+        logger: Option<&str>,
+        data: T,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            level,
+            logger: logger.map(str::to_owned),
+            data: serde_json::to_value(data)?,
+        })
+    }
+
+    /// The data if it's a text
+    pub fn text_data(&self) -> Option<&str> {
+        self.data.as_str()
+    }
+
+    /// Deserialize the data, e.g. into the type of the events of a structured log
+    pub fn data_as<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(&self.data)
+    }
+}
+
 pub type LoggingMessageNotification =
     Notification<LoggingMessageNotificationMethod, LoggingMessageNotificationParam>;
 
@@ -647,6 +1034,7 @@ const_string!(CreateMessageRequestMethod = "sampling/createMessage");
 pub type CreateMessageRequest = Request<CreateMessageRequestMethod, CreateMessageRequestParam>;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum Role {
     User,
@@ -654,12 +1042,14 @@ pub enum Role {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SamplingMessage {
     pub role: Role,
     pub content: Content,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CreateMessageRequestParam {
     pub messages: Vec<SamplingMessage>,
@@ -676,9 +1066,40 @@ pub struct CreateMessageRequestParam {
     pub stop_sequences: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Value>,
+    /// Set a progress token to receive the partial text of the `experimental` streaming,
+    /// see `PartialMessageStream`
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
+}
+
+impl CreateMessageRequestParam {
+    /// Check the common mistakes before sending the request to the client.
+    ///
+    /// - `messages` must not be empty
+    /// - `max_tokens` must be greater than 0
+    /// - priorities in `model_preferences` must be in `[0, 1]`
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        if self.messages.is_empty() {
+            return Err(crate::Error::invalid_params(
+                "sampling messages must not be empty",
+                None,
+            ));
+        }
+        if self.max_tokens == 0 {
+            return Err(crate::Error::invalid_params(
+                "max_tokens must be greater than 0",
+                None,
+            ));
+        }
+        if let Some(model_preferences) = &self.model_preferences {
+            model_preferences.validate()?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ModelPreferences {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -691,13 +1112,97 @@ pub struct ModelPreferences {
     pub intelligence_priority: Option<f32>,
 }
 
+impl ModelPreferences {
+    /// Check that all the priorities are in `[0, 1]`
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        for (name, priority) in [
+            ("cost_priority", self.cost_priority),
+            ("speed_priority", self.speed_priority),
+            ("intelligence_priority", self.intelligence_priority),
+        ] {
+            if let Some(priority) = priority.filter(|p| !(0.0..=1.0).contains(p)) {
+                return Err(crate::Error::invalid_params(
+                    format!("{name} must be between 0 and 1, got {priority}"),
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`ModelPreferences`]
+///
+/// ```rust
+/// # use rmcp::model::ModelPreferences;
+/// let preferences = ModelPreferences::builder()
+///     .hint("claude-3")
+///     .cost(0.2)
+///     .speed(0.8)
+///     .intelligence(0.9)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModelPreferencesBuilder {
+    hints: Vec<ModelHint>,
+    cost_priority: Option<f32>,
+    speed_priority: Option<f32>,
+    intelligence_priority: Option<f32>,
+}
+
+impl ModelPreferences {
+    pub fn builder() -> ModelPreferencesBuilder {
+        ModelPreferencesBuilder::default()
+    }
+}
+
+impl ModelPreferencesBuilder {
+    /// Clamp the priority into `[0, 1]`, `NaN` will be treated as 0.
+    fn clamp_priority(priority: f32) -> f32 {
+        if priority.is_nan() {
+            0.0
+        } else {
+            priority.clamp(0.0, 1.0)
+        }
+    }
+    /// Add a model name hint, hints are evaluated in order.
+    pub fn hint(mut self, name: impl Into<String>) -> Self {
+        self.hints.push(ModelHint {
+            name: Some(name.into()),
+        });
+        self
+    }
+    pub fn cost(mut self, priority: f32) -> Self {
+        self.cost_priority = Some(Self::clamp_priority(priority));
+        self
+    }
+    pub fn speed(mut self, priority: f32) -> Self {
+        self.speed_priority = Some(Self::clamp_priority(priority));
+        self
+    }
+    pub fn intelligence(mut self, priority: f32) -> Self {
+        self.intelligence_priority = Some(Self::clamp_priority(priority));
+        self
+    }
+    pub fn build(self) -> ModelPreferences {
+        ModelPreferences {
+            hints: (!self.hints.is_empty()).then_some(self.hints),
+            cost_priority: self.cost_priority,
+            speed_priority: self.speed_priority,
+            intelligence_priority: self.intelligence_priority,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ModelHint {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CompleteRequestParam {
     pub r#ref: Reference,
@@ -707,6 +1212,7 @@ pub struct CompleteRequestParam {
 pub type CompleteRequest = Request<CompleteRequestMethod, CompleteRequestParam>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CompletionInfo {
     pub values: Vec<String>,
@@ -717,12 +1223,16 @@ pub struct CompletionInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CompleteResult {
     pub completion: CompletionInfo,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type")]
 pub enum Reference {
     #[serde(rename = "ref/resource")]
@@ -732,17 +1242,20 @@ pub enum Reference {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ResourceReference {
     pub uri: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PromptReference {
     pub name: String,
 }
 
 const_string!(CompleteRequestMethod = "completion/complete");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ArgumentInfo {
     pub name: String,
@@ -751,45 +1264,169 @@ pub struct ArgumentInfo {
 
 // 根目录相关
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Root {
     pub uri: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
 
+impl Root {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Check the uri is a `file://` URL, the only roots the protocol allows
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        let is_file_url = self
+            .uri
+            .split_once("://")
+            .is_some_and(|(scheme, path)| scheme.eq_ignore_ascii_case("file") && !path.is_empty());
+        if !is_file_url {
+            return Err(crate::Error::invalid_params(
+                format!("root {} is not a file:// URL", self.uri),
+                None,
+            ));
+        }
+        Ok(())
+    }
+}
+
 const_string!(ListRootsRequestMethod = "roots/list");
 pub type ListRootsRequest = RequestNoParam<ListRootsRequestMethod>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ListRootsResult {
     pub roots: Vec<Root>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
+}
+
+impl ListRootsResult {
+    /// Check each root with [`Root::validate`]
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        self.roots.iter().try_for_each(Root::validate)
+    }
 }
 
 const_string!(RootsListChangedNotificationMethod = "notifications/roots/list_changed");
 pub type RootsListChangedNotification = NotificationNoParam<RootsListChangedNotificationMethod>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CallToolResult {
     pub content: Vec<Content>,
+    /// The machine-readable output, it should conform to the `output_schema` of the tool if declared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
 }
 
 impl CallToolResult {
     pub fn success(content: Vec<Content>) -> Self {
         CallToolResult {
             content,
+            structured_content: None,
             is_error: Some(false),
+            meta: None,
         }
     }
     pub fn error(content: Vec<Content>) -> Self {
         CallToolResult {
             content,
+            structured_content: None,
             is_error: Some(true),
+            meta: None,
         }
     }
+    /// A successful result with structured content, the serialized JSON is also added as a text content
+    /// for the clients which don't support structured content.
+    pub fn structured(value: Value) -> Self {
+        CallToolResult {
+            content: vec![Content::text(value.to_string())],
+            structured_content: Some(value),
+            is_error: Some(false),
+            meta: None,
+        }
+    }
+    /// Check the invariants a hand-built result may break, which the constructors keep.
+    ///
+    /// The server checks the results of the tools with it in debug builds, and logs a warning.
+    pub fn validate(&self) -> Result<(), CallToolResultError> {
+        let is_error = self.is_error == Some(true);
+        if is_error && self.content.is_empty() {
+            return Err(CallToolResultError::EmptyError);
+        }
+        if is_error && self.structured_content.is_some() {
+            return Err(CallToolResultError::StructuredError);
+        }
+        if self.structured_content.is_some() && self.content.is_empty() {
+            return Err(CallToolResultError::StructuredWithoutContent);
+        }
+        Ok(())
+    }
+    /// Convert into a [`Result`], it's an error if `is_error` is `Some(true)`.
+    ///
+    /// ```rust,ignore
+    /// let content = client.call_tool(param).await?.into_result()?;
+    /// ```
+    pub fn into_result(self) -> Result<Vec<Content>, ToolCallError> {
+        if self.is_error == Some(true) {
+            Err(ToolCallError {
+                content: self.content,
+            })
+        } else {
+            Ok(self.content)
+        }
+    }
+}
+
+/// A suspicious [`CallToolResult`], see [`CallToolResult::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum CallToolResultError {
+    #[error("the result is an error without content to tell what failed")]
+    EmptyError,
+    #[error(
+        "the result is an error with structured content, which is the output of a successful call"
+    )]
+    StructuredError,
+    #[error(
+        "the result has structured content without content, for the clients which don't support structured content"
+    )]
+    StructuredWithoutContent,
+}
+
+/// A tool call which reported an error, carrying the content returned by the tool
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("tool call failed: {}", self.text())]
+pub struct ToolCallError {
+    pub content: Vec<Content>,
+}
+
+impl ToolCallError {
+    /// Concatenate all the text contents, separated by newlines
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|content| content.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 const_string!(ListToolsRequestMethod = "tools/list");
@@ -802,16 +1439,51 @@ paginated_result!(
 
 const_string!(CallToolRequestMethod = "tools/call");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CallToolRequestParam {
     pub name: Cow<'static, str>,
+    /// `None` when the arguments are absent or `null`, both are sent as absent
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<JsonObject>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
+}
+
+impl CallToolRequestParam {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            arguments: None,
+            meta: None,
+        }
+    }
+    pub fn with_arguments(mut self, arguments: JsonObject) -> Self {
+        self.arguments = Some(arguments);
+        self
+    }
+    /// Ask the server to report the progress of the call, with `notifications/progress` carrying this token
+    pub fn with_progress_token(mut self, progress_token: ProgressToken) -> Self {
+        let token = serde_json::to_value(progress_token).expect("a token serializes into json");
+        self.meta
+            .get_or_insert_with(JsonObject::new)
+            .insert("progressToken".to_owned(), token);
+        self
+    }
+    /// Mark the retries of the call as duplicates, with the same `key` in all of them,
+    /// see [`IdempotencyCache`](crate::handler::server::idempotency::IdempotencyCache)
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.meta
+            .get_or_insert_with(JsonObject::new)
+            .insert("idempotencyKey".to_owned(), Value::String(key.into()));
+        self
+    }
 }
 
 pub type CallToolRequest = Request<CallToolRequestMethod, CallToolRequestParam>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CreateMessageResult {
     pub model: String,
@@ -819,21 +1491,59 @@ pub struct CreateMessageResult {
     pub stop_reason: Option<String>,
     #[serde(flatten)]
     pub message: SamplingMessage,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
 }
 
 impl CreateMessageResult {
     pub const STOP_REASON_END_TURN: &str = "endTurn";
     pub const STOP_REASON_END_SEQUENCE: &str = "stopSequence";
     pub const STOP_REASON_END_MAX_TOKEN: &str = "maxTokens";
+
+    /// The text of the message, `None` if it's not a text content
+    pub fn text(&self) -> Option<&str> {
+        self.message
+            .content
+            .as_text()
+            .map(|content| content.text.as_str())
+    }
+
+    /// Check that the message is from the assistant, a sampling result must not ask for a user turn back
+    pub fn validate(&self) -> Result<(), crate::Error> {
+        if self.message.role != Role::Assistant {
+            return Err(crate::Error::invalid_params(
+                "sampling result must be from the assistant",
+                Some(serde_json::json!({ "role": self.message.role })),
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct GetPromptResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub messages: Vec<PromptMessage>,
-}
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
+}
+
+impl_get_meta!(
+    InitializeResult,
+    CompleteResult,
+    ListResourcesResult,
+    ListResourceTemplatesResult,
+    ReadResourceResult,
+    ListPromptsResult,
+    GetPromptResult,
+    ListToolsResult,
+    CallToolResult,
+    ListRootsResult,
+    CreateMessageResult,
+);
 
 macro_rules! ts_union {
     (
@@ -841,11 +1551,151 @@ macro_rules! ts_union {
             $(|)?$($V: ident)|*;
     ) => {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+        #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
         #[serde(untagged)]
         pub enum $U {
             $($V($V),)*
         }
+        $(ts_union!(@convert $U::$V($V));)*
     };
+    (
+        export type $U: ident =
+            $(|)?$($V: ident)|*;
+        unknown $Unknown: ident($T: ty);
+    ) => {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+        #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+        #[serde(untagged)]
+        pub enum $U {
+            $($V($V),)*
+            /// Anything not matching the variants above, it must be the last one to be tried
+            $Unknown($T),
+        }
+        $(ts_union!(@convert $U::$V($V));)*
+        ts_union!(@convert $U::$Unknown($T));
+
+        impl GetMethod for $U {
+            fn method(&self) -> &str {
+                match self {
+                    $($U::$V(value) => value.method(),)*
+                    $U::$Unknown(value) => value.method(),
+                }
+            }
+            fn static_method(&self) -> Option<&'static str> {
+                match self {
+                    $($U::$V(value) => value.static_method(),)*
+                    $U::$Unknown(_) => None,
+                }
+            }
+        }
+
+        impl $U {
+            /// Why a message with a known method ended up unknown, i.e. the error of deserializing it
+            /// as its variant, e.g. a missing param, `None` if the method is unknown
+            pub fn invalid_params_error(unknown: &$T) -> Option<serde_json::Error> {
+                $(
+                    if unknown.method() == <$V as ConstMethod>::METHOD {
+                        let value = serde_json::to_value(unknown).ok()?;
+                        return serde_json::from_value::<$V>(value).err();
+                    }
+                )*
+                None
+            }
+        }
+    };
+    (@convert $U: ident :: $V: ident($T: ty)) => {
+        impl From<$T> for $U {
+            fn from(value: $T) -> Self {
+                $U::$V(value)
+            }
+        }
+
+        impl TryFrom<$U> for $T {
+            type Error = $U;
+            fn try_from(value: $U) -> Result<Self, Self::Error> {
+                if let $U::$V(value) = value {
+                    Ok(value)
+                } else {
+                    Err(value)
+                }
+            }
+        }
+    };
+}
+
+/// A notification whose method is unknown to this crate, e.g. an experimental one.
+///
+/// It's kept as is, so clients, servers and proxies can observe or forward it instead of losing it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct UnknownNotification {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<JsonObject>,
+}
+
+impl UnknownNotification {
+    pub fn new(method: impl Into<String>, params: Option<JsonObject>) -> Self {
+        Self {
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+impl GetMethod for UnknownNotification {
+    fn method(&self) -> &str {
+        &self.method
+    }
+}
+
+/// A request whose method is unknown to this crate, kept as is so a proxy can pass it through.
+///
+/// The request enums are untagged, so the known variants are tried in order before this one:
+/// a request with a known method but malformed params also ends up here instead of failing to deserialize.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct UnknownRequest {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<JsonObject>,
+}
+
+impl UnknownRequest {
+    pub fn new(method: impl Into<String>, params: Option<JsonObject>) -> Self {
+        Self {
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+impl GetMethod for UnknownRequest {
+    fn method(&self) -> &str {
+        &self.method
+    }
+}
+
+/// The raw result of an [`UnknownRequest`].
+///
+/// It's tried after the known results and before [`EmptyResult`], so an empty object is still an [`EmptyResult`],
+/// and an object which happens to match a known result is deserialized as that result.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct UnknownResult(pub JsonObject);
+
+impl<'de> Deserialize<'de> for UnknownResult {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let object = JsonObject::deserialize(deserializer)?;
+        if object.is_empty() {
+            return Err(serde::de::Error::custom("expect a non-empty object"));
+        }
+        Ok(UnknownResult(object))
+    }
 }
 
 ts_union!(
@@ -863,6 +1713,7 @@ ts_union!(
     | UnsubscribeRequest
     | CallToolRequest
     | ListToolsRequest;
+    unknown Unknown(UnknownRequest);
 );
 
 ts_union!(
@@ -871,10 +1722,11 @@ ts_union!(
     | ProgressNotification
     | InitializedNotification
     | RootsListChangedNotification;
+    unknown Unknown(UnknownNotification);
 );
 
 ts_union!(
-    export type ClientResult = CreateMessageResult | ListRootsResult | EmptyResult;
+    export type ClientResult = CreateMessageResult | ListRootsResult | UnknownResult | EmptyResult;
 );
 
 impl ClientResult {
@@ -891,8 +1743,34 @@ ts_union!(
     | PingRequest
     | CreateMessageRequest
     | ListRootsRequest;
+    unknown Unknown(UnknownRequest);
 );
 
+impl GetProgressToken for ClientRequest {
+    fn progress_token(&self) -> Option<ProgressToken> {
+        match self {
+            ClientRequest::CallToolRequest(request) => {
+                let token = request.params.meta.as_ref()?.get("progressToken")?;
+                ProgressToken::deserialize(token).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl GetProgressToken for ServerRequest {
+    fn progress_token(&self) -> Option<ProgressToken> {
+        match self {
+            ServerRequest::CreateMessageRequest(request) => request
+                .params
+                .meta
+                .as_ref()
+                .map(|meta| meta.progress_token.clone()),
+            _ => None,
+        }
+    }
+}
+
 ts_union!(
     export type ServerNotification =
     | CancelledNotification
@@ -902,6 +1780,7 @@ ts_union!(
     | ResourceListChangedNotification
     | ToolListChangedNotification
     | PromptListChangedNotification;
+    unknown Unknown(UnknownNotification);
 );
 
 ts_union!(
@@ -915,6 +1794,7 @@ ts_union!(
     | ReadResourceResult
     | CallToolResult
     | ListToolsResult
+    | UnknownResult
     | EmptyResult
     ;
 );
@@ -928,39 +1808,6 @@ impl ServerResult {
 pub type ServerJsonRpcMessage = JsonRpcMessage<ServerRequest, ServerResult, ServerNotification>;
 pub type ServerMessage = Message<ServerRequest, ServerResult, ServerNotification>;
 
-impl TryInto<CancelledNotification> for ServerNotification {
-    type Error = ServerNotification;
-    fn try_into(self) -> Result<CancelledNotification, Self::Error> {
-        if let ServerNotification::CancelledNotification(t) = self {
-            Ok(t)
-        } else {
-            Err(self)
-        }
-    }
-}
-
-impl TryInto<CancelledNotification> for ClientNotification {
-    type Error = ClientNotification;
-    fn try_into(self) -> Result<CancelledNotification, Self::Error> {
-        if let ClientNotification::CancelledNotification(t) = self {
-            Ok(t)
-        } else {
-            Err(self)
-        }
-    }
-}
-impl From<CancelledNotification> for ServerNotification {
-    fn from(value: CancelledNotification) -> Self {
-        ServerNotification::CancelledNotification(value)
-    }
-}
-
-impl From<CancelledNotification> for ClientNotification {
-    fn from(value: CancelledNotification) -> Self {
-        ClientNotification::CancelledNotification(value)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1010,6 +1857,32 @@ mod tests {
         assert_eq!(json, raw);
     }
 
+    #[test]
+    fn test_request_id_out_of_range() {
+        for id in [u32::MAX as i64 + 1, i64::MAX, -1, i64::MIN] {
+            let raw = json!({
+                "jsonrpc": JsonRpcVersion2_0,
+                "id": id,
+                "method": "ping",
+            });
+            let message: ClientJsonRpcMessage =
+                serde_json::from_value(raw.clone()).expect("valid request");
+            assert!(
+                matches!(&message, ClientJsonRpcMessage::Request(request) if request.id == RequestId::Number(id))
+            );
+            assert_eq!(serde_json::to_value(&message).expect("valid json"), raw);
+        }
+        // a truncated id would answer another request
+        for id in [json!(i64::MAX as u64 + 1), json!(1.5)] {
+            let raw = json!({
+                "jsonrpc": JsonRpcVersion2_0,
+                "id": id,
+                "method": "ping",
+            });
+            serde_json::from_value::<ClientJsonRpcMessage>(raw).expect_err("id out of range");
+        }
+    }
+
     #[test]
     fn test_initial_request_response_serde() {
         let request = json!({
@@ -1092,6 +1965,7 @@ mod tests {
                 capabilities,
                 server_info,
                 instructions,
+                meta: None,
             }) => {
                 assert_eq!(capabilities.logging.unwrap().len(), 0);
                 assert_eq!(capabilities.prompts.unwrap().list_changed, Some(true));
@@ -1112,4 +1986,400 @@ mod tests {
 
         assert_eq!(server_response_json, raw_response_json);
     }
+
+    #[test]
+    fn test_root_validate() {
+        assert!(Root::new("file:///home/user/project").validate().is_ok());
+        assert!(Root::new("FILE://host/share").validate().is_ok());
+        assert!(Root::new("https://example.com").validate().is_err());
+        assert!(Root::new("/home/user/project").validate().is_err());
+        assert!(Root::new("file://").validate().is_err());
+        let result = ListRootsResult {
+            roots: vec![Root::new("file:///a").with_name("a"), Root::new("b")],
+            meta: None,
+        };
+        assert!(result.validate().is_err());
+    }
+
+    #[test]
+    fn test_create_message_param_validate() {
+        let mut param = CreateMessageRequestParam {
+            messages: vec![],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: None,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+            meta: None,
+        };
+        assert!(param.validate().is_err());
+        param.messages.push(SamplingMessage {
+            role: Role::User,
+            content: Content::text("hello"),
+        });
+        assert!(param.validate().is_ok());
+        param.max_tokens = 0;
+        assert!(param.validate().is_err());
+        param.max_tokens = 100;
+        param.model_preferences = Some(ModelPreferences {
+            hints: None,
+            cost_priority: Some(1.5),
+            speed_priority: None,
+            intelligence_priority: None,
+        });
+        let error = param.validate().expect_err("cost priority out of range");
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_model_preferences_builder() {
+        let preferences = ModelPreferences::builder()
+            .hint("claude-3")
+            .hint("gpt-4")
+            .cost(-0.5)
+            .speed(0.8)
+            .intelligence(1.5)
+            .build();
+        assert_eq!(preferences.cost_priority, Some(0.0));
+        assert_eq!(preferences.speed_priority, Some(0.8));
+        assert_eq!(preferences.intelligence_priority, Some(1.0));
+        let hints = preferences.hints.as_ref().expect("hints");
+        assert_eq!(hints[0].name.as_deref(), Some("claude-3"));
+        assert_eq!(hints[1].name.as_deref(), Some("gpt-4"));
+        assert!(preferences.validate().is_ok());
+        let empty = ModelPreferences::builder().speed(f32::NAN).build();
+        assert_eq!(empty.hints, None);
+        assert_eq!(empty.speed_priority, Some(0.0));
+    }
+
+    #[test]
+    fn test_call_tool_result_into_result() {
+        let content = CallToolResult::success(vec![Content::text("42")])
+            .into_result()
+            .expect("success result");
+        assert_eq!(content[0].as_text().expect("text").text, "42");
+
+        let result = CallToolResult {
+            content: vec![Content::text("ok")],
+            structured_content: None,
+            is_error: None,
+            meta: None,
+        };
+        assert!(result.into_result().is_ok());
+
+        let error =
+            CallToolResult::error(vec![Content::text("division"), Content::text("by zero")])
+                .into_result()
+                .expect_err("error result");
+        assert_eq!(error.content.len(), 2);
+        assert_eq!(error.text(), "division\nby zero");
+        assert_eq!(error.to_string(), "tool call failed: division\nby zero");
+    }
+
+    #[test]
+    fn test_call_tool_result_validate() {
+        assert_eq!(CallToolResult::success(vec![]).validate(), Ok(()));
+        assert_eq!(
+            CallToolResult::error(vec![Content::text("failed")]).validate(),
+            Ok(())
+        );
+        assert_eq!(
+            CallToolResult::structured(serde_json::json!({ "sum": 3 })).validate(),
+            Ok(())
+        );
+
+        assert_eq!(
+            CallToolResult::error(vec![]).validate(),
+            Err(CallToolResultError::EmptyError)
+        );
+        let structured_error = CallToolResult {
+            is_error: Some(true),
+            ..CallToolResult::structured(serde_json::json!({ "sum": 3 }))
+        };
+        assert_eq!(
+            structured_error.validate(),
+            Err(CallToolResultError::StructuredError)
+        );
+        let structured_only = CallToolResult {
+            content: vec![],
+            structured_content: Some(serde_json::json!({ "sum": 3 })),
+            is_error: None,
+            meta: None,
+        };
+        assert_eq!(
+            structured_only.validate(),
+            Err(CallToolResultError::StructuredWithoutContent)
+        );
+    }
+
+    #[test]
+    fn test_prompt_message_mixed_content() {
+        let result = GetPromptResult {
+            description: None,
+            messages: vec![
+                PromptMessage::new(
+                    PromptMessageRole::User,
+                    Content::image("aGVsbG8=", "image/png"),
+                ),
+                PromptMessage::new_text(PromptMessageRole::User, "describe this image"),
+                PromptMessage::new(
+                    PromptMessageRole::Assistant,
+                    Content::audio("d29ybGQ=", "audio/wav"),
+                ),
+            ],
+            meta: None,
+        };
+        let json = serde_json::to_value(&result).expect("serialize prompt");
+        assert_eq!(
+            json["messages"],
+            json!([
+                {
+                    "role": "user",
+                    "content": { "type": "image", "data": "aGVsbG8=", "mimeType": "image/png" }
+                },
+                {
+                    "role": "user",
+                    "content": { "type": "text", "text": "describe this image" }
+                },
+                {
+                    "role": "assistant",
+                    "content": { "type": "audio", "data": "d29ybGQ=", "mimeType": "audio/wav" }
+                }
+            ])
+        );
+        let parsed: GetPromptResult = serde_json::from_value(json).expect("deserialize prompt");
+        assert_eq!(parsed, result);
+        assert!(parsed.messages[0].content.as_image().is_some());
+        assert_eq!(
+            parsed.messages[1]
+                .content
+                .as_text()
+                .map(|text| text.text.as_str()),
+            Some("describe this image")
+        );
+    }
+
+    #[test]
+    fn test_unknown_notification_serde() {
+        let raw = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/experimental/heartbeat",
+            "params": { "sequence": 1 }
+        });
+        let message: ServerJsonRpcMessage =
+            serde_json::from_value(raw.clone()).expect("invalid notification");
+        let JsonRpcMessage::Notification(notification) = &message else {
+            panic!("expect a notification, got {message:?}");
+        };
+        let ServerNotification::Unknown(unknown) = &notification.notification else {
+            panic!("expect an unknown notification");
+        };
+        assert_eq!(unknown.method, "notifications/experimental/heartbeat");
+        assert_eq!(
+            unknown
+                .params
+                .as_ref()
+                .and_then(|params| params.get("sequence")),
+            Some(&json!(1))
+        );
+        assert_eq!(serde_json::to_value(&message).expect("serialize"), raw);
+
+        let raw = json!({ "jsonrpc": "2.0", "method": "notifications/experimental/ping" });
+        let message: ClientJsonRpcMessage =
+            serde_json::from_value(raw.clone()).expect("invalid notification");
+        assert!(matches!(
+            message,
+            JsonRpcMessage::Notification(JsonRpcNotification {
+                notification: ClientNotification::Unknown(_),
+                ..
+            })
+        ));
+        assert_eq!(serde_json::to_value(&message).expect("serialize"), raw);
+
+        // known notifications are still recognized
+        let message: ClientJsonRpcMessage = serde_json::from_value(
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        )
+        .expect("invalid notification");
+        assert!(matches!(
+            message,
+            JsonRpcMessage::Notification(JsonRpcNotification {
+                notification: ClientNotification::InitializedNotification(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_notification_union_conversions() {
+        let progress = ProgressNotification {
+            method: ProgressNotificationMethod,
+            params: ProgressNotificationParam {
+                progress_token: NumberOrString::Number(1),
+                progress: 50,
+                total: Some(100),
+                message: None,
+            },
+        };
+        let notification = ServerNotification::from(progress.clone());
+        assert!(matches!(
+            notification,
+            ServerNotification::ProgressNotification(_)
+        ));
+        assert_eq!(
+            ProgressNotification::try_from(notification.clone()),
+            Ok(progress.clone())
+        );
+        assert_eq!(
+            LoggingMessageNotification::try_from(notification.clone()),
+            Err(notification)
+        );
+        assert!(matches!(
+            ClientNotification::from(progress),
+            ClientNotification::ProgressNotification(_)
+        ));
+
+        let logging = LoggingMessageNotification {
+            method: LoggingMessageNotificationMethod,
+            params: LoggingMessageNotificationParam {
+                level: LoggingLevel::Info,
+                logger: None,
+                data: json!("hello"),
+            },
+        };
+        let notification: ServerNotification = logging.clone().into();
+        let converted: Result<LoggingMessageNotification, _> = notification.try_into();
+        assert_eq!(converted, Ok(logging));
+
+        let unknown = UnknownNotification::new("notifications/experimental", None);
+        assert_eq!(
+            ClientNotification::from(unknown.clone()),
+            ClientNotification::Unknown(unknown)
+        );
+    }
+
+    #[test]
+    fn test_create_message_result() {
+        let result: CreateMessageResult = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": { "type": "text", "text": "The capital of France is Paris." },
+            "model": "claude-3-sonnet-20240307",
+            "stopReason": "endTurn"
+        }))
+        .unwrap();
+        assert_eq!(result.text(), Some("The capital of France is Paris."));
+        assert_eq!(
+            result.stop_reason.as_deref(),
+            Some(CreateMessageResult::STOP_REASON_END_TURN)
+        );
+        assert!(result.validate().is_ok());
+
+        let result: CreateMessageResult = serde_json::from_value(json!({
+            "role": "user",
+            "content": { "type": "image", "data": "aGVsbG8=", "mimeType": "image/png" },
+            "model": "claude-3-sonnet-20240307"
+        }))
+        .unwrap();
+        assert_eq!(result.text(), None);
+        let error = result.validate().expect_err("user role");
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_progress_notification() {
+        let notification = ServerNotification::progress(NumberOrString::Number(1), 10, Some(100));
+        assert_eq!(
+            serde_json::to_value(&notification).unwrap(),
+            json!({
+                "method": "notifications/progress",
+                "params": { "progressToken": 1, "progress": 10, "total": 100 }
+            })
+        );
+        assert_eq!(
+            ClientNotification::progress(NumberOrString::Number(1), 10, Some(100)),
+            ClientNotification::ProgressNotification(ProgressNotification::new(
+                NumberOrString::Number(1),
+                10,
+                Some(100)
+            ))
+        );
+
+        let mut tracker = ProgressTracker::new(NumberOrString::String("task".into()));
+        assert!(tracker.advance(10, None).is_ok());
+        assert!(tracker.advance(10, None).is_ok());
+        let notification = tracker.advance(20, Some(30)).unwrap();
+        assert_eq!(notification.params.progress, 20);
+        assert_eq!(tracker.last(), Some(20));
+        let error = tracker.advance(5, Some(30)).unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(tracker.last(), Some(20));
+    }
+
+    #[test]
+    fn test_result_meta_round_trip() {
+        let results = [
+            json!({ "messages": [], "_meta": { "traceId": "a" } }),
+            json!({ "tools": [], "nextCursor": "2", "_meta": { "traceId": "b" } }),
+            json!({ "content": [], "isError": false, "_meta": { "traceId": "c" } }),
+        ];
+        for result in results {
+            let message: ServerJsonRpcMessage = serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": result.clone()
+            }))
+            .unwrap();
+            let response = serde_json::to_value(&message).unwrap();
+            assert_eq!(response["result"], result);
+        }
+
+        let result: GetPromptResult =
+            serde_json::from_value(json!({ "messages": [], "_meta": { "traceId": "a" } })).unwrap();
+        assert_eq!(result.meta_field("traceId"), Some(&json!("a")));
+        let result = ListToolsResult::default().with_meta_field("traceId", "b");
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({ "tools": [], "_meta": { "traceId": "b" } })
+        );
+        assert_eq!(ListPromptsResult::default().meta(), None);
+    }
+
+    #[test]
+    fn test_call_tool_null_arguments() {
+        let call = |params: serde_json::Value| -> CallToolRequestParam {
+            let message: ClientJsonRpcMessage = serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": params,
+            }))
+            .expect("a tools/call request");
+            match message {
+                JsonRpcMessage::Request(JsonRpcRequest {
+                    request: ClientRequest::CallToolRequest(request),
+                    ..
+                }) => request.params,
+                message => panic!("expect a tools/call request, got {message:?}"),
+            }
+        };
+        let absent = call(json!({ "name": "sum" }));
+        let null = call(json!({ "name": "sum", "arguments": null }));
+        let empty = call(json!({ "name": "sum", "arguments": {} }));
+        assert_eq!(absent.arguments, None);
+        assert_eq!(null.arguments, None);
+        assert_eq!(empty.arguments, Some(JsonObject::new()));
+        // `null` is sent again as absent, an empty object as is
+        assert_eq!(
+            serde_json::to_value(&null).unwrap(),
+            json!({ "name": "sum" })
+        );
+        assert_eq!(
+            serde_json::to_value(&empty).unwrap(),
+            json!({ "name": "sum", "arguments": {} })
+        );
+        let null_meta = call(json!({ "name": "sum", "arguments": null, "_meta": null }));
+        assert_eq!(null_meta.meta, None);
+    }
 }
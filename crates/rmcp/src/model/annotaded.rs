@@ -1,10 +1,14 @@
 use std::ops::{Deref, DerefMut};
 
-use super::{RawContent, RawEmbeddedResource, RawImageContent, RawResource, RawTextContent, Role};
+use super::{
+    RawAudioContent, RawContent, RawEmbeddedResource, RawImageContent, RawResource, RawTextContent,
+    Role,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Annotations {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,6 +36,7 @@ impl Annotations {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Annotated<T: AnnotateAble> {
     #[serde(flatten)]
     pub raw: T,
@@ -158,6 +163,7 @@ macro_rules! annotate {
 annotate!(RawContent);
 annotate!(RawTextContent);
 annotate!(RawImageContent);
+annotate!(RawAudioContent);
 annotate!(RawEmbeddedResource);
 annotate!(RawResource);
 
@@ -0,0 +1,497 @@
+//! [`Arbitrary`] implementations to generate spec-valid messages, e.g. for fuzzing or property tests.
+//!
+//! The generated messages survive a serialization round-trip: methods of unknown requests and notifications
+//! are prefixed with `experimental/`, and keys of unknown results with `x-`, so they're never confused with
+//! a known variant. The JSON numbers are integers, as floats may lose precision when they're parsed back.
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use serde_json::Value;
+
+use super::*;
+
+/// The maximum nesting of the generated JSON values
+const MAX_DEPTH: usize = 3;
+/// The maximum length of the generated JSON arrays and objects
+const MAX_LEN: usize = 4;
+
+fn arbitrary_value(u: &mut Unstructured<'_>, depth: usize) -> Result<Value> {
+    let max_kind = if depth >= MAX_DEPTH { 3 } else { 5 };
+    Ok(match u.int_in_range(0..=max_kind)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::from(u.arbitrary::<i64>()?),
+        3 => Value::String(u.arbitrary()?),
+        4 => {
+            let len = u.int_in_range(0..=MAX_LEN)?;
+            Value::Array(
+                (0..len)
+                    .map(|_| arbitrary_value(u, depth + 1))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        _ => Value::Object(arbitrary_object(u, depth + 1)?),
+    })
+}
+
+fn arbitrary_object(u: &mut Unstructured<'_>, depth: usize) -> Result<JsonObject> {
+    let len = u.int_in_range(0..=MAX_LEN)?;
+    (0..len)
+        .map(|_| Ok((u.arbitrary()?, arbitrary_value(u, depth)?)))
+        .collect()
+}
+
+fn arbitrary_optional_object(u: &mut Unstructured<'_>) -> Result<Option<JsonObject>> {
+    if u.arbitrary()? {
+        Ok(Some(arbitrary_object(u, 0)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A method never used by the spec
+fn arbitrary_experimental_method(u: &mut Unstructured<'_>) -> Result<String> {
+    Ok(format!("experimental/{}", u.arbitrary::<String>()?))
+}
+
+impl<'a> Arbitrary<'a> for JsonRpcVersion2_0 {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(JsonRpcVersion2_0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProtocolVersion {
+    /// Only the versions known to this crate
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(u.choose(&[ProtocolVersion::V_2024_11_05])?.clone())
+    }
+}
+
+impl<'a> Arbitrary<'a> for NumberOrString {
+    /// Any `i64`, they're exactly represented in JSON by serde_json
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            Ok(NumberOrString::Number(u.arbitrary()?))
+        } else {
+            Ok(NumberOrString::String(u.arbitrary::<String>()?.into()))
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for EmptyObject {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(EmptyObject {})
+    }
+}
+
+impl<'a> Arbitrary<'a> for Cursor {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Cursor::new(u.arbitrary::<String>()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Implementation {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Implementation {
+            name: u.arbitrary()?,
+            version: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ErrorCode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ErrorCode(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ErrorData {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // `null` data is read back as no data
+        let data = Some(arbitrary_value(u, 0)?).filter(|data| !data.is_null());
+        Ok(ErrorData::new(
+            u.arbitrary()?,
+            u.arbitrary::<String>()?,
+            data,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for LoggingLevel {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            LoggingLevel::Debug,
+            LoggingLevel::Info,
+            LoggingLevel::Notice,
+            LoggingLevel::Warning,
+            LoggingLevel::Error,
+            LoggingLevel::Critical,
+            LoggingLevel::Alert,
+            LoggingLevel::Emergency,
+        ])?)
+    }
+}
+
+impl<'a, M: ConstString, P: Arbitrary<'a>> Arbitrary<'a> for Request<M, P> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Request {
+            method: M::default(),
+            params: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, M: ConstString> Arbitrary<'a> for RequestNoParam<M> {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(RequestNoParam {
+            method: M::default(),
+        })
+    }
+}
+
+impl<'a, M: ConstString, P: Arbitrary<'a>> Arbitrary<'a> for Notification<M, P> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Notification {
+            method: M::default(),
+            params: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, M: ConstString> Arbitrary<'a> for NotificationNoParam<M> {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(NotificationNoParam {
+            method: M::default(),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for UnknownRequest {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(UnknownRequest::new(
+            arbitrary_experimental_method(u)?,
+            arbitrary_optional_object(u)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for UnknownNotification {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(UnknownNotification::new(
+            arbitrary_experimental_method(u)?,
+            arbitrary_optional_object(u)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for UnknownResult {
+    /// A non-empty object, its keys are prefixed with `x-`
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut object = JsonObject::new();
+        object.insert(
+            format!("x-{}", u.arbitrary::<String>()?),
+            arbitrary_value(u, 0)?,
+        );
+        for (key, value) in arbitrary_object(u, 0)? {
+            object.insert(format!("x-{key}"), value);
+        }
+        Ok(UnknownResult(object))
+    }
+}
+
+impl<'a> Arbitrary<'a> for PaginatedRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(PaginatedRequestParam {
+            cursor: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for InitializeRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(InitializeRequestParam {
+            protocol_version: u.arbitrary()?,
+            capabilities: ClientCapabilities::default(),
+            client_info: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for CallToolRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CallToolRequestParam {
+            name: u.arbitrary::<String>()?.into(),
+            arguments: arbitrary_optional_object(u)?,
+            meta: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for GetPromptRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(GetPromptRequestParam {
+            name: u.arbitrary()?,
+            arguments: arbitrary_optional_object(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ReadResourceRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ReadResourceRequestParam {
+            uri: u.arbitrary()?,
+            meta: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for SubscribeRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(SubscribeRequestParam {
+            uri: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for UnsubscribeRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(UnsubscribeRequestParam {
+            uri: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for SetLevelRequestParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(SetLevelRequestParam {
+            level: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for CancelledNotificationParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CancelledNotificationParam {
+            request_id: u.arbitrary()?,
+            reason: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProgressNotificationParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ProgressNotificationParam {
+            progress_token: u.arbitrary()?,
+            progress: u.arbitrary()?,
+            total: u.arbitrary()?,
+            message: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for LoggingMessageNotificationParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(LoggingMessageNotificationParam {
+            level: u.arbitrary()?,
+            logger: u.arbitrary()?,
+            data: arbitrary_value(u, 0)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ResourceUpdatedNotificationParam {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ResourceUpdatedNotificationParam {
+            uri: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Root {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Root {
+            uri: u.arbitrary()?,
+            name: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ListRootsResult {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ListRootsResult {
+            roots: u.arbitrary()?,
+            meta: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Tool {
+    /// A tool taking an object, its input schema has no properties
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut input_schema = JsonObject::new();
+        input_schema.insert("type".to_string(), Value::from("object"));
+        Ok(Tool::new(
+            u.arbitrary::<String>()?,
+            u.arbitrary::<String>()?,
+            input_schema,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ListToolsResult {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ListToolsResult {
+            next_cursor: u.arbitrary()?,
+            tools: u.arbitrary()?,
+            meta: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for CallToolResult {
+    /// A result with text contents
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let content = u
+            .arbitrary::<Vec<String>>()?
+            .into_iter()
+            .map(Content::text)
+            .collect();
+        Ok(CallToolResult {
+            content,
+            structured_content: None,
+            is_error: u.arbitrary()?,
+            meta: None,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ClientRequest {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=12)? {
+            0 => ClientRequest::PingRequest(u.arbitrary()?),
+            1 => ClientRequest::InitializeRequest(u.arbitrary()?),
+            2 => ClientRequest::SetLevelRequest(u.arbitrary()?),
+            3 => ClientRequest::GetPromptRequest(u.arbitrary()?),
+            4 => ClientRequest::ListPromptsRequest(u.arbitrary()?),
+            5 => ClientRequest::ListResourcesRequest(u.arbitrary()?),
+            6 => ClientRequest::ListResourceTemplatesRequest(u.arbitrary()?),
+            7 => ClientRequest::ReadResourceRequest(u.arbitrary()?),
+            8 => ClientRequest::SubscribeRequest(u.arbitrary()?),
+            9 => ClientRequest::UnsubscribeRequest(u.arbitrary()?),
+            10 => ClientRequest::CallToolRequest(u.arbitrary()?),
+            11 => ClientRequest::ListToolsRequest(u.arbitrary()?),
+            _ => ClientRequest::Unknown(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ClientNotification {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=4)? {
+            0 => ClientNotification::CancelledNotification(u.arbitrary()?),
+            1 => ClientNotification::ProgressNotification(u.arbitrary()?),
+            2 => ClientNotification::InitializedNotification(u.arbitrary()?),
+            3 => ClientNotification::RootsListChangedNotification(u.arbitrary()?),
+            _ => ClientNotification::Unknown(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ClientResult {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=2)? {
+            0 => ClientResult::ListRootsResult(u.arbitrary()?),
+            1 => ClientResult::UnknownResult(u.arbitrary()?),
+            _ => ClientResult::EmptyResult(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ServerRequest {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=2)? {
+            0 => ServerRequest::PingRequest(u.arbitrary()?),
+            1 => ServerRequest::ListRootsRequest(u.arbitrary()?),
+            _ => ServerRequest::Unknown(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ServerNotification {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=7)? {
+            0 => ServerNotification::CancelledNotification(u.arbitrary()?),
+            1 => ServerNotification::ProgressNotification(u.arbitrary()?),
+            2 => ServerNotification::LoggingMessageNotification(u.arbitrary()?),
+            3 => ServerNotification::ResourceUpdatedNotification(u.arbitrary()?),
+            4 => ServerNotification::ResourceListChangedNotification(u.arbitrary()?),
+            5 => ServerNotification::ToolListChangedNotification(u.arbitrary()?),
+            6 => ServerNotification::PromptListChangedNotification(u.arbitrary()?),
+            _ => ServerNotification::Unknown(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ServerResult {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => ServerResult::CallToolResult(u.arbitrary()?),
+            1 => ServerResult::ListToolsResult(u.arbitrary()?),
+            2 => ServerResult::UnknownResult(u.arbitrary()?),
+            _ => ServerResult::EmptyResult(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a, R: Arbitrary<'a>> Arbitrary<'a> for JsonRpcRequest<R> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(JsonRpcRequest {
+            jsonrpc: JsonRpcVersion2_0,
+            id: u.arbitrary()?,
+            request: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, R: Arbitrary<'a>> Arbitrary<'a> for JsonRpcResponse<R> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(JsonRpcResponse {
+            jsonrpc: JsonRpcVersion2_0,
+            id: u.arbitrary()?,
+            result: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for JsonRpcError {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(JsonRpcError {
+            jsonrpc: JsonRpcVersion2_0,
+            id: u.arbitrary()?,
+            error: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, N: Arbitrary<'a>> Arbitrary<'a> for JsonRpcNotification<N> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(JsonRpcNotification {
+            jsonrpc: JsonRpcVersion2_0,
+            notification: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, Req, Resp, Noti> Arbitrary<'a> for JsonRpcMessage<Req, Resp, Noti>
+where
+    Req: Arbitrary<'a>,
+    Resp: Arbitrary<'a>,
+    Noti: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => JsonRpcMessage::Request(u.arbitrary()?),
+            1 => JsonRpcMessage::Response(u.arbitrary()?),
+            2 => JsonRpcMessage::Notification(u.arbitrary()?),
+            _ => JsonRpcMessage::Error(u.arbitrary()?),
+        })
+    }
+}
@@ -0,0 +1,186 @@
+//! Base64 for the binary data of contents and resources, i.e. images, audio and blobs
+use base64::{
+    DecodeError, DecodeSliceError, alphabet,
+    engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig},
+};
+use serde::{Deserialize, Deserializer};
+
+const DECODE_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, DECODE_CONFIG);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, DECODE_CONFIG);
+
+/// The alphabets of [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648), they only differ by `+/` and `-_`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Base64Alphabet {
+    /// The one MCP uses, `+` and `/`
+    #[default]
+    Standard,
+    /// `-` and `_`, safe in urls and file names
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn engine(self) -> &'static GeneralPurpose {
+        match self {
+            Self::Standard => &STANDARD,
+            Self::UrlSafe => &URL_SAFE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Base64Error {
+    #[error("invalid byte {byte:#04x} at offset {offset}")]
+    InvalidByte { offset: usize, byte: u8 },
+    #[error("invalid length {0}, a single symbol remains after the last group of 4")]
+    InvalidLength(usize),
+    #[error("the last symbol at offset {0} has non-zero trailing bits")]
+    InvalidLastSymbol(usize),
+    #[error("invalid padding")]
+    InvalidPadding,
+    #[error(
+        "both the standard and the url-safe alphabets are used, at offsets {standard} and {url_safe}"
+    )]
+    MixedAlphabets { standard: usize, url_safe: usize },
+}
+
+impl Base64Error {
+    /// `offset` is the one of the decoded chunk, in data of length `len`
+    fn from_decode(error: DecodeError, offset: usize, len: usize) -> Self {
+        match error {
+            DecodeError::InvalidByte(at, byte) => Self::InvalidByte {
+                offset: offset + at,
+                byte,
+            },
+            DecodeError::InvalidLength => Self::InvalidLength(len),
+            DecodeError::InvalidLastSymbol(at, _) => Self::InvalidLastSymbol(offset + at),
+            DecodeError::InvalidPadding => Self::InvalidPadding,
+        }
+    }
+}
+
+/// Encode binary data with padding, in the standard alphabet unless another one is asked
+pub fn encode_base64(data: impl AsRef<[u8]>, alphabet: Base64Alphabet) -> String {
+    let engine = match alphabet {
+        Base64Alphabet::Standard => &base64::engine::general_purpose::STANDARD,
+        Base64Alphabet::UrlSafe => &base64::engine::general_purpose::URL_SAFE,
+    };
+    engine.encode(data)
+}
+
+/// The alphabet of base64 data, it's the standard one if no symbol tells them apart
+pub fn detect_base64_alphabet(data: &str) -> Result<Base64Alphabet, Base64Error> {
+    let standard = data.find(['+', '/']);
+    let url_safe = data.find(['-', '_']);
+    match (standard, url_safe) {
+        (Some(standard), Some(url_safe)) => Err(Base64Error::MixedAlphabets { standard, url_safe }),
+        (None, Some(_)) => Ok(Base64Alphabet::UrlSafe),
+        _ => Ok(Base64Alphabet::Standard),
+    }
+}
+
+/// Decode base64 data in either alphabet, with or without padding
+pub fn decode_base64(data: &str) -> Result<Vec<u8>, Base64Error> {
+    let engine = detect_base64_alphabet(data)?.engine();
+    engine
+        .decode(data)
+        .map_err(|error| Base64Error::from_decode(error, 0, data.len()))
+}
+
+/// Check base64 data like [`decode_base64`], without keeping the decoded data.
+///
+/// It's decoded by chunks into a buffer on the stack, so validating a large blob doesn't allocate.
+pub fn validate_base64(data: &str) -> Result<(), Base64Error> {
+    // a multiple of 4, so that only the last chunk can be padded
+    const CHUNK: usize = 4096;
+    let engine = detect_base64_alphabet(data)?.engine();
+    let mut buffer = [0u8; CHUNK / 4 * 3];
+    for (index, chunk) in data.as_bytes().chunks(CHUNK).enumerate() {
+        match engine.decode_slice(chunk, &mut buffer) {
+            Ok(_) => {}
+            Err(DecodeSliceError::DecodeError(error)) => {
+                return Err(Base64Error::from_decode(error, index * CHUNK, data.len()));
+            }
+            Err(DecodeSliceError::OutputSliceTooSmall) => unreachable!("the buffer fits a chunk"),
+        }
+    }
+    Ok(())
+}
+
+/// Remove the ASCII whitespace of base64 data, e.g. the line breaks of a MIME encoder.
+///
+/// The data is returned as is when there's none, the usual case, so it doesn't allocate.
+pub(crate) fn strip_base64_whitespace(data: String) -> String {
+    if data.bytes().any(|byte| byte.is_ascii_whitespace()) {
+        data.split_ascii_whitespace().collect()
+    } else {
+        data
+    }
+}
+
+/// Deserialize a base64 string, failing on invalid data rather than when it's decoded.
+///
+/// Whitespace is stripped first, so the offset of an invalid byte is the one in the stripped data.
+pub(crate) fn deserialize_base64<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let data = strip_base64_whitespace(String::deserialize(deserializer)?);
+    validate_base64(&data)
+        .map_err(|error| serde::de::Error::custom(format_args!("invalid base64 data: {error}")))?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabets() {
+        let data = [0xfb, 0xff, 0xbf];
+        let standard = encode_base64(data, Base64Alphabet::Standard);
+        let url_safe = encode_base64(data, Base64Alphabet::UrlSafe);
+        assert_eq!(standard, "+/+/");
+        assert_eq!(url_safe, "-_-_");
+        assert_eq!(decode_base64(&standard), Ok(data.to_vec()));
+        assert_eq!(decode_base64(&url_safe), Ok(data.to_vec()));
+        assert_eq!(
+            decode_base64("+_"),
+            Err(Base64Error::MixedAlphabets {
+                standard: 0,
+                url_safe: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_padding_is_optional() {
+        assert_eq!(decode_base64("aGk="), Ok(b"hi".to_vec()));
+        assert_eq!(decode_base64("aGk"), Ok(b"hi".to_vec()));
+        assert_eq!(validate_base64("aGk"), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_offsets() {
+        // the invalid byte is in the second chunk of the validation
+        let mut data = "A".repeat(4096 + 8);
+        data.replace_range(4100..4101, "*");
+        let expected = Err(Base64Error::InvalidByte {
+            offset: 4100,
+            byte: b'*',
+        });
+        assert_eq!(validate_base64(&data), expected);
+        assert_eq!(decode_base64(&data).map(drop), expected);
+        assert_eq!(validate_base64("AAAAA"), Err(Base64Error::InvalidLength(5)));
+    }
+
+    #[test]
+    fn test_strip_whitespace() {
+        assert_eq!(strip_base64_whitespace("aGk=".to_owned()), "aGk=");
+        assert_eq!(
+            strip_base64_whitespace(" aGVs\r\nbG8=\n\t".to_owned()),
+            "aGVsbG8="
+        );
+    }
+}
@@ -6,6 +6,7 @@ use std::marker::PhantomData;
 pub type ExperimentalCapabilities = BTreeMap<String, JsonObject>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PromptsCapability {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -13,6 +14,7 @@ pub struct PromptsCapability {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ResourcesCapability {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -22,6 +24,7 @@ pub struct ResourcesCapability {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ToolsCapability {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -29,6 +32,7 @@ pub struct ToolsCapability {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RootsCapabilities {
     pub list_changed: Option<bool>,
@@ -45,6 +49,7 @@ pub struct RootsCapabilities {
 ///     .build();
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ClientCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<ExperimentalCapabilities>,
@@ -60,6 +65,7 @@ pub struct ClientCapabilities {
 /// # use rmcp::model::ServerCapabilities;
 /// let cap = ServerCapabilities::builder()
 ///     .enable_logging()
+///     .enable_completions()
 ///     .enable_experimental()
 ///     .enable_prompts()
 ///     .enable_resources()
@@ -68,12 +74,16 @@ pub struct ClientCapabilities {
 ///     .build();
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ServerCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<ExperimentalCapabilities>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logging: Option<JsonObject>,
+    /// The server answers `completion/complete` requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<JsonObject>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompts: Option<PromptsCapability>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -82,6 +92,109 @@ pub struct ServerCapabilities {
     pub tools: Option<ToolsCapability>,
 }
 
+/// Merge a capability into another, see [`ServerCapabilities::merge`]
+trait Merge {
+    fn merge(&mut self, other: Self);
+}
+
+impl Merge for bool {
+    fn merge(&mut self, other: Self) {
+        *self |= other;
+    }
+}
+
+impl<T: Merge> Merge for Option<T> {
+    fn merge(&mut self, other: Self) {
+        match (self.as_mut(), other) {
+            (Some(value), Some(other)) => value.merge(other),
+            (None, other) => *self = other,
+            (Some(_), None) => {}
+        }
+    }
+}
+
+/// The keys are unioned, the value of `self` is kept for a key in both
+impl Merge for JsonObject {
+    fn merge(&mut self, other: Self) {
+        for (key, value) in other {
+            self.entry(key).or_insert(value);
+        }
+    }
+}
+
+impl Merge for ExperimentalCapabilities {
+    fn merge(&mut self, other: Self) {
+        for (name, capability) in other {
+            self.entry(name).or_default().merge(capability);
+        }
+    }
+}
+
+impl Merge for PromptsCapability {
+    fn merge(&mut self, other: Self) {
+        self.list_changed.merge(other.list_changed);
+    }
+}
+
+impl Merge for ResourcesCapability {
+    fn merge(&mut self, other: Self) {
+        self.subscribe.merge(other.subscribe);
+        self.list_changed.merge(other.list_changed);
+    }
+}
+
+impl Merge for ToolsCapability {
+    fn merge(&mut self, other: Self) {
+        self.list_changed.merge(other.list_changed);
+    }
+}
+
+impl Merge for RootsCapabilities {
+    fn merge(&mut self, other: Self) {
+        self.list_changed.merge(other.list_changed);
+    }
+}
+
+impl ServerCapabilities {
+    /// The capabilities of both, e.g. of two handlers served together.
+    ///
+    /// A capability is enabled if either enables it, and a flag like `listChanged` or `subscribe` is true if
+    /// either sets it to true. The objects without a schema, `logging`, `completions` and the experimental
+    /// capabilities, get the keys of both; on a conflicting key, the value of `self` is kept.
+    pub fn merge(mut self, other: Self) -> Self {
+        let Self {
+            experimental,
+            logging,
+            completions,
+            prompts,
+            resources,
+            tools,
+        } = other;
+        self.experimental.merge(experimental);
+        self.logging.merge(logging);
+        self.completions.merge(completions);
+        self.prompts.merge(prompts);
+        self.resources.merge(resources);
+        self.tools.merge(tools);
+        self
+    }
+}
+
+impl ClientCapabilities {
+    /// The capabilities of both, with the conflict policy of [`ServerCapabilities::merge`]
+    pub fn merge(mut self, other: Self) -> Self {
+        let Self {
+            experimental,
+            roots,
+            sampling,
+        } = other;
+        self.experimental.merge(experimental);
+        self.roots.merge(roots);
+        self.sampling.merge(sampling);
+        self
+    }
+}
+
 macro_rules! builder {
     ($Target: ident {$($f: ident: $T: ty),* $(,)?}) => {
         paste! {
@@ -191,14 +304,15 @@ builder! {
     ServerCapabilities {
         experimental: ExperimentalCapabilities,
         logging: JsonObject,
+        completions: JsonObject,
         prompts: PromptsCapability,
         resources: ResourcesCapability,
         tools: ToolsCapability
     }
 }
 
-impl<const E: bool, const L: bool, const P: bool, const R: bool>
-    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, P, R, true>>
+impl<const E: bool, const L: bool, const C: bool, const P: bool, const R: bool>
+    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, C, P, R, true>>
 {
     pub fn enable_tool_list_changed(mut self) -> Self {
         if let Some(c) = self.tools.as_mut() {
@@ -208,8 +322,8 @@ impl<const E: bool, const L: bool, const P: bool, const R: bool>
     }
 }
 
-impl<const E: bool, const L: bool, const R: bool, const T: bool>
-    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, true, R, T>>
+impl<const E: bool, const L: bool, const C: bool, const R: bool, const T: bool>
+    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, C, true, R, T>>
 {
     pub fn enable_prompts_list_changed(mut self) -> Self {
         if let Some(c) = self.prompts.as_mut() {
@@ -219,8 +333,8 @@ impl<const E: bool, const L: bool, const R: bool, const T: bool>
     }
 }
 
-impl<const E: bool, const L: bool, const P: bool, const T: bool>
-    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, P, true, T>>
+impl<const E: bool, const L: bool, const C: bool, const P: bool, const T: bool>
+    ServerCapabilitiesBuilder<ServerCapabilitiesBuilderState<E, L, C, P, true, T>>
 {
     pub fn enable_resources_list_changed(mut self) -> Self {
         if let Some(c) = self.resources.as_mut() {
@@ -263,12 +377,14 @@ mod test {
     fn test_builder() {
         let builder = <ServerCapabilitiesBuilder>::default()
             .enable_logging()
+            .enable_completions()
             .enable_experimental()
             .enable_prompts()
             .enable_resources()
             .enable_tools()
             .enable_tool_list_changed();
         assert_eq!(builder.logging, Some(JsonObject::default()));
+        assert_eq!(builder.completions, Some(JsonObject::default()));
         assert_eq!(builder.prompts, Some(PromptsCapability::default()));
         assert_eq!(builder.resources, Some(ResourcesCapability::default()));
         assert_eq!(
@@ -297,4 +413,85 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_completions_serde() {
+        let capabilities = ServerCapabilities::builder()
+            .enable_completions()
+            .enable_prompts()
+            .build();
+        let json = serde_json::to_value(&capabilities).expect("valid json");
+        assert_eq!(json, serde_json::json!({"completions": {}, "prompts": {}}));
+        let parsed: ServerCapabilities = serde_json::from_value(json).expect("valid capabilities");
+        assert_eq!(parsed, capabilities);
+        let parsed: ServerCapabilities =
+            serde_json::from_value(serde_json::json!({"tools": {}})).expect("valid capabilities");
+        assert_eq!(parsed.completions, None);
+    }
+
+    #[test]
+    fn test_merge_server() {
+        let tools = ServerCapabilities::builder()
+            .enable_tools()
+            .enable_tool_list_changed()
+            .enable_logging()
+            .build();
+        let mut prompts = ServerCapabilities::builder()
+            .enable_prompts()
+            .enable_resources()
+            .enable_logging()
+            .build();
+        prompts.resources.as_mut().unwrap().subscribe = Some(true);
+        prompts.tools = Some(ToolsCapability {
+            list_changed: Some(false),
+        });
+        prompts.experimental = Some(BTreeMap::from([(
+            "streaming".to_owned(),
+            serde_json::from_value(serde_json::json!({"chunks": true})).unwrap(),
+        )]));
+
+        let merged = tools.clone().merge(prompts.clone());
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::json!({
+                "experimental": {"streaming": {"chunks": true}},
+                "logging": {},
+                "prompts": {},
+                "resources": {"subscribe": true},
+                "tools": {"listChanged": true},
+            })
+        );
+        // the flags are ORed whatever the order
+        assert_eq!(prompts.merge(tools), merged);
+        assert_eq!(merged.clone().merge(ServerCapabilities::default()), merged);
+    }
+
+    #[test]
+    fn test_merge_client() {
+        let mut roots = ClientCapabilities::builder().enable_roots().build();
+        roots.experimental = Some(BTreeMap::from([(
+            "ext".to_owned(),
+            serde_json::from_value(serde_json::json!({"version": 1, "a": true})).unwrap(),
+        )]));
+        let mut sampling = ClientCapabilities::builder()
+            .enable_roots()
+            .enable_roots_list_changed()
+            .enable_sampling()
+            .build();
+        sampling.experimental = Some(BTreeMap::from([(
+            "ext".to_owned(),
+            serde_json::from_value(serde_json::json!({"version": 2, "b": true})).unwrap(),
+        )]));
+
+        let merged = roots.merge(sampling);
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::json!({
+                // the value of the receiver is kept on a conflicting key
+                "experimental": {"ext": {"version": 1, "a": true, "b": true}},
+                "roots": {"listChanged": true},
+                "sampling": {},
+            })
+        );
+    }
 }
@@ -2,26 +2,161 @@
 //! The various content types can be display to humans but also understood by models
 //! They include optional annotations used to help inform agent usage
 use super::resource::ResourceContents;
-use super::{AnnotateAble, Annotated};
+use super::{AnnotateAble, Annotated, MimeType};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RawTextContent {
     pub text: String,
 }
 pub type TextContent = Annotated<RawTextContent>;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RawImageContent {
     /// The base64-encoded image
+    #[cfg_attr(
+        feature = "base64",
+        serde(deserialize_with = "super::binary::deserialize_base64")
+    )]
     pub data: String,
     pub mime_type: String,
 }
 
 pub type ImageContent = Annotated<RawImageContent>;
+
+/// Detect the mime type of an image from its magic bytes, for PNG, JPEG, GIF and WebP
+pub fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => Some("image/png"),
+        [0xff, 0xd8, 0xff, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ImageContentError {
+    #[error("the image data is not valid base64: {0}")]
+    InvalidBase64(String),
+    #[error("the image is declared as {declared}, but its data is {}", detected.unwrap_or("not a known image format"))]
+    MimeTypeMismatch {
+        declared: String,
+        detected: Option<&'static str>,
+    },
+    #[error("the image format isn't detected, only PNG, JPEG, GIF and WebP are")]
+    UnknownFormat,
+    #[error("no image encoding is given")]
+    NoEncoding,
+    #[error("no image encoding fits in {budget} bytes, the smallest takes {smallest}")]
+    OverBudget { budget: usize, smallest: usize },
+}
+
+#[cfg(feature = "base64")]
+impl RawImageContent {
+    /// Encode an image, its mime type is detected from its magic bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageContentError> {
+        use base64::engine::{Engine, general_purpose::STANDARD};
+        let mime_type = image_mime_type(bytes).ok_or(ImageContentError::UnknownFormat)?;
+        Ok(Self {
+            data: STANDARD.encode(bytes),
+            mime_type: mime_type.to_owned(),
+        })
+    }
+
+    /// Pick the smallest of several encodings of an image, e.g. a PNG and a JPEG,
+    /// whose base64 data takes at most `budget` bytes.
+    ///
+    /// MCP doesn't negotiate the image formats, so the size is what's left to optimize.
+    pub fn smallest_within<'a>(
+        encodings: impl IntoIterator<Item = &'a [u8]>,
+        budget: usize,
+    ) -> Result<Self, ImageContentError> {
+        let smallest = encodings
+            .into_iter()
+            .map(Self::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .min_by_key(|image| image.data.len())
+            .ok_or(ImageContentError::NoEncoding)?;
+        if smallest.data.len() > budget {
+            return Err(ImageContentError::OverBudget {
+                budget,
+                smallest: smallest.data.len(),
+            });
+        }
+        Ok(smallest)
+    }
+
+    /// Decode the image data, in either base64 alphabet
+    pub fn decode(&self) -> Result<Vec<u8>, super::Base64Error> {
+        super::decode_base64(&self.data)
+    }
+
+    /// Check that the declared mime type matches the magic bytes of the data, e.g. to reject a PNG labeled as JPEG.
+    ///
+    /// Only PNG, JPEG, GIF and WebP are detected, an image declared with another mime type isn't checked.
+    pub fn validate(&self) -> Result<(), ImageContentError> {
+        let declared = MimeType::new(&self.mime_type);
+        let declared = match declared.essence() {
+            declared @ ("image/png" | "image/jpeg" | "image/gif" | "image/webp") => declared,
+            _ => return Ok(()),
+        };
+        let bytes = self
+            .decode()
+            .map_err(|e| ImageContentError::InvalidBase64(e.to_string()))?;
+        let detected = image_mime_type(&bytes);
+        if detected != Some(declared) {
+            return Err(ImageContentError::MimeTypeMismatch {
+                declared: self.mime_type.clone(),
+                detected,
+            });
+        }
+        Ok(())
+    }
+}
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RawAudioContent {
+    /// The base64-encoded audio
+    #[cfg_attr(
+        feature = "base64",
+        serde(deserialize_with = "super::binary::deserialize_base64")
+    )]
+    pub data: String,
+    pub mime_type: String,
+}
+
+#[cfg(feature = "base64")]
+impl RawAudioContent {
+    /// Decode the audio data, in either base64 alphabet
+    pub fn decode(&self) -> Result<Vec<u8>, super::Base64Error> {
+        super::decode_base64(&self.data)
+    }
+}
+
+pub type AudioContent = Annotated<RawAudioContent>;
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RawEmbeddedResource {
     pub resource: ResourceContents,
@@ -38,10 +173,12 @@ impl EmbeddedResource {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum RawContent {
     Text(RawTextContent),
     Image(RawImageContent),
+    Audio(RawAudioContent),
     Resource(RawEmbeddedResource),
 }
 
@@ -67,7 +204,14 @@ impl RawContent {
     pub fn image<S: Into<String>, T: Into<String>>(data: S, mime_type: T) -> Self {
         RawContent::Image(RawImageContent {
             data: data.into(),
-            mime_type: mime_type.into(),
+            mime_type: MimeType::new(mime_type.into()).into(),
+        })
+    }
+
+    pub fn audio<S: Into<String>, T: Into<String>>(data: S, mime_type: T) -> Self {
+        RawContent::Audio(RawAudioContent {
+            data: data.into(),
+            mime_type: MimeType::new(mime_type.into()).into(),
         })
     }
 
@@ -101,6 +245,14 @@ impl RawContent {
         }
     }
 
+    /// Get the audio content if this is an AudioContent variant
+    pub fn as_audio(&self) -> Option<&RawAudioContent> {
+        match self {
+            RawContent::Audio(audio) => Some(audio),
+            _ => None,
+        }
+    }
+
     /// Get the resource content if this is an ImageContent variant
     pub fn as_resource(&self) -> Option<&RawEmbeddedResource> {
         match self {
@@ -119,6 +271,10 @@ impl Content {
         RawContent::image(data, mime_type).no_annotation()
     }
 
+    pub fn audio<S: Into<String>, T: Into<String>>(data: S, mime_type: T) -> Self {
+        RawContent::audio(data, mime_type).no_annotation()
+    }
+
     pub fn resource(resource: ResourceContents) -> Self {
         RawContent::resource(resource).no_annotation()
     }
@@ -133,6 +289,7 @@ impl Content {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct JsonContent<S: Serialize>(S);
 /// Types that can be converted into a list of contents
 pub trait IntoContents {
@@ -150,3 +307,175 @@ impl IntoContents for String {
         vec![Content::text(self)]
     }
 }
+
+impl IntoContents for Vec<Content> {
+    fn into_contents(self) -> Vec<Content> {
+        self
+    }
+}
+
+impl From<String> for Content {
+    fn from(text: String) -> Self {
+        Content::text(text)
+    }
+}
+
+impl From<&str> for Content {
+    fn from(text: &str) -> Self {
+        Content::text(text)
+    }
+}
+
+#[cfg(all(test, feature = "base64"))]
+mod tests {
+    use base64::engine::{Engine, general_purpose::STANDARD};
+
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+    const JPEG: &[u8] = b"\xff\xd8\xff\xe0\0\x10JFIF";
+
+    #[test]
+    fn test_content_conversions() {
+        assert_eq!(Content::from("hello"), Content::text("hello"));
+        assert_eq!(Content::from("hello".to_owned()), Content::text("hello"));
+        let json = Content::json(json!({ "a": 1 })).unwrap();
+        assert_eq!(json.as_text().unwrap().text, r#"{"a":1}"#);
+
+        assert_eq!(
+            "hello".to_owned().into_contents(),
+            vec![Content::text("hello")]
+        );
+        assert_eq!(
+            Content::text("hello").into_contents(),
+            vec![Content::text("hello")]
+        );
+        let contents = vec![Content::text("a"), Content::from("b")];
+        assert_eq!(contents.clone().into_contents(), contents);
+    }
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(image_mime_type(PNG), Some("image/png"));
+        assert_eq!(image_mime_type(JPEG), Some("image/jpeg"));
+        assert_eq!(image_mime_type(b"GIF89a\x01\0"), Some("image/gif"));
+        assert_eq!(image_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(image_mime_type(b"<svg/>"), None);
+    }
+
+    #[test]
+    fn test_validate_mime_type_mismatch() {
+        let png_as_jpeg = RawImageContent {
+            data: STANDARD.encode(PNG),
+            mime_type: "image/jpeg".to_owned(),
+        };
+        assert_eq!(
+            png_as_jpeg.validate(),
+            Err(ImageContentError::MimeTypeMismatch {
+                declared: "image/jpeg".to_owned(),
+                detected: Some("image/png"),
+            })
+        );
+        let text_as_png = RawImageContent {
+            data: STANDARD.encode("not an image"),
+            mime_type: "image/png".to_owned(),
+        };
+        assert!(matches!(
+            text_as_png.validate(),
+            Err(ImageContentError::MimeTypeMismatch { detected: None, .. })
+        ));
+        let invalid = RawImageContent {
+            data: "not base64!".to_owned(),
+            mime_type: "image/png".to_owned(),
+        };
+        assert!(matches!(
+            invalid.validate(),
+            Err(ImageContentError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_matching_mime_type() {
+        let jpeg = RawImageContent {
+            data: STANDARD.encode(JPEG),
+            mime_type: "image/jpg".to_owned(),
+        };
+        assert_eq!(jpeg.validate(), Ok(()));
+        assert_eq!(RawImageContent::from_bytes(PNG).unwrap().validate(), Ok(()));
+        // not detected, so not checked
+        let svg = RawImageContent {
+            data: STANDARD.encode("<svg/>"),
+            mime_type: "image/svg+xml".to_owned(),
+        };
+        assert_eq!(svg.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_smallest_within() {
+        let image = RawImageContent::smallest_within([PNG, JPEG], 100).unwrap();
+        assert_eq!(image.mime_type, "image/jpeg");
+        assert_eq!(image.data, STANDARD.encode(JPEG));
+        assert_eq!(
+            RawImageContent::smallest_within([PNG, b"<svg/>".as_slice()], 100),
+            Err(ImageContentError::UnknownFormat)
+        );
+        assert_eq!(
+            RawImageContent::smallest_within([PNG, JPEG], 8),
+            Err(ImageContentError::OverBudget {
+                budget: 8,
+                smallest: STANDARD.encode(JPEG).len(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_base64_content() {
+        let error = serde_json::from_value::<Content>(json!({
+            "type": "image",
+            "data": "iVBORw0K*",
+            "mimeType": "image/png",
+        }))
+        .expect_err("invalid base64");
+        assert!(
+            error
+                .to_string()
+                .contains("invalid base64 data: invalid byte 0x2a at offset 8"),
+            "{error}"
+        );
+        let wrapped = serde_json::from_value::<Content>(json!({
+            "type": "image",
+            "data": "iVBORw0K\n",
+            "mimeType": "image/png",
+        }))
+        .expect("line-wrapped base64");
+        assert_eq!(wrapped.as_image().expect("image").data, "iVBORw0K");
+        let url_safe = serde_json::from_value::<Content>(json!({
+            "type": "audio",
+            "data": "-_-_",
+            "mimeType": "audio/wav",
+        }))
+        .expect("url-safe base64");
+        let audio = url_safe.as_audio().expect("audio");
+        assert_eq!(audio.decode(), Ok(vec![0xfb, 0xff, 0xbf]));
+    }
+
+    #[test]
+    fn test_large_blob_round_trip() {
+        // 4MB of xorshift noise, with every byte value
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let data = (0..4 << 20)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        let content = Content::resource(ResourceContents::blob(&data, "file:///noise"));
+        let json = serde_json::to_string(&content).expect("serialize content");
+        let deserialized: Content = serde_json::from_str(&json).expect("deserialize content");
+        assert_eq!(deserialized, content);
+        let resource = &deserialized.as_resource().expect("resource").resource;
+        assert_eq!(resource.decode_blob(), Some(Ok(data)));
+    }
+}
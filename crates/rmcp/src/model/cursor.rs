@@ -0,0 +1,185 @@
+use std::{borrow::Borrow, ops::Deref};
+
+#[cfg(feature = "base64")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "base64")]
+use serde_json::Value;
+
+/// An opaque token used to represent a cursor for pagination.
+///
+/// On the wire it's a plain string, clients should never inspect or construct it.
+///
+/// Servers can use [`Cursor::encode`] and [`Cursor::decode`] to carry typed pagination state,
+/// the state is wrapped in a [`CursorData`] with a checksum and base64 encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    pub fn new(cursor: impl Into<String>) -> Self {
+        Self(cursor.into())
+    }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+#[cfg(feature = "base64")]
+impl Cursor {
+    /// Encode the pagination state into an opaque cursor
+    pub fn encode<T: Serialize>(state: &T) -> Result<Self, crate::Error> {
+        use base64::engine::{Engine, general_purpose::URL_SAFE_NO_PAD};
+        let data = CursorData::new(state).map_err(|e| {
+            crate::Error::internal_error(format!("fail to encode cursor: {e}"), None)
+        })?;
+        let json = serde_json::to_vec(&data).map_err(|e| {
+            crate::Error::internal_error(format!("fail to encode cursor: {e}"), None)
+        })?;
+        Ok(Self(URL_SAFE_NO_PAD.encode(json)))
+    }
+
+    /// Decode the pagination state from a cursor created by [`Cursor::encode`]
+    ///
+    /// An `invalid_params` error will be returned if the cursor is malformed or has been tampered.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, crate::Error> {
+        use base64::engine::{Engine, general_purpose::URL_SAFE_NO_PAD};
+        let invalid_cursor = |reason: String| {
+            crate::Error::invalid_params(
+                "invalid cursor",
+                Some(serde_json::json!({ "reason": reason })),
+            )
+        };
+        let json = URL_SAFE_NO_PAD
+            .decode(self.0.as_bytes())
+            .map_err(|e| invalid_cursor(e.to_string()))?;
+        let data: CursorData =
+            serde_json::from_slice(&json).map_err(|e| invalid_cursor(e.to_string()))?;
+        data.into_state().map_err(invalid_cursor)
+    }
+}
+
+impl Deref for Cursor {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Cursor {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Cursor {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<String> for Cursor {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Cursor {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(value: Cursor) -> Self {
+        value.0
+    }
+}
+
+/// The pagination state carried by a [`Cursor`], with a checksum to validate its integrity
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CursorData {
+    state: Value,
+    checksum: u64,
+}
+
+#[cfg(feature = "base64")]
+impl CursorData {
+    pub fn new<T: Serialize>(state: &T) -> Result<Self, serde_json::Error> {
+        let state = serde_json::to_value(state)?;
+        let checksum = checksum(&state)?;
+        Ok(Self { state, checksum })
+    }
+
+    /// Validate the checksum and deserialize the state
+    pub fn into_state<T: DeserializeOwned>(self) -> Result<T, String> {
+        let expected = checksum(&self.state).map_err(|e| e.to_string())?;
+        if expected != self.checksum {
+            return Err("checksum mismatch".to_owned());
+        }
+        serde_json::from_value(self.state).map_err(|e| e.to_string())
+    }
+}
+
+/// FNV-1a over the canonical json form, this is not a cryptographic signature.
+#[cfg(feature = "base64")]
+fn checksum(state: &Value) -> Result<u64, serde_json::Error> {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    let bytes = serde_json::to_vec(state)?;
+    Ok(bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    }))
+}
+
+#[cfg(all(test, feature = "base64"))]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    struct Page {
+        offset: usize,
+        filter: String,
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let page = Page {
+            offset: 42,
+            filter: "\"}, {\"offset\": 0".to_owned(),
+        };
+        let cursor = Cursor::encode(&page).expect("encode cursor");
+        let json = serde_json::to_value(&cursor).expect("serialize cursor");
+        assert!(json.is_string());
+        let cursor: Cursor = serde_json::from_value(json).expect("deserialize cursor");
+        assert_eq!(cursor.decode::<Page>().expect("decode cursor"), page);
+    }
+
+    #[test]
+    fn test_cursor_tampered() {
+        use base64::engine::{Engine, general_purpose::URL_SAFE_NO_PAD};
+        let cursor = Cursor::encode(&Page {
+            offset: 42,
+            filter: String::new(),
+        })
+        .expect("encode cursor");
+        let json = URL_SAFE_NO_PAD.decode(cursor.as_str()).expect("base64");
+        let tampered = String::from_utf8(json).expect("utf8").replace("42", "0");
+        let tampered = Cursor::new(URL_SAFE_NO_PAD.encode(tampered));
+        assert!(tampered.decode::<Page>().is_err());
+        assert!(Cursor::from("not a cursor").decode::<Page>().is_err());
+    }
+}
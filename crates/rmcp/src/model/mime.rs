@@ -0,0 +1,188 @@
+use std::{borrow::Cow, fmt};
+
+/// A mime type, normalized so that the aliases of a type compare equal, e.g. `image/jpg` is `image/jpeg`.
+///
+/// The mime types of the contents and the resources stay strings on the wire, and are normalized by their
+/// constructors, e.g. [`Content::image`](super::Content::image) or [`RawResource::with_mime_type`](super::RawResource::with_mime_type).
+/// The constants cover the common types:
+///
+/// ```rust
+/// # use rmcp::model::{Content, MimeType};
+/// let image = Content::image("iVBORw0KGgo=", MimeType::IMAGE_PNG);
+/// assert_eq!(image.as_image().unwrap().mime_type, "image/png");
+/// let image = Content::image("/9j/4AAQ", "Image/JPG");
+/// assert_eq!(image.as_image().unwrap().mime_type, "image/jpeg");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MimeType(Cow<'static, str>);
+
+impl MimeType {
+    pub const TEXT_PLAIN: Self = Self::from_static("text/plain");
+    pub const TEXT_MARKDOWN: Self = Self::from_static("text/markdown");
+    pub const TEXT_HTML: Self = Self::from_static("text/html");
+    pub const TEXT_CSS: Self = Self::from_static("text/css");
+    pub const TEXT_CSV: Self = Self::from_static("text/csv");
+    pub const TEXT_JAVASCRIPT: Self = Self::from_static("text/javascript");
+    pub const APPLICATION_JSON: Self = Self::from_static("application/json");
+    pub const APPLICATION_XML: Self = Self::from_static("application/xml");
+    pub const APPLICATION_PDF: Self = Self::from_static("application/pdf");
+    pub const APPLICATION_ZIP: Self = Self::from_static("application/zip");
+    pub const APPLICATION_OCTET_STREAM: Self = Self::from_static("application/octet-stream");
+    pub const IMAGE_PNG: Self = Self::from_static("image/png");
+    pub const IMAGE_JPEG: Self = Self::from_static("image/jpeg");
+    pub const IMAGE_GIF: Self = Self::from_static("image/gif");
+    pub const IMAGE_WEBP: Self = Self::from_static("image/webp");
+    pub const IMAGE_SVG: Self = Self::from_static("image/svg+xml");
+    pub const AUDIO_WAV: Self = Self::from_static("audio/wav");
+    pub const AUDIO_MPEG: Self = Self::from_static("audio/mpeg");
+    pub const AUDIO_OGG: Self = Self::from_static("audio/ogg");
+    pub const AUDIO_FLAC: Self = Self::from_static("audio/flac");
+
+    /// The aliases of the constants, by their lowercase essence
+    const ALIASES: &[(&str, Self)] = &[
+        ("image/jpg", Self::IMAGE_JPEG),
+        ("image/pjpeg", Self::IMAGE_JPEG),
+        ("image/x-png", Self::IMAGE_PNG),
+        ("image/svg", Self::IMAGE_SVG),
+        ("audio/x-wav", Self::AUDIO_WAV),
+        ("audio/wave", Self::AUDIO_WAV),
+        ("audio/vnd.wave", Self::AUDIO_WAV),
+        ("audio/mp3", Self::AUDIO_MPEG),
+        ("audio/x-flac", Self::AUDIO_FLAC),
+        ("text/x-markdown", Self::TEXT_MARKDOWN),
+        ("text/json", Self::APPLICATION_JSON),
+        ("text/xml", Self::APPLICATION_XML),
+        ("application/javascript", Self::TEXT_JAVASCRIPT),
+        ("application/x-javascript", Self::TEXT_JAVASCRIPT),
+        ("application/x-zip-compressed", Self::APPLICATION_ZIP),
+    ];
+
+    const fn from_static(mime: &'static str) -> Self {
+        Self(Cow::Borrowed(mime))
+    }
+
+    /// Normalize a mime type: the type and the subtype are lowercased and their aliases replaced,
+    /// the parameters, e.g. `; charset=utf-8`, are kept, and a string which isn't a mime type is kept as is.
+    pub fn new(mime: impl AsRef<str>) -> Self {
+        let mime = mime.as_ref().trim();
+        let (essence, parameters) = match mime.split_once(';') {
+            Some((essence, parameters)) => (essence.trim(), Some(parameters)),
+            None => (mime, None),
+        };
+        let essence = essence.to_ascii_lowercase();
+        let essence = Self::ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == essence)
+            .map(|(_, canonical)| canonical.as_str().to_owned())
+            .unwrap_or(essence);
+        match parameters {
+            None => Self(Cow::Owned(essence)),
+            Some(parameters) => {
+                let parameters = parameters
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|parameter| !parameter.is_empty());
+                let mut mime = essence;
+                for parameter in parameters {
+                    mime.push_str("; ");
+                    mime.push_str(parameter);
+                }
+                Self(Cow::Owned(mime))
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The type and the subtype, without the parameters
+    pub fn essence(&self) -> &str {
+        self.0.split(';').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for MimeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for MimeType {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<MimeType> for String {
+    fn from(mime: MimeType) -> Self {
+        mime.0.into_owned()
+    }
+}
+
+impl PartialEq<str> for MimeType {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for MimeType {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Content, RawResource, ResourceContents};
+
+    #[test]
+    fn test_normalize_aliases() {
+        assert_eq!(MimeType::new("image/jpg"), MimeType::IMAGE_JPEG);
+        assert_eq!(MimeType::new(" Image/JPG "), MimeType::IMAGE_JPEG);
+        assert_eq!(MimeType::new("image/pjpeg"), MimeType::IMAGE_JPEG);
+        assert_eq!(MimeType::new("audio/mp3"), MimeType::AUDIO_MPEG);
+        assert_eq!(MimeType::new("audio/x-wav"), MimeType::AUDIO_WAV);
+        assert_eq!(MimeType::new("text/x-markdown"), MimeType::TEXT_MARKDOWN);
+        assert_eq!(
+            MimeType::new("application/javascript"),
+            MimeType::TEXT_JAVASCRIPT
+        );
+        // canonical and unknown types are only lowercased
+        assert_eq!(MimeType::new("image/png"), MimeType::IMAGE_PNG);
+        assert_eq!(
+            MimeType::new("Application/Vnd.Custom+JSON"),
+            "application/vnd.custom+json"
+        );
+        assert_eq!(MimeType::new("text"), "text");
+    }
+
+    #[test]
+    fn test_normalize_parameters() {
+        let mime = MimeType::new("Text/X-Markdown;charset=UTF-8 ;  variant=GFM");
+        assert_eq!(mime, "text/markdown; charset=UTF-8; variant=GFM");
+        assert_eq!(mime.essence(), "text/markdown");
+        assert_eq!(MimeType::new("text/plain;"), MimeType::TEXT_PLAIN);
+    }
+
+    #[test]
+    fn test_constructors_normalize() {
+        let image = Content::image("AAAA", "image/jpg");
+        assert_eq!(image.as_image().unwrap().mime_type, "image/jpeg");
+        let audio = Content::audio("AAAA", MimeType::AUDIO_WAV);
+        assert_eq!(audio.as_audio().unwrap().mime_type, "audio/wav");
+        let resource = RawResource::new("file:///a.md", "a").with_mime_type("text/x-markdown");
+        assert_eq!(resource.mime_type.as_deref(), Some("text/markdown"));
+        let contents = ResourceContents::text("a", "file:///a.mp3").with_mime_type("audio/mp3");
+        assert_eq!(contents.mime_type(), Some("audio/mpeg"));
+        // on the wire, the mime type is kept as sent
+        let image: Content = serde_json::from_value(serde_json::json!({
+            "type": "image",
+            "data": "AAAA",
+            "mimeType": "image/jpg",
+        }))
+        .unwrap();
+        assert_eq!(image.as_image().unwrap().mime_type, "image/jpg");
+    }
+}
@@ -1,11 +1,15 @@
-use super::content::{EmbeddedResource, ImageContent};
+use super::content::{Content, RawContent};
 use super::resource::ResourceContents;
-use super::{AnnotateAble, Annotations, RawEmbeddedResource, RawImageContent};
+use super::{
+    AnnotateAble, Annotated, Annotations, EmbeddedResource, ImageContent, MimeType,
+    RawEmbeddedResource, RawImageContent,
+};
 use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
 
 /// A prompt that can be used to generate text from a model
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Prompt {
     /// The name of the prompt
@@ -35,10 +39,20 @@ impl Prompt {
             arguments,
         }
     }
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+    /// Add an argument, listed after the ones already added
+    pub fn with_argument(mut self, argument: PromptArgument) -> Self {
+        self.arguments.get_or_insert_with(Vec::new).push(argument);
+        self
+    }
 }
 
 /// Represents a prompt argument that can be passed to customize the prompt
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PromptArgument {
     /// The name of the argument
     pub name: String,
@@ -50,15 +64,43 @@ pub struct PromptArgument {
     pub required: Option<bool>,
 }
 
+impl PromptArgument {
+    /// Create an optional argument, without description
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            required: None,
+        }
+    }
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+    /// Mark the argument as required, a client must fill it in before getting the prompt
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = Some(required);
+        self
+    }
+    pub fn is_required(&self) -> bool {
+        self.required.unwrap_or(false)
+    }
+}
+
 /// Represents the role of a message sender in a prompt conversation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum PromptMessageRole {
     User,
     Assistant,
 }
 
-/// Content types that can be included in prompt messages
+/// The former content of prompt messages, without audio.
+///
+/// Convert it into a [`Content`] with `into`. A [`Content`] converts back with `try_from`,
+/// except for audio, and the annotations of a text are dropped.
+#[deprecated(note = "the content of a `PromptMessage` is a `Content`")]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PromptMessageContent {
@@ -73,27 +115,67 @@ pub enum PromptMessageContent {
     Resource { resource: EmbeddedResource },
 }
 
+#[allow(deprecated)]
 impl PromptMessageContent {
     pub fn text(text: impl Into<String>) -> Self {
         Self::Text { text: text.into() }
     }
 }
 
+#[allow(deprecated)]
+impl From<PromptMessageContent> for Content {
+    fn from(content: PromptMessageContent) -> Self {
+        match content {
+            PromptMessageContent::Text { text } => Content::text(text),
+            PromptMessageContent::Image { image } => {
+                RawContent::Image(image.raw).optional_annotate(image.annotations)
+            }
+            PromptMessageContent::Resource { resource } => {
+                RawContent::Resource(resource.raw).optional_annotate(resource.annotations)
+            }
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl TryFrom<Content> for PromptMessageContent {
+    /// The audio content, which has no variant
+    type Error = Content;
+
+    fn try_from(content: Content) -> Result<Self, Self::Error> {
+        let Annotated { raw, annotations } = content;
+        match raw {
+            RawContent::Text(text) => Ok(Self::Text { text: text.text }),
+            RawContent::Image(image) => Ok(Self::Image {
+                image: image.optional_annotate(annotations),
+            }),
+            RawContent::Resource(resource) => Ok(Self::Resource {
+                resource: resource.optional_annotate(annotations),
+            }),
+            raw @ RawContent::Audio(_) => Err(Annotated { raw, annotations }),
+        }
+    }
+}
+
 /// A message in a prompt conversation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PromptMessage {
     /// The role of the message sender
     pub role: PromptMessageRole,
-    /// The content of the message
-    pub content: PromptMessageContent,
+    /// The content of the message: text, image, audio or an embedded resource, like the content of tool results
+    pub content: Content,
 }
 
 impl PromptMessage {
+    pub fn new(role: PromptMessageRole, content: Content) -> Self {
+        Self { role, content }
+    }
     /// Create a new text message with the given role and text content
     pub fn new_text<S: Into<String>>(role: PromptMessageRole, text: S) -> Self {
         Self {
             role,
-            content: PromptMessageContent::Text { text: text.into() },
+            content: Content::text(text),
         }
     }
     #[cfg(feature = "base64")]
@@ -103,19 +185,17 @@ impl PromptMessage {
         mime_type: &str,
         annotations: Option<Annotations>,
     ) -> Self {
-        let mime_type = mime_type.into();
+        let mime_type = MimeType::new(mime_type).into();
 
         let base64 = BASE64_STANDARD.encode(data);
 
         Self {
             role,
-            content: PromptMessageContent::Image {
-                image: RawImageContent {
-                    data: base64,
-                    mime_type,
-                }
-                .optional_annotate(annotations),
-            },
+            content: RawContent::Image(RawImageContent {
+                data: base64,
+                mime_type,
+            })
+            .optional_annotate(annotations),
         }
     }
 
@@ -129,24 +209,23 @@ impl PromptMessage {
     ) -> Self {
         let resource_contents = ResourceContents::TextResourceContents {
             uri,
-            mime_type: Some(mime_type),
+            mime_type: Some(MimeType::new(mime_type).into()),
             text: text.unwrap_or_default(),
         };
 
         Self {
             role,
-            content: PromptMessageContent::Resource {
-                resource: RawEmbeddedResource {
-                    resource: resource_contents,
-                }
-                .optional_annotate(annotations),
-            },
+            content: RawContent::Resource(RawEmbeddedResource {
+                resource: resource_contents,
+            })
+            .optional_annotate(annotations),
         }
     }
 }
 
 /// A template for a prompt
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PromptTemplate {
     pub id: String,
     pub template: String,
@@ -155,8 +234,79 @@ pub struct PromptTemplate {
 
 /// A template for a prompt argument, this should be identical to PromptArgument
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PromptArgumentTemplate {
     pub name: String,
     pub description: Option<String>,
     pub required: Option<bool>,
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::ListPromptsResult;
+
+    #[test]
+    fn test_prompt_arguments_round_trip() {
+        let prompt = Prompt::new("code_review", None::<String>, None)
+            .with_description("Review a piece of code")
+            .with_argument(
+                PromptArgument::new("code")
+                    .with_description("The code to review")
+                    .with_required(true),
+            )
+            .with_argument(PromptArgument::new("language"));
+        let result = ListPromptsResult {
+            next_cursor: None,
+            prompts: vec![prompt.clone()],
+            meta: None,
+        };
+        let json = serde_json::to_value(&result).expect("serialize prompts");
+        assert_eq!(
+            json,
+            json!({
+                "prompts": [{
+                    "name": "code_review",
+                    "description": "Review a piece of code",
+                    "arguments": [
+                        { "name": "code", "description": "The code to review", "required": true },
+                        { "name": "language" }
+                    ]
+                }]
+            })
+        );
+        let result: ListPromptsResult = serde_json::from_value(json).expect("deserialize prompts");
+        assert_eq!(result.prompts, vec![prompt]);
+        let arguments = result.prompts[0].arguments.as_deref().unwrap();
+        assert!(arguments[0].is_required());
+        assert!(!arguments[1].is_required());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_prompt_message_content_conversions() {
+        let text = PromptMessageContent::text("hello");
+        let content = Content::from(text.clone());
+        assert_eq!(content, Content::text("hello"));
+        // the same json on the wire
+        assert_eq!(
+            serde_json::to_value(&text).unwrap(),
+            serde_json::to_value(&content).unwrap()
+        );
+        assert_eq!(PromptMessageContent::try_from(content), Ok(text));
+
+        let image = Content::image("aGVsbG8=", "image/png");
+        let legacy = PromptMessageContent::try_from(image.clone()).expect("an image converts");
+        assert!(matches!(legacy, PromptMessageContent::Image { .. }));
+        assert_eq!(Content::from(legacy), image);
+
+        let audio = RawContent::Audio(crate::model::RawAudioContent {
+            data: "aGVsbG8=".into(),
+            mime_type: "audio/wav".into(),
+        })
+        .no_annotation();
+        assert_eq!(PromptMessageContent::try_from(audio.clone()), Err(audio));
+    }
+}
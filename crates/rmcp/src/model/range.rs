@@ -0,0 +1,220 @@
+//! Read a byte range of a resource, a non-standard extension of `resources/read`.
+//!
+//! MCP has no in-band way to read a part of a resource, so the range is set in the `_meta` of the request,
+//! under [`RANGE_META_KEY`], and a server honoring it sets the range it returns in the `_meta` of the result,
+//! under [`CONTENT_RANGE_META_KEY`], like the `Range` and `Content-Range` headers of HTTP. A server which
+//! doesn't know the extension ignores the range and returns the whole resource, without a content range:
+//!
+//! ```rust,ignore
+//! // client
+//! let result = client
+//!     .read_resource(ReadResourceRequestParam::new("file:///large.bin").with_range(ByteRange::new(1024, 512)))
+//!     .await?;
+//! match result.content_range()? {
+//!     Some(range) => println!("bytes {}-{} of {}", range.offset, range.end(), range.total),
+//!     None => println!("the whole resource"),
+//! }
+//!
+//! // server
+//! async fn read_resource(&self, request: ReadResourceRequestParam, _: RequestContext<RoleServer>) -> Result<ReadResourceResult, McpError> {
+//!     let bytes = std::fs::read(path_of(&request.uri))?;
+//!     match request.range()? {
+//!         Some(range) => ReadResourceResult::blob_range(&bytes, request.uri, range),
+//!         None => Ok(ReadResourceResult { contents: vec![ResourceContents::blob(bytes, request.uri)], meta: None }),
+//!     }
+//! }
+//! ```
+use serde::{Deserialize, Serialize};
+
+use super::{JsonObject, ReadResourceRequestParam, ReadResourceResult};
+use crate::Error as McpError;
+
+/// The key of the [`ByteRange`] in the `_meta` of `resources/read`
+pub const RANGE_META_KEY: &str = "rmcp/range";
+/// The key of the [`ContentRange`] in the `_meta` of the result of `resources/read`
+pub const CONTENT_RANGE_META_KEY: &str = "rmcp/contentRange";
+
+/// The bytes of a resource the client asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ByteRange {
+    pub offset: u64,
+    /// Up to the end of the resource if it's not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+}
+
+impl ByteRange {
+    pub fn new(offset: u64, length: u64) -> Self {
+        Self {
+            offset,
+            length: Some(length),
+        }
+    }
+
+    /// From `offset` to the end of the resource
+    pub fn from_offset(offset: u64) -> Self {
+        Self {
+            offset,
+            length: None,
+        }
+    }
+
+    /// The part of `bytes` in the range, it's shorter than the range at the end of the bytes.
+    ///
+    /// It fails if the range starts after the end of the bytes, unless both are empty.
+    pub fn slice<'a>(&self, bytes: &'a [u8]) -> Result<(&'a [u8], ContentRange), McpError> {
+        let total = bytes.len() as u64;
+        if self.offset > total {
+            return Err(McpError::invalid_params(
+                format!(
+                    "the range starts at byte {}, after the end of the resource, {total} bytes",
+                    self.offset
+                ),
+                None,
+            ));
+        }
+        let end = match self.length {
+            Some(length) => self.offset.saturating_add(length).min(total),
+            None => total,
+        };
+        let range = ContentRange {
+            offset: self.offset,
+            length: end - self.offset,
+            total,
+        };
+        Ok((&bytes[self.offset as usize..end as usize], range))
+    }
+}
+
+/// The bytes of a resource the server returns, out of its `total` size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ContentRange {
+    pub offset: u64,
+    pub length: u64,
+    pub total: u64,
+}
+
+impl ContentRange {
+    /// The offset after the last byte returned
+    pub fn end(&self) -> u64 {
+        self.offset + self.length
+    }
+}
+
+pub(super) fn set_meta<T: Serialize>(meta: &mut Option<JsonObject>, key: &str, value: T) {
+    let value = serde_json::to_value(value).expect("a range serializes into json");
+    meta.get_or_insert_with(JsonObject::new)
+        .insert(key.to_owned(), value);
+}
+
+pub(super) fn get_meta<T: for<'de> Deserialize<'de>>(
+    meta: &Option<JsonObject>,
+    key: &str,
+) -> Result<Option<T>, serde_json::Error> {
+    meta.as_ref()
+        .and_then(|meta| meta.get(key))
+        .map(|value| T::deserialize(value))
+        .transpose()
+}
+
+impl ReadResourceRequestParam {
+    /// Ask for a part of the resource, see [`ByteRange`]
+    pub fn with_range(mut self, range: ByteRange) -> Self {
+        set_meta(&mut self.meta, RANGE_META_KEY, range);
+        self
+    }
+
+    /// The range asked by the client, an invalid range is an `INVALID_PARAMS` error
+    pub fn range(&self) -> Result<Option<ByteRange>, McpError> {
+        get_meta(&self.meta, RANGE_META_KEY).map_err(|error| {
+            McpError::invalid_params(format!("invalid {RANGE_META_KEY}: {error}"), None)
+        })
+    }
+}
+
+impl ReadResourceResult {
+    /// Tell the client the contents are a part of the resource
+    pub fn with_content_range(mut self, range: ContentRange) -> Self {
+        set_meta(&mut self.meta, CONTENT_RANGE_META_KEY, range);
+        self
+    }
+
+    /// The part of the resource the server returned, `None` if it returned the whole resource
+    pub fn content_range(&self) -> Result<Option<ContentRange>, serde_json::Error> {
+        get_meta(&self.meta, CONTENT_RANGE_META_KEY)
+    }
+
+    /// The bytes of `range` as a blob, with their content range
+    #[cfg(feature = "base64")]
+    pub fn blob_range(
+        bytes: &[u8],
+        uri: impl Into<String>,
+        range: ByteRange,
+    ) -> Result<Self, McpError> {
+        let (bytes, content_range) = range.slice(bytes)?;
+        Ok(Self {
+            contents: vec![super::ResourceContents::blob(bytes, uri)],
+            meta: None,
+        }
+        .with_content_range(content_range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice() {
+        let bytes = b"0123456789";
+        let slice = |range: ByteRange| {
+            range
+                .slice(bytes)
+                .map(|(bytes, range)| (std::str::from_utf8(bytes).unwrap(), range))
+        };
+        assert_eq!(
+            slice(ByteRange::new(2, 3)).unwrap(),
+            (
+                "234",
+                ContentRange {
+                    offset: 2,
+                    length: 3,
+                    total: 10
+                }
+            )
+        );
+        assert_eq!(slice(ByteRange::new(8, 5)).unwrap().0, "89");
+        assert_eq!(slice(ByteRange::from_offset(7)).unwrap().0, "789");
+        assert_eq!(slice(ByteRange::from_offset(10)).unwrap().0, "");
+        assert!(slice(ByteRange::new(11, 1)).is_err());
+    }
+
+    #[test]
+    fn test_range_meta() {
+        let request =
+            ReadResourceRequestParam::new("file:///large.bin").with_range(ByteRange::new(4, 2));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "uri": "file:///large.bin",
+                "_meta": { "rmcp/range": { "offset": 4, "length": 2 } }
+            })
+        );
+        assert_eq!(request.range().unwrap(), Some(ByteRange::new(4, 2)));
+        assert_eq!(
+            ReadResourceRequestParam::new("file:///a").range().unwrap(),
+            None
+        );
+
+        let invalid: ReadResourceRequestParam = serde_json::from_value(serde_json::json!({
+            "uri": "file:///a",
+            "_meta": { "rmcp/range": { "offset": -1 } }
+        }))
+        .unwrap();
+        assert!(invalid.range().is_err());
+    }
+}
@@ -1,9 +1,12 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
 use serde::{Deserialize, Serialize};
 
-use super::Annotated;
+use super::{Annotated, MimeType};
 
 /// Represents a resource in the extension with metadata
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RawResource {
     /// URI representing the resource location (e.g., "file:///path/to/file" or "str:///content")
@@ -13,7 +16,7 @@ pub struct RawResource {
     /// Optional description of the resource
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    /// MIME type of the resource content ("text" or "blob")
+    /// MIME type of the resource content, e.g. `text/markdown`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
 
@@ -27,6 +30,7 @@ pub struct RawResource {
 pub type Resource = Annotated<RawResource>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RawResourceTemplate {
     pub uri_template: String,
@@ -39,24 +43,91 @@ pub struct RawResourceTemplate {
 
 pub type ResourceTemplate = Annotated<RawResourceTemplate>;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase", untagged)]
 pub enum ResourceContents {
     TextResourceContents {
         uri: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
         text: String,
     },
     BlobResourceContents {
         uri: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
         blob: String,
     },
 }
 
+impl<'de> Deserialize<'de> for ResourceContents {
+    /// Like an untagged enum, but the error of an invalid blob is kept, instead of matching no variant
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Raw {
+            uri: String,
+            #[serde(default)]
+            mime_type: Option<String>,
+            #[serde(default)]
+            text: Option<String>,
+            #[serde(default)]
+            blob: Option<String>,
+        }
+        let Raw {
+            uri,
+            mime_type,
+            text,
+            blob,
+        } = Raw::deserialize(deserializer)?;
+        match (text, blob) {
+            (Some(text), _) => Ok(Self::TextResourceContents {
+                uri,
+                mime_type,
+                text,
+            }),
+            (None, Some(blob)) => {
+                #[cfg(feature = "base64")]
+                let blob = super::binary::strip_base64_whitespace(blob);
+                #[cfg(feature = "base64")]
+                super::validate_base64(&blob).map_err(|error| {
+                    serde::de::Error::custom(format_args!("invalid base64 blob of {uri}: {error}"))
+                })?;
+                Ok(Self::BlobResourceContents {
+                    uri,
+                    mime_type,
+                    blob,
+                })
+            }
+            (None, None) => Err(serde::de::Error::custom(
+                "expect either a text or a blob in the resource contents",
+            )),
+        }
+    }
+}
+
 impl ResourceContents {
+    /// Encode binary contents in standard base64
+    #[cfg(feature = "base64")]
+    pub fn blob(data: impl AsRef<[u8]>, uri: impl Into<String>) -> Self {
+        Self::BlobResourceContents {
+            uri: uri.into(),
+            mime_type: None,
+            blob: super::encode_base64(data, super::Base64Alphabet::Standard),
+        }
+    }
+    /// Decode the blob of binary contents, in either base64 alphabet, `None` for text contents
+    #[cfg(feature = "base64")]
+    pub fn decode_blob(&self) -> Option<Result<Vec<u8>, super::Base64Error>> {
+        match self {
+            Self::BlobResourceContents { blob, .. } => Some(super::decode_base64(blob)),
+            Self::TextResourceContents { .. } => None,
+        }
+    }
     pub fn text(text: impl Into<String>, uri: impl Into<String>) -> Self {
         Self::TextResourceContents {
             uri: uri.into(),
@@ -64,6 +135,27 @@ impl ResourceContents {
             text: text.into(),
         }
     }
+    pub fn uri(&self) -> &str {
+        match self {
+            Self::TextResourceContents { uri, .. } | Self::BlobResourceContents { uri, .. } => uri,
+        }
+    }
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Self::TextResourceContents { mime_type, .. }
+            | Self::BlobResourceContents { mime_type, .. } => mime_type.as_deref(),
+        }
+    }
+    /// Set the MIME type of this content
+    pub fn with_mime_type(mut self, mime: impl Into<String>) -> Self {
+        match &mut self {
+            Self::TextResourceContents { mime_type, .. }
+            | Self::BlobResourceContents { mime_type, .. } => {
+                *mime_type = Some(MimeType::new(mime.into()).into())
+            }
+        }
+        self
+    }
 }
 
 impl RawResource {
@@ -77,4 +169,230 @@ impl RawResource {
             size: None,
         }
     }
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(MimeType::new(mime_type.into()).into());
+        self
+    }
+    /// Set the size of the raw content in bytes
+    pub fn with_size(mut self, size: u32) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
+/// The error of [`RawResourceTemplate::expand`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UriTemplateError {
+    #[error("no value for the variable {0} of the uri template")]
+    MissingVariable(String),
+    #[error("unclosed expression at byte {0} of the uri template")]
+    UnclosedExpression(usize),
+    #[error("unsupported expression {{{0}}} in the uri template")]
+    UnsupportedExpression(String),
+}
+
+impl RawResourceTemplate {
+    /// Expand the uri template with the values of its variables, into a uri to read.
+    ///
+    /// This implements the [RFC 6570](https://www.rfc-editor.org/rfc/rfc6570) simple string expansion `{var}`,
+    /// where the characters other than the unreserved ones are percent-encoded,
+    /// and the reserved expansion `{+var}`, which keeps the reserved characters, e.g. the `/` of a path.
+    /// Other operators and lists of variables are not supported.
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use rmcp::model::RawResourceTemplate;
+    /// let template = RawResourceTemplate {
+    ///     uri_template: "file:///{+path}".into(),
+    ///     name: "file".into(),
+    ///     description: None,
+    ///     mime_type: None,
+    /// };
+    /// let vars = HashMap::from([("path", "src/lib.rs")]);
+    /// assert_eq!(template.expand(&vars).unwrap(), "file:///src/lib.rs");
+    /// ```
+    pub fn expand<K, V>(&self, vars: &HashMap<K, V>) -> Result<String, UriTemplateError>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: AsRef<str>,
+    {
+        let template = self.uri_template.as_str();
+        let mut uri = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            uri.push_str(&rest[..start]);
+            let offset = template.len() - rest.len() + start;
+            let end = rest[start..]
+                .find('}')
+                .ok_or(UriTemplateError::UnclosedExpression(offset))?;
+            let expression = &rest[start + 1..start + end];
+            let (reserved, name) = match expression.strip_prefix('+') {
+                Some(name) => (true, name),
+                None => (false, expression),
+            };
+            let is_varname = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '%'));
+            if !is_varname {
+                return Err(UriTemplateError::UnsupportedExpression(
+                    expression.to_owned(),
+                ));
+            }
+            let value = vars
+                .get(name)
+                .ok_or_else(|| UriTemplateError::MissingVariable(name.to_owned()))?;
+            percent_encode(&mut uri, value.as_ref(), reserved);
+            rest = &rest[start + end + 1..];
+        }
+        uri.push_str(rest);
+        Ok(uri)
+    }
+}
+
+fn percent_encode(out: &mut String, value: &str, reserved: bool) {
+    for byte in value.bytes() {
+        let allowed = byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'.' | b'_' | b'~')
+            || (reserved && b":/?#[]@!$&'()*+,;=".contains(&byte));
+        if allowed {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AnnotateAble;
+
+    #[test]
+    fn test_resource_metadata_serde() {
+        let resource = RawResource::new("file:///README.md", "README")
+            .with_description("project readme")
+            .with_mime_type("text/markdown")
+            .with_size(1024)
+            .no_annotation();
+        let json = serde_json::to_value(&resource).expect("serialize resource");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "uri": "file:///README.md",
+                "name": "README",
+                "description": "project readme",
+                "mimeType": "text/markdown",
+                "size": 1024,
+            })
+        );
+        let deserialized: Resource = serde_json::from_value(json).expect("deserialize resource");
+        assert_eq!(deserialized, resource);
+
+        let contents =
+            ResourceContents::text("# rmcp", "file:///README.md").with_mime_type("text/markdown");
+        let json = serde_json::to_value(&contents).expect("serialize contents");
+        assert_eq!(json["mimeType"], "text/markdown");
+        let deserialized: ResourceContents =
+            serde_json::from_value(json).expect("deserialize contents");
+        assert_eq!(deserialized.mime_type(), Some("text/markdown"));
+        assert_eq!(deserialized.uri(), "file:///README.md");
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_blob_contents() {
+        let data = (0..=255u8).collect::<Vec<_>>();
+        let contents = ResourceContents::blob(&data, "file:///bytes")
+            .with_mime_type("application/octet-stream");
+        let json = serde_json::to_value(&contents).expect("serialize contents");
+        let deserialized: ResourceContents =
+            serde_json::from_value(json).expect("deserialize contents");
+        assert_eq!(deserialized.decode_blob(), Some(Ok(data)));
+
+        let error = serde_json::from_value::<ResourceContents>(serde_json::json!({
+            "uri": "file:///bytes",
+            "blob": "AAAA*AAA",
+        }))
+        .expect_err("invalid base64");
+        assert_eq!(
+            error.to_string(),
+            "invalid base64 blob of file:///bytes: invalid byte 0x2a at offset 4"
+        );
+        let wrapped: ResourceContents = serde_json::from_value(serde_json::json!({
+            "uri": "file:///bytes",
+            "blob": "AAEC\r\nAwQF\n",
+        }))
+        .expect("line-wrapped base64");
+        assert_eq!(wrapped.decode_blob(), Some(Ok(vec![0, 1, 2, 3, 4, 5])));
+        let error = serde_json::from_value::<ResourceContents>(serde_json::json!({
+            "uri": "file:///bytes",
+        }))
+        .expect_err("no text nor blob");
+        assert!(error.to_string().contains("either a text or a blob"));
+    }
+
+    fn template(uri_template: &str) -> RawResourceTemplate {
+        RawResourceTemplate {
+            uri_template: uri_template.into(),
+            name: "template".into(),
+            description: None,
+            mime_type: None,
+        }
+    }
+
+    #[test]
+    fn test_expand_simple() {
+        let vars = HashMap::from([("name", "hello world/x")]);
+        assert_eq!(
+            template("str:///{name}").expand(&vars),
+            Ok("str:///hello%20world%2Fx".to_owned())
+        );
+        assert_eq!(
+            template("file:///{+name}").expand(&vars),
+            Ok("file:///hello%20world/x".to_owned())
+        );
+        assert_eq!(
+            template("str:///static").expand(&vars),
+            Ok("str:///static".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_expand_multiple_variables() {
+        let vars = HashMap::from([
+            ("owner".to_owned(), "RWDai".to_owned()),
+            ("repo".to_owned(), "rmcp".to_owned()),
+            ("path".to_owned(), "crates/rmcp/src/lib.rs".to_owned()),
+        ]);
+        assert_eq!(
+            template("repo://{owner}/{repo}/blob/{+path}?raw={owner}").expand(&vars),
+            Ok("repo://RWDai/rmcp/blob/crates/rmcp/src/lib.rs?raw=RWDai".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let vars = HashMap::from([("a", "1")]);
+        assert_eq!(
+            template("x:///{a}/{b}").expand(&vars),
+            Err(UriTemplateError::MissingVariable("b".into()))
+        );
+        assert_eq!(
+            template("x:///{a").expand(&vars),
+            Err(UriTemplateError::UnclosedExpression(5))
+        );
+        assert_eq!(
+            template("x:///{?a}").expand(&vars),
+            Err(UriTemplateError::UnsupportedExpression("?a".into()))
+        );
+        assert_eq!(
+            template("x:///{a,b}").expand(&vars),
+            Err(UriTemplateError::UnsupportedExpression("a,b".into()))
+        );
+    }
 }
@@ -0,0 +1,123 @@
+//! The JSON Schema of the protocol, generated from the model types, e.g. to check the interop with the JS SDK.
+//!
+//! [`protocol_schema`] describes every JSON-RPC message a client or a server sends, with a definition for each
+//! type of the model, which tools like `json-schema-to-typescript` turn into TypeScript definitions. Run the
+//! `protocol_schema` example to print it:
+//!
+//! ```sh
+//! cargo run -p rmcp --example protocol_schema --features schemars > schema.json
+//! ```
+use schemars::{
+    JsonSchema,
+    r#gen::SchemaGenerator,
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, SubschemaValidation},
+};
+
+use super::{ClientJsonRpcMessage, NumberOrString, ProtocolVersion, ServerJsonRpcMessage};
+
+/// A message of either peer, only to generate the schema
+#[derive(JsonSchema)]
+#[schemars(untagged)]
+#[allow(dead_code)]
+enum ProtocolMessage {
+    Client(ClientJsonRpcMessage),
+    Server(ServerJsonRpcMessage),
+}
+
+/// The JSON Schema of the messages of both peers, with the model types in its `definitions`
+pub fn protocol_schema() -> RootSchema {
+    SchemaGenerator::default().into_root_schema_for::<ProtocolMessage>()
+}
+
+/// The schema of a [`ConstString`](super::ConstString), a string with a single value
+#[doc(hidden)]
+pub fn const_string_schema(value: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        const_value: Some(value.into()),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl JsonSchema for NumberOrString {
+    fn schema_name() -> String {
+        "NumberOrString".to_owned()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    generator.subschema_for::<u32>(),
+                    generator.subschema_for::<String>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for ProtocolVersion {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "ProtocolVersion".to_owned()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::schema_for;
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::CallToolResult;
+
+    #[test]
+    fn test_call_tool_result_schema() {
+        let schema = serde_json::to_value(schema_for!(CallToolResult)).unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["content"]));
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["content"]["items"]["$ref"],
+            "#/definitions/Annotated_for_RawContent"
+        );
+        assert_eq!(properties["isError"]["type"], json!(["boolean", "null"]));
+        assert_eq!(properties["_meta"]["type"], json!(["object", "null"]));
+        assert!(properties.get("structuredContent").is_some());
+        assert!(properties.get("meta").is_none());
+        assert!(
+            schema["definitions"]
+                .get("Annotated_for_RawContent")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_protocol_schema() {
+        let schema = serde_json::to_value(protocol_schema()).unwrap();
+        let definitions = schema["definitions"].as_object().unwrap();
+        for name in [
+            "InitializeRequestParam",
+            "ServerResult",
+            "CallToolResult",
+            "Tool",
+        ] {
+            assert!(definitions.contains_key(name), "{name}");
+        }
+        assert_eq!(
+            definitions["JsonRpcRequest_for_ClientRequest"]["properties"]["jsonrpc"],
+            json!({ "type": "string", "const": "2.0" })
+        );
+    }
+}
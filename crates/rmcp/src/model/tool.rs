@@ -8,14 +8,49 @@ use super::JsonObject;
 
 /// A tool that can be used by a model.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Tool {
     /// The name of the tool
     pub name: Cow<'static, str>,
+    /// A human-readable title for the tool, the `name` will be displayed if it's absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Cow<'static, str>>,
     /// A description of what the tool does
     pub description: Cow<'static, str>,
     /// A JSON Schema object defining the expected parameters for the tool
     pub input_schema: Arc<JsonObject>,
+    /// A JSON Schema object defining the structure of the tool's output in `structuredContent`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Arc<JsonObject>>,
+    /// Additional hints about the tool's behavior
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Additional properties describing a tool to clients.
+///
+/// All properties are **hints**, clients should never make tool use decisions based on them
+/// when the server is untrusted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    /// A human-readable title for the tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Cow<'static, str>>,
+    /// If true, the tool does not modify its environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    /// If true, the tool may perform destructive updates to its environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    /// If true, calling the tool repeatedly with the same arguments will have no additional effect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    /// If true, the tool may interact with an "open world" of external entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
 }
 
 impl Tool {
@@ -28,8 +63,66 @@ impl Tool {
     {
         Tool {
             name: name.into(),
+            title: None,
             description: description.into(),
             input_schema: input_schema.into(),
+            output_schema: None,
+            annotations: None,
         }
     }
+
+    /// Set the schema of the structured output of this tool
+    pub fn with_output_schema(mut self, output_schema: impl Into<Arc<JsonObject>>) -> Self {
+        self.output_schema = Some(output_schema.into());
+        self
+    }
+
+    /// Set the human-readable title of this tool
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the annotations of this tool
+    pub fn with_annotations(mut self, annotations: ToolAnnotations) -> Self {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    /// The name for display, fall back to `annotations.title` and then `name`
+    pub fn display_name(&self) -> &str {
+        self.title
+            .as_deref()
+            .or_else(|| self.annotations.as_ref()?.title.as_deref())
+            .unwrap_or(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_title_serde() {
+        let tool = Tool::new("get_weather", "Get the weather", JsonObject::new());
+        let json = serde_json::to_value(&tool).expect("serialize tool");
+        assert!(json.get("title").is_none());
+        assert!(json.get("annotations").is_none());
+        assert!(json.get("outputSchema").is_none());
+        assert_eq!(tool.display_name(), "get_weather");
+
+        let tool = tool.with_annotations(ToolAnnotations {
+            title: Some("Weather".into()),
+            read_only_hint: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(tool.display_name(), "Weather");
+        let tool = tool.with_title("Get Weather");
+        assert_eq!(tool.display_name(), "Get Weather");
+        let json = serde_json::to_value(&tool).expect("serialize tool");
+        assert_eq!(json["title"], "Get Weather");
+        assert_eq!(json["annotations"]["readOnlyHint"], true);
+        let deserialized: Tool = serde_json::from_value(json).expect("deserialize tool");
+        assert_eq!(deserialized, tool);
+    }
 }
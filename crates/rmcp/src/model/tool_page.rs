@@ -0,0 +1,144 @@
+//! Page the content of a tool result, a non-standard extension of `tools/call`.
+//!
+//! MCP paginates the lists, but not the results of the tools, so a tool returning thousands of contents sends them
+//! in one message. A client opting in sets a cursor in the `_meta` of the call, under [`CURSOR_META_KEY`], with an
+//! empty cursor for the first page. A tool honoring it returns a page of its content, with the cursor of the next
+//! page in the `_meta` of the result, under [`NEXT_CURSOR_META_KEY`], and the client calls it again with the same
+//! arguments and that cursor, until there's no next cursor. A tool which doesn't know the extension returns all of
+//! its content, without a next cursor:
+//!
+//! ```rust,ignore
+//! // client, the pages are concatenated
+//! let result = client.call_tool_all_pages(CallToolRequestParam::new("list_files")).await?;
+//!
+//! // server
+//! #[tool(description = "List the files")]
+//! fn list_files(&self, context: ToolContext) -> Result<CallToolResult, McpError> {
+//!     let files = self.files().map(Content::text).collect();
+//!     match context.cursor()? {
+//!         Some(cursor) => CallToolResult::paginate(files, &cursor, 100),
+//!         None => Ok(CallToolResult::success(files)),
+//!     }
+//! }
+//! ```
+use super::{
+    CallToolRequestParam, CallToolResult, Content, Cursor, JsonObject,
+    range::{get_meta, set_meta},
+};
+use crate::Error as McpError;
+
+/// The key of the [`Cursor`] of the page to return in the `_meta` of `tools/call`
+pub const CURSOR_META_KEY: &str = "rmcp/cursor";
+/// The key of the [`Cursor`] of the next page in the `_meta` of the result of `tools/call`
+pub const NEXT_CURSOR_META_KEY: &str = "rmcp/nextCursor";
+
+impl CallToolRequestParam {
+    /// Ask for a page of the content, the first page with [`Cursor::default`], see the [module documentation](self)
+    pub fn with_cursor(mut self, cursor: Cursor) -> Self {
+        set_meta(&mut self.meta, CURSOR_META_KEY, cursor);
+        self
+    }
+
+    /// The page asked by the client, `None` if it wants all the content, an invalid cursor is an `INVALID_PARAMS` error
+    pub fn cursor(&self) -> Result<Option<Cursor>, McpError> {
+        cursor_of(&self.meta)
+    }
+}
+
+pub(crate) fn cursor_of(meta: &Option<JsonObject>) -> Result<Option<Cursor>, McpError> {
+    get_meta(meta, CURSOR_META_KEY).map_err(|error| {
+        McpError::invalid_params(format!("invalid {CURSOR_META_KEY}: {error}"), None)
+    })
+}
+
+impl CallToolResult {
+    /// Tell the client there's another page of content, it's the last page without it
+    pub fn with_next_cursor(mut self, cursor: Cursor) -> Self {
+        set_meta(&mut self.meta, NEXT_CURSOR_META_KEY, cursor);
+        self
+    }
+
+    /// The cursor of the next page, `None` on the last page, or if the tool returned all its content
+    pub fn next_cursor(&self) -> Result<Option<Cursor>, serde_json::Error> {
+        get_meta(&self.meta, NEXT_CURSOR_META_KEY)
+    }
+
+    /// A successful result with the page of `content` at `cursor`, of at most `page_size` contents,
+    /// with the cursor of the next page if there's more content.
+    ///
+    /// The cursors are the offsets of the pages, so the tool must return the same content on each call.
+    pub fn paginate(
+        mut content: Vec<Content>,
+        cursor: &Cursor,
+        page_size: usize,
+    ) -> Result<Self, McpError> {
+        let offset = match cursor.as_str() {
+            "" => 0,
+            offset => offset
+                .parse::<usize>()
+                .ok()
+                .filter(|offset| *offset <= content.len())
+                .ok_or_else(|| {
+                    McpError::invalid_params(format!("invalid {CURSOR_META_KEY}: {offset}"), None)
+                })?,
+        };
+        let end = offset.saturating_add(page_size.max(1)).min(content.len());
+        let has_next = end < content.len();
+        content.truncate(end);
+        let page = content.split_off(offset);
+        let result = Self::success(page);
+        Ok(match has_next {
+            true => result.with_next_cursor(Cursor::new(end.to_string())),
+            false => result,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(result: &CallToolResult) -> Vec<&str> {
+        result
+            .content
+            .iter()
+            .map(|content| content.as_text().expect("text").text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_paginate() {
+        let content = || (0..5).map(|i| Content::text(i.to_string())).collect();
+        let first = CallToolResult::paginate(content(), &Cursor::default(), 2).unwrap();
+        assert_eq!(texts(&first), ["0", "1"]);
+        let cursor = first.next_cursor().unwrap().expect("a next page");
+        assert_eq!(cursor.as_str(), "2");
+        let second = CallToolResult::paginate(content(), &cursor, 2).unwrap();
+        assert_eq!(texts(&second), ["2", "3"]);
+        let last = CallToolResult::paginate(content(), &Cursor::new("4"), 2).unwrap();
+        assert_eq!(texts(&last), ["4"]);
+        assert_eq!(last.next_cursor().unwrap(), None);
+        assert_eq!(last.meta, None);
+
+        assert!(CallToolResult::paginate(content(), &Cursor::new("6"), 2).is_err());
+        assert!(CallToolResult::paginate(content(), &Cursor::new("x"), 2).is_err());
+    }
+
+    #[test]
+    fn test_cursor_meta() {
+        let request = CallToolRequestParam::new("list").with_cursor(Cursor::new("2"));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "name": "list", "_meta": { "rmcp/cursor": "2" } })
+        );
+        assert_eq!(request.cursor().unwrap(), Some(Cursor::new("2")));
+        assert_eq!(CallToolRequestParam::new("list").cursor().unwrap(), None);
+
+        let invalid: CallToolRequestParam = serde_json::from_value(serde_json::json!({
+            "name": "list",
+            "_meta": { "rmcp/cursor": 2 }
+        }))
+        .unwrap();
+        assert!(invalid.cursor().is_err());
+    }
+}
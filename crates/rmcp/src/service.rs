@@ -1,8 +1,10 @@
 use crate::error::Error as McpError;
 use crate::model::{
-    CancelledNotification, CancelledNotificationParam, JsonRpcMessage, Message, RequestId,
+    CancelledNotification, CancelledNotificationParam, ConstString, EmptyResult, GetMethod,
+    GetProgressToken, JsonObject, JsonRpcMessage, Message, PingRequestMethod, ProgressNotification,
+    ProgressToken, RequestId, UnknownNotification, UnknownRequest,
 };
-use crate::transport::IntoTransport;
+use crate::transport::{IntoTransport, MalformedMessage, WireDirection};
 use futures::future::BoxFuture;
 use thiserror::Error;
 #[cfg(feature = "client")]
@@ -15,7 +17,13 @@ mod server;
 pub use server::*;
 #[cfg(feature = "tower")]
 mod tower;
+#[cfg(feature = "tower")]
 pub use tower::*;
+mod session;
+mod stats;
+pub use session::SessionState;
+pub use stats::PeerStats;
+use stats::StatsCounters;
 
 use tokio_util::sync::CancellationToken;
 #[derive(Error, Debug)]
@@ -31,9 +39,57 @@ pub enum ServiceError {
     Cancelled { reason: Option<String> },
     #[error("request timeout after {}", chrono::Duration::from_std(*timeout).unwrap_or_default())]
     Timeout { timeout: Duration },
+    #[error("the peer doesn't support the {capability} capability")]
+    CapabilityNotSupported { capability: &'static str },
+    /// The queue of the outgoing messages is full, the transport doesn't keep up, see [`Peer::try_send_notification`]
+    #[error("the queue of the outgoing messages is full")]
+    Backpressure,
 }
 
 impl ServiceError {}
+
+/// The `tracing` target of the wire tracing, filter on it to route or silence these events.
+pub const WIRE_TRACING_TARGET: &str = "rmcp::wire";
+
+/// The span of an outgoing request, finished when the request is settled
+struct WireSpan {
+    span: tracing::Span,
+    start: std::time::Instant,
+}
+
+impl WireSpan {
+    fn new(id: &RequestId, method: &str) -> Self {
+        let span = tracing::info_span!(target: WIRE_TRACING_TARGET, "request", %id, method);
+        span.in_scope(|| tracing::info!(target: WIRE_TRACING_TARGET, "send request"));
+        Self {
+            span,
+            start: std::time::Instant::now(),
+        }
+    }
+    fn response(self) {
+        let elapsed = self.start.elapsed();
+        self.span
+            .in_scope(|| tracing::info!(target: WIRE_TRACING_TARGET, ?elapsed, "receive response"));
+    }
+    fn error(self, error: &McpError) {
+        let elapsed = self.start.elapsed();
+        self.span.in_scope(|| {
+            tracing::warn!(target: WIRE_TRACING_TARGET, ?elapsed, code = error.code.0, message = %error.message, "receive error")
+        });
+    }
+    fn failed(self, error: &dyn std::fmt::Display) {
+        let elapsed = self.start.elapsed();
+        self.span.in_scope(|| {
+            tracing::warn!(target: WIRE_TRACING_TARGET, ?elapsed, %error, "fail to send request")
+        });
+    }
+    fn cancelled(self, reason: Option<&str>) {
+        let elapsed = self.start.elapsed();
+        self.span.in_scope(
+            || tracing::info!(target: WIRE_TRACING_TARGET, ?elapsed, reason, "request cancelled"),
+        );
+    }
+}
 trait TransferObject:
     std::fmt::Debug + Clone + serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static
 {
@@ -52,12 +108,15 @@ impl<T> TransferObject for T where
 
 #[allow(private_bounds, reason = "there's no the third implementation")]
 pub trait ServiceRole: std::fmt::Debug + Send + Sync + 'static + Copy + Clone {
-    type Req: TransferObject;
-    type Resp: TransferObject;
+    type Req: TransferObject + GetMethod;
+    type Resp: TransferObject + From<EmptyResult>;
     type Not: TryInto<CancelledNotification, Error = Self::Not>
         + From<CancelledNotification>
+        + TryInto<ProgressNotification, Error = Self::Not>
+        + From<ProgressNotification>
+        + GetMethod
         + TransferObject;
-    type PeerReq: TransferObject;
+    type PeerReq: TransferObject + GetMethod + GetProgressToken;
     type PeerResp: TransferObject;
     type PeerNot: TryInto<CancelledNotification, Error = Self::PeerNot>
         + From<CancelledNotification>
@@ -177,6 +236,7 @@ impl<S: Service> DynService<S::Role> for S {
     }
 }
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -194,9 +254,22 @@ pub struct AtomicU32RequestIdProvider {
     id: AtomicU32,
 }
 
+impl AtomicU32RequestIdProvider {
+    /// Start the ids at `id` rather than 0, e.g. after the ids of a previous client of the same session
+    pub fn starting_at(id: u32) -> Self {
+        Self {
+            id: AtomicU32::new(id),
+        }
+    }
+}
+
 impl RequestIdProvider for AtomicU32RequestIdProvider {
     fn next_request_id(&self) -> RequestId {
-        RequestId::Number(self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst))
+        RequestId::Number(
+            self.id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+        )
     }
 }
 
@@ -206,10 +279,14 @@ type Responder<T> = tokio::sync::oneshot::Sender<T>;
 ///
 /// You can cancel it by call [`RequestHandle::cancel`] with a reason,
 ///
-/// or wait for response by call [`RequestHandle::await_response`]
+/// or wait for response by call [`RequestHandle::await_response`],
+///
+/// or wait for the request to be written to the transport by call [`RequestHandle::await_sent`]
 #[derive(Debug)]
 pub struct RequestHandle<R: ServiceRole> {
     pub rx: tokio::sync::oneshot::Receiver<Result<R::PeerResp, ServiceError>>,
+    /// Resolved once the request is written to the transport, `None` once it's awaited
+    pub sent: Option<tokio::sync::oneshot::Receiver<Result<(), ServiceError>>>,
     pub options: PeerRequestOptions,
     pub peer: Peer<R>,
     pub id: RequestId,
@@ -217,6 +294,19 @@ pub struct RequestHandle<R: ServiceRole> {
 
 impl<R: ServiceRole> RequestHandle<R> {
     pub const REQUEST_TIMEOUT_REASON: &str = "request timeout";
+
+    /// Wait until the request is written to the transport and flushed, e.g. the POST of the SSE transport
+    /// has completed, or the write to stdio is flushed, without waiting for the response.
+    ///
+    /// A send failure is also the result of [`RequestHandle::await_response`].
+    /// Once it has resolved, it returns `Ok` at once.
+    pub async fn await_sent(&mut self) -> Result<(), ServiceError> {
+        let Some(sent) = self.sent.take() else {
+            return Ok(());
+        };
+        sent.await
+            .map_err(|_e| ServiceError::Transport(std::io::Error::other("disconnected")))?
+    }
     pub async fn await_response(self) -> Result<R::PeerResp, ServiceError> {
         if let Some(timeout) = self.options.timeout {
             let timeout_result = tokio::time::timeout(timeout, async move {
@@ -268,6 +358,8 @@ pub enum PeerSinkMessage<R: ServiceRole> {
         R::Req,
         RequestId,
         Responder<Result<R::PeerResp, ServiceError>>,
+        /// Answered once the request is sent
+        Responder<Result<(), ServiceError>>,
     ),
     Notification(R::Not, Responder<Result<(), ServiceError>>),
 }
@@ -282,6 +374,15 @@ pub struct Peer<R: ServiceRole> {
     tx: mpsc::Sender<PeerSinkMessage<R>>,
     request_id_provider: Arc<dyn RequestIdProvider>,
     info: Arc<R::PeerInfo>,
+    /// The info of a handshake driven by the caller, it replaces `info` once it's set
+    initialized_info: Arc<std::sync::OnceLock<R::PeerInfo>>,
+    /// The info of the local service, it's set once the service loop starts
+    local_info: Arc<std::sync::OnceLock<R::Info>>,
+    state: Arc<tokio::sync::watch::Sender<ConnectionState>>,
+    stats: Arc<StatsCounters>,
+    session: SessionState,
+    /// The timeout of the requests sent without one
+    request_timeout: Arc<std::sync::RwLock<Option<Duration>>>,
 }
 
 impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
@@ -289,12 +390,110 @@ impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
         f.debug_struct("PeerSink")
             .field("tx", &self.tx)
             .field("is_client", &R::IS_CLIENT)
+            .field("state", &*self.state.borrow())
             .finish()
     }
 }
 
+/// The state of the connection to the remote peer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    /// The service loop hasn't started yet
+    Connecting,
+    /// The service loop is running
+    Ready,
+    /// The service loop has finished, no more messages can be sent or received
+    Closed { reason: QuitReason },
+}
+
 type ProxyOutbound<R> = mpsc::Receiver<PeerSinkMessage<R>>;
 
+/// The options of the service loop, see [`serve_server_with_config`],
+/// [`ClientInitializeOptions::with_serve_config`] and [`serve_directly_with_config`].
+///
+/// A full queue applies backpressure rather than growing: [`Peer::send_notification`] and the requests wait
+/// for room, [`Peer::try_send_notification`] and [`Peer::try_send_request`] fail with [`ServiceError::Backpressure`],
+/// and a handler waits to send its response, while the service loop keeps reading the messages of the peer.
+/// A capacity of 0 is taken as 1.
+///
+/// The service loop writes to the transport itself, so it stops reading the messages of the peer while the
+/// transport is full: two peers both writing to full transports, e.g. in-memory channels without a buffer, wait
+/// for each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServeConfig {
+    /// The queue of the messages sent with the [`Peer`], 1024 by default
+    pub peer_channel_capacity: usize,
+    /// The queue of the responses of the handlers, 64 by default
+    pub response_channel_capacity: usize,
+    /// Complete the progress of a request when it's answered, `false` by default.
+    ///
+    /// If the handler reported progress with a total, but the last progress sent isn't the total,
+    /// a notification with `progress == total` is sent right before the response, so the peer sees the progress
+    /// completed. Nothing is sent for an error, nor if the handler never sent a total.
+    /// The notifications the handler queued before answering, e.g. with [`Peer::try_send_notification`], are
+    /// sent before the response, so they're taken into account.
+    pub final_progress: bool,
+    /// Capture the backtrace of a [`TransportFailure`] even without `RUST_BACKTRACE`, `false` by default
+    pub backtrace: bool,
+    /// Trace the outgoing requests, `false` by default.
+    ///
+    /// Every outgoing request opens a span under [`WIRE_TRACING_TARGET`] with its id and method,
+    /// and its response, error or cancellation is logged in that span with the elapsed time.
+    pub wire_tracing: bool,
+    /// Panic at shutdown on the requests whose response is no longer awaited, `false` by default.
+    ///
+    /// They're always logged as an error, the panic makes a test waiting for the service fail.
+    #[cfg(feature = "test-util")]
+    pub panic_on_leaked_requests: bool,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            peer_channel_capacity: 1024,
+            response_channel_capacity: 64,
+            final_progress: false,
+            backtrace: false,
+            wire_tracing: false,
+            #[cfg(feature = "test-util")]
+            panic_on_leaked_requests: false,
+        }
+    }
+}
+
+impl ServeConfig {
+    pub fn with_peer_channel_capacity(mut self, capacity: usize) -> Self {
+        self.peer_channel_capacity = capacity;
+        self
+    }
+
+    pub fn with_response_channel_capacity(mut self, capacity: usize) -> Self {
+        self.response_channel_capacity = capacity;
+        self
+    }
+
+    pub fn with_final_progress(mut self, final_progress: bool) -> Self {
+        self.final_progress = final_progress;
+        self
+    }
+
+    pub fn with_backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    pub fn with_wire_tracing(mut self, wire_tracing: bool) -> Self {
+        self.wire_tracing = wire_tracing;
+        self
+    }
+
+    #[cfg(feature = "test-util")]
+    pub fn with_panic_on_leaked_requests(mut self, panic_on_leaked_requests: bool) -> Self {
+        self.panic_on_leaked_requests = panic_on_leaked_requests;
+        self
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct PeerRequestOptions {
     timeout: Option<Duration>,
@@ -304,24 +503,56 @@ impl PeerRequestOptions {
     pub fn no_options() -> Self {
         Self::default()
     }
+
+    /// Fail the request with [`ServiceError::Timeout`] and cancel it if the response doesn't arrive in time,
+    /// it overrides the timeout of the peer, see [`Peer::set_request_timeout`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
 }
 
 impl<R: ServiceRole> Peer<R> {
-    const CLIENT_CHANNEL_BUFFER_SIZE: usize = 1024;
     pub fn new(
         request_id_provider: Arc<dyn RequestIdProvider>,
         peer_info: R::PeerInfo,
     ) -> (Peer<R>, ProxyOutbound<R>) {
-        let (tx, rx) = mpsc::channel(Self::CLIENT_CHANNEL_BUFFER_SIZE);
+        Self::with_capacity(
+            request_id_provider,
+            peer_info,
+            ServeConfig::default().peer_channel_capacity,
+        )
+    }
+    /// Like [`Peer::new`], with `capacity` messages in the queue, see [`ServeConfig::peer_channel_capacity`]
+    pub fn with_capacity(
+        request_id_provider: Arc<dyn RequestIdProvider>,
+        peer_info: R::PeerInfo,
+        capacity: usize,
+    ) -> (Peer<R>, ProxyOutbound<R>) {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
         (
             Self {
                 tx,
                 request_id_provider,
                 info: peer_info.into(),
+                initialized_info: Default::default(),
+                local_info: Default::default(),
+                state: Arc::new(tokio::sync::watch::Sender::new(ConnectionState::Connecting)),
+                stats: Default::default(),
+                session: Default::default(),
+                request_timeout: Default::default(),
             },
             rx,
         )
     }
+    /// Send a notification, it resolves once it's written to the transport and flushed,
+    /// e.g. the POST of the SSE transport has completed, or the write to stdio is flushed.
+    ///
+    /// A failure of the transport to send it is returned, rather than only logged.
     pub async fn send_notification(&self, notification: R::Not) -> Result<(), ServiceError> {
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
@@ -332,6 +563,47 @@ impl<R: ServiceRole> Peer<R> {
             .await
             .map_err(|_e| ServiceError::Transport(std::io::Error::other("disconnected")))?
     }
+    /// Queue a notification without waiting, it fails with [`ServiceError::Backpressure`] if the queue of the
+    /// outgoing messages is full, e.g. the transport is slower than the notifications, so that the caller can
+    /// drop it or send it later instead of waiting.
+    ///
+    /// It doesn't wait for the notification to be written either, a failure of the transport isn't returned.
+    pub fn try_send_notification(&self, notification: R::Not) -> Result<(), ServiceError> {
+        let (responder, _receiver) = tokio::sync::oneshot::channel();
+        self.try_enqueue(PeerSinkMessage::Notification(notification, responder))
+    }
+    /// Queue a request without waiting, like [`Peer::try_send_notification`], and return its handle to await its response
+    pub fn try_send_request(
+        &self,
+        request: R::Req,
+        mut options: PeerRequestOptions,
+    ) -> Result<RequestHandle<R>, ServiceError> {
+        options.timeout = options.timeout.or_else(|| self.request_timeout());
+        let id = self.request_id_provider.next_request_id();
+        let (responder, receiver) = tokio::sync::oneshot::channel();
+        let (sent_responder, sent) = tokio::sync::oneshot::channel();
+        self.try_enqueue(PeerSinkMessage::Request(
+            request,
+            id.clone(),
+            responder,
+            sent_responder,
+        ))?;
+        Ok(RequestHandle {
+            id,
+            rx: receiver,
+            sent: Some(sent),
+            options,
+            peer: self.clone(),
+        })
+    }
+    fn try_enqueue(&self, message: PeerSinkMessage<R>) -> Result<(), ServiceError> {
+        self.tx.try_send(message).map_err(|error| match error {
+            mpsc::error::TrySendError::Full(_) => ServiceError::Backpressure,
+            mpsc::error::TrySendError::Closed(_) => {
+                ServiceError::Transport(std::io::Error::other("disconnected"))
+            }
+        })
+    }
     pub async fn send_request(&self, request: R::Req) -> Result<R::PeerResp, ServiceError> {
         self.send_cancellable_request(request, PeerRequestOptions::no_options())
             .await?
@@ -341,26 +613,134 @@ impl<R: ServiceRole> Peer<R> {
     pub async fn send_cancellable_request(
         &self,
         request: R::Req,
-        options: PeerRequestOptions,
+        mut options: PeerRequestOptions,
     ) -> Result<RequestHandle<R>, ServiceError> {
+        options.timeout = options.timeout.or_else(|| self.request_timeout());
         let id = self.request_id_provider.next_request_id();
         let (responder, receiver) = tokio::sync::oneshot::channel();
+        let (sent_responder, sent) = tokio::sync::oneshot::channel();
         self.tx
-            .send(PeerSinkMessage::Request(request, id.clone(), responder))
+            .send(PeerSinkMessage::Request(
+                request,
+                id.clone(),
+                responder,
+                sent_responder,
+            ))
             .await
             .map_err(|_m| ServiceError::Transport(std::io::Error::other("disconnected")))?;
         Ok(RequestHandle {
             id,
             rx: receiver,
+            sent: Some(sent),
             options,
             peer: self.clone(),
         })
     }
+    /// Set the timeout of the requests sent without one in their [`PeerRequestOptions`], for all the clones
+    /// of the peer, including the typed methods like `ping`. There's no timeout by default.
+    pub fn set_request_timeout(&self, timeout: Option<Duration>) {
+        *self
+            .request_timeout
+            .write()
+            .expect("request timeout lock poisoned") = timeout;
+    }
+
+    /// The timeout of the requests sent without one, see [`Peer::set_request_timeout`]
+    pub fn request_timeout(&self) -> Option<Duration> {
+        *self
+            .request_timeout
+            .read()
+            .expect("request timeout lock poisoned")
+    }
+
     pub fn peer_info(&self) -> &R::PeerInfo {
-        &self.info
+        self.initialized_info.get().unwrap_or(&self.info)
+    }
+
+    /// The info of the local service, the one it advertises to the peer
+    pub fn local_info(&self) -> Option<&R::Info> {
+        self.local_info.get()
+    }
+
+    /// The current state of the connection, it's updated by the service loop
+    pub fn connection_state(&self) -> ConnectionState {
+        self.state.borrow().clone()
+    }
+
+    /// If the service loop is running
+    pub fn is_connected(&self) -> bool {
+        self.connection_state() == ConnectionState::Ready
+    }
+
+    /// Subscribe the changes of the connection state, e.g. to show a status indicator
+    pub fn watch_connection_state(&self) -> tokio::sync::watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    fn set_connection_state(&self, state: ConnectionState) {
+        self.state.send_replace(state);
+    }
+
+    /// A snapshot of the counters of the connection, e.g. for a health dashboard.
+    ///
+    /// The counters are updated by the service loop, they're cheap to read at any rate.
+    /// The messages of the initialization are sent before the loop starts, so they aren't counted.
+    pub fn stats(&self) -> PeerStats {
+        self.stats.snapshot()
+    }
+
+    /// The state of this connection, shared by all the clones of the peer, see [`SessionState`]
+    pub fn session(&self) -> &SessionState {
+        &self.session
     }
 }
 
+/// Low level escape hatches to send methods unknown to this crate, e.g. experimental protocol extensions.
+///
+/// These bypass the capability checks and the typed params and results, the peer must know the method.
+/// A method known to this crate is sent as its typed message, the same as the peer reads it from the wire.
+impl<R: ServiceRole> Peer<R>
+where
+    R::Req: From<UnknownRequest>,
+    R::Not: From<UnknownNotification>,
+{
+    /// Send a request with any method, and return the raw result.
+    ///
+    /// A result matching a known result type is deserialized as that type first,
+    /// so the returned value is its serialization, e.g. an empty result is `{}`.
+    pub async fn request(
+        &self,
+        method: impl Into<String>,
+        params: Option<JsonObject>,
+    ) -> Result<serde_json::Value, ServiceError> {
+        let response = self
+            .send_request(parse_raw(UnknownRequest::new(method, params)))
+            .await?;
+        serde_json::to_value(response).map_err(|_e| ServiceError::UnexpectedResponse)
+    }
+
+    /// Send a notification with any method
+    pub async fn notify(
+        &self,
+        method: impl Into<String>,
+        params: Option<JsonObject>,
+    ) -> Result<(), ServiceError> {
+        self.send_notification(parse_raw(UnknownNotification::new(method, params)))
+            .await
+    }
+}
+
+/// Read a raw message as the peer would, so a known method gets its typed variant
+fn parse_raw<T, U>(raw: U) -> T
+where
+    T: serde::de::DeserializeOwned,
+    U: serde::Serialize + Into<T>,
+{
+    serde_json::to_value(&raw)
+        .and_then(serde_json::from_value)
+        .unwrap_or_else(|_| raw.into())
+}
+
 #[derive(Debug)]
 pub struct RunningService<S: Service> {
     service: Arc<S>,
@@ -368,6 +748,8 @@ pub struct RunningService<S: Service> {
     handle: tokio::task::JoinHandle<QuitReason>,
     /// cancellation token
     ct: CancellationToken,
+    /// The type of the transport, for the reports
+    transport: &'static str,
 }
 impl<S: Service> Deref for RunningService<S> {
     type Target = Peer<S::Role>;
@@ -386,19 +768,177 @@ impl<S: Service> RunningService<S> {
     pub fn service(&self) -> &S {
         self.service.as_ref()
     }
-    pub async fn waiting(self) -> Result<QuitReason, tokio::task::JoinError> {
-        self.handle.await
+    /// Wait for the service loop to finish, e.g. to decide whether to restart the service.
+    ///
+    /// A panic of the service loop is logged with the transport, and resumed here.
+    pub async fn waiting(self) -> QuitReason {
+        match self.handle.await {
+            Ok(reason) => reason,
+            Err(error) if error.is_panic() => {
+                let panic = error.into_panic();
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("a non-string payload");
+                tracing::error!(
+                    transport = self.transport,
+                    panic = message,
+                    "the service loop panicked"
+                );
+                std::panic::resume_unwind(panic)
+            }
+            // the runtime is shutting down
+            Err(_) => QuitReason::Cancelled,
+        }
     }
     pub async fn cancel(self) -> Result<QuitReason, tokio::task::JoinError> {
         self.ct.cancel();
-        self.waiting().await
+        self.handle.await
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+/// Why the service loop has finished
+#[derive(Debug, Clone)]
 pub enum QuitReason {
+    /// The service was cancelled with [`RunningService::cancel`]
     Cancelled,
+    /// The peer closed the transport, e.g. the stdout of a child process is closed
     Closed,
+    /// The transport failed to read a message, or was closed after it failed to send one, with the last failure
+    TransportError(Arc<TransportFailure>),
+    /// The peer went away, the connection is broken
+    PeerDisconnected,
+}
+
+impl PartialEq for QuitReason {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::TransportError(this), Self::TransportError(other)) => Arc::ptr_eq(this, other),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// A message the service loop failed to send or to receive, with what it was, the transport, and where it failed.
+///
+/// It's displayed on one line, `failed to send <message>: <source>`, or as a report with `{:#}`, with the
+/// transport, the chain of sources, and the backtrace if it was captured, see [`ServeConfig::backtrace`].
+#[derive(Debug)]
+pub struct TransportFailure {
+    /// [`WireDirection::Outgoing`] for a send, [`WireDirection::Incoming`] for a read
+    pub direction: WireDirection,
+    /// What was being sent, e.g. ``request 3 `tools/call` ``, or received
+    pub message: String,
+    /// The type of the transport
+    pub transport: &'static str,
+    /// The error of the transport
+    pub source: Arc<dyn std::error::Error + Send + Sync>,
+    pub backtrace: std::backtrace::Backtrace,
+}
+
+impl TransportFailure {
+    fn new(
+        direction: WireDirection,
+        message: String,
+        transport: &'static str,
+        source: Arc<dyn std::error::Error + Send + Sync>,
+        force_backtrace: bool,
+    ) -> Self {
+        let backtrace = if force_backtrace {
+            std::backtrace::Backtrace::force_capture()
+        } else {
+            std::backtrace::Backtrace::capture()
+        };
+        // the service may go on, e.g. a later send succeeds, it's an error once the service loop quits on it
+        tracing::warn!(?direction, message, transport, error = %source, "transport failure");
+        Self {
+            direction,
+            message,
+            transport,
+            source,
+            backtrace,
+        }
+    }
+}
+
+impl std::fmt::Display for TransportFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operation = match self.direction {
+            WireDirection::Outgoing => "send",
+            WireDirection::Incoming => "receive",
+        };
+        if !f.alternate() {
+            return write!(f, "failed to {operation} {}: {}", self.message, self.source);
+        }
+        writeln!(f, "failed to {operation} {}", self.message)?;
+        writeln!(f, "  transport: {}", self.transport)?;
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(self.source.as_ref());
+        while let Some(error) = source {
+            writeln!(f, "  caused by: {error}")?;
+            source = error.source();
+        }
+        if self.backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            write!(f, "  backtrace:\n{}", self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TransportFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// A transport failure because the connection is broken, rather than because of the message or of the peer
+fn is_disconnection(error: &(dyn std::error::Error + 'static)) -> bool {
+    use std::io::ErrorKind;
+    let mut error = Some(error);
+    while let Some(current) = error {
+        if let Some(io_error) = current.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io_error.kind(),
+                ErrorKind::BrokenPipe
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+            ) {
+                return true;
+            }
+            // a wrapped error isn't the source of the io error
+            if io_error
+                .get_ref()
+                .is_some_and(|inner| is_disconnection(inner))
+            {
+                return true;
+            }
+        }
+        error = current.source();
+    }
+    false
+}
+
+/// The [`MalformedMessage`] of a read error, which the service answers instead of quitting
+fn malformed_message<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a MalformedMessage> {
+    let mut error = Some(error);
+    while let Some(current) = error {
+        if let Some(malformed) = current.downcast_ref::<MalformedMessage>() {
+            return Some(malformed);
+        }
+        // a wrapped error isn't the source of the io error
+        if let Some(malformed) = current
+            .downcast_ref::<std::io::Error>()
+            .and_then(|io_error| io_error.get_ref())
+            .and_then(|inner| malformed_message(inner))
+        {
+            return Some(malformed);
+        }
+        error = current.source();
+    }
+    None
 }
 
 /// Request execution context
@@ -411,6 +951,13 @@ pub struct RequestContext<R: ServiceRole> {
     pub peer: Peer<R>,
 }
 
+impl<R: ServiceRole> RequestContext<R> {
+    /// The state of the connection the request comes from, see [`SessionState`]
+    pub fn session(&self) -> &SessionState {
+        self.peer.session()
+    }
+}
+
 /// Use this function to skip initialization process
 pub async fn serve_directly<S, T, E, A>(
     service: S,
@@ -422,7 +969,50 @@ where
     T: IntoTransport<S::Role, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
-    serve_inner(service, transport, peer_info, Default::default()).await
+    serve_directly_with_config(service, transport, peer_info, ServeConfig::default()).await
+}
+
+/// Like [`serve_directly`], with the capacities of `config`
+pub async fn serve_directly_with_config<S, T, E, A>(
+    service: S,
+    transport: T,
+    peer_info: <S::Role as ServiceRole>::PeerInfo,
+    config: ServeConfig,
+) -> Result<RunningService<S>, E>
+where
+    S: Service,
+    T: IntoTransport<S::Role, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    serve_inner(service, transport, peer_info, Default::default(), config).await
+}
+
+/// Check the requests still pending at shutdown, those nobody awaits anymore are leaked,
+/// e.g. a dropped [`RequestHandle`], or a timed out request whose cancellation got lost.
+///
+/// They're logged as an error, or panic with [`ServeConfig::panic_on_leaked_requests`].
+/// The requests still awaited at shutdown are fine, they resolve as disconnected.
+#[cfg(feature = "test-util")]
+fn check_leaked_requests<T>(pool: &HashMap<RequestId, Responder<T>>, panic: bool) {
+    let mut leaked = pool
+        .iter()
+        .filter(|(_, responder)| responder.is_closed())
+        .map(|(id, _)| id.to_string())
+        .collect::<Vec<_>>();
+    if leaked.is_empty() {
+        return;
+    }
+    leaked.sort();
+    let message = format!(
+        "{} request(s) leaked at shutdown, their response is no longer awaited: {}",
+        leaked.len(),
+        leaked.join(", ")
+    );
+    if panic {
+        panic!("{message}");
+    } else {
+        tracing::error!("{message}");
+    }
 }
 
 async fn serve_inner<S, T, E, A>(
@@ -430,6 +1020,7 @@ async fn serve_inner<S, T, E, A>(
     transport: T,
     peer_info: <S::Role as ServiceRole>::PeerInfo,
     id_provider: Arc<AtomicU32RequestIdProvider>,
+    config: ServeConfig,
 ) -> Result<RunningService<S>, E>
 where
     S: Service,
@@ -437,7 +1028,6 @@ where
     E: std::error::Error + Send + Sync + 'static,
 {
     use futures::{SinkExt, StreamExt};
-    const SINK_PROXY_BUFFER_SIZE: usize = 64;
     tracing::info!("Server started");
     let (sink_proxy_tx, mut sink_proxy_rx) = tokio::sync::mpsc::channel::<
         Message<
@@ -445,7 +1035,7 @@ where
             <S::Role as ServiceRole>::Resp,
             <S::Role as ServiceRole>::Not,
         >,
-    >(SINK_PROXY_BUFFER_SIZE);
+    >(config.response_channel_capacity.max(1));
 
     if S::Role::IS_CLIENT {
         tracing::info!(?peer_info, "Server initialized as client");
@@ -453,10 +1043,19 @@ where
         tracing::info!(?peer_info, "Server initialized as server");
     }
 
-    let (peer, mut peer_proxy) = <Peer<S::Role>>::new(id_provider, peer_info);
+    let (peer, mut peer_proxy) =
+        <Peer<S::Role>>::with_capacity(id_provider, peer_info, config.peer_channel_capacity);
+    let _ = peer.local_info.set(service.get_info());
     service.set_peer(peer.clone());
     let mut local_responder_pool = HashMap::new();
+    let mut local_wire_spans = HashMap::<RequestId, WireSpan>::new();
     let mut local_ct_pool = HashMap::<RequestId, CancellationToken>::new();
+    // when the pings waiting for their response were sent, to measure their round trip
+    let mut local_ping_pool = HashMap::<RequestId, std::time::Instant>::new();
+    // the progress tokens of the requests being handled, and the last progress and total sent for each,
+    // only with `final_progress`
+    let mut local_progress_tokens = HashMap::<RequestId, ProgressToken>::new();
+    let mut local_progress = HashMap::<ProgressToken, Option<(u32, Option<u32>)>>::new();
     let shared_service = Arc::new(service);
     // for return
     let service = shared_service.clone();
@@ -465,45 +1064,87 @@ where
     // let mut stream = std::pin::pin!(stream);
     let ct = CancellationToken::new();
     let serve_loop_ct = ct.child_token();
+    let final_progress = config.final_progress;
+    let wire_tracing = config.wire_tracing;
+    #[cfg(feature = "test-util")]
+    let panic_on_leaked_requests = config.panic_on_leaked_requests;
+    let transport_type = std::any::type_name::<T>();
+    let failure = move |direction: WireDirection,
+                        message: String,
+                        error: Arc<dyn std::error::Error + Send + Sync>| {
+        Arc::new(TransportFailure::new(
+            direction,
+            message,
+            transport_type,
+            error,
+            config.backtrace,
+        ))
+    };
     let peer_return: Peer<<S as Service>::Role> = peer.clone();
     let handle = tokio::spawn(async move {
-        let (mut sink, mut stream) = transport.into_transport();
+        let (mut sink, mut stream) = transport.into_try_transport();
         let mut sink = std::pin::pin!(sink);
         let mut stream = std::pin::pin!(stream);
+        peer.set_connection_state(ConnectionState::Ready);
         #[derive(Debug)]
         enum Event<P, R, T> {
             ProxyMessage(P),
             PeerMessage(R),
             ToSink(T),
         }
+        // the last send failure, cleared by a successful send
+        let mut transport_error: Option<Arc<TransportFailure>> = None;
         let quit_reason = loop {
+            // biased, so that the notifications a handler sent before answering are sent before its response
             let evt = tokio::select! {
-                m = sink_proxy_rx.recv() => {
-                    if let Some(m) = m {
-                        Event::ToSink(m)
-                    } else {
-                        continue
-                    }
+                biased;
+                _ = serve_loop_ct.cancelled() => {
+                    tracing::info!("task cancelled");
+                    break QuitReason::Cancelled
                 }
-                m = stream.next() => {
+                m = peer_proxy.recv() => {
                     if let Some(m) = m {
-                        Event::PeerMessage(m.into_message())
+                        Event::ProxyMessage(m)
                     } else {
-                        // input stream closed
-                        tracing::info!("input stream terminated");
-                        break QuitReason::Closed
+                        continue
                     }
                 }
-                m = peer_proxy.recv() => {
+                m = sink_proxy_rx.recv() => {
                     if let Some(m) = m {
-                        Event::ProxyMessage(m)
+                        Event::ToSink(m)
                     } else {
                         continue
                     }
                 }
-                _ = serve_loop_ct.cancelled() => {
-                    tracing::info!("task cancelled");
-                    break QuitReason::Cancelled
+                m = stream.next() => {
+                    match m {
+                        Some(Ok(m)) => {
+                            peer.stats.received();
+                            Event::PeerMessage(m.into_message())
+                        }
+                        Some(Err(error)) => {
+                            if let Some(malformed) = malformed_message(&error) {
+                                tracing::warn!(%malformed, "answering a malformed message with a parse error");
+                                let error = McpError::parse_error(malformed.reason.clone(), None);
+                                Event::ToSink(Message::Error(error, malformed.id.clone()))
+                            } else {
+                                let error = Arc::new(error);
+                                if is_disconnection(error.as_ref()) {
+                                    tracing::info!(%error, "input stream disconnected");
+                                    break QuitReason::PeerDisconnected
+                                }
+                                let message = "the next message".to_owned();
+                                break QuitReason::TransportError(failure(WireDirection::Incoming, message, error))
+                            }
+                        }
+                        None => {
+                            // input stream closed
+                            tracing::info!("input stream terminated");
+                            break transport_error
+                                .take()
+                                .map_or(QuitReason::Closed, QuitReason::TransportError)
+                        }
+                    }
                 }
             };
             tracing::debug!(?evt, "new event");
@@ -518,22 +1159,97 @@ where
                         if let Some(ct) = local_ct_pool.remove(id) {
                             ct.cancel();
                         }
+                        let progress = local_progress_tokens
+                            .remove(id)
+                            .and_then(|token| Some((local_progress.remove(&token)??, token)));
+                        if let (Message::Response(..), Some(((progress, Some(total)), token))) =
+                            (&e, progress)
+                            && progress < total
+                        {
+                            let notification = ProgressNotification::new(token, total, Some(total));
+                            let send_result = sink
+                                .send(
+                                    Message::Notification(notification.into())
+                                        .into_json_rpc_message(),
+                                )
+                                .await;
+                            match send_result {
+                                Ok(()) => peer.stats.sent(),
+                                Err(error) => {
+                                    tracing::warn!(%error, "fail to send the final progress")
+                                }
+                            }
+                        }
+                        let (id, is_error) = (id.clone(), matches!(e, Message::Error(..)));
                         let send_result = sink.send(e.into_json_rpc_message()).await;
-                        if let Err(error) = send_result {
-                            tracing::error!(%error, "fail to response message");
+                        match send_result {
+                            Ok(()) => {
+                                transport_error = None;
+                                peer.stats.sent();
+                            }
+                            Err(error) => {
+                                tracing::error!(%error, "fail to response message");
+                                let error = Arc::new(error);
+                                if is_disconnection(error.as_ref()) {
+                                    break QuitReason::PeerDisconnected;
+                                }
+                                let response_to = if is_error {
+                                    format!("the error response to request {id}")
+                                } else {
+                                    format!("the response to request {id}")
+                                };
+                                transport_error =
+                                    Some(failure(WireDirection::Outgoing, response_to, error));
+                            }
                         }
                     }
                 }
-                Event::ProxyMessage(PeerSinkMessage::Request(request, id, responder)) => {
+                Event::ProxyMessage(PeerSinkMessage::Request(
+                    request,
+                    id,
+                    responder,
+                    sent_responder,
+                )) => {
                     local_responder_pool.insert(id.clone(), responder);
+                    if wire_tracing {
+                        local_wire_spans.insert(id.clone(), WireSpan::new(&id, request.method()));
+                    }
+                    let is_ping = request.method() == PingRequestMethod::VALUE;
+                    // an unknown method is kept as is for the report of a failure
+                    let method = request
+                        .static_method()
+                        .map_or_else(|| Cow::Owned(request.method().to_owned()), Cow::Borrowed);
                     let send_result = sink
                         .send(Message::Request(request, id.clone()).into_json_rpc_message())
                         .await;
                     if let Err(e) = send_result {
+                        if let Some(span) = local_wire_spans.remove(&id) {
+                            span.failed(&e);
+                        }
+                        let e = Arc::new(e);
                         if let Some(responder) = local_responder_pool.remove(&id) {
-                            let _ = responder
-                                .send(Err(ServiceError::Transport(std::io::Error::other(e))));
+                            let _ = responder.send(Err(ServiceError::Transport(
+                                std::io::Error::other(e.clone()),
+                            )));
+                        }
+                        let _ = sent_responder.send(Err(ServiceError::Transport(
+                            std::io::Error::other(e.clone()),
+                        )));
+                        if is_disconnection(e.as_ref()) {
+                            break QuitReason::PeerDisconnected;
                         }
+                        transport_error = Some(failure(
+                            WireDirection::Outgoing,
+                            format!("request {id} `{method}`"),
+                            e,
+                        ));
+                    } else {
+                        transport_error = None;
+                        peer.stats.sent();
+                        if is_ping {
+                            local_ping_pool.insert(id, std::time::Instant::now());
+                        }
+                        let _ = sent_responder.send(Ok(()));
                     }
                 }
                 Event::ProxyMessage(PeerSinkMessage::Notification(notification, responder)) => {
@@ -546,14 +1262,50 @@ where
                         }
                         Err(notification) => notification,
                     };
+                    // catch the progress of a request being handled
+                    let notification = match notification.try_into() {
+                        Ok::<ProgressNotification, _>(progress) => {
+                            let params = &progress.params;
+                            if let Some(last) = local_progress.get_mut(&params.progress_token) {
+                                *last = Some((params.progress, params.total));
+                            }
+                            progress.into()
+                        }
+                        Err(notification) => notification,
+                    };
+                    let method = notification.static_method().map_or_else(
+                        || Cow::Owned(notification.method().to_owned()),
+                        Cow::Borrowed,
+                    );
                     let send_result = sink
                         .send(Message::Notification(notification).into_json_rpc_message())
-                        .await;
-                    if let Err(e) = send_result {
-                        let _ =
-                            responder.send(Err(ServiceError::Transport(std::io::Error::other(e))));
+                        .await
+                        .map_err(Arc::new);
+                    let _ = responder.send(match &send_result {
+                        Ok(()) => Ok(()),
+                        Err(e) => Err(ServiceError::Transport(std::io::Error::other(e.clone()))),
+                    });
+                    match send_result {
+                        Ok(()) => {
+                            transport_error = None;
+                            peer.stats.sent();
+                        }
+                        Err(e) if is_disconnection(e.as_ref()) => {
+                            break QuitReason::PeerDisconnected;
+                        }
+                        Err(e) => {
+                            transport_error = Some(failure(
+                                WireDirection::Outgoing,
+                                format!("notification `{method}`"),
+                                e,
+                            ))
+                        }
                     }
                     if let Some(param) = cancellation_param {
+                        local_ping_pool.remove(&param.request_id);
+                        if let Some(span) = local_wire_spans.remove(&param.request_id) {
+                            span.cancelled(param.reason.as_deref());
+                        }
                         if let Some(responder) = local_responder_pool.remove(&param.request_id) {
                             tracing::info!(id = %param.request_id, reason = param.reason, "cancelled");
                             let _response_result = responder.send(Err(ServiceError::Cancelled {
@@ -564,7 +1316,18 @@ where
                 }
                 Event::PeerMessage(Message::Request(request, id)) => {
                     tracing::info!(%id, ?request, "received request");
-                    {
+                    // the spec requires every peer to answer a ping, it never reaches the service
+                    if request.method() == PingRequestMethod::VALUE {
+                        let sink = sink_proxy_tx.clone();
+                        tokio::spawn(async move {
+                            let response = Message::Response(EmptyResult {}.into(), id);
+                            let _send_result = sink.send(response).await;
+                        });
+                    } else {
+                        if let Some(token) = request.progress_token().filter(|_| final_progress) {
+                            local_progress.insert(token.clone(), None);
+                            local_progress_tokens.insert(id.clone(), token);
+                        }
                         let service = shared_service.clone();
                         let sink = sink_proxy_tx.clone();
                         let request_ct = serve_loop_ct.child_token();
@@ -599,6 +1362,14 @@ where
                             if let Some(ct) = local_ct_pool.remove(&cancelled.params.request_id) {
                                 tracing::info!(id = %cancelled.params.request_id, reason = cancelled.params.reason, "cancelled");
                                 ct.cancel();
+                                if let Some(token) =
+                                    local_progress_tokens.remove(&cancelled.params.request_id)
+                                {
+                                    local_progress.remove(&token);
+                                }
+                            } else {
+                                // the request has been completed or never seen, ignore it as the spec says
+                                tracing::debug!(id = %cancelled.params.request_id, "ignore cancellation for unknown request");
                             }
                             cancelled.into()
                         }
@@ -615,6 +1386,12 @@ where
                     }
                 }
                 Event::PeerMessage(Message::Response(result, id)) => {
+                    if let Some(span) = local_wire_spans.remove(&id) {
+                        span.response();
+                    }
+                    if let Some(sent_at) = local_ping_pool.remove(&id) {
+                        peer.stats.ping_answered(sent_at.elapsed());
+                    }
                     if let Some(responder) = local_responder_pool.remove(&id) {
                         let response_result = responder.send(Ok(result));
                         if let Err(_error) = response_result {
@@ -623,6 +1400,10 @@ where
                     }
                 }
                 Event::PeerMessage(Message::Error(error, id)) => {
+                    if let Some(span) = local_wire_spans.remove(&id) {
+                        span.error(&error);
+                    }
+                    local_ping_pool.remove(&id);
                     if let Some(responder) = local_responder_pool.remove(&id) {
                         let _response_result = responder.send(Err(ServiceError::McpError(error)));
                         if let Err(_error) = _response_result {
@@ -631,8 +1412,19 @@ where
                     }
                 }
             }
+            peer.stats
+                .set_in_flight(local_responder_pool.len(), local_ct_pool.len());
         };
         tracing::info!(?quit_reason, "serve finished");
+        if let QuitReason::TransportError(failure) = &quit_reason {
+            tracing::error!(error = %failure, transport = failure.transport, "the service loop quit on a transport failure");
+        }
+        peer.set_connection_state(ConnectionState::Closed {
+            reason: quit_reason.clone(),
+        });
+        peer.session.clear();
+        #[cfg(feature = "test-util")]
+        check_leaked_requests(&local_responder_pool, panic_on_leaked_requests);
         quit_reason
     });
     Ok(RunningService {
@@ -640,5 +1432,6 @@ where
         peer: peer_return,
         handle,
         ct,
+        transport: transport_type,
     })
 }
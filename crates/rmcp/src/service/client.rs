@@ -2,17 +2,20 @@ use crate::model::{
     CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
     CancelledNotificationParam, ClientInfo, ClientMessage, ClientNotification, ClientRequest,
     ClientResult, CompleteRequest, CompleteRequestParam, CompleteResult, GetPromptRequest,
-    GetPromptRequestParam, GetPromptResult, InitializeRequest, InitializedNotification,
-    ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
-    ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
-    ListToolsResult, PaginatedRequestParam, ProgressNotification, ProgressNotificationParam,
-    ReadResourceRequest, ReadResourceRequestParam, ReadResourceResult,
-    RootsListChangedNotification, ServerInfo, ServerNotification, ServerRequest, ServerResult,
-    SetLevelRequest, SetLevelRequestParam, SubscribeRequest, SubscribeRequestParam,
-    UnsubscribeRequest, UnsubscribeRequestParam,
+    GetPromptRequestParam, GetPromptResult, InitializeRequest, InitializeRequestParam,
+    InitializeResult, InitializedNotification, ListPromptsRequest, ListPromptsResult,
+    ListResourceTemplatesRequest, ListResourceTemplatesResult, ListResourcesRequest,
+    ListResourcesResult, ListToolsRequest, ListToolsResult, PaginatedRequestParam, PingRequest,
+    ProgressNotification, ProgressNotificationParam, Prompt, ProtocolVersion, ReadResourceRequest,
+    ReadResourceRequestParam, ReadResourceResult, Resource, RootsListChangedNotification,
+    ServerInfo, ServerNotification, ServerRequest, ServerResult, SetLevelRequest,
+    SetLevelRequestParam, SubscribeRequest, SubscribeRequestParam, Tool, UnsubscribeRequest,
+    UnsubscribeRequestParam,
 };
 
 use super::*;
+use crate::backoff::ExponentialBackoff;
+use crate::transport::TryStreamSink;
 use futures::{SinkExt, StreamExt};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -33,41 +36,198 @@ impl ServiceRole for RoleClient {
 
 pub type ServerSink = Peer<RoleClient>;
 
+/// How [`Peer::call_tools_all`] handles a failed call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailMode {
+    /// Cancel the calls still running at the first failure, they result in [`ServiceError::Cancelled`]
+    FailFast,
+    /// Wait for all the calls, whatever their results
+    #[default]
+    CollectAll,
+}
+
+impl FailMode {
+    /// The reason of the cancellations sent by [`FailMode::FailFast`]
+    pub const CANCEL_REASON: &str = "another tool call failed";
+}
+
+/// A call fails with an error, or with a result flagged `isError`
+fn is_failed_call(result: &Result<CallToolResult, ServiceError>) -> bool {
+    result
+        .as_ref()
+        .map_or(true, |result| result.is_error == Some(true))
+}
+
+/// Options of the initialization, and of the service loop, in [`serve_client_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct ClientInitializeOptions {
+    /// How long to wait for the initialize response, forever if `None`
+    pub timeout: Option<Duration>,
+    /// How many more times to send the initialize request after a timeout
+    pub retries: usize,
+    /// The delays added to the timeout of the retries, they wait as long as the first attempt if `None`
+    pub backoff: Option<ExponentialBackoff>,
+    /// The capacities of the queues of the service loop
+    pub serve_config: ServeConfig,
+}
+
+impl ClientInitializeOptions {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: ExponentialBackoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    pub fn with_serve_config(mut self, serve_config: ServeConfig) -> Self {
+        self.serve_config = serve_config;
+        self
+    }
+}
+
+/// The server didn't answer the initialize request in time.
+///
+/// It's the inner error of a [`std::io::ErrorKind::TimedOut`] error, which is converted into the transport error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("no initialize response after {attempts} attempt(s) of at least {timeout:?}")]
+pub struct InitializeTimeout {
+    pub timeout: Duration,
+    pub attempts: usize,
+}
+
 pub async fn serve_client<S, T, E, A>(service: S, transport: T) -> Result<RunningService<S>, E>
 where
     S: Service<Role = RoleClient>,
     T: IntoTransport<RoleClient, E, A>,
     E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
 {
-    let (sink, stream) = transport.into_transport();
+    serve_client_with_options(service, transport, Default::default()).await
+}
+
+/// Serve the client without the initialization, the caller drives the handshake.
+///
+/// The request ids and the responses are handled as usual, but nothing is sent:
+/// the caller sends the `initialize` request with [`Peer::initialize`], and then [`Peer::notify_initialized`],
+/// e.g. to choose when it happens, or to forward the parameters of another client.
+///
+/// Until the initialize result arrives, the peer info is [`ServerInfo::default`], which has no capabilities,
+/// so the methods checking a server capability fail. Other requests are sent, but a server may reject them.
+pub async fn serve_client_no_init<S, T, E, A>(
+    service: S,
+    transport: T,
+) -> Result<RunningService<S>, E>
+where
+    S: Service<Role = RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+{
+    serve_inner(
+        service,
+        transport,
+        ServerInfo::default(),
+        Default::default(),
+        ServeConfig::default(),
+    )
+    .await
+}
+
+/// Serve the client in a session initialized by a previous client, without a new initialization.
+///
+/// The `server_info` is the initialize result of the session, and the request ids start at `next_request_id`,
+/// so they don't collide with the ids of the previous client. See
+/// `transport::sse::SseSession` to resume an SSE session after the client restarts.
+pub async fn serve_client_resumed<S, T, E, A>(
+    service: S,
+    transport: T,
+    server_info: ServerInfo,
+    next_request_id: u32,
+) -> Result<RunningService<S>, E>
+where
+    S: Service<Role = RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+{
+    serve_inner(
+        service,
+        transport,
+        server_info,
+        Arc::new(AtomicU32RequestIdProvider::starting_at(next_request_id)),
+        ServeConfig::default(),
+    )
+    .await
+}
+
+/// Like [`serve_client`], but the wait for the initialize response can time out and be retried.
+///
+/// A server launched on demand may be slow to answer, e.g. when it's downloaded first.
+/// A retry sends the initialize request again, with a new id, e.g. for a server which dropped the first one
+/// while starting. The first response to any of them initializes the client, the later ones are ignored.
+/// [`serve_server`](crate::serve_server) answers all the initialize requests it reads before the
+/// initialized notification. With a backoff, each retry waits longer by the next delay.
+pub async fn serve_client_with_options<S, T, E, A>(
+    service: S,
+    transport: T,
+    mut options: ClientInitializeOptions,
+) -> Result<RunningService<S>, E>
+where
+    S: Service<Role = RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+{
+    let (sink, stream) = transport.into_try_transport();
     let mut sink = Box::pin(sink);
     let mut stream = Box::pin(stream);
     let id_provider = <Arc<AtomicU32RequestIdProvider>>::default();
     // service
-    let id = id_provider.next_request_id();
-    let init_request = InitializeRequest {
+    let init_request = ClientRequest::InitializeRequest(InitializeRequest {
         method: Default::default(),
         params: service.get_info(),
+    });
+    // the ids of the initialize requests sent, the response may be the one of any of them
+    let mut ids = Vec::new();
+    let mut wait = options.timeout;
+    let message = loop {
+        let id = id_provider.next_request_id();
+        sink.send(ClientMessage::Request(init_request.clone(), id.clone()).into_json_rpc_message())
+            .await?;
+        ids.push(id);
+        let (Some(timeout), Some(wait)) = (options.timeout, wait.as_mut()) else {
+            break stream.next().await;
+        };
+        match tokio::time::timeout(*wait, stream.next()).await {
+            Ok(message) => break message,
+            Err(_) if ids.len() <= options.retries => {
+                tracing::warn!(attempts = ids.len(), "initialize timeout, sending it again");
+                let delay = options.backoff.as_mut().and_then(Iterator::next);
+                *wait = timeout + delay.unwrap_or_default();
+            }
+            Err(_) => {
+                let attempts = ids.len();
+                let timeout = InitializeTimeout { timeout, attempts };
+                return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, timeout).into());
+            }
+        }
     };
-    sink.send(
-        ClientMessage::Request(ClientRequest::InitializeRequest(init_request), id.clone())
-            .into_json_rpc_message(),
-    )
-    .await?;
-    let (response, response_id) = stream
-        .next()
-        .await
+    let (response, response_id) = message
         .ok_or(std::io::Error::new(
             std::io::ErrorKind::UnexpectedEof,
             "expect initialize response",
-        ))?
+        ))??
         .into_message()
         .into_result()
         .ok_or(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             "expect initialize result",
         ))?;
-    if id != response_id {
+    if !ids.contains(&response_id) {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             "conflict initialize response id",
@@ -89,10 +249,30 @@ where
         },
     ));
     sink.send(notification.into_json_rpc_message()).await?;
-    serve_inner(service, (sink, stream), initialize_result, id_provider).await
+    serve_inner(
+        service,
+        TryStreamSink(sink, stream),
+        initialize_result,
+        id_provider,
+        options.serve_config,
+    )
+    .await
 }
 
 macro_rules! method {
+    (peer_req $method:ident $Req:ident()) => {
+        pub async fn $method(&self) -> Result<(), ServiceError> {
+            let result = self
+                .send_request(ClientRequest::$Req($Req {
+                    method: Default::default(),
+                }))
+                .await?;
+            match result {
+                ServerResult::EmptyResult(_) => Ok(()),
+                _ => Err(ServiceError::UnexpectedResponse),
+            }
+        }
+    };
     (peer_req $method:ident $Req:ident() => $Resp: ident ) => {
         pub async fn $method(&self) -> Result<$Resp, ServiceError> {
             let result = self
@@ -156,8 +336,137 @@ macro_rules! method {
     };
 }
 
+/// Fetch every page of a list, or nothing if the server doesn't advertise its capability
+macro_rules! list_all {
+    ($peer: expr, $capability: ident, $method: ident, $items: ident) => {
+        async {
+            let mut items = Vec::new();
+            if $peer.peer_info().capabilities.$capability.is_none() {
+                return Ok(items);
+            }
+            let mut cursor = None;
+            let mut seen = std::collections::HashSet::new();
+            loop {
+                let result = $peer.$method(PaginatedRequestParam { cursor }).await?;
+                items.extend(result.$items);
+                cursor = result.next_cursor;
+                match &cursor {
+                    None => return Ok(items),
+                    // a cursor seen before would page forever
+                    Some(next) if !seen.insert(next.clone()) => {
+                        return Err(ServiceError::UnexpectedResponse);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    };
+}
+
+/// Everything a server exposes, see [`Peer::describe`].
+///
+/// A list whose capability isn't advertised by the server is empty, a list which couldn't be fetched holds the error.
+#[derive(Debug)]
+pub struct ServerDescription {
+    pub info: ServerInfo,
+    pub tools: Result<Vec<Tool>, ServiceError>,
+    pub prompts: Result<Vec<Prompt>, ServiceError>,
+    pub resources: Result<Vec<Resource>, ServiceError>,
+}
+
 impl Peer<RoleClient> {
-    method!(peer_req complete CompleteRequest(CompleteRequestParam) => CompleteResult);
+    /// Describe the server: its info from the initialization, and all its tools, prompts and resources.
+    ///
+    /// The lists are fetched concurrently, all their pages are fetched, and a failed list doesn't fail the others.
+    /// A list whose next cursor was already seen fails with [`ServiceError::UnexpectedResponse`], instead of paging forever.
+    pub async fn describe(&self) -> ServerDescription {
+        let (tools, prompts, resources) = futures::join!(
+            list_all!(self, tools, list_tools, tools),
+            list_all!(self, prompts, list_prompts, prompts),
+            list_all!(self, resources, list_resources, resources),
+        );
+        ServerDescription {
+            info: self.peer_info().clone(),
+            tools,
+            prompts,
+            resources,
+        }
+    }
+
+    /// Read several resources concurrently, the results are keyed by uri.
+    ///
+    /// MCP has no batch read, so a `resources/read` request is sent for each uri, an identical uri is read once,
+    /// and a failed read doesn't fail the others.
+    pub async fn read_resources<U: Into<String>>(
+        &self,
+        uris: impl IntoIterator<Item = U>,
+    ) -> HashMap<String, Result<ReadResourceResult, ServiceError>> {
+        let mut uris = uris.into_iter().map(Into::into).collect::<Vec<String>>();
+        uris.sort();
+        uris.dedup();
+        let reads = uris.into_iter().map(|uri| async move {
+            let result = self
+                .read_resource(ReadResourceRequestParam {
+                    uri: uri.clone(),
+                    meta: None,
+                })
+                .await;
+            (uri, result)
+        });
+        futures::future::join_all(reads).await.into_iter().collect()
+    }
+
+    /// Send the initialize request of a handshake driven by the caller, see [`serve_client_no_init`].
+    ///
+    /// On success, the result becomes the [`Peer::peer_info`], only the first result is kept.
+    /// Send [`Peer::notify_initialized`] afterwards to complete the handshake.
+    pub async fn initialize(
+        &self,
+        params: InitializeRequestParam,
+    ) -> Result<InitializeResult, ServiceError> {
+        let result = self
+            .send_request(ClientRequest::InitializeRequest(InitializeRequest {
+                method: Default::default(),
+                params,
+            }))
+            .await?;
+        match result {
+            ServerResult::InitializeResult(result) => {
+                let _ = self.initialized_info.set(result.clone());
+                Ok(result)
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Fails early with [`ServiceError::CapabilityNotSupported`] if the server doesn't advertise the `completions` capability.
+    ///
+    /// The capability is defined since the `2025-03-26` protocol, so it's only checked from this version,
+    /// the servers of the `2024-11-05` protocol are sent the request.
+    pub async fn complete(
+        &self,
+        params: CompleteRequestParam,
+    ) -> Result<CompleteResult, ServiceError> {
+        let peer_info = self.peer_info();
+        if peer_info.protocol_version >= ProtocolVersion::V_2025_03_26
+            && peer_info.capabilities.completions.is_none()
+        {
+            return Err(ServiceError::CapabilityNotSupported {
+                capability: "completions",
+            });
+        }
+        let result = self
+            .send_request(ClientRequest::CompleteRequest(CompleteRequest {
+                method: Default::default(),
+                params,
+            }))
+            .await?;
+        match result {
+            ServerResult::CompleteResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+    method!(peer_req ping PingRequest());
     method!(peer_req set_level SetLevelRequest(SetLevelRequestParam));
     method!(peer_req get_prompt GetPromptRequest(GetPromptRequestParam) => GetPromptResult);
     method!(peer_req list_prompts ListPromptsRequest(PaginatedRequestParam) => ListPromptsResult);
@@ -173,4 +482,115 @@ impl Peer<RoleClient> {
     method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
     method!(peer_not notify_initialized InitializedNotification);
     method!(peer_not notify_roots_list_changed RootsListChangedNotification);
+
+    /// Call several tools concurrently, the results are in the order of the params.
+    ///
+    /// A call fails with an error, or with a result flagged `isError`. With [`FailMode::FailFast`], the first failure
+    /// cancels the calls still running with `notifications/cancelled`, and those not sent yet aren't sent,
+    /// their results are [`ServiceError::Cancelled`] with [`FailMode::CANCEL_REASON`].
+    pub async fn call_tools_all(
+        &self,
+        params: impl IntoIterator<Item = CallToolRequestParam>,
+        mode: FailMode,
+    ) -> Vec<Result<CallToolResult, ServiceError>> {
+        let fail_fast = mode == FailMode::FailFast;
+        let cancelled = || ServiceError::Cancelled {
+            reason: Some(FailMode::CANCEL_REASON.to_owned()),
+        };
+        let mut results = Vec::new();
+        let mut running = Vec::new();
+        let mut responses = futures::stream::FuturesUnordered::new();
+        for (index, params) in params.into_iter().enumerate() {
+            if fail_fast && results.iter().flatten().any(is_failed_call) {
+                results.push(Some(Err(cancelled())));
+                continue;
+            }
+            let request = ClientRequest::CallToolRequest(CallToolRequest {
+                method: Default::default(),
+                params,
+            });
+            match self
+                .send_cancellable_request(request, PeerRequestOptions::no_options())
+                .await
+            {
+                Ok(handle) => {
+                    results.push(None);
+                    running.push((index, handle.id.clone()));
+                    responses.push(async move { (index, handle.await_response().await) });
+                }
+                Err(error) => results.push(Some(Err(error))),
+            }
+        }
+        let mut failed = fail_fast && results.iter().flatten().any(is_failed_call);
+        if failed {
+            self.cancel_calls(&running).await;
+        }
+        while let Some((index, response)) = responses.next().await {
+            let result = response.and_then(|response| match response {
+                ServerResult::CallToolResult(result) => Ok(result),
+                _ => Err(ServiceError::UnexpectedResponse),
+            });
+            running.retain(|(running, _)| *running != index);
+            if fail_fast && !failed && is_failed_call(&result) {
+                failed = true;
+                self.cancel_calls(&running).await;
+            }
+            results[index] = Some(result);
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("every call has a result"))
+            .collect()
+    }
+
+    async fn cancel_calls(&self, running: &[(usize, RequestId)]) {
+        for (_, id) in running {
+            let param = CancelledNotificationParam {
+                request_id: id.clone(),
+                reason: Some(FailMode::CANCEL_REASON.to_owned()),
+            };
+            if let Err(error) = self.notify_cancelled(param).await {
+                tracing::warn!(%id, %error, "fail to cancel a tool call");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "experimental")]
+impl Peer<RoleClient> {
+    /// Call a tool page by page and concatenate the pages, see [`tool_page`](crate::model::CallToolResult::paginate).
+    ///
+    /// The first page is asked with an empty cursor, so a tool which doesn't page its content returns it all at once.
+    /// An error result is returned as is, and a cursor sent twice in a row fails with [`ServiceError::UnexpectedResponse`].
+    pub async fn call_tool_all_pages(
+        &self,
+        params: CallToolRequestParam,
+    ) -> Result<CallToolResult, ServiceError> {
+        use crate::model::Cursor;
+        let mut cursor = Cursor::default();
+        let mut result = self
+            .call_tool(params.clone().with_cursor(cursor.clone()))
+            .await?;
+        loop {
+            if result.is_error == Some(true) {
+                return Ok(result);
+            }
+            let next = result
+                .next_cursor()
+                .map_err(|_| ServiceError::UnexpectedResponse)?;
+            match next {
+                None => return Ok(result),
+                Some(next) if next == cursor => return Err(ServiceError::UnexpectedResponse),
+                Some(next) => cursor = next,
+            }
+            let page = self
+                .call_tool(params.clone().with_cursor(cursor.clone()))
+                .await?;
+            if page.is_error == Some(true) {
+                return Ok(page);
+            }
+            result.content.extend(page.content);
+            result.meta = page.meta;
+        }
+    }
 }
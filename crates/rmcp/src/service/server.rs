@@ -1,14 +1,15 @@
 use crate::model::{
     CancelledNotification, CancelledNotificationParam, ClientInfo, ClientNotification,
     ClientRequest, ClientResult, CreateMessageRequest, CreateMessageRequestParam,
-    CreateMessageResult, ListRootsRequest, ListRootsResult, LoggingMessageNotification,
-    LoggingMessageNotificationParam, ProgressNotification, ProgressNotificationParam,
-    PromptListChangedNotification, ResourceListChangedNotification, ResourceUpdatedNotification,
-    ResourceUpdatedNotificationParam, ServerInfo, ServerMessage, ServerNotification, ServerRequest,
-    ServerResult, ToolListChangedNotification,
+    CreateMessageResult, ListRootsRequest, ListRootsResult, LoggingLevel,
+    LoggingMessageNotification, LoggingMessageNotificationParam, PingRequest, ProgressNotification,
+    ProgressNotificationParam, PromptListChangedNotification, ResourceListChangedNotification,
+    ResourceUpdatedNotification, ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo,
+    ServerMessage, ServerNotification, ServerRequest, ServerResult, ToolListChangedNotification,
 };
 
 use super::*;
+use crate::transport::TryStreamSink;
 use futures::{SinkExt, StreamExt};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -34,7 +35,21 @@ where
     T: IntoTransport<RoleServer, E, A>,
     E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
 {
-    let (sink, stream) = transport.into_transport();
+    serve_server_with_config(service, transport, ServeConfig::default()).await
+}
+
+/// Like [`serve_server`], with the capacities of the queues of the service loop, see [`ServeConfig`]
+pub async fn serve_server_with_config<S, T, E, A>(
+    service: S,
+    transport: T,
+    config: ServeConfig,
+) -> Result<RunningService<S>, E>
+where
+    S: Service<Role = RoleServer>,
+    T: IntoTransport<RoleServer, E, A>,
+    E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+{
+    let (sink, stream) = transport.into_try_transport();
     let mut sink = Box::pin(sink);
     let mut stream = Box::pin(stream);
     let id_provider = <Arc<AtomicU32RequestIdProvider>>::default();
@@ -46,7 +61,7 @@ where
         .ok_or(std::io::Error::new(
             std::io::ErrorKind::UnexpectedEof,
             "expect initialize request",
-        ))?
+        ))??
         .into_message()
         .into_request()
         .ok_or(std::io::Error::new(
@@ -62,24 +77,33 @@ where
     };
     let init_response = service.get_info();
     sink.send(
-        ServerMessage::Response(ServerResult::InitializeResult(init_response), id)
+        ServerMessage::Response(ServerResult::InitializeResult(init_response.clone()), id)
             .into_json_rpc_message(),
     )
     .await?;
-    // waiting for notification
-    let notification = stream
-        .next()
-        .await
-        .ok_or(std::io::Error::new(
-            std::io::ErrorKind::UnexpectedEof,
-            "expect initialize notification",
-        ))?
-        .into_message()
-        .into_notification()
-        .ok_or(std::io::Error::new(
+    // waiting for notification, the initialize requests sent again by a client which timed out are answered the same
+    let notification = loop {
+        let message = stream
+            .next()
+            .await
+            .ok_or(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "expect initialize notification",
+            ))??
+            .into_message();
+        if let Message::Request(ClientRequest::InitializeRequest(_), id) = message {
+            sink.send(
+                ServerMessage::Response(ServerResult::InitializeResult(init_response.clone()), id)
+                    .into_json_rpc_message(),
+            )
+            .await?;
+            continue;
+        }
+        break message.into_notification().ok_or(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             "expect initialize notification",
         ))?;
+    };
     let ClientNotification::InitializedNotification(_) = notification else {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
@@ -87,10 +111,30 @@ where
         )
         .into());
     };
-    serve_inner(service, (sink, stream), peer_info.params, id_provider).await
+    serve_inner(
+        service,
+        TryStreamSink(sink, stream),
+        peer_info.params,
+        id_provider,
+        config,
+    )
+    .await
 }
 
 macro_rules! method {
+    (peer_req $method:ident $Req:ident()) => {
+        pub async fn $method(&self) -> Result<(), ServiceError> {
+            let result = self
+                .send_request(ServerRequest::$Req($Req {
+                    method: Default::default(),
+                }))
+                .await?;
+            match result {
+                ClientResult::EmptyResult(_) => Ok(()),
+                _ => Err(ServiceError::UnexpectedResponse),
+            }
+        }
+    };
     (peer_req $method:ident $Req:ident() => $Resp: ident ) => {
         pub async fn $method(&self) -> Result<$Resp, ServiceError> {
             let result = self
@@ -148,7 +192,8 @@ macro_rules! method {
             Ok(())
         }
     };
-    (peer_not $method:ident $Not:ident) => {
+    ($(#[$attr:meta])* peer_not $method:ident $Not:ident) => {
+        $(#[$attr])*
         pub async fn $method(&self) -> Result<(), ServiceError> {
             self.send_notification(ServerNotification::$Not($Not {
                 method: Default::default(),
@@ -160,14 +205,121 @@ macro_rules! method {
 }
 
 impl Peer<RoleServer> {
-    method!(peer_req create_message CreateMessageRequest(CreateMessageRequestParam) => CreateMessageResult);
+    method!(peer_req ping PingRequest());
     method!(peer_req list_roots ListRootsRequest() => ListRootsResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
     method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
     method!(peer_not notify_logging_message LoggingMessageNotification(LoggingMessageNotificationParam));
     method!(peer_not notify_resource_updated ResourceUpdatedNotification(ResourceUpdatedNotificationParam));
-    method!(peer_not notify_resource_list_changed ResourceListChangedNotification);
-    method!(peer_not notify_tool_list_changed ToolListChangedNotification);
-    method!(peer_not notify_prompt_list_changed PromptListChangedNotification);
+    method!(
+        /// Send `notifications/resources/list_changed` without checking the capabilities,
+        /// [`Peer::notify_resources_changed`] checks `resources.listChanged` is advertised first
+        peer_not notify_resource_list_changed ResourceListChangedNotification
+    );
+    method!(
+        /// Send `notifications/tools/list_changed` without checking the capabilities,
+        /// [`Peer::notify_tools_changed`] checks `tools.listChanged` is advertised first
+        peer_not notify_tool_list_changed ToolListChangedNotification
+    );
+    method!(
+        /// Send `notifications/prompts/list_changed` without checking the capabilities,
+        /// [`Peer::notify_prompts_changed`] checks `prompts.listChanged` is advertised first
+        peer_not notify_prompt_list_changed PromptListChangedNotification
+    );
+
+    /// Ask the client to sample a LLM.
+    ///
+    /// The params are checked with [`CreateMessageRequestParam::validate`] first, an invalid request
+    /// fails with `INVALID_PARAMS` without being sent, as the client would reject it.
+    pub async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+    ) -> Result<CreateMessageResult, ServiceError> {
+        params.validate().map_err(ServiceError::McpError)?;
+        let result = self
+            .send_request(ServerRequest::CreateMessageRequest(CreateMessageRequest {
+                method: Default::default(),
+                params,
+            }))
+            .await?;
+        match result {
+            ClientResult::CreateMessageResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Notify the client the tools have changed, e.g. a tool is registered at runtime.
+    ///
+    /// It's [`Peer::notify_tool_list_changed`] with a check of the capabilities: it fails early with
+    /// [`ServiceError::CapabilityNotSupported`] unless the server advertises `tools.listChanged`,
+    /// use [`Peer::notify_tool_list_changed`] to send it anyway.
+    pub async fn notify_tools_changed(&self) -> Result<(), ServiceError> {
+        self.require_list_changed("tools.listChanged", |capabilities| {
+            capabilities.tools.as_ref()?.list_changed
+        })?;
+        self.notify_tool_list_changed().await
+    }
+
+    /// Notify the client the prompts have changed, like [`Peer::notify_tools_changed`] with `prompts.listChanged`
+    pub async fn notify_prompts_changed(&self) -> Result<(), ServiceError> {
+        self.require_list_changed("prompts.listChanged", |capabilities| {
+            capabilities.prompts.as_ref()?.list_changed
+        })?;
+        self.notify_prompt_list_changed().await
+    }
+
+    /// Notify the client the resources have changed, like [`Peer::notify_tools_changed`] with `resources.listChanged`
+    pub async fn notify_resources_changed(&self) -> Result<(), ServiceError> {
+        self.require_list_changed("resources.listChanged", |capabilities| {
+            capabilities.resources.as_ref()?.list_changed
+        })?;
+        self.notify_resource_list_changed().await
+    }
+
+    /// Send a log message of a text to the client, see [`LoggingMessageNotificationParam::text`]
+    pub async fn log_text(
+        &self,
+        level: LoggingLevel,
+        logger: Option<&str>,
+        text: &str,
+    ) -> Result<(), ServiceError> {
+        self.notify_logging_message(LoggingMessageNotificationParam::text(level, logger, text))
+            .await
+    }
+
+    /// Send a log message of structured data to the client, the client reads it back with
+    /// [`LoggingMessageNotificationParam::data_as`].
+    ///
+    /// A data which doesn't serialize fails with an internal error without being sent.
+    pub async fn log_structured(
+        &self,
+        level: LoggingLevel,
+        logger: Option<&str>,
+        data: impl serde::Serialize,
+    ) -> Result<(), ServiceError> {
+        let params =
+            LoggingMessageNotificationParam::structured(level, logger, data).map_err(|e| {
+                ServiceError::McpError(McpError::internal_error(
+                    format!("failed to serialize the log data: {e}"),
+                    None,
+                ))
+            })?;
+        self.notify_logging_message(params).await
+    }
+
+    fn require_list_changed(
+        &self,
+        capability: &'static str,
+        list_changed: impl FnOnce(&ServerCapabilities) -> Option<bool>,
+    ) -> Result<(), ServiceError> {
+        let advertised = self
+            .local_info()
+            .and_then(|info| list_changed(&info.capabilities));
+        if advertised == Some(true) {
+            Ok(())
+        } else {
+            Err(ServiceError::CapabilityNotSupported { capability })
+        }
+    }
 }
@@ -0,0 +1,116 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+type StateMap = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+/// The state of a single connection, e.g. the user authenticated by the client, see [`Peer::session`](super::Peer::session).
+///
+/// Each service loop creates its own, so a handler shared by several connections keeps the shared data in
+/// `&self` and the per-connection data here, instead of in a map keyed by connection. It holds one value per type:
+///
+/// ```rust,ignore
+/// #[derive(Clone)]
+/// struct User(String);
+///
+/// async fn call_tool(&self, request: CallToolRequestParam, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+///     let Some(User(name)) = context.session().get::<User>() else {
+///         return Err(McpError::invalid_request("login first", None));
+///     };
+///     // ...
+/// }
+/// ```
+///
+/// The values are dropped when the service loop finishes, whatever the reason, even if a clone of the peer is kept around.
+/// The session is empty from then on.
+#[derive(Clone)]
+pub struct SessionState {
+    id: u64,
+    values: Arc<RwLock<StateMap>>,
+}
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            values: Default::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for SessionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionState")
+            .field("id", &self.id)
+            .field("len", &self.read().len())
+            .finish()
+    }
+}
+
+impl SessionState {
+    /// The id of this connection, unique in the process, e.g. to tell the connections apart in a shared cache
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, StateMap> {
+        self.values.read().expect("session lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, StateMap> {
+        self.values.write().expect("session lock poisoned")
+    }
+
+    /// Set the value of its type, the previous one is returned
+    pub fn insert<T: Send + Sync + 'static>(&self, value: T) -> Option<T> {
+        self.write()
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    /// A clone of the value of type `T`, wrap it in an `Arc` if it's costly to clone or must be shared mutably
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.read()
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    }
+
+    /// The value of type `T`, inserted with `init` if there is none yet
+    pub fn get_or_insert_with<T: Clone + Send + Sync + 'static>(
+        &self,
+        init: impl FnOnce() -> T,
+    ) -> T {
+        self.write()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(init()))
+            .downcast_ref::<T>()
+            .expect("the value is stored with its type id")
+            .clone()
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.read().contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Option<T> {
+        self.write()
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Drop all the values, outside of the lock in case a value's drop uses the session
+    pub(crate) fn clear(&self) {
+        let values = std::mem::take(&mut *self.write());
+        drop(values);
+    }
+}
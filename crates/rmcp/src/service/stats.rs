@@ -0,0 +1,99 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// A snapshot of the counters of a connection, see [`Peer::stats`](super::Peer::stats)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerStats {
+    /// The requests, responses and notifications written to the transport
+    pub messages_sent: u64,
+    /// The requests, responses and notifications read from the transport
+    pub messages_received: u64,
+    /// The requests sent to the peer which are waiting for their response
+    pub requests_in_flight: u64,
+    /// The requests of the peer being handled by the local service
+    pub requests_handling: u64,
+    /// The last time a message was sent or received, `None` before the first one
+    pub last_activity: Option<Instant>,
+    /// The round trip of the last answered `ping` sent to the peer
+    pub last_ping_rtt: Option<Duration>,
+}
+
+/// The counters updated by the service loop, they're atomics so reading them never blocks the loop
+#[derive(Debug)]
+pub(crate) struct StatsCounters {
+    created_at: Instant,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    requests_in_flight: AtomicU64,
+    requests_handling: AtomicU64,
+    /// Nanoseconds since `created_at` plus one, zero means no activity yet
+    last_activity: AtomicU64,
+    /// Nanoseconds plus one, zero means no ping answered yet
+    last_ping_rtt: AtomicU64,
+}
+
+impl Default for StatsCounters {
+    fn default() -> Self {
+        Self {
+            created_at: Instant::now(),
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+            requests_in_flight: AtomicU64::new(0),
+            requests_handling: AtomicU64::new(0),
+            last_activity: AtomicU64::new(0),
+            last_ping_rtt: AtomicU64::new(0),
+        }
+    }
+}
+
+fn encode(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos())
+        .unwrap_or(u64::MAX - 1)
+        .saturating_add(1)
+}
+
+fn decode(value: u64) -> Option<Duration> {
+    value.checked_sub(1).map(Duration::from_nanos)
+}
+
+impl StatsCounters {
+    fn touch(&self) {
+        self.last_activity
+            .store(encode(self.created_at.elapsed()), Ordering::Relaxed);
+    }
+
+    pub(crate) fn sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub(crate) fn received(&self) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub(crate) fn set_in_flight(&self, requests_in_flight: usize, requests_handling: usize) {
+        self.requests_in_flight
+            .store(requests_in_flight as u64, Ordering::Relaxed);
+        self.requests_handling
+            .store(requests_handling as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn ping_answered(&self, rtt: Duration) {
+        self.last_ping_rtt.store(encode(rtt), Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> PeerStats {
+        PeerStats {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            requests_in_flight: self.requests_in_flight.load(Ordering::Relaxed),
+            requests_handling: self.requests_handling.load(Ordering::Relaxed),
+            last_activity: decode(self.last_activity.load(Ordering::Relaxed))
+                .map(|elapsed| self.created_at + elapsed),
+            last_ping_rtt: decode(self.last_ping_rtt.load(Ordering::Relaxed)),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! A scripted server to test clients against, see [`MockServer`]
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    error::Error as McpError,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientNotification, ClientRequest, ErrorCode,
+        GetMethod, GetPromptRequestParam, GetPromptResult, ListToolsResult,
+        ReadResourceRequestParam, ReadResourceResult, ServerCapabilities, ServerInfo, ServerResult,
+        Tool,
+    },
+    service::{
+        Peer, RequestContext, RoleClient, RoleServer, RunningService, Service, serve_client,
+        serve_server,
+    },
+};
+
+type Matcher = dyn Fn(&ClientRequest) -> bool + Send + Sync;
+type Responder = dyn Fn(ClientRequest) -> Result<ServerResult, McpError> + Send + Sync;
+
+#[derive(Clone)]
+struct Rule {
+    matcher: Arc<Matcher>,
+    responder: Arc<Responder>,
+}
+
+/// A server answering the requests with scripted responses, to test a client.
+///
+/// The responses are registered by method, or by a matcher of the request, and the rules are tried in
+/// the order they're registered. A request matching no rule fails with `METHOD_NOT_FOUND`, except `ping`,
+/// and `tools/list` which lists the tools registered with [`MockServer::on_call_tool`].
+///
+/// Every capability is advertised unless the info is set with [`MockServer::with_info`].
+///
+/// ```rust,ignore
+/// let mock = MockServer::new()
+///     .on_call_tool("sum", |request| Ok(CallToolResult::success(vec![Content::text("3")])))
+///     .connect()
+///     .await?;
+/// let result = mock.client.call_tool(CallToolRequestParam::new("sum")).await?;
+/// mock.assert_tool_called("sum");
+/// ```
+#[derive(Clone, Default)]
+pub struct MockServer {
+    info: Option<ServerInfo>,
+    rules: Vec<Rule>,
+    tools: Vec<Cow<'static, str>>,
+    requests: Arc<Mutex<Vec<ClientRequest>>>,
+    notifications: Arc<Mutex<Vec<ClientNotification>>>,
+    peer: Option<Peer<RoleServer>>,
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("info", &self.info)
+            .field("rules", &self.rules.len())
+            .field("tools", &self.tools)
+            .finish()
+    }
+}
+
+impl MockServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the info sent in the initialize result, e.g. to test a client with the capabilities of a server
+    pub fn with_info(mut self, info: ServerInfo) -> Self {
+        self.info = Some(info);
+        self
+    }
+
+    /// Answer the requests matching `matcher`
+    pub fn on_match<M, F>(mut self, matcher: M, responder: F) -> Self
+    where
+        M: Fn(&ClientRequest) -> bool + Send + Sync + 'static,
+        F: Fn(ClientRequest) -> Result<ServerResult, McpError> + Send + Sync + 'static,
+    {
+        self.rules.push(Rule {
+            matcher: Arc::new(matcher),
+            responder: Arc::new(responder),
+        });
+        self
+    }
+
+    /// Answer the requests of a method, e.g. `resources/list`
+    pub fn on<F>(self, method: &'static str, responder: F) -> Self
+    where
+        F: Fn(ClientRequest) -> Result<ServerResult, McpError> + Send + Sync + 'static,
+    {
+        self.on_match(move |request| request.method() == method, responder)
+    }
+
+    /// Answer the calls of a tool, it's also listed by `tools/list`
+    pub fn on_call_tool<F>(mut self, name: impl Into<Cow<'static, str>>, responder: F) -> Self
+    where
+        F: Fn(CallToolRequestParam) -> Result<CallToolResult, McpError> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.tools.push(name.clone());
+        self.on_match(
+            move |request| {
+                matches!(request, ClientRequest::CallToolRequest(call) if call.params.name == name)
+            },
+            move |request| match request {
+                ClientRequest::CallToolRequest(call) => {
+                    responder(call.params).map(ServerResult::CallToolResult)
+                }
+                _ => unreachable!("matched a tool call"),
+            },
+        )
+    }
+
+    /// Answer the reads of a resource
+    pub fn on_read_resource<F>(self, uri: impl Into<String>, responder: F) -> Self
+    where
+        F: Fn(ReadResourceRequestParam) -> Result<ReadResourceResult, McpError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let uri = uri.into();
+        self.on_match(
+            move |request| {
+                matches!(request, ClientRequest::ReadResourceRequest(read) if read.params.uri == uri)
+            },
+            move |request| match request {
+                ClientRequest::ReadResourceRequest(read) => {
+                    responder(read.params).map(ServerResult::ReadResourceResult)
+                }
+                _ => unreachable!("matched a resource read"),
+            },
+        )
+    }
+
+    /// Answer the gets of a prompt
+    pub fn on_get_prompt<F>(self, name: impl Into<String>, responder: F) -> Self
+    where
+        F: Fn(GetPromptRequestParam) -> Result<GetPromptResult, McpError> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.on_match(
+            move |request| {
+                matches!(request, ClientRequest::GetPromptRequest(get) if get.params.name == name)
+            },
+            move |request| match request {
+                ClientRequest::GetPromptRequest(get) => {
+                    responder(get.params).map(ServerResult::GetPromptResult)
+                }
+                _ => unreachable!("matched a prompt get"),
+            },
+        )
+    }
+
+    /// Serve it over an in-memory transport, to a client with the default handler
+    pub async fn connect(self) -> std::io::Result<MockConnection> {
+        self.connect_client(crate::ClientHandlerService::simple())
+            .await
+    }
+
+    /// Serve it over an in-memory transport, to the client service
+    pub async fn connect_client<S>(self, client: S) -> std::io::Result<MockConnection<S>>
+    where
+        S: Service<Role = RoleClient>,
+    {
+        const BUFFER_SIZE: usize = 64 * 1024;
+        let (server_transport, client_transport) = tokio::io::duplex(BUFFER_SIZE);
+        let requests = self.requests.clone();
+        let notifications = self.notifications.clone();
+        let server = tokio::spawn(serve_server(self, server_transport));
+        let client = serve_client(client, client_transport).await?;
+        let server = server.await.map_err(std::io::Error::other)??;
+        Ok(MockConnection {
+            client,
+            server,
+            requests,
+            notifications,
+        })
+    }
+
+    fn default_response(&self, request: &ClientRequest) -> Result<ServerResult, McpError> {
+        match request {
+            ClientRequest::PingRequest(_) => Ok(ServerResult::empty(())),
+            ClientRequest::ListToolsRequest(_) => {
+                Ok(ServerResult::ListToolsResult(ListToolsResult {
+                    next_cursor: None,
+                    tools: self
+                        .tools
+                        .iter()
+                        .map(|name| Tool::new(name.clone(), "", Arc::new(Default::default())))
+                        .collect(),
+                    meta: None,
+                }))
+            }
+            request => Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                request.method().to_owned(),
+                None,
+            )),
+        }
+    }
+}
+
+impl Service for MockServer {
+    type Role = RoleServer;
+
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, McpError> {
+        lock(&self.requests).push(request.clone());
+        match self.rules.iter().find(|rule| (rule.matcher)(&request)) {
+            Some(rule) => (rule.responder)(request),
+            None => self.default_response(&request),
+        }
+    }
+
+    async fn handle_notification(&self, notification: ClientNotification) -> Result<(), McpError> {
+        lock(&self.notifications).push(notification);
+        Ok(())
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        self.peer = Some(peer);
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.info.clone().unwrap_or_else(|| ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_logging()
+                .enable_completions()
+                .enable_prompts()
+                .enable_resources()
+                .enable_tools()
+                .build(),
+            ..Default::default()
+        })
+    }
+}
+
+/// A client connected to a [`MockServer`], with the requests the server has received.
+///
+/// The initialize handshake happens before the server starts recording, so neither the initialize request
+/// nor the initialized notification are in the received messages.
+pub struct MockConnection<S: Service<Role = RoleClient> = crate::ClientHandlerService> {
+    pub client: RunningService<S>,
+    pub server: RunningService<MockServer>,
+    requests: Arc<Mutex<Vec<ClientRequest>>>,
+    notifications: Arc<Mutex<Vec<ClientNotification>>>,
+}
+
+impl<S: Service<Role = RoleClient>> MockConnection<S> {
+    /// The requests received so far, in order
+    pub fn requests(&self) -> Vec<ClientRequest> {
+        lock(&self.requests).clone()
+    }
+
+    /// The requests of a method received so far, in order
+    pub fn requests_of(&self, method: &str) -> Vec<ClientRequest> {
+        lock(&self.requests)
+            .iter()
+            .filter(|request| request.method() == method)
+            .cloned()
+            .collect()
+    }
+
+    /// The notifications received so far, in order
+    pub fn notifications(&self) -> Vec<ClientNotification> {
+        lock(&self.notifications).clone()
+    }
+
+    /// The parameters of the calls of a tool received so far, in order
+    pub fn tool_calls(&self, name: &str) -> Vec<CallToolRequestParam> {
+        lock(&self.requests)
+            .iter()
+            .filter_map(|request| match request {
+                ClientRequest::CallToolRequest(call) if call.params.name == name => {
+                    Some(call.params.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Panic unless the server has received `count` requests of the method
+    #[track_caller]
+    pub fn assert_request_count(&self, method: &str, count: usize) {
+        let received = self.requests_of(method).len();
+        assert_eq!(
+            received, count,
+            "expect {count} {method} request(s), received {received}"
+        );
+    }
+
+    /// Panic unless the server has received a request of the method
+    #[track_caller]
+    pub fn assert_requested(&self, method: &str) {
+        assert!(
+            !self.requests_of(method).is_empty(),
+            "expect a {method} request, received {:?}",
+            self.requests()
+                .iter()
+                .map(|request| request.method().to_owned())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Panic unless the tool was called, and return the parameters of its last call
+    #[track_caller]
+    pub fn assert_tool_called(&self, name: &str) -> CallToolRequestParam {
+        match self.tool_calls(name).pop() {
+            Some(call) => call,
+            None => panic!("expect a call of the tool {name}"),
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().expect("mock server lock poisoned")
+}
@@ -7,7 +7,8 @@
 //!
 //! ## These types is automatically implemented [`IntoTransport`] trait
 //! 1. For type that already implement both [`Sink`] and [`Stream`] trait, they are automatically implemented [`IntoTransport`] trait
-//! 2. For tuple of sink `Tx` and stream `Rx`, type `(Tx, Rx)` are automatically implemented [`IntoTransport`] trait
+//! 2. For tuple of sink `Tx` and stream `Rx`, type `(Tx, Rx)` are automatically implemented [`IntoTransport`] trait,
+//!    a stream yielding the read errors too is wrapped in a [`TryStreamSink`]
 //! 3. For type that implement both [`tokio::io::AsyncRead`] and [`tokio::io::AsyncWrite`] trait, they are automatically implemented [`IntoTransport`] trait
 //! 4. For tuple of [`tokio::io::AsyncRead`] `R `and [`tokio::io::AsyncWrite`] `W`, type `(R, W)` are automatically implemented [`IntoTransport`] trait
 //!
@@ -38,8 +39,12 @@
 //! }
 //! ```
 
+use std::sync::Arc;
+
 use futures::{Sink, Stream};
+use tokio_util::bytes::Bytes;
 
+use crate::model::RequestId;
 use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
 #[cfg(feature = "transport-child-process")]
 pub mod child_process;
@@ -50,6 +55,9 @@ pub mod io;
 #[cfg(feature = "transport-sse")]
 pub mod sse;
 
+#[cfg(feature = "transport-sse-server")]
+pub mod sse_server;
+
 // #[cfg(feature = "tower")]
 // pub mod tower;
 
@@ -64,6 +72,24 @@ where
         impl Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
         impl Stream<Item = RxJsonRpcMessage<R>> + Send + 'static,
     );
+    /// Like [`IntoTransport::into_transport`], the stream also yields the error which stopped the reading,
+    /// the service then quits with a [`QuitReason::TransportError`](crate::service::QuitReason::TransportError).
+    ///
+    /// This is what the service uses. By default the stream of [`IntoTransport::into_transport`] never fails,
+    /// the transports which can tell a read error from the end of the stream override it.
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, E>> + Send + 'static,
+    )
+    where
+        Self: Sized,
+    {
+        use futures::StreamExt;
+        let (sink, stream) = self.into_transport();
+        (sink, stream.map(Ok))
+    }
 }
 
 pub enum TransportAdapterStreamSink {}
@@ -85,6 +111,51 @@ where
     }
 }
 
+/// A sink, and a stream yielding the read errors, the service quits on the first one.
+///
+/// The stream of a `(Tx, Rx)` yields the messages alone, so its element type can be inferred.
+/// [`IntoTransport::into_transport`] logs the first error and ends the stream there.
+#[derive(Debug)]
+pub struct TryStreamSink<Tx, Rx>(pub Tx, pub Rx);
+
+pub enum TransportAdapterTryStreamSink {}
+
+impl<Role, Rx, Tx, E> IntoTransport<Role, E, TransportAdapterTryStreamSink>
+    for TryStreamSink<Tx, Rx>
+where
+    Role: ServiceRole,
+    Tx: Sink<TxJsonRpcMessage<Role>, Error = E> + Send + 'static,
+    Rx: Stream<Item = Result<RxJsonRpcMessage<Role>, E>> + Send + 'static,
+    E: std::error::Error + Send + 'static,
+{
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = E> + Send + 'static,
+        impl Stream<Item = RxJsonRpcMessage<Role>> + Send + 'static,
+    ) {
+        use futures::StreamExt;
+        let stream = self.1.scan((), |_, result| {
+            futures::future::ready(match result {
+                Ok(message) => Some(message),
+                Err(error) => {
+                    tracing::error!(%error, "fail to read from the transport");
+                    None
+                }
+            })
+        });
+        (self.0, stream)
+    }
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = E> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, E>> + Send + 'static,
+    ) {
+        (self.0, self.1)
+    }
+}
+
 impl<R, T, E> IntoTransport<R, E, ()> for T
 where
     T: Stream<Item = RxJsonRpcMessage<R>> + Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
@@ -101,3 +172,61 @@ where
         self.split()
     }
 }
+
+/// A message which failed to parse, but whose request id could be read.
+///
+/// A transport yields it as a read error of [`IntoTransport::into_try_transport`], as is or wrapped in an
+/// [`std::io::Error`], e.g. the `io` transport. The service answers the request with a parse error (`-32700`)
+/// and keeps reading, instead of quitting as for the other read errors.
+#[derive(Debug, thiserror::Error)]
+#[error("malformed message of request {id}: {reason}")]
+pub struct MalformedMessage {
+    pub id: RequestId,
+    pub reason: String,
+}
+
+/// Which way a message went through the transport, see [`WireTap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// A hook receiving the exact bytes of the messages a transport reads and writes, e.g. for an audit log,
+/// or to verify a signature, which a re-serialization of the parsed message would break by reordering its keys.
+///
+/// The bytes are those of the message alone, without the framing of the transport: the line ending of the
+/// `io` transport, or the fields of an SSE event. An incoming message is tapped before it's parsed, so the
+/// message the service receives next is the one of the last incoming bytes. The bytes failing to parse are
+/// tapped too, but no message follows them. An outgoing message is tapped once serialized, before it's written.
+///
+/// ```rust,ignore
+/// let tap = WireTap::new(|direction, bytes| audit.record(direction, bytes.clone()));
+/// let client = serve_client(handler, (tokio::io::stdin(), tokio::io::stdout(), tap)).await?;
+/// ```
+///
+/// The tap is called inline by the transport, it should be quick, e.g. send the bytes to a channel.
+/// Cloning the [`Bytes`] doesn't copy them, but the incoming bytes of the `io` transport are a slice of its
+/// read buffer: as long as a slice is retained, its part of the buffer can't be reused and the transport
+/// allocates a new one. Copy them with [`Bytes::copy_from_slice`] to keep them for long, e.g. until a batch is written.
+/// Besides, tapping the outgoing messages of the `io` transport costs a copy of each message.
+#[derive(Clone)]
+pub struct WireTap(Arc<DynWireTap>);
+
+type DynWireTap = dyn Fn(WireDirection, &Bytes) + Send + Sync;
+
+impl std::fmt::Debug for WireTap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WireTap").finish_non_exhaustive()
+    }
+}
+
+impl WireTap {
+    pub fn new(tap: impl Fn(WireDirection, &Bytes) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(tap))
+    }
+
+    pub(crate) fn record(&self, direction: WireDirection, bytes: &Bytes) {
+        (self.0)(direction, bytes)
+    }
+}
@@ -88,4 +88,14 @@ impl<R: ServiceRole> IntoTransport<R, std::io::Error, ()> for TokioChildProcess
             self.split(),
         )
     }
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<R>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, std::io::Error>> + Send + 'static,
+    ) {
+        IntoTransport::<R, std::io::Error, super::io::TransportAdapterAsyncRW>::into_try_transport(
+            self.split(),
+        )
+    }
 }
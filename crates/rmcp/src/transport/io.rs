@@ -7,13 +7,14 @@ use thiserror::Error;
 
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::{
-    bytes::{Buf, BufMut, BytesMut},
+    bytes::{Buf, BufMut, Bytes, BytesMut},
     codec::{Decoder, Encoder, FramedRead, FramedWrite},
 };
 
+use crate::model::RequestId;
 use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
 
-use super::IntoTransport;
+use super::{IntoTransport, MalformedMessage, WireDirection, WireTap};
 pub enum TransportAdapterAsyncRW {}
 
 impl<Role, R, W> IntoTransport<Role, std::io::Error, TransportAdapterAsyncRW> for (R, W)
@@ -30,6 +31,17 @@ where
     ) {
         (from_async_write(self.1), from_async_read(self.0))
     }
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
+    ) {
+        (
+            from_async_write(self.1),
+            read_with_codec(self.0, JsonRpcMessageCodec::default()),
+        )
+    }
 }
 
 pub enum TransportAdapterAsyncCombinedRW {}
@@ -48,10 +60,153 @@ where
             tokio::io::split(self),
         )
     }
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
+    ) {
+        IntoTransport::<Role, std::io::Error, TransportAdapterAsyncRW>::into_try_transport(
+            tokio::io::split(self),
+        )
+    }
 }
 
+pub enum TransportAdapterTappedAsyncRW {}
+
+/// The reader and the writer of [`TransportAdapterAsyncRW`], with a [`WireTap`] receiving the bytes of the messages
+impl<Role, R, W> IntoTransport<Role, std::io::Error, TransportAdapterTappedAsyncRW>
+    for (R, W, WireTap)
+where
+    Role: ServiceRole,
+    R: AsyncRead + Send + 'static,
+    W: AsyncWrite + Send + 'static,
+{
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = RxJsonRpcMessage<Role>> + Send + 'static,
+    ) {
+        let (reader, writer, tap) = self;
+        (
+            from_async_write_with_tap(writer, tap.clone()),
+            from_async_read_with_tap(reader, tap),
+        )
+    }
+    fn into_try_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
+    ) {
+        let (reader, writer, tap) = self;
+        (
+            from_async_write_with_tap(writer, tap.clone()),
+            read_with_codec(reader, JsonRpcMessageCodec::default().with_tap(tap)),
+        )
+    }
+}
+
+/// The messages read, a read error is logged and ends the stream
 pub fn from_async_read<T: DeserializeOwned, R: AsyncRead>(reader: R) -> impl Stream<Item = T> {
-    FramedRead::new(reader, JsonRpcMessageCodec::<T>::default()).filter_map(|result| {
+    skip_errors(read_with_codec(reader, JsonRpcMessageCodec::default()))
+}
+
+pub fn from_async_write<T: Serialize, W: AsyncWrite>(
+    writer: W,
+) -> impl Sink<T, Error = std::io::Error> {
+    write_with_codec(writer, JsonRpcMessageCodec::default())
+}
+
+/// Like [`from_async_read`], the bytes of each line are given to the tap before they're parsed
+pub fn from_async_read_with_tap<T: DeserializeOwned, R: AsyncRead>(
+    reader: R,
+    tap: WireTap,
+) -> impl Stream<Item = T> {
+    skip_errors(read_with_codec(
+        reader,
+        JsonRpcMessageCodec::default().with_tap(tap),
+    ))
+}
+
+/// Like [`from_async_write`], the bytes of each message are given to the tap before they're written
+pub fn from_async_write_with_tap<T: Serialize, W: AsyncWrite>(
+    writer: W,
+    tap: WireTap,
+) -> impl Sink<T, Error = std::io::Error> {
+    write_with_codec(writer, JsonRpcMessageCodec::default().with_tap(tap))
+}
+
+/// The messages read, and the error ending the stream, e.g. the reader failed.
+///
+/// A line which isn't a valid message, but whose request id can be read, is yielded as a [`MalformedMessage`]
+/// error, which doesn't end the stream, so that the service answers it with a parse error.
+fn read_with_codec<T: DeserializeOwned, R: AsyncRead>(
+    reader: R,
+    codec: JsonRpcMessageCodec<T>,
+) -> impl Stream<Item = Result<T, std::io::Error>> {
+    FramedRead::new(reader, TransportCodec(codec)).map(|result| match result {
+        Ok(Ok(message)) => Ok(message),
+        Ok(Err(malformed)) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            malformed,
+        )),
+        Err(e) => Err(e.into()),
+    })
+}
+
+/// The decoder of the transports, which keeps the malformed requests to answer them
+struct TransportCodec<T>(JsonRpcMessageCodec<T>);
+
+impl<T: DeserializeOwned> TransportCodec<T> {
+    fn decode_line(line: Bytes) -> Result<Result<T, MalformedMessage>, JsonRpcMessageCodecError> {
+        match serde_json::from_slice(&line) {
+            Ok(message) => Ok(Ok(message)),
+            Err(e) => match malformed_request_id(&line) {
+                Some(id) => Ok(Err(MalformedMessage {
+                    id,
+                    reason: e.to_string(),
+                })),
+                None => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for TransportCodec<T> {
+    type Item = Result<T, MalformedMessage>;
+
+    type Error = JsonRpcMessageCodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.0.decode_next(buf, false, Self::decode_line)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.0.decode_next(buf, true, Self::decode_line)
+    }
+}
+
+/// The id of a line which looks like a request, i.e. with a `method`, even if it isn't valid UTF-8.
+///
+/// A response or a notification isn't answered, nor a line which isn't JSON.
+fn malformed_request_id(line: &[u8]) -> Option<RequestId> {
+    #[derive(serde::Deserialize)]
+    struct Request {
+        id: RequestId,
+        #[serde(rename = "method")]
+        _method: serde::de::IgnoredAny,
+    }
+    // the invalid UTF-8 of a string is replaced, the rest of the line can still be read
+    let line = String::from_utf8_lossy(line);
+    serde_json::from_str::<Request>(&line)
+        .ok()
+        .map(|request| request.id)
+}
+
+fn skip_errors<T>(stream: impl Stream<Item = Result<T, std::io::Error>>) -> impl Stream<Item = T> {
+    stream.filter_map(|result| {
         if let Err(e) = &result {
             tracing::error!("Error reading from stream: {}", e);
         }
@@ -59,10 +214,89 @@ pub fn from_async_read<T: DeserializeOwned, R: AsyncRead>(reader: R) -> impl Str
     })
 }
 
-pub fn from_async_write<T: Serialize, W: AsyncWrite>(
+fn write_with_codec<T: Serialize, W: AsyncWrite>(
     writer: W,
+    codec: JsonRpcMessageCodec<T>,
 ) -> impl Sink<T, Error = std::io::Error> {
-    FramedWrite::new(writer, JsonRpcMessageCodec::<T>::default()).sink_map_err(Into::into)
+    SinkExt::<T>::sink_map_err(FramedWrite::new(writer, codec), Into::into)
+}
+
+/// Read the messages as [`RawMessage`]s, without deserializing them, e.g. for a relay
+pub fn raw_from_async_read<R: AsyncRead>(reader: R) -> impl Stream<Item = RawMessage> {
+    let stream = FramedRead::new(reader, JsonRpcMessageCodec::<RawMessage>::default());
+    skip_errors(stream.map(|result| result.map_err(Into::into)))
+}
+
+/// Write [`RawMessage`]s verbatim, e.g. the messages read by [`raw_from_async_read`] on the other side of a relay
+pub fn raw_from_async_write<W: AsyncWrite>(
+    writer: W,
+) -> impl Sink<RawMessage, Error = std::io::Error> {
+    SinkExt::<RawMessage>::sink_map_err(
+        FramedWrite::new(writer, JsonRpcMessageCodec::<RawMessage>::default()),
+        Into::into,
+    )
+}
+
+/// An already serialized JSON-RPC message, written to the transport as is.
+///
+/// A relay forwarding the messages of a peer to another doesn't need to deserialize and serialize them again.
+/// Its bytes are checked to be a single JSON object on a single line, so it can't break the framing,
+/// but they're not checked to be a valid message of the protocol. Call [`RawMessage::parse`] to inspect one.
+///
+/// Besides [`raw_from_async_write`], a `FramedWrite` with a [`JsonRpcMessageCodec`] accepts both the typed
+/// and the raw messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawMessage {
+    bytes: Bytes,
+}
+
+#[derive(Debug, Error)]
+pub enum RawMessageError {
+    #[error("a raw message can't contain a line break")]
+    LineBreak,
+    #[error("a raw message must be a JSON object")]
+    NotAnObject,
+    #[error("invalid JSON {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid UTF-8 {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+impl RawMessage {
+    /// Check the bytes are a single JSON object in UTF-8 on a single line, without building it
+    pub fn new(bytes: impl Into<Bytes>) -> Result<Self, RawMessageError> {
+        let bytes = bytes.into();
+        if bytes.contains(&b'\n') {
+            return Err(RawMessageError::LineBreak);
+        }
+        if bytes.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'{') {
+            return Err(RawMessageError::NotAnObject);
+        }
+        // the strings are skipped without being checked
+        let json = std::str::from_utf8(&bytes)?;
+        serde_json::from_str::<serde::de::IgnoredAny>(json)?;
+        Ok(Self { bytes })
+    }
+
+    /// Serialize a message, e.g. to forward it along with raw messages
+    pub fn from_message<T: Serialize>(message: &T) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            bytes: serde_json::to_vec(message)?.into(),
+        })
+    }
+
+    /// Deserialize the message, e.g. into a [`ServerJsonRpcMessage`](crate::model::ServerJsonRpcMessage)
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +305,7 @@ pub struct JsonRpcMessageCodec<T> {
     next_index: usize,
     max_length: usize,
     is_discarding: bool,
+    tap: Option<WireTap>,
 }
 
 impl<T> Default for JsonRpcMessageCodec<T> {
@@ -86,6 +321,7 @@ impl<T> JsonRpcMessageCodec<T> {
             next_index: 0,
             max_length: usize::MAX,
             is_discarding: false,
+            tap: None,
         }
     }
 
@@ -99,47 +335,69 @@ impl<T> JsonRpcMessageCodec<T> {
     pub fn max_length(&self) -> usize {
         self.max_length
     }
-}
 
-fn without_carriage_return(s: &[u8]) -> &[u8] {
-    if let Some(&b'\r') = s.last() {
-        &s[..s.len() - 1]
-    } else {
-        s
+    /// Give the bytes of the decoded lines and of the encoded messages to the tap, see [`WireTap`]
+    pub fn with_tap(mut self, tap: WireTap) -> Self {
+        self.tap = Some(tap);
+        self
     }
-}
 
-#[derive(Debug, Error)]
-pub enum JsonRpcMessageCodecError {
-    #[error("max line length exceeded")]
-    MaxLineLengthExceeded,
-    #[error("serde error {0}")]
-    Serde(#[from] serde_json::Error),
-    #[error("io error {0}")]
-    Io(#[from] std::io::Error),
-}
+    /// The next line, given to the tap
+    fn next_tapped_line(
+        &mut self,
+        buf: &mut BytesMut,
+        eof: bool,
+    ) -> Result<Option<Bytes>, JsonRpcMessageCodecError> {
+        let line = if eof {
+            self.last_line(buf)?
+        } else {
+            self.next_line(buf)?
+        };
+        let line = line.map(BytesMut::freeze);
+        if let (Some(tap), Some(line)) = (&self.tap, &line) {
+            tap.record(WireDirection::Incoming, line);
+        }
+        Ok(line)
+    }
 
-impl From<JsonRpcMessageCodecError> for std::io::Error {
-    fn from(value: JsonRpcMessageCodecError) -> Self {
-        match value {
-            JsonRpcMessageCodecError::MaxLineLengthExceeded => {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, value)
+    /// The next frame which decodes.
+    ///
+    /// A `FramedRead` ends at the first error, so an invalid frame, e.g. with bytes which aren't UTF-8,
+    /// or a line over the max length is logged and skipped instead, and the blank lines are ignored.
+    fn decode_next<U>(
+        &mut self,
+        buf: &mut BytesMut,
+        eof: bool,
+        decode: impl Fn(Bytes) -> Result<U, JsonRpcMessageCodecError>,
+    ) -> Result<Option<U>, JsonRpcMessageCodecError> {
+        loop {
+            let line = match self.next_tapped_line(buf, eof) {
+                Ok(Some(line)) => line,
+                Ok(None) => return Ok(None),
+                Err(JsonRpcMessageCodecError::MaxLineLengthExceeded) => {
+                    tracing::warn!(
+                        max_length = self.max_length,
+                        "skipping a line over the max length"
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            match decode(line) {
+                Ok(item) => return Ok(Some(item)),
+                Err(e) => tracing::warn!("skipping an invalid frame: {}", e),
             }
-            JsonRpcMessageCodecError::Serde(e) => e.into(),
-            JsonRpcMessageCodecError::Io(e) => e,
         }
     }
-}
-
-impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
-    type Item = T;
-
-    type Error = JsonRpcMessageCodecError;
 
-    fn decode(
+    /// The next line of the buffer, without its line ending
+    fn next_line(
         &mut self,
         buf: &mut BytesMut,
-    ) -> Result<Option<Self::Item>, JsonRpcMessageCodecError> {
+    ) -> Result<Option<BytesMut>, JsonRpcMessageCodecError> {
         loop {
             // Determine how far into the buffer we'll search for a newline. If
             // there's no max_length set, we'll read to the end of the buffer.
@@ -172,12 +430,10 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     // Found a line!
                     let newline_index = offset + self.next_index;
                     self.next_index = 0;
-                    let line = buf.split_to(newline_index + 1);
-                    let line = &line[..line.len() - 1];
-                    let line = without_carriage_return(line);
-                    let item =
-                        serde_json::from_slice(line).map_err(JsonRpcMessageCodecError::Serde)?;
-                    return Ok(Some(item));
+                    let mut line = buf.split_to(newline_index + 1);
+                    line.truncate(newline_index);
+                    without_carriage_return(&mut line);
+                    return Ok(Some(line));
                 }
                 (false, None) if buf.len() > self.max_length => {
                     // Reached the maximum length without finding a
@@ -196,31 +452,128 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
         }
     }
 
-    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<T>, JsonRpcMessageCodecError> {
-        Ok(match self.decode(buf)? {
-            Some(frame) => Some(frame),
+    /// The next line, or the remaining data without a terminating newline
+    fn last_line(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<BytesMut>, JsonRpcMessageCodecError> {
+        Ok(match self.next_line(buf)? {
+            Some(line) => Some(line),
             None => {
                 self.next_index = 0;
                 // No terminating newline - return remaining data, if any
                 if buf.is_empty() || buf == &b"\r"[..] {
                     None
                 } else {
-                    let line = buf.split_to(buf.len());
-                    let line = without_carriage_return(&line);
-                    let item =
-                        serde_json::from_slice(line).map_err(JsonRpcMessageCodecError::Serde)?;
-                    Some(item)
+                    let mut line = buf.split_to(buf.len());
+                    without_carriage_return(&mut line);
+                    Some(line)
                 }
             }
         })
     }
 }
 
+fn without_carriage_return(line: &mut BytesMut) {
+    if let Some(&b'\r') = line.last() {
+        line.truncate(line.len() - 1);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JsonRpcMessageCodecError {
+    #[error("max line length exceeded")]
+    MaxLineLengthExceeded,
+    #[error("serde error {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+    #[error("raw message error {0}")]
+    RawMessage(#[from] RawMessageError),
+}
+
+impl From<JsonRpcMessageCodecError> for std::io::Error {
+    fn from(value: JsonRpcMessageCodecError) -> Self {
+        match value {
+            JsonRpcMessageCodecError::MaxLineLengthExceeded => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, value)
+            }
+            JsonRpcMessageCodecError::Serde(e) => e.into(),
+            JsonRpcMessageCodecError::Io(e) => e,
+            JsonRpcMessageCodecError::RawMessage(e) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
+    type Item = T;
+
+    type Error = JsonRpcMessageCodecError;
+
+    fn decode(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<Self::Item>, JsonRpcMessageCodecError> {
+        self.decode_next(buf, false, |line| Ok(serde_json::from_slice(&line)?))
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<T>, JsonRpcMessageCodecError> {
+        self.decode_next(buf, true, |line| Ok(serde_json::from_slice(&line)?))
+    }
+}
+
+impl Decoder for JsonRpcMessageCodec<RawMessage> {
+    type Item = RawMessage;
+
+    type Error = JsonRpcMessageCodecError;
+
+    fn decode(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<RawMessage>, JsonRpcMessageCodecError> {
+        self.decode_next(buf, false, |line| Ok(RawMessage::new(line)?))
+    }
+
+    fn decode_eof(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<RawMessage>, JsonRpcMessageCodecError> {
+        self.decode_next(buf, true, |line| Ok(RawMessage::new(line)?))
+    }
+}
+
 impl<T: Serialize> Encoder<T> for JsonRpcMessageCodec<T> {
     type Error = JsonRpcMessageCodecError;
 
     fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
-        serde_json::to_writer(buf.writer(), &item)?;
+        match &self.tap {
+            Some(tap) => {
+                let bytes = Bytes::from(serde_json::to_vec(&item)?);
+                tap.record(WireDirection::Outgoing, &bytes);
+                buf.put_slice(&bytes);
+            }
+            None => serde_json::to_writer(buf.writer(), &item)?,
+        }
+        buf.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+impl<T> Encoder<RawMessage> for JsonRpcMessageCodec<T> {
+    type Error = JsonRpcMessageCodecError;
+
+    fn encode(
+        &mut self,
+        item: RawMessage,
+        buf: &mut BytesMut,
+    ) -> Result<(), JsonRpcMessageCodecError> {
+        if let Some(tap) = &self.tap {
+            tap.record(WireDirection::Outgoing, &item.bytes);
+        }
+        buf.reserve(item.bytes.len() + 1);
+        buf.put_slice(&item.bytes);
         buf.put_u8(b'\n');
         Ok(())
     }
@@ -264,6 +617,32 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_decode_skips_invalid_frames() {
+        use futures::StreamExt;
+
+        let mut data = b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\n".to_vec();
+        // not UTF-8, neither outside nor inside a string
+        data.extend_from_slice(b"\xff\xfe\x00garbage\n");
+        data.extend_from_slice(b"{\"jsonrpc\":\"2.0\",\"method\":\"\xc3\x28\",\"id\":2}\n");
+        data.extend_from_slice(b"\n{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":3}\n");
+        data.extend_from_slice(&[b'x'; 64]);
+        data.extend_from_slice(b"\n{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":4}");
+
+        let codec = JsonRpcMessageCodec::<serde_json::Value>::new_with_max_length(60);
+        let ids = FramedRead::new(data.as_slice(), codec)
+            .map(|message| message.expect("no error")["id"].clone())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(ids, [1, 3, 4]);
+
+        let codec = JsonRpcMessageCodec::<RawMessage>::new_with_max_length(60);
+        let messages = FramedRead::new(data.as_slice(), codec)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(messages.len(), 3);
+    }
+
     #[tokio::test]
     async fn test_encode() {
         let test_messages = vec![
@@ -304,4 +683,120 @@ mod test {
         // Make sure there are no extra lines
         assert!(lines.next().is_none());
     }
+
+    #[test]
+    fn test_raw_message_validation() {
+        assert!(RawMessage::new(r#"{"jsonrpc":"2.0","method":"ping","id":1}"#).is_ok());
+        assert!(matches!(
+            RawMessage::new("{\"jsonrpc\":\n\"2.0\"}"),
+            Err(RawMessageError::LineBreak)
+        ));
+        assert!(matches!(
+            RawMessage::new("[1, 2]"),
+            Err(RawMessageError::NotAnObject)
+        ));
+        assert!(matches!(
+            RawMessage::new(""),
+            Err(RawMessageError::NotAnObject)
+        ));
+        assert!(matches!(
+            RawMessage::new(r#"{"jsonrpc":"2.0""#),
+            Err(RawMessageError::Json(_))
+        ));
+        assert!(matches!(
+            RawMessage::new(r#"{"id":1} {"id":2}"#),
+            Err(RawMessageError::Json(_))
+        ));
+        assert!(matches!(
+            RawMessage::new(&b"{\"method\":\"\xc3\x28\"}"[..]),
+            Err(RawMessageError::Utf8(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_raw_relay() {
+        // spacing and key order are kept as they are
+        let data = "{ \"id\": 1, \"jsonrpc\": \"2.0\", \"result\": {} }\r\n{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}";
+        let messages = raw_from_async_read(data.as_bytes())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(messages.len(), 2);
+        let notification: crate::model::ClientJsonRpcMessage = messages[1].parse().unwrap();
+        assert!(matches!(
+            notification,
+            crate::model::JsonRpcMessage::Notification(_)
+        ));
+
+        let mut buffer = Vec::new();
+        let mut writer = FramedWrite::new(
+            &mut buffer,
+            JsonRpcMessageCodec::<serde_json::Value>::default(),
+        );
+        for message in messages {
+            writer.send(message).await.unwrap();
+        }
+        writer
+            .send(serde_json::json!({ "jsonrpc": "2.0", "method": "ping", "id": 2 }))
+            .await
+            .unwrap();
+        drop(writer);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{ \"id\": 1, \"jsonrpc\": \"2.0\", \"result\": {} }\n{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n{\"id\":2,\"jsonrpc\":\"2.0\",\"method\":\"ping\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wire_tap() {
+        use std::sync::{Arc, Mutex};
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let tap = {
+            let records = records.clone();
+            WireTap::new(move |direction, bytes| {
+                records.lock().unwrap().push((direction, bytes.clone()))
+            })
+        };
+
+        // the bytes are tapped as they are, even when they don't parse
+        let data = "{ \"method\": \"ping\", \"jsonrpc\": \"2.0\", \"id\": 1 }\r\nnot json\n";
+        let messages = from_async_read_with_tap::<crate::model::ClientJsonRpcMessage, _>(
+            data.as_bytes(),
+            tap.clone(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(messages.len(), 1);
+
+        let mut buffer = Vec::new();
+        let mut writer = from_async_write_with_tap(&mut buffer, tap);
+        writer
+            .send(serde_json::json!({ "jsonrpc": "2.0", "result": {}, "id": 1 }))
+            .await
+            .unwrap();
+        drop(writer);
+
+        let records = records.lock().unwrap();
+        assert_eq!(
+            records
+                .iter()
+                .map(|(direction, bytes)| (*direction, std::str::from_utf8(bytes).unwrap()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    WireDirection::Incoming,
+                    "{ \"method\": \"ping\", \"jsonrpc\": \"2.0\", \"id\": 1 }"
+                ),
+                (WireDirection::Incoming, "not json"),
+                (
+                    WireDirection::Outgoing,
+                    "{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":{}}"
+                ),
+            ]
+        );
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":{}}\n"
+        );
+    }
 }
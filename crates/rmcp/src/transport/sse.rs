@@ -1,16 +1,25 @@
-use crate::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
-use eventsource_client::{
-    BoxStream, Client as EventSourceClient, ClientBuilder, Error as SseError, SSE,
+use super::{WireDirection, WireTap};
+use crate::backoff::ExponentialBackoff;
+use crate::model::{
+    ClientJsonRpcMessage, Implementation, JsonRpcMessage, NumberOrString, ServerJsonRpcMessage,
+};
+use futures::{FutureExt, Sink, Stream, StreamExt, stream::BoxStream};
+pub use reqwest::Certificate;
+use reqwest::{
+    Client as HttpClient, IntoUrl, Url,
+    header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue, InvalidHeaderValue, USER_AGENT},
 };
-use futures::{FutureExt, Sink, Stream, StreamExt};
-use reqwest::{Client as HttpClient, IntoUrl, Url, header::HeaderMap};
 use std::{collections::VecDeque, sync::Arc, time::Duration};
 use thiserror::Error;
 
+mod event;
+mod session;
+
+use event::{SseEvent, sse_events};
+pub use session::{SseSession, SseSessionTracker};
+
 #[derive(Error, Debug)]
 pub enum SseTransportError {
-    #[error("SSE error: {0}")]
-    Sse(#[from] SseError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Reqwest error: {0}")]
@@ -19,18 +28,581 @@ pub enum SseTransportError {
     UnexpectedEndOfStream,
     #[error("Url error: {0}")]
     Url(#[from] url::ParseError),
+    #[error("Invalid header value: {0}")]
+    InvalidHeaderValue(#[from] InvalidHeaderValue),
+    #[error("{} request(s) failed while closing: {}", .0.len(), join_errors(.0))]
+    Close(Vec<SseTransportError>),
+    #[error(
+        "the server at {0} speaks streamable HTTP rather than the legacy SSE transport, connect it with a streamable HTTP client"
+    )]
+    StreamableHttp(Url),
+    #[error(
+        "no endpoint event within {0:?}, the server may speak streamable HTTP rather than the legacy SSE transport"
+    )]
+    EndpointTimeout(Duration),
+    #[error(
+        "the session can't be resumed, the server answered {0}: it's closed, or the events since the last one are gone"
+    )]
+    SessionNotResumable(reqwest::StatusCode),
+}
+
+fn join_errors(errors: &[SseTransportError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// How [`SseTransport`] handles the POST requests still in flight when it's closed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SseCloseMode {
+    /// Wait for the requests in order, and fail with the first error, the next ones are not awaited
+    #[default]
+    FailFast,
+    /// Wait for all the requests, even after a failure, and fail with all the errors once they're done,
+    /// so a teardown sends everything it can
+    DrainBestEffort,
+}
+
+/// The HTTP version of the SSE stream and of the POST requests, they share one HTTP client.
+///
+/// The connections are pooled and kept alive, so a burst of messages doesn't pay the TCP and TLS setup for each one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SseHttpVersion {
+    /// HTTP/2 if the server offers it in the TLS handshake, HTTP/1.1 otherwise, e.g. without TLS
+    #[default]
+    Auto,
+    /// HTTP/1.1 only, for the servers mishandling HTTP/2
+    Http1Only,
+    /// HTTP/2 without negotiation, e.g. for a server known to speak HTTP/2 over plain TCP
+    Http2PriorKnowledge,
+}
+
+/// The timeouts of [`SseTransport`], none is set by default.
+///
+/// The SSE stream is long-lived and stays idle while the server has nothing to send, so a read timeout
+/// on it closes a healthy connection, while a POST is answered at once and should fail fast when it isn't:
+///
+/// ```rust,ignore
+/// let transport = SseTransport::builder("http://localhost:8000/sse")
+///     .connect_timeout(Duration::from_secs(5))
+///     .post_timeout(Duration::from_secs(30))
+///     .start()
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SseTimeouts {
+    /// How long to wait for the TCP and TLS connection, of the SSE stream and of the POST requests
+    pub connect_timeout: Option<Duration>,
+    /// How long the SSE stream may stay silent before it's closed, leave it unset unless the server
+    /// sends keep-alive comments more often than that
+    pub sse_read_timeout: Option<Duration>,
+    /// How long a POST request may take, from connecting to reading the response
+    pub post_timeout: Option<Duration>,
+}
+
+/// How a POST request failing transiently is sent again, before its error fails the sink.
+///
+/// Only the failures where the server didn't handle the message are retried, so a request is never handled twice:
+/// the connection errors, and the statuses `429 Too Many Requests`, `502 Bad Gateway`, `503 Service Unavailable`
+/// and `504 Gateway Timeout`. A timeout isn't retried, the server may have handled the message.
+/// The SSE stream is kept open meanwhile.
+#[derive(Debug, Clone)]
+pub struct SsePostRetry {
+    /// How many times a POST is sent again, 2 by default
+    pub retries: usize,
+    /// The delays before the retries, from 200ms up to 2s by default
+    pub backoff: ExponentialBackoff,
+}
+
+impl Default for SsePostRetry {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            backoff: ExponentialBackoff::new(Duration::from_millis(200), Duration::from_secs(2)),
+        }
+    }
+}
+
+impl SsePostRetry {
+    /// Never retry, the first failure fails the sink
+    pub fn none() -> Self {
+        Self {
+            retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: ExponentialBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+/// How the SSE stream is reopened after it breaks or the server closes it, before the transport ends.
+///
+/// The stream is resumed like with [`SseTransportBuilder::resume`]: a GET of the endpoint with the `Last-Event-ID`
+/// header set to the id of the last event received, so the server replays the events the client missed.
+/// A server answering `404 Not Found` or `410 Gone` lost the session, and the transport ends at once.
+#[derive(Debug, Clone)]
+pub struct SseReconnect {
+    /// How many times in a row the stream is reopened, 3 by default, an event received resets the count
+    pub retries: usize,
+    /// The delays before the attempts, from 500ms up to 5s by default
+    pub backoff: ExponentialBackoff,
+}
+
+impl Default for SseReconnect {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: ExponentialBackoff::new(Duration::from_millis(500), Duration::from_secs(5)),
+        }
+    }
 }
+
+impl SseReconnect {
+    /// Never reopen the stream, the transport ends with it
+    pub fn none() -> Self {
+        Self {
+            retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: ExponentialBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+type EventStream = BoxStream<'static, Result<SseEvent, SseTransportError>>;
+
+/// Reopen the stream of a session with a GET of its endpoint, from the event after `last_event_id`
+async fn resume_stream(
+    http_client: HttpClient,
+    endpoint: Url,
+    last_event_id: Option<String>,
+    read_timeout: Option<Duration>,
+) -> Result<EventStream, SseTransportError> {
+    let mut request = http_client
+        .get(endpoint)
+        .header(ACCEPT, "text/event-stream");
+    if let Some(last_event_id) = &last_event_id {
+        request = request.header("Last-Event-ID", last_event_id);
+    }
+    let response = request.send().await?;
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+    ) {
+        return Err(SseTransportError::SessionNotResumable(response.status()));
+    }
+    let response = response.error_for_status()?;
+    Ok(sse_events(response.bytes_stream(), read_timeout))
+}
+
+fn is_transient_post_error(error: &reqwest::Error) -> bool {
+    use reqwest::StatusCode;
+    error.is_connect()
+        || matches!(
+            error.status(),
+            Some(
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            )
+        )
+}
+
+/// Send a POST request, and again after its transient failures
+async fn send_post(
+    request: reqwest::RequestBuilder,
+    mut retry: SsePostRetry,
+) -> Result<(), SseTransportError> {
+    let mut attempt = 0;
+    loop {
+        let result = request
+            .try_clone()
+            .expect("the body of a post is in memory")
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        match result {
+            Err(error) if attempt < retry.retries && is_transient_post_error(&error) => {
+                attempt += 1;
+                let delay = retry.backoff.next().unwrap_or_default();
+                tracing::warn!(%error, attempt, ?delay, "post failed, retrying");
+                tokio::time::sleep(delay).await;
+            }
+            result => return result.map(drop).map_err(SseTransportError::from),
+        }
+    }
+}
+
+/// The TLS settings of [`SseTransport`], applied to both the SSE stream and the POST requests.
+///
+/// By default, the certificate of the server is verified against the built-in roots of trust. To reach a server
+/// whose certificate is issued by an internal CA, add the CA as a root; to pin it, also leave the built-in roots out:
+///
+/// ```rust,ignore
+/// let ca = Certificate::from_pem(&std::fs::read("internal-ca.pem")?)?;
+/// let transport = SseTransport::builder("https://mcp.internal:8443/sse")
+///     .tls(SseTlsConfig::default().with_root_certificate(ca).without_built_in_roots())
+///     .start()
+///     .await?;
+/// ```
+///
+/// A dev server with a self-signed certificate is reached the same way, by adding its certificate as a root,
+/// e.g. the `cert.pem` generated with
+/// `openssl req -x509 -newkey rsa:2048 -nodes -keyout key.pem -out cert.pem -days 365 -subj /CN=localhost -addext subjectAltName=DNS:localhost,IP:127.0.0.1 -addext basicConstraints=critical,CA:TRUE`.
+/// [`SseTlsConfig::danger_accept_invalid_certs`] skips the verification altogether, never use it outside of development.
+///
+/// With the `transport-sse-rustls` feature, [`SseTlsConfig::with_rustls_config`] takes a whole rustls configuration instead,
+/// e.g. with a custom certificate verifier.
+#[derive(Debug, Clone, Default)]
+pub struct SseTlsConfig {
+    root_certificates: Vec<Certificate>,
+    without_built_in_roots: bool,
+    accept_invalid_certs: bool,
+    #[cfg(feature = "transport-sse-rustls")]
+    rustls_config: Option<rustls::ClientConfig>,
+}
+
+impl SseTlsConfig {
+    /// Trust the certificates issued by `certificate`, besides the built-in roots
+    pub fn with_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+    /// Only trust the roots added with [`SseTlsConfig::with_root_certificate`], e.g. to pin the CA of a server
+    pub fn without_built_in_roots(mut self) -> Self {
+        self.without_built_in_roots = true;
+        self
+    }
+    /// Accept any certificate, even expired or for another host, which makes the connection open to interception
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+    /// Use this rustls configuration, the other settings are ignored
+    #[cfg(feature = "transport-sse-rustls")]
+    pub fn with_rustls_config(mut self, config: rustls::ClientConfig) -> Self {
+        self.rustls_config = Some(config);
+        self
+    }
+    fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        #[cfg(feature = "transport-sse-rustls")]
+        if let Some(config) = &self.rustls_config {
+            return builder.use_preconfigured_tls(config.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+            .tls_built_in_root_certs(!self.without_built_in_roots)
+            .danger_accept_invalid_certs(self.accept_invalid_certs)
+    }
+}
+
+/// The client of the SSE stream and of the POST requests, shared by all of them to reuse the connections
+fn build_http_client(
+    headers: HeaderMap,
+    http_version: SseHttpVersion,
+    connect_timeout: Option<Duration>,
+    tls: &SseTlsConfig,
+) -> Result<HttpClient, reqwest::Error> {
+    let mut builder = tls.apply(HttpClient::builder().default_headers(headers));
+    if let Some(connect_timeout) = connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    let builder = match http_version {
+        SseHttpVersion::Auto => builder,
+        SseHttpVersion::Http1Only => builder.http1_only(),
+        SseHttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+    };
+    builder.build()
+}
+
+type PendingPost = tokio::sync::oneshot::Receiver<Result<(), SseTransportError>>;
+
 pub struct SseTransport {
     http_client: HttpClient,
-    event_source: BoxStream<Result<SSE, SseError>>,
+    event_source: EventStream,
+    session: SseSessionTracker,
     post_url: Arc<Url>,
     _sse_url: Arc<Url>,
-    timeout: Option<Duration>,
-    #[allow(clippy::type_complexity)]
-    request_queue: VecDeque<tokio::sync::oneshot::Receiver<Result<(), SseTransportError>>>,
+    post_timeout: Option<Duration>,
+    sse_read_timeout: Option<Duration>,
+    post_retry: SsePostRetry,
+    reconnect: SseReconnect,
+    /// The attempts to reopen the stream since the last event received
+    reconnect_attempt: usize,
+    reconnecting:
+        Option<futures::future::BoxFuture<'static, Result<EventStream, SseTransportError>>>,
+    request_queue: VecDeque<PendingPost>,
+    close_mode: SseCloseMode,
+    close_errors: Vec<SseTransportError>,
+    wire_tap: Option<WireTap>,
+}
+
+/// Resolve the endpoint sent by the server in the first event against the SSE url.
+///
+/// The endpoint is resolved as a relative reference, then the query parameters of the SSE url
+/// which are absent in the endpoint are appended, so a session token in the SSE url is kept:
+///
+/// - `http://host/sse?token=a` + `/message?sessionId=1` = `http://host/message?sessionId=1&token=a`
+/// - `http://host/sse?token=a` + `/message?token=b` = `http://host/message?token=b`, the endpoint wins
+///
+/// The query is only merged when the endpoint has the same origin as the SSE url, so the parameters
+/// never leak to another server. The fragment of the endpoint is kept, and the one of the SSE url is dropped.
+pub fn join_endpoint(sse_url: &Url, endpoint: &str) -> Result<Url, url::ParseError> {
+    let mut url = sse_url.join(endpoint)?;
+    if url.origin() != sse_url.origin() {
+        return Ok(url);
+    }
+    let missing = sse_url
+        .query_pairs()
+        .filter(|(key, _)| !url.query_pairs().any(|(existing, _)| existing == *key))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        url.query_pairs_mut().extend_pairs(missing);
+    }
+    Ok(url)
+}
+
+/// If the first event of the stream carries the endpoint of the legacy SSE transport.
+///
+/// The endpoint event is named `endpoint`, but some servers send it as a plain message, so any event
+/// is taken for the endpoint unless its data is JSON, which is a message of a streamable HTTP server.
+fn is_endpoint_event(event_type: &str, data: &str) -> bool {
+    event_type == "endpoint" || !data.trim_start().starts_with(['{', '['])
+}
+
+/// If the response to a GET of the SSE url comes from a streamable HTTP server.
+///
+/// A streamable HTTP server takes the messages as POST on the same url, so it either refuses the GET
+/// with `405 Method Not Allowed`, or answers it with a JSON-RPC error, e.g. for the missing session id.
+fn is_streamable_http_response(status: reqwest::StatusCode, content_type: Option<&str>) -> bool {
+    let is_json = content_type.is_some_and(|content_type| {
+        content_type
+            .split(';')
+            .next()
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+    });
+    status == reqwest::StatusCode::METHOD_NOT_ALLOWED
+        || (is_json && (status.is_success() || status.is_client_error()))
+}
+
+/// The default `User-Agent` of the SSE transport, `{name}/{version}` of the client implementation
+pub fn default_user_agent(client_info: &Implementation) -> String {
+    format!("{}/{}", client_info.name, client_info.version)
+}
+
+/// Build a [`SseTransport`] with custom headers, timeouts and `User-Agent`.
+///
+/// The `User-Agent` is sent with both the SSE stream request and the POST requests. It's, in order of priority:
+/// the one set by [`SseTransportBuilder::user_agent`], the one in the headers,
+/// and the [`default_user_agent`] of the client info, which is `rmcp/{version}` if not set.
+///
+/// A streamable HTTP server is detected while waiting for the endpoint event, the transport fails with
+/// [`SseTransportError::StreamableHttp`] rather than waiting for an event that never comes.
+#[derive(Debug, Clone)]
+pub struct SseTransportBuilder {
+    url: String,
+    headers: HeaderMap,
+    timeouts: SseTimeouts,
+    endpoint_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    client_info: Implementation,
+    close_mode: SseCloseMode,
+    http_version: SseHttpVersion,
+    wire_tap: Option<WireTap>,
+    post_retry: SsePostRetry,
+    reconnect: SseReconnect,
+    tls: SseTlsConfig,
+    resume: Option<SseSession>,
+}
+
+impl SseTransportBuilder {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: HeaderMap::new(),
+            timeouts: SseTimeouts::default(),
+            endpoint_timeout: Some(SseTransport::DEFAULT_ENDPOINT_TIMEOUT),
+            user_agent: None,
+            client_info: Implementation::from_build_env(),
+            close_mode: SseCloseMode::default(),
+            http_version: SseHttpVersion::default(),
+            wire_tap: None,
+            post_retry: SsePostRetry::default(),
+            reconnect: SseReconnect::default(),
+            tls: SseTlsConfig::default(),
+            resume: None,
+        }
+    }
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+    /// The timeout of the POST requests, the same as [`SseTransportBuilder::post_timeout`].
+    ///
+    /// The SSE stream has no read timeout unless [`SseTransportBuilder::sse_read_timeout`] is set.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.post_timeout(timeout)
+    }
+    /// Set all the timeouts at once
+    pub fn timeouts(mut self, timeouts: SseTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+    /// How long to wait for the connection of the SSE stream and of the POST requests
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.timeouts.connect_timeout = Some(connect_timeout);
+        self
+    }
+    /// Close the SSE stream when it stays silent that long, see [`SseTimeouts::sse_read_timeout`]
+    pub fn sse_read_timeout(mut self, sse_read_timeout: Duration) -> Self {
+        self.timeouts.sse_read_timeout = Some(sse_read_timeout);
+        self
+    }
+    /// How long a POST request may take
+    pub fn post_timeout(mut self, post_timeout: Duration) -> Self {
+        self.timeouts.post_timeout = Some(post_timeout);
+        self
+    }
+    /// How long to wait for the endpoint event, [`SseTransport::DEFAULT_ENDPOINT_TIMEOUT`] by default
+    pub fn endpoint_timeout(mut self, endpoint_timeout: Duration) -> Self {
+        self.endpoint_timeout = Some(endpoint_timeout);
+        self
+    }
+    /// Wait for the endpoint event forever
+    pub fn no_endpoint_timeout(mut self) -> Self {
+        self.endpoint_timeout = None;
+        self
+    }
+    /// Override the `User-Agent` header
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+    /// The client implementation the default `User-Agent` is made of
+    pub fn client_info(mut self, client_info: Implementation) -> Self {
+        self.client_info = client_info;
+        self
+    }
+    /// How the POST requests in flight are handled when the transport is closed
+    pub fn close_mode(mut self, close_mode: SseCloseMode) -> Self {
+        self.close_mode = close_mode;
+        self
+    }
+    /// The HTTP version of the SSE stream and of the POST requests, e.g. [`SseHttpVersion::Http1Only`] for a server mishandling HTTP/2
+    pub fn http_version(mut self, http_version: SseHttpVersion) -> Self {
+        self.http_version = http_version;
+        self
+    }
+    /// Give the data of the received events and the bodies of the POST requests to the tap
+    pub fn wire_tap(mut self, tap: WireTap) -> Self {
+        self.wire_tap = Some(tap);
+        self
+    }
+    /// How the POST requests failing transiently are retried, see [`SsePostRetry`]
+    pub fn post_retry(mut self, post_retry: SsePostRetry) -> Self {
+        self.post_retry = post_retry;
+        self
+    }
+    /// How the SSE stream is reopened after it breaks, see [`SseReconnect`]
+    pub fn reconnect(mut self, reconnect: SseReconnect) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+    /// The TLS settings of the SSE stream and of the POST requests, see [`SseTlsConfig`]
+    pub fn tls(mut self, tls: SseTlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+    /// The headers sent with every request, including the `User-Agent`
+    pub fn build_headers(&self) -> Result<HeaderMap, SseTransportError> {
+        let mut headers = self.headers.clone();
+        if let Some(user_agent) = &self.user_agent {
+            headers.insert(USER_AGENT, HeaderValue::from_str(user_agent)?);
+        } else if !headers.contains_key(USER_AGENT) {
+            let user_agent = default_user_agent(&self.client_info);
+            headers.insert(USER_AGENT, HeaderValue::from_str(&user_agent)?);
+        }
+        Ok(headers)
+    }
+    /// Re-attach to a saved session rather than opening a new one, see [`SseSession`].
+    ///
+    /// The stream is reopened with a GET of the endpoint of the session, with the `Last-Event-ID` header,
+    /// so the server sends the events after the saved one, without an endpoint event. It fails with
+    /// [`SseTransportError::SessionNotResumable`] if the server answers `404 Not Found` or `410 Gone`.
+    pub fn resume(mut self, session: &SseSession) -> Self {
+        self.resume = Some(session.clone());
+        self
+    }
+    pub async fn start(self) -> Result<SseTransport, SseTransportError> {
+        let headers = self.build_headers()?;
+        let transport = SseTransport::connect(
+            self.url.as_str(),
+            headers,
+            self.timeouts,
+            self.endpoint_timeout,
+            self.http_version,
+            &self.tls,
+            self.resume.as_ref(),
+        )
+        .await?;
+        let transport = transport
+            .with_close_mode(self.close_mode)
+            .with_post_retry(self.post_retry)
+            .with_reconnect(self.reconnect);
+        Ok(match self.wire_tap {
+            Some(tap) => transport.with_wire_tap(tap),
+            None => transport,
+        })
+    }
 }
 
 impl SseTransport {
+    pub const DEFAULT_ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+    pub fn builder(url: impl Into<String>) -> SseTransportBuilder {
+        SseTransportBuilder::new(url)
+    }
+    /// How the POST requests in flight are handled when the transport is closed
+    pub fn with_close_mode(mut self, close_mode: SseCloseMode) -> Self {
+        self.close_mode = close_mode;
+        self
+    }
+    /// Give the data of the received events and the bodies of the POST requests to the tap, see [`WireTap`]
+    pub fn with_wire_tap(mut self, tap: WireTap) -> Self {
+        self.wire_tap = Some(tap);
+        self
+    }
+    /// How the POST requests failing transiently are retried, see [`SsePostRetry`]
+    pub fn with_post_retry(mut self, post_retry: SsePostRetry) -> Self {
+        self.post_retry = post_retry;
+        self
+    }
+    /// How the SSE stream is reopened after it breaks, see [`SseReconnect`]
+    pub fn with_reconnect(mut self, reconnect: SseReconnect) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+    /// Start with a timeout of the POST requests, see [`SseTransportBuilder::timeout`]
     pub async fn start_with_timeout<U>(
         url: U,
         headers: HeaderMap,
@@ -40,37 +612,116 @@ impl SseTransport {
         U: IntoUrl,
     {
         let url = url.into_url()?;
-        let mut sse_client_builder = ClientBuilder::for_url(url.as_str())?;
-        for (name, value) in &headers {
-            if let Ok(value) = std::str::from_utf8(value.as_bytes()) {
-                sse_client_builder = sse_client_builder.header(name.as_str(), value)?;
-            }
-        }
+        let mut builder = SseTransportBuilder::new(url.as_str()).headers(headers);
         if let Some(timeout) = timeout {
-            sse_client_builder = sse_client_builder.read_timeout(timeout);
+            builder = builder.timeout(timeout);
+        }
+        builder.start().await
+    }
+    async fn connect(
+        url: &str,
+        headers: HeaderMap,
+        timeouts: SseTimeouts,
+        endpoint_timeout: Option<Duration>,
+        http_version: SseHttpVersion,
+        tls: &SseTlsConfig,
+        resume: Option<&SseSession>,
+    ) -> Result<Self, SseTransportError> {
+        let url = Url::parse(url)?;
+        let http_client = build_http_client(headers, http_version, timeouts.connect_timeout, tls)?;
+        if let Some(session) = resume {
+            return Self::reconnect(http_client, url, timeouts, endpoint_timeout, session).await;
         }
-        let client = sse_client_builder.build();
-        let mut event_stream = client.stream();
-        let first_event = loop {
-            let next_event = event_stream
+        let endpoint = async {
+            let response = http_client
+                .get(url.clone())
+                .header(ACCEPT, "text/event-stream")
+                .send()
+                .await?;
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok());
+            if is_streamable_http_response(response.status(), content_type) {
+                return Err(SseTransportError::StreamableHttp(url.clone()));
+            }
+            let response = response.error_for_status()?;
+            let mut event_stream = sse_events(response.bytes_stream(), timeouts.sse_read_timeout);
+            let event = event_stream
                 .next()
                 .await
                 .ok_or(SseTransportError::UnexpectedEndOfStream)??;
-            match next_event {
-                SSE::Event(event) => {
-                    break event;
-                }
-                _ => continue,
+            if !is_endpoint_event(&event.event_type, &event.data) {
+                return Err(SseTransportError::StreamableHttp(url.clone()));
             }
+            Ok((event.data, event_stream))
         };
-        let post_uri = url.join(&first_event.data)?;
+        let (endpoint, event_stream) = match endpoint_timeout {
+            Some(endpoint_timeout) => tokio::time::timeout(endpoint_timeout, endpoint)
+                .await
+                .map_err(|_elapsed| SseTransportError::EndpointTimeout(endpoint_timeout))??,
+            None => endpoint.await?,
+        };
+        let post_uri = Arc::new(join_endpoint(&url, &endpoint)?);
         Ok(SseTransport {
-            http_client: HttpClient::builder().default_headers(headers).build()?,
+            http_client,
             event_source: event_stream,
-            post_url: Arc::from(post_uri),
+            session: SseSessionTracker::new(post_uri.clone(), None, 0),
+            post_url: post_uri,
             _sse_url: Arc::from(url),
-            timeout,
+            post_timeout: timeouts.post_timeout,
+            sse_read_timeout: timeouts.sse_read_timeout,
+            post_retry: SsePostRetry::default(),
+            reconnect: SseReconnect::default(),
+            reconnect_attempt: 0,
+            reconnecting: None,
             request_queue: Default::default(),
+            close_mode: SseCloseMode::default(),
+            close_errors: Vec::new(),
+            wire_tap: None,
+        })
+    }
+    /// Reopen the stream of a saved session, see [`SseTransportBuilder::resume`]
+    async fn reconnect(
+        http_client: HttpClient,
+        url: Url,
+        timeouts: SseTimeouts,
+        response_timeout: Option<Duration>,
+        session: &SseSession,
+    ) -> Result<Self, SseTransportError> {
+        let post_uri = Arc::new(Url::parse(&session.endpoint)?);
+        let event_stream = resume_stream(
+            http_client.clone(),
+            post_uri.as_ref().clone(),
+            session.last_event_id.clone(),
+            timeouts.sse_read_timeout,
+        );
+        let event_stream = match response_timeout {
+            Some(response_timeout) => tokio::time::timeout(response_timeout, event_stream)
+                .await
+                .map_err(|_elapsed| SseTransportError::EndpointTimeout(response_timeout))??,
+            None => event_stream.await?,
+        };
+        Ok(SseTransport {
+            http_client,
+            event_source: event_stream,
+            session: SseSessionTracker::new(
+                post_uri.clone(),
+                session.last_event_id.clone(),
+                session.next_request_id,
+            ),
+            post_url: post_uri,
+            _sse_url: Arc::from(url),
+            post_timeout: timeouts.post_timeout,
+            sse_read_timeout: timeouts.sse_read_timeout,
+            post_retry: SsePostRetry::default(),
+            reconnect: SseReconnect::default(),
+            reconnect_attempt: 0,
+            reconnecting: None,
+            request_queue: Default::default(),
+            close_mode: SseCloseMode::default(),
+            close_errors: Vec::new(),
+            wire_tap: None,
         })
     }
     pub async fn start<U>(url: U, headers: HeaderMap) -> Result<Self, SseTransportError>
@@ -81,29 +732,102 @@ impl SseTransport {
     }
 }
 
+impl SseTransport {
+    /// Follow the session to save it, see [`SseSession`]
+    pub fn session_tracker(&self) -> SseSessionTracker {
+        self.session.clone()
+    }
+    fn parse_event(&self, data: String) -> Result<ServerJsonRpcMessage, serde_json::Error> {
+        let Some(tap) = &self.wire_tap else {
+            return serde_json::from_str(&data);
+        };
+        let bytes = data.into();
+        tap.record(WireDirection::Incoming, &bytes);
+        serde_json::from_slice(&bytes)
+    }
+    /// Start reopening the stream, false once the attempts are exhausted
+    fn start_reconnect(&mut self) -> bool {
+        if self.reconnect_attempt >= self.reconnect.retries {
+            return false;
+        }
+        self.reconnect_attempt += 1;
+        let delay = self.reconnect.backoff.next().unwrap_or_default();
+        let last_event_id = self.session.last_event_id();
+        tracing::warn!(
+            attempt = self.reconnect_attempt,
+            ?delay,
+            ?last_event_id,
+            "reopening the sse stream"
+        );
+        let reopen = resume_stream(
+            self.http_client.clone(),
+            self.post_url.as_ref().clone(),
+            last_event_id,
+            self.sse_read_timeout,
+        );
+        self.reconnecting = Some(Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            reopen.await
+        }));
+        true
+    }
+}
+
 impl Stream for SseTransport {
     type Item = ServerJsonRpcMessage;
 
+    /// A broken stream is reopened as set by [`SseReconnect`], the transport ends once it can't be
     fn poll_next(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        let event = std::task::ready!(self.event_source.poll_next_unpin(cx));
-        match event {
-            Some(Ok(SSE::Event(event))) => match serde_json::from_str(&event.data) {
-                Ok(message) => std::task::Poll::Ready(Some(message)),
-                Err(e) => {
-                    tracing::error!(error = %e, "failed to parse json rpc request");
-                    self.poll_next(cx)
+        loop {
+            if let Some(reconnecting) = self.reconnecting.as_mut() {
+                let result = std::task::ready!(reconnecting.poll_unpin(cx));
+                self.reconnecting = None;
+                match result {
+                    Ok(event_source) => self.event_source = event_source,
+                    Err(e @ SseTransportError::SessionNotResumable(_)) => {
+                        tracing::error!(error = %e, "sse stream not resumable");
+                        return std::task::Poll::Ready(None);
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to reopen the sse stream");
+                        if !self.start_reconnect() {
+                            return std::task::Poll::Ready(None);
+                        }
+                    }
+                }
+                continue;
+            }
+            match std::task::ready!(self.event_source.poll_next_unpin(cx)) {
+                Some(Ok(event)) => {
+                    if self.reconnect_attempt > 0 {
+                        self.reconnect_attempt = 0;
+                        self.reconnect.backoff.reset();
+                    }
+                    if let Some(id) = &event.id {
+                        self.session.event_received(id);
+                    }
+                    match self.parse_event(event.data) {
+                        Ok(message) => return std::task::Poll::Ready(Some(message)),
+                        Err(e) => {
+                            tracing::error!(error = %e, "failed to parse json rpc request");
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    tracing::warn!(error = %e, "sse event stream encounter an error");
+                    if !self.start_reconnect() {
+                        return std::task::Poll::Ready(None);
+                    }
+                }
+                None => {
+                    if !self.start_reconnect() {
+                        return std::task::Poll::Ready(None);
+                    }
                 }
-            },
-            Some(Ok(SSE::Comment(_))) => self.poll_next(cx),
-            Some(Ok(SSE::Connected(_))) => self.poll_next(cx),
-            Some(Err(e)) => {
-                tracing::error!(error = %e, "sse event stream encounter an error");
-                std::task::Poll::Ready(None)
             }
-            None => std::task::Poll::Ready(None),
         }
     }
 }
@@ -117,35 +841,54 @@ impl Sink<ClientJsonRpcMessage> for SseTransport {
     ) -> std::task::Poll<Result<(), Self::Error>> {
         const QUEUE_SIZE: usize = 16;
         if self.request_queue.len() >= QUEUE_SIZE {
-            std::task::ready!(
+            let result = std::task::ready!(
                 self.request_queue
                     .front_mut()
                     .expect("queue is not empty")
                     .poll_unpin(cx)
             )
-            .expect("sender shall not drop")?;
+            .expect("sender shall not drop");
+            // a completed post is never polled again, even if it failed
+            self.request_queue.pop_front();
+            result?;
         }
         std::task::Poll::Ready(Ok(()))
     }
 
+    /// The message is posted in the background, flushing waits for the posts to complete,
+    /// and fails with the error of the first failed one.
     fn start_send(
         mut self: std::pin::Pin<&mut Self>,
         item: ClientJsonRpcMessage,
     ) -> Result<(), Self::Error> {
+        if let JsonRpcMessage::Request(request) = &item
+            && let NumberOrString::Number(id) = request.id
+            && let Ok(id) = u32::try_from(id)
+        {
+            self.session.request_posted(id);
+        }
         let client = self.http_client.clone();
         let uri = self.post_url.clone();
         let (tx, rx) = tokio::sync::oneshot::channel();
-        let mut request_builder = client.post(uri.as_ref().clone()).json(&item);
-        if let Some(timeout) = self.timeout.as_ref() {
-            request_builder = request_builder.timeout(*timeout);
+        let mut request_builder = client.post(uri.as_ref().clone());
+        request_builder = match &self.wire_tap {
+            Some(tap) => {
+                let body = serde_json::to_vec(&item)
+                    .map_err(std::io::Error::from)?
+                    .into();
+                tap.record(WireDirection::Outgoing, &body);
+                request_builder
+                    .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+                    .body(body)
+            }
+            None => request_builder.json(&item),
+        };
+        if let Some(post_timeout) = self.post_timeout {
+            request_builder = request_builder.timeout(post_timeout);
         }
+        let post_retry = self.post_retry.clone();
         tokio::spawn(async move {
-            let result = request_builder
-                .send()
-                .await
-                .and_then(|resp| resp.error_for_status())
-                .map_err(SseTransportError::from)
-                .map(drop);
+            let result = send_post(request_builder, post_retry).await;
             let _ = tx.send(result);
         });
         self.as_mut().request_queue.push_back(rx);
@@ -158,8 +901,9 @@ impl Sink<ClientJsonRpcMessage> for SseTransport {
     ) -> std::task::Poll<Result<(), Self::Error>> {
         let queue = &mut self.as_mut().request_queue;
         while let Some(fut) = queue.front_mut() {
-            std::task::ready!(fut.poll_unpin(cx)).expect("sender shall not drop")?;
+            let result = std::task::ready!(fut.poll_unpin(cx)).expect("sender shall not drop");
             queue.pop_front();
+            result?;
         }
         std::task::Poll::Ready(Ok(()))
     }
@@ -168,6 +912,640 @@ impl Sink<ClientJsonRpcMessage> for SseTransport {
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Result<(), Self::Error>> {
-        self.poll_flush(cx)
+        match self.close_mode {
+            SseCloseMode::FailFast => self.poll_flush(cx),
+            SseCloseMode::DrainBestEffort => {
+                let this = self.get_mut();
+                poll_drain(&mut this.request_queue, &mut this.close_errors, cx)
+            }
+        }
+    }
+}
+
+/// Poll all the pending posts at once, collecting their errors until none is left
+fn poll_drain(
+    queue: &mut VecDeque<PendingPost>,
+    errors: &mut Vec<SseTransportError>,
+    cx: &mut std::task::Context<'_>,
+) -> std::task::Poll<Result<(), SseTransportError>> {
+    queue.retain_mut(|post| match post.poll_unpin(cx) {
+        std::task::Poll::Ready(result) => {
+            if let Err(error) = result.expect("sender shall not drop") {
+                errors.push(error);
+            }
+            false
+        }
+        std::task::Poll::Pending => true,
+    });
+    if !queue.is_empty() {
+        return std::task::Poll::Pending;
+    }
+    if errors.is_empty() {
+        std::task::Poll::Ready(Ok(()))
+    } else {
+        std::task::Poll::Ready(Err(SseTransportError::Close(std::mem::take(errors))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(sse_url: &str, endpoint: &str) -> String {
+        let sse_url = Url::parse(sse_url).expect("valid url");
+        join_endpoint(&sse_url, endpoint)
+            .expect("valid endpoint")
+            .to_string()
+    }
+
+    #[test]
+    fn test_join_endpoint() {
+        assert_eq!(
+            join("http://localhost/sse", "/message?sessionId=1"),
+            "http://localhost/message?sessionId=1"
+        );
+        assert_eq!(
+            join("http://localhost/sse?token=a", "/message?sessionId=1"),
+            "http://localhost/message?sessionId=1&token=a"
+        );
+        assert_eq!(
+            join("http://localhost/mcp/sse?token=a", "message"),
+            "http://localhost/mcp/message?token=a"
+        );
+        assert_eq!(
+            join(
+                "http://localhost/sse?token=a&sessionId=0",
+                "/message?sessionId=1"
+            ),
+            "http://localhost/message?sessionId=1&token=a"
+        );
+        assert_eq!(
+            join(
+                "http://localhost/sse?token=a#events",
+                "/message?sessionId=1#post"
+            ),
+            "http://localhost/message?sessionId=1&token=a#post"
+        );
+        // never leak the query to another origin
+        assert_eq!(
+            join(
+                "http://localhost/sse?token=a",
+                "https://example.com/message"
+            ),
+            "https://example.com/message"
+        );
+    }
+
+    #[test]
+    fn test_drain_on_close() {
+        let mut cx = std::task::Context::from_waker(futures::task::noop_waker_ref());
+        let (senders, mut queue): (Vec<_>, VecDeque<_>) =
+            (0..3).map(|_| tokio::sync::oneshot::channel()).unzip();
+        let mut senders = senders.into_iter();
+        let (first, failing, last) = (
+            senders.next().unwrap(),
+            senders.next().unwrap(),
+            senders.next().unwrap(),
+        );
+        let mut errors = Vec::new();
+
+        // the failing post doesn't stop the drain
+        failing
+            .send(Err(
+                std::io::Error::from(std::io::ErrorKind::BrokenPipe).into()
+            ))
+            .unwrap();
+        assert!(poll_drain(&mut queue, &mut errors, &mut cx).is_pending());
+        assert_eq!(queue.len(), 2);
+        first.send(Ok(())).unwrap();
+        assert!(poll_drain(&mut queue, &mut errors, &mut cx).is_pending());
+        assert_eq!(queue.len(), 1);
+
+        last.send(Ok(())).unwrap();
+        let std::task::Poll::Ready(Err(SseTransportError::Close(close_errors))) =
+            poll_drain(&mut queue, &mut errors, &mut cx)
+        else {
+            panic!("expect the close error");
+        };
+        assert!(queue.is_empty());
+        assert!(matches!(
+            close_errors.as_slice(),
+            [SseTransportError::Io(error)] if error.kind() == std::io::ErrorKind::BrokenPipe
+        ));
+        assert!(errors.is_empty());
+        assert!(matches!(
+            poll_drain(&mut queue, &mut errors, &mut cx),
+            std::task::Poll::Ready(Ok(()))
+        ));
+    }
+
+    /// Read the head and the body of a http request, `None` once the connection is closed
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> std::io::Result<Option<String>> {
+        use tokio::io::AsyncReadExt;
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0; 1];
+            if stream.read(&mut byte).await? == 0 {
+                return Ok(None);
+            }
+            head.push(byte[0]);
+        }
+        let head = String::from_utf8_lossy(&head).into_owned();
+        let length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or_default();
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).await?;
+        Ok(Some(head))
+    }
+
+    #[test]
+    fn test_server_style_detection() {
+        assert!(is_endpoint_event("endpoint", "/message?sessionId=1"));
+        assert!(is_endpoint_event("message", "/message?sessionId=1"));
+        assert!(!is_endpoint_event(
+            "message",
+            r#"{"jsonrpc":"2.0","method":"ping","id":1}"#
+        ));
+        assert!(is_streamable_http_response(
+            reqwest::StatusCode::METHOD_NOT_ALLOWED,
+            None
+        ));
+        assert!(is_streamable_http_response(
+            reqwest::StatusCode::BAD_REQUEST,
+            Some("application/json; charset=utf-8")
+        ));
+        assert!(!is_streamable_http_response(
+            reqwest::StatusCode::OK,
+            Some("text/event-stream")
+        ));
+        assert!(!is_streamable_http_response(
+            reqwest::StatusCode::NOT_FOUND,
+            Some("text/html")
+        ));
+    }
+
+    /// Answer each request with the response returned for its head, the connections are kept open
+    async fn serve_fake(
+        respond: fn(&str) -> &'static str,
+    ) -> std::io::Result<(String, tokio::task::JoinHandle<std::io::Result<()>>)> {
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/sse", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                let (mut stream, _) = listener.accept().await?;
+                if let Some(head) = read_request(&mut stream).await? {
+                    stream.write_all(respond(&head).as_bytes()).await?;
+                    stream.flush().await?;
+                }
+                streams.push(stream);
+            }
+        });
+        Ok((url, server))
+    }
+
+    const EVENT_STREAM: &str =
+        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncache-control: no-cache\r\n\r\n";
+
+    #[tokio::test]
+    async fn test_legacy_sse_server() -> Result<(), Box<dyn std::error::Error>> {
+        let (url, server) = serve_fake(|_head| {
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\nevent: endpoint\ndata: /message?sessionId=1\n\n"
+        })
+        .await?;
+        let transport = SseTransport::start(url.as_str(), HeaderMap::new()).await?;
+        assert_eq!(
+            transport.post_url.as_str(),
+            url.replace("/sse", "/message?sessionId=1")
+        );
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streamable_http_server() -> Result<(), Box<dyn std::error::Error>> {
+        // refuses the GET
+        let (url, server) = serve_fake(
+            |_head| "HTTP/1.1 405 Method Not Allowed\r\nallow: POST\r\ncontent-length: 0\r\n\r\n",
+        )
+        .await?;
+        let error = SseTransport::start(url.as_str(), HeaderMap::new())
+            .await
+            .err()
+            .expect("not a legacy SSE server");
+        assert!(
+            matches!(&error, SseTransportError::StreamableHttp(server_url) if server_url.as_str() == url),
+            "{error}"
+        );
+        server.abort();
+
+        // answers the GET with a JSON-RPC error
+        let (url, server) = serve_fake(|_head| {
+            "HTTP/1.1 400 Bad Request\r\ncontent-type: application/json\r\ncontent-length: 77\r\n\r\n\
+            {\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32000,\"message\":\"No session id\"},\"id\":null}"
+        })
+        .await?;
+        let error = SseTransport::start(url.as_str(), HeaderMap::new())
+            .await
+            .err()
+            .expect("not a legacy SSE server");
+        assert!(
+            matches!(error, SseTransportError::StreamableHttp(_)),
+            "{error}"
+        );
+        server.abort();
+
+        // opens a stream of messages, without any endpoint
+        let (url, server) = serve_fake(|_head| {
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n\
+            event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\n\n"
+        })
+        .await?;
+        let error = SseTransport::start(url.as_str(), HeaderMap::new())
+            .await
+            .err()
+            .expect("not a legacy SSE server");
+        assert!(
+            matches!(error, SseTransportError::StreamableHttp(_)),
+            "{error}"
+        );
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        // a stream without any event
+        let (url, server) = serve_fake(|_head| EVENT_STREAM).await?;
+        let error = SseTransport::builder(url)
+            .endpoint_timeout(Duration::from_millis(200))
+            .start()
+            .await
+            .err()
+            .expect("no endpoint event");
+        assert!(
+            matches!(error, SseTransportError::EndpointTimeout(timeout) if timeout == Duration::from_millis(200)),
+            "{error}"
+        );
+        server.abort();
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let second = Duration::from_secs(1);
+        let builder = SseTransport::builder("http://localhost/sse");
+        assert_eq!(builder.timeouts, SseTimeouts::default());
+        let builder = builder
+            .connect_timeout(second)
+            .sse_read_timeout(2 * second)
+            .post_timeout(3 * second);
+        assert_eq!(
+            builder.timeouts,
+            SseTimeouts {
+                connect_timeout: Some(second),
+                sse_read_timeout: Some(2 * second),
+                post_timeout: Some(3 * second),
+            }
+        );
+        // the legacy timeout only bounds the posts
+        let builder = SseTransport::builder("http://localhost/sse").timeout(second);
+        assert_eq!(
+            builder.timeouts,
+            SseTimeouts {
+                post_timeout: Some(second),
+                ..Default::default()
+            }
+        );
+    }
+
+    /// A server sending the endpoint, then a ping after staying silent for `idle`, the posts are never answered
+    async fn serve_idle(
+        idle: Duration,
+    ) -> std::io::Result<(String, tokio::task::JoinHandle<std::io::Result<()>>)> {
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/sse", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                let (mut stream, _) = listener.accept().await?;
+                let Some(head) = read_request(&mut stream).await? else {
+                    continue;
+                };
+                if head.starts_with("GET") {
+                    tokio::spawn(async move {
+                        stream.write_all(EVENT_STREAM.as_bytes()).await?;
+                        stream
+                            .write_all(b"event: endpoint\ndata: /message\n\n")
+                            .await?;
+                        tokio::time::sleep(idle).await;
+                        stream
+                            .write_all(
+                                b"data: {\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\n\n",
+                            )
+                            .await?;
+                        // keep the stream open
+                        std::future::pending::<()>().await;
+                        std::io::Result::Ok(())
+                    });
+                } else {
+                    streams.push(stream);
+                }
+            }
+        });
+        Ok((url, server))
+    }
+
+    #[tokio::test]
+    async fn test_idle_stream_outlives_post_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let (url, server) = serve_idle(Duration::from_millis(600)).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .post_timeout(Duration::from_millis(200))
+            .start()
+            .await?;
+        let message = tokio::time::timeout(Duration::from_secs(5), transport.next()).await?;
+        assert!(
+            matches!(message, Some(ServerJsonRpcMessage::Request(_))),
+            "{message:?}"
+        );
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sse_read_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let (url, server) = serve_idle(Duration::from_secs(5)).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .sse_read_timeout(Duration::from_millis(200))
+            .reconnect(SseReconnect::none())
+            .start()
+            .await?;
+        let message = tokio::time::timeout(Duration::from_secs(2), transport.next()).await?;
+        assert!(message.is_none(), "{message:?}");
+        server.abort();
+        Ok(())
+    }
+
+    /// Close the first stream after a ping with the event id 1, the stream resumed from it sends a ping with the id 2
+    async fn serve_closing_stream() -> std::io::Result<(
+        String,
+        tokio::task::JoinHandle<std::io::Result<Vec<String>>>,
+    )> {
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/sse", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let mut heads = Vec::new();
+            let (mut stream, _) = listener.accept().await?;
+            heads.extend(read_request(&mut stream).await?);
+            stream.write_all(EVENT_STREAM.as_bytes()).await?;
+            stream
+                .write_all(b"event: endpoint\ndata: /message?sessionId=1\n\nid: 1\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\n\n")
+                .await?;
+            drop(stream);
+            let (mut stream, _) = listener.accept().await?;
+            heads.extend(read_request(&mut stream).await?);
+            stream.write_all(EVENT_STREAM.as_bytes()).await?;
+            stream
+                .write_all(b"id: 2\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":2}\n\n")
+                .await?;
+            stream.flush().await?;
+            // keep the stream open until the client is done
+            let _ = read_request(&mut stream).await;
+            Ok(heads)
+        });
+        Ok((url, server))
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_resumes_stream() -> Result<(), Box<dyn std::error::Error>> {
+        let (url, server) = serve_closing_stream().await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .reconnect(
+                SseReconnect::default().with_backoff(ExponentialBackoff::new(
+                    Duration::from_millis(10),
+                    Duration::from_millis(10),
+                )),
+            )
+            .start()
+            .await?;
+        for id in [1, 2] {
+            let message = tokio::time::timeout(Duration::from_secs(2), transport.next()).await?;
+            let Some(ServerJsonRpcMessage::Request(request)) = message else {
+                panic!("expected a ping, got {message:?}");
+            };
+            assert_eq!(request.id, crate::model::RequestId::Number(id));
+        }
+        drop(transport);
+        let heads = server.await??;
+        let resumed = heads[1].to_ascii_lowercase();
+        assert!(
+            resumed.starts_with("get /message?sessionid=1 "),
+            "{resumed}"
+        );
+        assert!(resumed.contains("last-event-id: 1\r\n"), "{resumed}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_reconnect_ends_stream() -> Result<(), Box<dyn std::error::Error>> {
+        let (url, server) = serve_closing_stream().await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .reconnect(SseReconnect::none())
+            .start()
+            .await?;
+        let message = tokio::time::timeout(Duration::from_secs(2), transport.next()).await?;
+        assert!(message.is_some());
+        let message = tokio::time::timeout(Duration::from_secs(2), transport.next()).await?;
+        assert!(message.is_none(), "{message:?}");
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        use futures::SinkExt;
+        let (url, server) = serve_idle(Duration::from_secs(5)).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .post_timeout(Duration::from_millis(200))
+            .start()
+            .await?;
+        let error = tokio::time::timeout(Duration::from_secs(2), transport.send(ping()))
+            .await?
+            .expect_err("the post is never answered");
+        assert!(
+            matches!(&error, SseTransportError::Reqwest(error) if error.is_timeout()),
+            "{error}"
+        );
+        server.abort();
+        Ok(())
+    }
+
+    /// A legacy SSE server answering the first `failures` posts with `503 Service Unavailable`, and the number of posts
+    async fn serve_flaky(
+        failures: usize,
+    ) -> std::io::Result<(
+        String,
+        Arc<std::sync::atomic::AtomicUsize>,
+        tokio::task::JoinHandle<std::io::Result<()>>,
+    )> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::AsyncWriteExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/sse", listener.local_addr()?);
+        let posts = Arc::new(AtomicUsize::new(0));
+        let counter = posts.clone();
+        let server = tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await?;
+                let posts = counter.clone();
+                tokio::spawn(async move {
+                    while let Some(head) = read_request(&mut stream).await? {
+                        if head.starts_with("GET") {
+                            stream.write_all(EVENT_STREAM.as_bytes()).await?;
+                            stream
+                                .write_all(b"event: endpoint\ndata: /message\n\n")
+                                .await?;
+                            continue;
+                        }
+                        let response: &[u8] = if posts.fetch_add(1, Ordering::SeqCst) < failures {
+                            b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n"
+                        } else {
+                            b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n"
+                        };
+                        stream.write_all(response).await?;
+                    }
+                    std::io::Result::Ok(())
+                });
+            }
+        });
+        Ok((url, posts, server))
+    }
+
+    fn ping() -> ClientJsonRpcMessage {
+        serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0", "method": "ping", "id": 1
+        }))
+        .expect("a ping")
+    }
+
+    #[tokio::test]
+    async fn test_post_retry() -> Result<(), Box<dyn std::error::Error>> {
+        use futures::SinkExt;
+        use std::sync::atomic::Ordering;
+        let retry = SsePostRetry::default().with_backoff(
+            ExponentialBackoff::new(Duration::from_millis(10), Duration::from_millis(10))
+                .with_jitter(0.0),
+        );
+
+        // recovers within the retries
+        let (url, posts, server) = serve_flaky(2).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .post_retry(retry.clone())
+            .start()
+            .await?;
+        transport.send(ping()).await?;
+        assert_eq!(posts.load(Ordering::SeqCst), 3);
+        server.abort();
+
+        // fails after the retries
+        let (url, posts, server) = serve_flaky(usize::MAX).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .post_retry(retry.with_retries(1))
+            .start()
+            .await?;
+        let error = transport.send(ping()).await.expect_err("still unavailable");
+        assert!(
+            matches!(&error, SseTransportError::Reqwest(error) if error.status() == Some(reqwest::StatusCode::SERVICE_UNAVAILABLE)),
+            "{error}"
+        );
+        assert_eq!(posts.load(Ordering::SeqCst), 2);
+        server.abort();
+
+        // without retries, the first failure is the error
+        let (url, posts, server) = serve_flaky(1).await?;
+        let mut transport = SseTransport::builder(url.as_str())
+            .post_retry(SsePostRetry::none())
+            .start()
+            .await?;
+        transport.send(ping()).await.expect_err("unavailable");
+        assert_eq!(posts.load(Ordering::SeqCst), 1);
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_posts_reuse_connection() -> Result<(), Box<dyn std::error::Error>> {
+        use tokio::io::AsyncWriteExt;
+        const POSTS: usize = 100;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/message", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let mut connections = 0;
+            let mut requests = 0;
+            while requests < POSTS {
+                let (mut stream, _) = listener.accept().await?;
+                connections += 1;
+                while let Some(head) = read_request(&mut stream).await? {
+                    assert!(head.starts_with("POST /message HTTP/1.1"), "{head}");
+                    stream
+                        .write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n")
+                        .await?;
+                    requests += 1;
+                    if requests == POSTS {
+                        break;
+                    }
+                }
+            }
+            std::io::Result::Ok(connections)
+        });
+        let client = build_http_client(
+            HeaderMap::new(),
+            SseHttpVersion::Http1Only,
+            None,
+            &SseTlsConfig::default(),
+        )?;
+        for id in 0..POSTS {
+            client
+                .post(&url)
+                .json(&serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "ping" }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        assert_eq!(server.await??, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http2_prior_knowledge() -> Result<(), Box<dyn std::error::Error>> {
+        use tokio::io::AsyncReadExt;
+        const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/message", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut preface = vec![0; PREFACE.len()];
+            stream.read_exact(&mut preface).await?;
+            std::io::Result::Ok(preface)
+        });
+        let client = build_http_client(
+            HeaderMap::new(),
+            SseHttpVersion::Http2PriorKnowledge,
+            None,
+            &SseTlsConfig::default(),
+        )?;
+        // the fake server doesn't answer, only the preface matters
+        let _ = tokio::time::timeout(Duration::from_millis(200), client.post(&url).send()).await;
+        assert_eq!(server.await??, PREFACE);
+        Ok(())
     }
 }
@@ -0,0 +1,234 @@
+//! The events of a `text/event-stream` response, parsed as in the
+//! [HTML standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation)
+use std::time::Duration;
+
+use futures::{FutureExt, Stream, StreamExt, stream::BoxStream};
+use tokio_util::bytes::{Bytes, BytesMut};
+
+use super::SseTransportError;
+
+/// An event with data, the comments and the events without data are skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SseEvent {
+    /// `message` if the event has no type
+    pub event_type: String,
+    pub data: String,
+    /// The last event id of the stream, set by this event or by one before it, to resume the stream after it
+    pub id: Option<String>,
+}
+
+/// The fields of the event being read
+#[derive(Debug, Default)]
+struct EventBuilder {
+    event_type: String,
+    data: Option<String>,
+    /// Kept across the events, as the last event id of the standard
+    last_event_id: Option<String>,
+}
+
+impl EventBuilder {
+    /// Read a line, without its end, the event is complete at an empty line
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let event_type = std::mem::take(&mut self.event_type);
+            let event_type = match event_type.is_empty() {
+                true => "message".to_owned(),
+                false => event_type,
+            };
+            return self.data.take().map(|data| SseEvent {
+                event_type,
+                data,
+                id: self.last_event_id.clone(),
+            });
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event_type = value.to_owned(),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_owned()),
+            },
+            // an empty id resets it, an id with a null is ignored
+            "id" if !value.contains('\0') => {
+                self.last_event_id = Some(value.to_owned()).filter(|id| !id.is_empty())
+            }
+            // a comment, or the retry delay, the transport reconnects with its own backoff
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Parse the events of a response body, a silence longer than `read_timeout` fails the stream.
+///
+/// The lines end with `\n` or `\r\n`, an event not ended by an empty line when the body ends is dropped.
+pub(super) fn sse_events<S>(
+    body: S,
+    read_timeout: Option<Duration>,
+) -> BoxStream<'static, Result<SseEvent, SseTransportError>>
+where
+    S: Stream<Item = reqwest::Result<Bytes>> + Send + 'static,
+{
+    struct State<S> {
+        body: S,
+        buffer: BytesMut,
+        event: EventBuilder,
+        done: bool,
+    }
+    let state = State {
+        body: body.boxed(),
+        buffer: BytesMut::new(),
+        event: EventBuilder::default(),
+        done: false,
+    };
+    futures::stream::unfold(state, move |mut state| async move {
+        loop {
+            while let Some(end) = state.buffer.iter().position(|&byte| byte == b'\n') {
+                let line = state.buffer.split_to(end + 1);
+                let line = line.strip_suffix(b"\n").unwrap_or(&line);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if let Some(event) = state.event.line(&String::from_utf8_lossy(line)) {
+                    return Some((Ok(event), state));
+                }
+            }
+            if state.done {
+                return None;
+            }
+            let next = state
+                .body
+                .next()
+                .map(|chunk| chunk.map(|chunk| chunk.map_err(SseTransportError::from)));
+            let chunk = match read_timeout {
+                Some(read_timeout) => tokio::time::timeout(read_timeout, next)
+                    .await
+                    .unwrap_or_else(|_elapsed| {
+                        let error = std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("no data on the SSE stream for {read_timeout:?}"),
+                        );
+                        Some(Err(error.into()))
+                    }),
+                None => next.await,
+            };
+            match chunk {
+                Some(Ok(chunk)) => state.buffer.extend_from_slice(&chunk),
+                Some(Err(error)) => {
+                    state.done = true;
+                    return Some((Err(error), state));
+                }
+                None => state.done = true,
+            }
+        }
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(chunks: &[&'static str]) -> Vec<SseEvent> {
+        let body = futures::stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+                .collect::<Vec<_>>(),
+        );
+        sse_events(body, None)
+            .map(|event| event.expect("no error"))
+            .collect()
+            .await
+    }
+
+    fn event(event_type: &str, data: &str) -> SseEvent {
+        SseEvent {
+            event_type: event_type.to_owned(),
+            data: data.to_owned(),
+            id: None,
+        }
+    }
+
+    fn event_with_id(data: &str, id: &str) -> SseEvent {
+        SseEvent {
+            id: Some(id.to_owned()),
+            ..event("message", data)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_events() {
+        assert_eq!(
+            parse(&[
+                ": keep-alive\n\n",
+                "event: endpoint\ndata: /message?sessionId=1\n\n",
+                "data:{\"a\":1}\r\nid: 7\r\nretry: 1000\r\n\r\n",
+                "data: first\ndata: second\n\n",
+                "event: empty\n\n",
+                "data\n\n",
+            ])
+            .await,
+            [
+                event("endpoint", "/message?sessionId=1"),
+                event_with_id("{\"a\":1}", "7"),
+                event_with_id("first\nsecond", "7"),
+                event_with_id("", "7"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_event_ids() {
+        assert_eq!(
+            parse(&[
+                "id: 1\ndata: a\n\n",
+                "id: 2\n\n",
+                "data: b\n\n",
+                "id: 3\0\ndata: c\n\n",
+                "id\ndata: d\n\n",
+            ])
+            .await,
+            [
+                event_with_id("a", "1"),
+                event_with_id("b", "2"),
+                event_with_id("c", "2"),
+                event("message", "d"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_split_chunks() {
+        assert_eq!(
+            parse(&[
+                "ev",
+                "ent: endpoint\nda",
+                "ta: /mess",
+                "age\n",
+                "\n",
+                "data: unfinished\n"
+            ])
+            .await,
+            [event("endpoint", "/message")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        let body = futures::stream::iter([Ok(Bytes::from_static(b"data: 1\n\n"))])
+            .chain(futures::stream::pending());
+        let mut events = sse_events(body, Some(Duration::from_millis(50)));
+        assert_eq!(events.next().await.unwrap().unwrap(), event("message", "1"));
+        let error = events.next().await.unwrap().expect_err("timeout");
+        assert!(
+            matches!(&error, SseTransportError::Io(error) if error.kind() == std::io::ErrorKind::TimedOut),
+            "{error}"
+        );
+        assert!(events.next().await.is_none());
+    }
+}
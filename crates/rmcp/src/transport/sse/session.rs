@@ -0,0 +1,114 @@
+//! The session of an [`SseTransport`](super::SseTransport), saved to resume it after the client restarts
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::model::ServerInfo;
+
+/// What a client needs to re-attach to its session after a restart, instead of initializing a new one.
+///
+/// Get it from the [`SseSessionTracker`] of the transport, save it, e.g. after each response or on
+/// shutdown, and resume with [`SseTransportBuilder::resume`](super::SseTransportBuilder::resume) and
+/// [`serve_client_resumed`](crate::serve_client_resumed). The events after the saved one are
+/// replayed on the resumed stream, so the older the saved session, the more events the server sends again.
+///
+/// The server must keep the session while the client restarts, and keep the events sent meanwhile: with
+/// `SseServer`, the restart must be shorter than
+/// `SseServerConfig::resume_timeout`, and the events must fit in `SseServerConfig::replay_buffer`.
+/// Otherwise the resume fails with [`SseTransportError::SessionNotResumable`](super::SseTransportError::SessionNotResumable),
+/// and the client starts a new session with [`serve_client`](crate::serve_client).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SseSession {
+    /// The url the messages are posted to, with the id of the session
+    pub endpoint: String,
+    /// The id of the last event received, `None` if the server sent no id
+    pub last_event_id: Option<String>,
+    /// The first request id of the resumed client, after those of the previous one,
+    /// so the replayed responses to its requests aren't taken for the responses to the new ones
+    pub next_request_id: u32,
+    /// The result of the initialization of the session
+    pub server_info: ServerInfo,
+}
+
+impl SseSession {
+    /// Write the session to a file, the file is replaced at once, so a crash never leaves half of it
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(temporary, path)
+    }
+
+    /// Read a session written by [`SseSession::save`], `None` if there's no file
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackedState {
+    last_event_id: Option<String>,
+    next_request_id: u32,
+}
+
+/// Follows the session of a transport as the events are received and the requests are posted,
+/// get it with [`SseTransport::session_tracker`](super::SseTransport::session_tracker) before the transport is served.
+#[derive(Debug, Clone)]
+pub struct SseSessionTracker {
+    endpoint: Arc<Url>,
+    state: Arc<Mutex<TrackedState>>,
+}
+
+impl SseSessionTracker {
+    pub(super) fn new(
+        endpoint: Arc<Url>,
+        last_event_id: Option<String>,
+        next_request_id: u32,
+    ) -> Self {
+        Self {
+            endpoint,
+            state: Arc::new(Mutex::new(TrackedState {
+                last_event_id,
+                next_request_id,
+            })),
+        }
+    }
+
+    pub(super) fn event_received(&self, id: &str) {
+        let mut state = self.state.lock().expect("session state poisoned");
+        if state.last_event_id.as_deref() != Some(id) {
+            state.last_event_id = Some(id.to_owned());
+        }
+    }
+
+    pub(super) fn last_event_id(&self) -> Option<String> {
+        let state = self.state.lock().expect("session state poisoned");
+        state.last_event_id.clone()
+    }
+
+    pub(super) fn request_posted(&self, id: u32) {
+        let mut state = self.state.lock().expect("session state poisoned");
+        state.next_request_id = state.next_request_id.max(id.saturating_add(1));
+    }
+
+    /// The session as of now, with the info of the server from the initialization, the
+    /// [`Peer::peer_info`](crate::Peer::peer_info) of the client
+    pub fn session(&self, server_info: ServerInfo) -> SseSession {
+        let state = self.state.lock().expect("session state poisoned");
+        SseSession {
+            endpoint: self.endpoint.to_string(),
+            last_event_id: state.last_event_id.clone(),
+            next_request_id: state.next_request_id,
+            server_info,
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! Serve a [`ServerHandler`] over the legacy HTTP+SSE transport from an axum app, the server counterpart of
+//! [`SseTransport`](super::sse::SseTransport).
+//!
+//! A client opens a session with a GET, the server creates a handler for it with the factory, and sends the
+//! `endpoint` event: the same path, with the id of the session in the query. The client posts its messages there,
+//! and receives the messages of the server on the SSE stream.
+//!
+//! The messages of the server have increasing event ids, and the latest ones are kept in a bounded replay buffer.
+//! A client whose stream broke resumes it with a GET of the endpoint, with the `Last-Event-ID` header set to the id
+//! of the last event it received: the server sends the events it missed, then the new ones. The resumed stream
+//! has no `endpoint` event. The session ends when its stream stays closed for [`SseServerConfig::resume_timeout`].
+//!
+//! ```rust,ignore
+//! let mcp = SseServer::new(SseServerConfig::default());
+//! let app = Router::new()
+//!     .route("/health", get(|| async { "ok" }))
+//!     .nest("/mcp", mcp.router(Counter::new));
+//! // the clients connect to http://127.0.0.1:8000/mcp/sse
+//! axum::serve(TcpListener::bind("127.0.0.1:8000").await?, app).await?;
+//! ```
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use futures::{SinkExt, StreamExt, channel::mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    ServerHandler, ServerHandlerService, model::ClientJsonRpcMessage, serve_server,
+    service::QuitReason,
+};
+
+type SessionId = Arc<str>;
+type Sessions = Arc<Mutex<HashMap<SessionId, Session>>>;
+/// An event of the SSE stream, by its id
+type EventData = (u64, Arc<str>);
+
+/// The options of [`SseServer`]
+#[derive(Debug, Clone)]
+pub struct SseServerConfig {
+    /// The path of both the SSE stream and the posts, relative to where the router is nested, `/sse` by default
+    pub path: String,
+    /// The interval of the keep-alive comments on the SSE stream, so the proxies don't close an idle stream,
+    /// 15s by default, `None` to send none
+    pub keep_alive: Option<Duration>,
+    /// How many messages of a client may wait to be handled, a post waits when the session is that far behind.
+    /// It's also how many messages may wait to be sent to a client, a stream that far behind is closed, and the
+    /// client resumes it from the replay buffer.
+    pub channel_capacity: usize,
+    /// How many of the latest events of a session are kept to be replayed to a resuming client, 256 by default
+    pub replay_buffer: usize,
+    /// How long a session waits for its client to resume its closed stream before ending, 30s by default
+    pub resume_timeout: Duration,
+    /// Cancel it to close all the sessions, e.g. on shutdown
+    pub ct: CancellationToken,
+}
+
+impl Default for SseServerConfig {
+    fn default() -> Self {
+        Self {
+            path: "/sse".to_owned(),
+            keep_alive: Some(Duration::from_secs(15)),
+            channel_capacity: 64,
+            replay_buffer: 256,
+            resume_timeout: Duration::from_secs(30),
+            ct: CancellationToken::new(),
+        }
+    }
+}
+
+/// The sessions of the SSE clients, see the [module documentation](self)
+#[derive(Clone, Default)]
+pub struct SseServer {
+    sessions: Sessions,
+    config: SseServerConfig,
+}
+
+impl std::fmt::Debug for SseServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseServer")
+            .field("sessions", &self.session_count())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl SseServer {
+    pub fn new(config: SseServerConfig) -> Self {
+        Self {
+            sessions: Default::default(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &SseServerConfig {
+        &self.config
+    }
+
+    /// The number of open sessions
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().expect("sessions poisoned").len()
+    }
+
+    /// Close all the sessions, and the ones opened later, like cancelling [`SseServerConfig::ct`]
+    pub fn cancel(&self) {
+        self.config.ct.cancel();
+    }
+
+    /// A router serving a handler made by `factory` to each session, to nest in an app or to serve as is
+    pub fn router<H, F>(&self, factory: F) -> Router
+    where
+        H: ServerHandler,
+        F: Fn() -> H + Send + Sync + 'static,
+    {
+        let state = RouterState {
+            server: self.clone(),
+            factory: Arc::new(factory),
+        };
+        Router::new()
+            .route(
+                &self.config.path,
+                get(get_stream::<H>).post(post_message::<H>),
+            )
+            .with_state(state)
+    }
+}
+
+struct RouterState<H> {
+    server: SseServer,
+    factory: Arc<dyn Fn() -> H + Send + Sync>,
+}
+
+impl<H> Clone for RouterState<H> {
+    fn clone(&self) -> Self {
+        Self {
+            server: self.server.clone(),
+            factory: self.factory.clone(),
+        }
+    }
+}
+
+fn session_id() -> SessionId {
+    Arc::from(format!("{:032x}", rand::random::<u128>()))
+}
+
+#[derive(Clone)]
+struct Session {
+    to_service: mpsc::Sender<ClientJsonRpcMessage>,
+    events: Arc<Mutex<SessionEvents>>,
+}
+
+/// The latest events of a session, and the stream they're sent to
+#[derive(Default)]
+struct SessionEvents {
+    /// The id of the last event, the ids start at 1
+    last_id: u64,
+    replay: VecDeque<EventData>,
+    /// The current stream, with its number, a resumed stream replaces it
+    stream: Option<(u64, mpsc::Sender<EventData>)>,
+    /// The number of streams opened
+    streams: u64,
+}
+
+impl SessionEvents {
+    /// Keep a message in the replay buffer and send it to the current stream
+    fn push(&mut self, data: Arc<str>, replay_buffer: usize) {
+        self.last_id += 1;
+        let event = (self.last_id, data);
+        if replay_buffer > 0 {
+            if self.replay.len() >= replay_buffer {
+                self.replay.pop_front();
+            }
+            self.replay.push_back(event.clone());
+        }
+        if let Some((_, stream)) = &mut self.stream
+            && stream.try_send(event).is_err()
+        {
+            // the client doesn't keep up, it resumes from the replay buffer
+            self.stream = None;
+        }
+    }
+
+    /// Replace the stream, with the events after `last_event_id` to replay on it first,
+    /// `None` if some of them were dropped from the replay buffer
+    fn open_stream(
+        &mut self,
+        last_event_id: u64,
+        capacity: usize,
+    ) -> Option<(u64, Vec<EventData>, mpsc::Receiver<EventData>)> {
+        let missed = self.last_id.saturating_sub(last_event_id);
+        let replay = self
+            .replay
+            .iter()
+            .filter(|(id, _)| *id > last_event_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        if replay.len() as u64 != missed {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel(capacity);
+        self.streams += 1;
+        self.stream = Some((self.streams, tx));
+        Some((self.streams, replay, rx))
+    }
+}
+
+/// Closes the session once its stream is dropped, unless the client resumes it in time
+struct StreamGuard {
+    id: SessionId,
+    stream: u64,
+    events: Arc<Mutex<SessionEvents>>,
+    server: SseServer,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        let Ok(mut events) = self.events.lock() else {
+            return;
+        };
+        if events.streams != self.stream {
+            // resumed already
+            return;
+        }
+        events.stream = None;
+        drop(events);
+        let (id, events, server, stream) = (
+            self.id.clone(),
+            self.events.clone(),
+            self.server.clone(),
+            self.stream,
+        );
+        let resume_timeout = server.config.resume_timeout;
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(resume_timeout) => {}
+                _ = server.config.ct.cancelled() => {}
+            }
+            let resumed = events
+                .lock()
+                .map_or(true, |events| events.streams != stream);
+            if !resumed {
+                tracing::info!(session = %id, "sse stream not resumed");
+                server.remove_session(&id);
+            }
+        });
+    }
+}
+
+impl SseServer {
+    /// Remove a session, which ends its service once its posts are handled
+    fn remove_session(&self, id: &str) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(id);
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamQuery {
+    /// Set to resume the stream of a session
+    session_id: Option<String>,
+}
+
+/// Open a session, or resume its stream if the query has its id, `404 Not Found` for an unknown session,
+/// and `410 Gone` if the events after `Last-Event-ID` are no longer in the replay buffer
+async fn get_stream<H: ServerHandler>(
+    State(state): State<RouterState<H>>,
+    Query(StreamQuery { session_id }): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(session_id) = session_id else {
+        return open_session(state);
+    };
+    let last_event_id = match headers.get("last-event-id") {
+        Some(value) => match value.to_str().ok().and_then(|id| id.trim().parse().ok()) {
+            Some(id) => id,
+            None => return StatusCode::BAD_REQUEST.into_response(),
+        },
+        None => 0,
+    };
+    let server = state.server;
+    let session = server
+        .sessions
+        .lock()
+        .expect("sessions poisoned")
+        .get_key_value(session_id.as_str())
+        .map(|(id, session)| (id.clone(), session.events.clone()));
+    let Some((id, events)) = session else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let stream = events
+        .lock()
+        .expect("events poisoned")
+        .open_stream(last_event_id, server.config.channel_capacity);
+    let Some((stream, replay, rx)) = stream else {
+        tracing::warn!(session = %id, last_event_id, "sse stream not resumable");
+        return StatusCode::GONE.into_response();
+    };
+    tracing::info!(session = %id, last_event_id, replayed = replay.len(), "sse stream resumed");
+    let guard = StreamGuard {
+        id,
+        stream,
+        events,
+        server: server.clone(),
+    };
+    sse_response(&server, None, replay, rx, guard)
+}
+
+fn open_session<H: ServerHandler>(state: RouterState<H>) -> Response {
+    let RouterState { server, factory } = state;
+    let id = session_id();
+    let (to_service, from_client_rx) = mpsc::channel(server.config.channel_capacity);
+    let events = Arc::new(Mutex::new(SessionEvents::default()));
+    let (stream, _, rx) = events
+        .lock()
+        .expect("events poisoned")
+        .open_stream(0, server.config.channel_capacity)
+        .expect("a new session has no events");
+    server.sessions.lock().expect("sessions poisoned").insert(
+        id.clone(),
+        Session {
+            to_service,
+            events: events.clone(),
+        },
+    );
+    tracing::info!(session = %id, "sse session opened");
+    let service = ServerHandlerService::new(factory());
+    tokio::spawn({
+        let (id, events, server) = (id.clone(), events.clone(), server.clone());
+        async move {
+            let replay_buffer = server.config.replay_buffer;
+            let to_client = futures::sink::unfold(events.clone(), move |events, message| {
+                match serde_json::to_string(&message) {
+                    Ok(data) => events
+                        .lock()
+                        .expect("events poisoned")
+                        .push(data.into(), replay_buffer),
+                    Err(error) => tracing::error!(%error, "failed to serialize a message"),
+                }
+                futures::future::ok::<_, std::io::Error>(events)
+            });
+            let transport = (Box::pin(to_client), from_client_rx);
+            match serve_server(service, transport).await {
+                Ok(running) => match running.waiting().await {
+                    QuitReason::Closed | QuitReason::Cancelled => {
+                        tracing::info!(session = %id, "sse session closed")
+                    }
+                    quit_reason => tracing::warn!(session = %id, ?quit_reason, "sse session ended"),
+                },
+                Err(error) => {
+                    tracing::warn!(session = %id, %error, "sse session failed to initialize")
+                }
+            }
+            // close the stream, and refuse the posts
+            events.lock().expect("events poisoned").stream = None;
+            server.remove_session(&id);
+        }
+    });
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("?sessionId={id}"));
+    let guard = StreamGuard {
+        id,
+        stream,
+        events,
+        server: server.clone(),
+    };
+    sse_response(&server, Some(endpoint), Vec::new(), rx, guard)
+}
+
+/// The SSE stream of the replayed events then of the new ones, which owns the guard of the stream
+fn sse_response(
+    server: &SseServer,
+    endpoint: Option<Event>,
+    replay: Vec<EventData>,
+    rx: mpsc::Receiver<EventData>,
+    guard: StreamGuard,
+) -> Response {
+    let messages = futures::stream::iter(replay)
+        .chain(rx)
+        .map(move |(id, data)| {
+            let _guard = &guard;
+            Ok::<_, Infallible>(
+                Event::default()
+                    .event("message")
+                    .id(id.to_string())
+                    .data(&*data),
+            )
+        });
+    let stream = futures::stream::iter(endpoint.map(Ok))
+        .chain(messages)
+        .take_until(server.config.ct.clone().cancelled_owned());
+    let sse = Sse::new(stream);
+    match server.config.keep_alive {
+        Some(interval) => sse
+            .keep_alive(KeepAlive::new().interval(interval))
+            .into_response(),
+        None => sse.into_response(),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostQuery {
+    session_id: String,
+}
+
+/// `404 Not Found` for an unknown session, `410 Gone` for a session whose service has ended
+async fn post_message<H: ServerHandler>(
+    State(state): State<RouterState<H>>,
+    Query(PostQuery { session_id }): Query<PostQuery>,
+    Json(message): Json<ClientJsonRpcMessage>,
+) -> StatusCode {
+    let sender = state
+        .server
+        .sessions
+        .lock()
+        .expect("sessions poisoned")
+        .get(session_id.as_str())
+        .map(|session| session.to_service.clone());
+    let Some(mut sender) = sender else {
+        return StatusCode::NOT_FOUND;
+    };
+    match sender.send(message).await {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::GONE,
+    }
+}
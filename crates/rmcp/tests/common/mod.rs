@@ -0,0 +1,96 @@
+//! The setup shared by the integration tests, a test includes it with `mod common;`
+// a test only uses some of the helpers
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use futures::{
+    SinkExt,
+    channel::mpsc::{self, SendError},
+    sink::SinkMapErr,
+};
+#[cfg(feature = "client")]
+use rmcp::{
+    ClientHandler, ClientHandlerService, RoleClient, ServerHandler, ServerHandlerService,
+    serve_client, serve_server_with_config,
+    service::{RunningService, ServeConfig},
+};
+use rmcp::{
+    RoleServer,
+    model::{ClientInfo, ClientJsonRpcMessage, NumberOrString, ServerJsonRpcMessage},
+    service::{AtomicU32RequestIdProvider, Peer, RequestContext},
+};
+use tokio_util::sync::CancellationToken;
+
+/// One end of an in-memory transport, it sends `Tx` and receives `Rx`
+pub type Transport<Tx, Rx> = (
+    SinkMapErr<mpsc::Sender<Tx>, fn(SendError) -> std::io::Error>,
+    mpsc::Receiver<Rx>,
+);
+
+pub type ServerTransport = Transport<ServerJsonRpcMessage, ClientJsonRpcMessage>;
+pub type ClientTransport = Transport<ClientJsonRpcMessage, ServerJsonRpcMessage>;
+
+/// The transports of a server and of a client connected to each other
+pub fn transports() -> (ServerTransport, ClientTransport) {
+    let (server_tx, client_rx) = mpsc::channel(16);
+    let (client_tx, server_rx) = mpsc::channel(16);
+    let map_err: fn(SendError) -> std::io::Error = std::io::Error::other;
+    (
+        (server_tx.sink_map_err(map_err), server_rx),
+        (client_tx.sink_map_err(map_err), client_rx),
+    )
+}
+
+#[cfg(feature = "client")]
+pub type Server<S> = RunningService<ServerHandlerService<S>>;
+#[cfg(feature = "client")]
+pub type Client<C> = RunningService<ClientHandlerService<C>>;
+
+/// Serve a server and a client connected to each other, once both are initialized
+#[cfg(feature = "client")]
+pub async fn serve<S: ServerHandler, C: ClientHandler>(
+    server: S,
+    client: C,
+) -> anyhow::Result<(Server<S>, Client<C>)> {
+    serve_with_config(server, client, ServeConfig::default()).await
+}
+
+/// Like [`serve`], with the config of the server
+#[cfg(feature = "client")]
+pub async fn serve_with_config<S: ServerHandler, C: ClientHandler>(
+    server: S,
+    client: C,
+    config: ServeConfig,
+) -> anyhow::Result<(Server<S>, Client<C>)> {
+    let (server_transport, client_transport) = transports();
+    let server = tokio::spawn(serve_server_with_config(
+        ServerHandlerService::new(server),
+        server_transport,
+        config,
+    ));
+    let client = serve_client(ClientHandlerService::new(client), client_transport).await?;
+    Ok((server.await??, client))
+}
+
+/// Serve a server, and a client which only answers the pings
+#[cfg(feature = "client")]
+pub async fn connect<S: ServerHandler>(
+    server: S,
+) -> anyhow::Result<(Server<S>, Client<Option<Peer<RoleClient>>>)> {
+    serve(server, None).await
+}
+
+/// The peer of a server without a transport, as if a client with this info connected
+pub fn server_peer(client_info: ClientInfo) -> Peer<RoleServer> {
+    Peer::new(Arc::new(AtomicU32RequestIdProvider::default()), client_info).0
+}
+
+/// The context of a request of a default client, to call a handler directly
+pub fn request_context() -> RequestContext<RoleServer> {
+    RequestContext {
+        ct: CancellationToken::new(),
+        id: NumberOrString::Number(0),
+        peer: server_peer(ClientInfo::default()),
+    }
+}
@@ -0,0 +1,34 @@
+use arbitrary::{Arbitrary, Unstructured};
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+
+/// Deterministic pseudo random bytes, so a failure can be reproduced
+fn bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn assert_round_trip<'a, T>(data: &'a [u8])
+where
+    T: Arbitrary<'a> + serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let message = T::arbitrary(&mut Unstructured::new(data)).expect("arbitrary message");
+    let json = serde_json::to_string(&message).expect("serialize");
+    let parsed: T = serde_json::from_str(&json).unwrap_or_else(|e| panic!("{e}: {json}"));
+    assert_eq!(parsed, message, "{json}");
+}
+
+#[test]
+fn test_arbitrary_message_round_trip() {
+    for seed in 0..1000 {
+        let data = bytes(seed, 512);
+        assert_round_trip::<ClientJsonRpcMessage>(&data);
+        assert_round_trip::<ServerJsonRpcMessage>(&data);
+    }
+}
@@ -0,0 +1,138 @@
+use futures::{SinkExt, StreamExt, channel::mpsc};
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    model::{PingRequest, ServerNotification, ServerRequest, ToolListChangedNotification},
+    serve_client_with_options, serve_server_with_config,
+    service::{
+        ClientInitializeOptions, PeerRequestOptions, ServeConfig, ServiceError, serve_directly,
+        serve_directly_with_config,
+    },
+};
+
+#[derive(Debug, Clone, Default)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+fn notification() -> ServerNotification {
+    ServerNotification::ToolListChangedNotification(ToolListChangedNotification {
+        method: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_full_queue() -> anyhow::Result<()> {
+    // the client never reads, so the first message blocks the transport, and the next ones are queued
+    let (to_client, client_rx) = mpsc::channel(0);
+    let transport = (
+        to_client.sink_map_err(std::io::Error::other),
+        futures::stream::pending(),
+    );
+    let server = serve_directly(
+        ServerHandlerService::new(Server),
+        transport,
+        Default::default(),
+    )
+    .await?;
+    let peer = server.peer().clone();
+
+    let mut queued = 0;
+    let error = loop {
+        match peer.try_send_notification(notification()) {
+            Ok(()) => queued += 1,
+            Err(error) => break error,
+        }
+        // let the service loop take the first messages
+        tokio::task::yield_now().await;
+        assert!(queued < 10_000, "the queue is never full");
+    };
+    assert!(matches!(error, ServiceError::Backpressure), "{error}");
+    assert!(queued > 1);
+    // the requests share the queue
+    let request = ServerRequest::PingRequest(PingRequest {
+        method: Default::default(),
+    });
+    assert!(matches!(
+        peer.try_send_request(request, PeerRequestOptions::no_options()),
+        Err(ServiceError::Backpressure)
+    ));
+
+    // the queue is drained once the client reads again
+    tokio::spawn(client_rx.for_each(|_| async {}));
+    tokio::time::timeout(std::time::Duration::from_secs(5), server.cancel()).await??;
+    assert!(matches!(
+        peer.try_send_notification(notification()),
+        Err(ServiceError::Transport(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_small_queue() -> anyhow::Result<()> {
+    // the client never reads, the queue of the peer holds at most 4 messages besides the one blocking the transport
+    let (to_client, client_rx) = mpsc::channel(0);
+    let transport = (
+        to_client.sink_map_err(std::io::Error::other),
+        futures::stream::pending(),
+    );
+    let server = serve_directly_with_config(
+        ServerHandlerService::new(Server),
+        transport,
+        Default::default(),
+        ServeConfig::default().with_peer_channel_capacity(4),
+    )
+    .await?;
+    let peer = server.peer().clone();
+
+    let mut queued = 0;
+    while peer.try_send_notification(notification()).is_ok() {
+        queued += 1;
+        tokio::task::yield_now().await;
+        assert!(queued <= 8, "the queue holds more than its capacity");
+    }
+    assert!(queued >= 4);
+
+    tokio::spawn(client_rx.for_each(|_| async {}));
+    tokio::time::timeout(std::time::Duration::from_secs(5), server.cancel()).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_small_buffers_under_load() -> anyhow::Result<()> {
+    let config = ServeConfig::default()
+        .with_peer_channel_capacity(1)
+        .with_response_channel_capacity(1);
+    // the transport itself has room, the service loops stop reading while they write to a full transport
+    let (server_tx, client_rx) = mpsc::channel(1024);
+    let (client_tx, server_rx) = mpsc::channel(1024);
+    let server = tokio::spawn(serve_server_with_config(
+        ServerHandlerService::new(Server),
+        (server_tx.sink_map_err(std::io::Error::other), server_rx),
+        config,
+    ));
+    let client = serve_client_with_options(
+        ClientHandlerService::simple(),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        ClientInitializeOptions::default().with_serve_config(config),
+    )
+    .await?;
+    let server = server.await??;
+
+    // both sides send at once, every message waits for room rather than being dropped
+    let pings = (0..200).map(|_| client.peer().ping());
+    let notifications = (0..200).map(|_| server.peer().send_notification(notification()));
+    let (pings, notifications) = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        futures::future::join(
+            futures::future::join_all(pings),
+            futures::future::join_all(notifications),
+        ),
+    )
+    .await?;
+    assert!(pings.iter().all(Result::is_ok));
+    assert!(notifications.iter().all(Result::is_ok));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ServerCapabilities, ServerInfo, ServerResult,
+    },
+    serve_client, serve_server, tool,
+};
+
+/// The first bytes of a png, encoded by a MIME encoder that breaks the lines
+const WRAPPED_PNG: &str = "iVBORw0K\r\nGgo=\n";
+
+#[derive(Debug, Clone)]
+pub struct Screenshot;
+
+#[tool(tool_box)]
+impl Screenshot {
+    #[tool(description = "Take a screenshot")]
+    fn screenshot(&self) -> Result<CallToolResult, rmcp::Error> {
+        Ok(CallToolResult::success(vec![Content::image(
+            WRAPPED_PNG,
+            "image/png",
+        )]))
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Screenshot {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_call_tool_with_wrapped_base64() -> anyhow::Result<()> {
+    // over a byte stream, so that the result is deserialized
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(serve_server(
+        ServerHandlerService::new(Screenshot),
+        server_io,
+    ));
+    let client = serve_client(ClientHandlerService::simple(), client_io).await?;
+
+    let result = client
+        .peer()
+        .call_tool(CallToolRequestParam {
+            name: "screenshot".into(),
+            arguments: None,
+            meta: None,
+        })
+        .await?;
+    let image = result.content[0].as_image().expect("image content");
+    assert_eq!(image.data, "iVBORw0KGgo=");
+    assert_eq!(image.decode(), Ok(b"\x89PNG\r\n\x1a\n".to_vec()));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_wrapped_base64_result() {
+    let result = serde_json::from_value::<ServerResult>(serde_json::json!({
+        "content": [{ "type": "image", "data": WRAPPED_PNG, "mimeType": "image/png" }],
+        "isError": false,
+    }))
+    .expect("deserialize result");
+    let ServerResult::CallToolResult(result) = result else {
+        panic!("expect a call tool result, got {result:?}");
+    };
+    let image = result.content[0].as_image().expect("image content");
+    assert_eq!(image.data, "iVBORw0KGgo=");
+}
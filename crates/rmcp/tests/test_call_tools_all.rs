@@ -0,0 +1,141 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandlerService, RoleClient, RoleServer, ServerHandler, ServiceError,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ErrorData, ServerCapabilities, ServerInfo,
+    },
+    service::{FailMode, Peer, RequestContext, RunningService},
+};
+
+/// `ok` answers at once, `fail` and `tool_error` after a short delay, and `hang` once it's cancelled
+#[derive(Debug, Clone, Default)]
+struct Tools {
+    cancelled: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for Tools {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if request.name != "ok" {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        match request.name.as_ref() {
+            "ok" => Ok(CallToolResult::success(vec![Content::text("ok")])),
+            "fail" => Err(ErrorData::internal_error("fail", None)),
+            "tool_error" => Ok(CallToolResult::error(vec![Content::text("tool error")])),
+            _ => {
+                context.ct.cancelled().await;
+                self.cancelled.fetch_add(1, Ordering::SeqCst);
+                Err(ErrorData::internal_error("cancelled", None))
+            }
+        }
+    }
+}
+
+async fn connect(tools: Tools) -> RunningService<ClientHandlerService<Option<Peer<RoleClient>>>> {
+    let (_, client) = common::connect(tools).await.expect("connected");
+    client
+}
+
+fn calls(names: &[&'static str]) -> Vec<CallToolRequestParam> {
+    names
+        .iter()
+        .map(|name| CallToolRequestParam {
+            name: (*name).into(),
+            arguments: None,
+            meta: None,
+        })
+        .collect()
+}
+
+fn is_cancelled(result: &Result<CallToolResult, ServiceError>) -> bool {
+    matches!(
+        result,
+        Err(ServiceError::Cancelled { reason: Some(reason) }) if reason == FailMode::CANCEL_REASON
+    )
+}
+
+async fn wait_cancelled(tools: &Tools, count: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while tools.cancelled.load(Ordering::SeqCst) < count {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("the server sees the cancellations");
+}
+
+#[tokio::test]
+async fn test_fail_fast() -> anyhow::Result<()> {
+    let tools = Tools::default();
+    let client = connect(tools.clone()).await;
+    let results = client
+        .peer()
+        .call_tools_all(calls(&["hang", "ok", "fail", "hang"]), FailMode::FailFast)
+        .await;
+    assert_eq!(results.len(), 4);
+    assert!(is_cancelled(&results[0]));
+    assert!(results[1].is_ok());
+    assert!(matches!(&results[2], Err(ServiceError::McpError(error)) if error.message == "fail"));
+    assert!(is_cancelled(&results[3]));
+    wait_cancelled(&tools, 2).await;
+
+    // a result flagged `isError` fails too
+    let results = client
+        .peer()
+        .call_tools_all(calls(&["tool_error", "hang"]), FailMode::FailFast)
+        .await;
+    assert_eq!(results[0].as_ref().unwrap().is_error, Some(true));
+    assert!(is_cancelled(&results[1]));
+    wait_cancelled(&tools, 3).await;
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_collect_all() -> anyhow::Result<()> {
+    let client = connect(Tools::default()).await;
+    let results = client
+        .peer()
+        .call_tools_all(
+            calls(&["fail", "ok", "tool_error", "ok"]),
+            FailMode::CollectAll,
+        )
+        .await;
+    assert_eq!(results.len(), 4);
+    assert!(matches!(&results[0], Err(ServiceError::McpError(error)) if error.message == "fail"));
+    for index in [1, 3] {
+        let result = results[index].as_ref().expect("the call succeeds");
+        assert_eq!(result.content[0].as_text().unwrap().text, "ok");
+    }
+    assert_eq!(results[2].as_ref().unwrap().is_error, Some(true));
+
+    assert!(
+        client
+            .peer()
+            .call_tools_all(Vec::new(), FailMode::FailFast)
+            .await
+            .is_empty()
+    );
+    client.cancel().await?;
+    Ok(())
+}
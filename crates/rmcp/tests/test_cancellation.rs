@@ -0,0 +1,49 @@
+use futures::{SinkExt, StreamExt, channel::mpsc};
+use rmcp::{
+    ServerHandler, ServerHandlerService,
+    model::{
+        ClientInfo, ClientJsonRpcMessage, JsonRpcMessage, NumberOrString, ServerJsonRpcMessage,
+    },
+    service::{QuitReason, serve_directly},
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_cancel_unknown_request() -> anyhow::Result<()> {
+    let (server_tx, mut client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let (mut client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (server_tx, server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+
+    let cancelled: ClientJsonRpcMessage = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 999, "reason": "never sent" }
+    }))?;
+    client_tx.send(cancelled).await?;
+    let ping: ClientJsonRpcMessage = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "ping"
+    }))?;
+    client_tx.send(ping).await?;
+
+    // the first message is the response of ping, not an error for the cancellation
+    let message = client_rx.next().await.expect("connection dropped");
+    let JsonRpcMessage::Response(response) = message else {
+        panic!("expect a response, got {message:?}");
+    };
+    assert_eq!(response.id, NumberOrString::Number(1));
+
+    assert_eq!(service.cancel().await?, QuitReason::Cancelled);
+    Ok(())
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, RoleServer, ServerHandler,
+    ServerHandlerService,
+    handler::client::Roots,
+    model::{
+        ClientCapabilities, Content, CreateMessageRequestParam, CreateMessageResult, Role, Root,
+        RootsCapabilities, SamplingMessage,
+    },
+    service::{RequestContext, RunningService},
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Tells when the roots change
+#[derive(Debug, Clone)]
+struct Server {
+    roots_changed: UnboundedSender<()>,
+}
+
+impl ServerHandler for Server {
+    async fn on_roots_list_changed(&self) {
+        let _ = self.roots_changed.send(());
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    sampling: bool,
+    roots: Option<Roots>,
+}
+
+impl ClientHandler for Client {
+    fn can_create_message(&self) -> bool {
+        self.sampling
+    }
+
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::Error> {
+        Ok(CreateMessageResult {
+            model: "echo".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("hello"),
+            },
+            meta: None,
+        })
+    }
+
+    fn roots(&self) -> Option<&Roots> {
+        self.roots.as_ref()
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    client: Client,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Client>>,
+    UnboundedReceiver<()>,
+)> {
+    let (roots_changed, roots_changed_rx) = unbounded_channel();
+    let (server, client) = common::serve(Server { roots_changed }, client).await?;
+    Ok((server, client, roots_changed_rx))
+}
+
+fn client_capabilities(server: &Peer<RoleServer>) -> ClientCapabilities {
+    server.peer_info().capabilities.clone()
+}
+
+#[test]
+fn test_capabilities_derived_from_handler() {
+    assert_eq!(
+        Client::default().capabilities(),
+        ClientCapabilities::default()
+    );
+    assert_eq!(
+        None::<Peer<RoleClient>>.get_info().capabilities,
+        ClientCapabilities::default()
+    );
+
+    let sampling = Client {
+        sampling: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        sampling.capabilities(),
+        ClientCapabilities::builder().enable_sampling().build()
+    );
+
+    let roots = Client {
+        roots: Some(Roots::default()),
+        ..Default::default()
+    };
+    assert_eq!(
+        roots.capabilities(),
+        ClientCapabilities::builder()
+            .enable_roots()
+            .enable_roots_list_changed()
+            .build()
+    );
+}
+
+#[tokio::test]
+async fn test_nothing_advertised() -> anyhow::Result<()> {
+    let (server, client, _) = connect(Client::default()).await?;
+    let capabilities = client_capabilities(server.peer());
+    assert!(capabilities.sampling.is_none());
+    assert!(capabilities.roots.is_none());
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sampling_advertised() -> anyhow::Result<()> {
+    let (server, client, _) = connect(Client {
+        sampling: true,
+        ..Default::default()
+    })
+    .await?;
+    let capabilities = client_capabilities(server.peer());
+    assert!(capabilities.sampling.is_some());
+    assert!(capabilities.roots.is_none());
+    let result = server
+        .peer()
+        .create_message(CreateMessageRequestParam {
+            messages: vec![SamplingMessage {
+                role: Role::User,
+                content: Content::text("hi"),
+            }],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: None,
+            temperature: None,
+            max_tokens: 16,
+            stop_sequences: None,
+            metadata: None,
+            meta: None,
+        })
+        .await?;
+    assert_eq!(result.model, "echo");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_roots_advertised() -> anyhow::Result<()> {
+    let roots = Roots::new([Root::new("file:///workspace")])?;
+    let (server, client, mut roots_changed) = connect(Client {
+        roots: Some(roots.clone()),
+        ..Default::default()
+    })
+    .await?;
+    let capabilities = client_capabilities(server.peer());
+    assert!(capabilities.sampling.is_none());
+    assert_eq!(
+        capabilities.roots,
+        Some(RootsCapabilities {
+            list_changed: Some(true)
+        })
+    );
+    assert_eq!(server.peer().list_roots().await?.roots, roots.list().roots);
+
+    roots
+        .update(
+            client.peer(),
+            [Root::new("file:///other").with_name("other")],
+        )
+        .await?;
+    roots_changed.recv().await;
+    let listed = server.peer().list_roots().await?;
+    assert_eq!(listed.roots.len(), 1);
+    assert_eq!(listed.roots[0].uri, "file:///other");
+    client.cancel().await?;
+    Ok(())
+}
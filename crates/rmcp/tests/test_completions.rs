@@ -0,0 +1,133 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, RoleServer, ServerHandler, ServiceError,
+    model::{
+        ArgumentInfo, CompleteRequestParam, CompleteResult, CompletionInfo, PromptReference,
+        ProtocolVersion, Reference, ServerCapabilities, ServerInfo,
+    },
+    service::{RequestContext, RunningService},
+};
+use serde_json::json;
+
+/// Completes the languages of a prompt, only advertises it if `advertise` is set
+#[derive(Debug, Clone)]
+pub struct Completer {
+    advertise: bool,
+    protocol_version: ProtocolVersion,
+}
+
+impl Completer {
+    fn new(advertise: bool) -> Self {
+        Self {
+            advertise,
+            protocol_version: ProtocolVersion::V_2025_03_26,
+        }
+    }
+}
+
+impl ServerHandler for Completer {
+    fn get_info(&self) -> ServerInfo {
+        let capabilities = if self.advertise {
+            ServerCapabilities::builder()
+                .enable_completions()
+                .enable_prompts()
+                .build()
+        } else {
+            ServerCapabilities::builder().enable_prompts().build()
+        };
+        ServerInfo {
+            protocol_version: self.protocol_version.clone(),
+            capabilities,
+            ..Default::default()
+        }
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, rmcp::Error> {
+        let values = ["python", "rust", "ruby"]
+            .into_iter()
+            .filter(|language| language.starts_with(&request.argument.value))
+            .map(String::from)
+            .collect::<Vec<_>>();
+        Ok(CompleteResult {
+            completion: CompletionInfo {
+                total: Some(values.len() as u32),
+                values,
+                has_more: Some(false),
+            },
+            meta: None,
+        })
+    }
+}
+
+async fn connect(server: Completer) -> anyhow::Result<RunningService<ClientHandlerService>> {
+    let (_, client) = common::connect(server).await?;
+    Ok(client)
+}
+
+fn language_param(value: &str) -> CompleteRequestParam {
+    CompleteRequestParam {
+        r#ref: Reference::Prompt(PromptReference {
+            name: "code_review".into(),
+        }),
+        argument: ArgumentInfo {
+            name: "language".into(),
+            value: value.into(),
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_complete_advertised() -> anyhow::Result<()> {
+    let client = connect(Completer::new(true)).await?;
+    let capabilities = &client.peer().peer_info().capabilities;
+    assert!(capabilities.completions.is_some());
+    let result = client.peer().complete(language_param("r")).await?;
+    assert_eq!(result.completion.values, ["rust", "ruby"]);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_complete_not_advertised() -> anyhow::Result<()> {
+    let client = connect(Completer::new(false)).await?;
+    assert_eq!(client.peer().peer_info().capabilities.completions, None);
+    let error = client
+        .peer()
+        .complete(language_param("r"))
+        .await
+        .expect_err("completions not advertised");
+    assert!(matches!(
+        error,
+        ServiceError::CapabilityNotSupported {
+            capability: "completions"
+        }
+    ));
+    // the raw request isn't checked
+    let params = serde_json::to_value(language_param("py"))?;
+    let result = client
+        .peer()
+        .request("completion/complete", params.as_object().cloned())
+        .await?;
+    assert_eq!(result["completion"]["values"], json!(["python"]));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_complete_before_the_capability() -> anyhow::Result<()> {
+    // the 2024-11-05 protocol has no completions capability, so it's not checked
+    let client = connect(Completer {
+        protocol_version: ProtocolVersion::V_2024_11_05,
+        ..Completer::new(false)
+    })
+    .await?;
+    let result = client.peer().complete(language_param("py")).await?;
+    assert_eq!(result.completion.values, ["python"]);
+    client.cancel().await?;
+    Ok(())
+}
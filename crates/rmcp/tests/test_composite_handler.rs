@@ -0,0 +1,199 @@
+mod common;
+
+use rmcp::{
+    ServerHandler,
+    handler::server::{
+        composite::{CompositeHandler, ToolNameCollision},
+        tool::ExclusiveToolRouter,
+    },
+    model::{CallToolRequestParam, ErrorCode},
+    tool,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct Calculator;
+
+#[tool(tool_box)]
+impl Calculator {
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, #[tool(param)] a: i32, #[tool(param)] b: i32) -> String {
+        (a + b).to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Echo;
+
+#[tool(tool_box)]
+impl Echo {
+    #[tool(description = "Echo the message")]
+    fn echo(&self, #[tool(param)] message: String) -> String {
+        message
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Greeter {
+    greeting: String,
+}
+
+#[tool(tool_box)]
+impl Greeter {
+    #[tool(description = "Greet someone")]
+    fn greet(&self, #[tool(param)] name: String) -> String {
+        format!("{}, {name}!", self.greeting)
+    }
+
+    #[tool(description = "Another sum, colliding with the calculator")]
+    fn sum(&self) -> String {
+        String::new()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: i32,
+}
+
+#[tool(tool_box)]
+impl Counter {
+    #[tool(description = "Increment the counter by 1")]
+    async fn increment(&mut self) -> String {
+        self.value += 1;
+        self.value.to_string()
+    }
+}
+
+async fn call_text(
+    handler: &CompositeHandler,
+    name: &'static str,
+    arguments: serde_json::Value,
+) -> Result<String, rmcp::Error> {
+    let result = handler
+        .call_tool(
+            CallToolRequestParam {
+                name: name.into(),
+                arguments: arguments.as_object().cloned(),
+                meta: None,
+            },
+            common::request_context(),
+        )
+        .await?;
+    Ok(result.content[0]
+        .as_text()
+        .expect("text content")
+        .text
+        .clone())
+}
+
+#[tokio::test]
+async fn test_composite_handler() -> anyhow::Result<()> {
+    let handler = CompositeHandler::new()
+        .with_tool_box(Calculator, Calculator::tool_box())?
+        .with_tool_box(Echo, Echo::tool_box())?;
+    assert!(handler.get_info().capabilities.tools.is_some());
+    let mut names = handler
+        .list()
+        .iter()
+        .map(|tool| tool.name.to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["echo", "sum"]);
+
+    assert_eq!(
+        call_text(&handler, "sum", json!({ "a": 1, "b": 2 })).await?,
+        "3"
+    );
+    assert_eq!(
+        call_text(&handler, "echo", json!({ "message": "hello" })).await?,
+        "hello"
+    );
+    let error = call_text(&handler, "missing", json!({}))
+        .await
+        .expect_err("tool not found");
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_composite_handler_collision() -> anyhow::Result<()> {
+    let greeter = Greeter {
+        greeting: "Hello".to_string(),
+    };
+    let error = CompositeHandler::new()
+        .with_tool_box(Calculator, Calculator::tool_box())?
+        .with_tool_box(greeter, Greeter::tool_box())
+        .err()
+        .expect("sum collides");
+    assert_eq!(error, ToolNameCollision { name: "sum".into() });
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_composite_handler_prefix() -> anyhow::Result<()> {
+    let greeter = Greeter {
+        greeting: "Hello".to_string(),
+    };
+    let handler = CompositeHandler::new()
+        .with_prefixed_tool_box("math", Calculator, Calculator::tool_box())?
+        .with_prefixed_tool_box("greeter", greeter, Greeter::tool_box())?;
+    let mut names = handler
+        .list()
+        .iter()
+        .map(|tool| tool.name.to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["greeter.greet", "greeter.sum", "math.sum"]);
+
+    assert_eq!(
+        call_text(&handler, "math.sum", json!({ "a": 1, "b": 2 })).await?,
+        "3"
+    );
+    assert_eq!(
+        call_text(&handler, "greeter.greet", json!({ "name": "Ferris" })).await?,
+        "Hello, Ferris!"
+    );
+    let error = call_text(&handler, "sum", json!({ "a": 1, "b": 2 }))
+        .await
+        .expect_err("the unprefixed name is not listed");
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_composite_handler_exclusive_router() -> anyhow::Result<()> {
+    let router = ExclusiveToolRouter::new(Counter::default(), Counter::tool_box());
+    let handler = CompositeHandler::new()
+        .with_tool_box(Calculator, Calculator::tool_box())?
+        .with_prefixed_exclusive_router("counter", router.clone())?;
+    let names = handler
+        .list()
+        .iter()
+        .map(|tool| tool.name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["sum", "counter.increment"]);
+
+    assert_eq!(
+        call_text(&handler, "counter.increment", json!({})).await?,
+        "1"
+    );
+    assert_eq!(
+        call_text(&handler, "counter.increment", json!({})).await?,
+        "2"
+    );
+    assert_eq!(router.state().lock().await.value, 2);
+
+    let error = CompositeHandler::new()
+        .with_exclusive_router(router.clone())?
+        .with_exclusive_router(router)
+        .err()
+        .expect("increment collides");
+    assert_eq!(
+        error,
+        ToolNameCollision {
+            name: "increment".into()
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use std::{
+    io::ErrorKind,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{Sink, StreamExt, channel::mpsc, stream};
+
+use rmcp::{
+    ServerHandler, ServerHandlerService,
+    model::{ClientInfo, ClientJsonRpcMessage, ServerJsonRpcMessage},
+    service::{
+        ConnectionState, QuitReason, ServeConfig, serve_directly, serve_directly_with_config,
+    },
+    transport::{TryStreamSink, WireDirection},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_connection_state_closed_when_stream_ends() -> anyhow::Result<()> {
+    let (server_tx, _client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let (client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (server_tx, server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+    let mut state = service.peer().watch_connection_state();
+    state
+        .wait_for(|state| *state == ConnectionState::Ready)
+        .await?;
+    assert!(service.peer().is_connected());
+
+    drop(client_tx);
+    state
+        .wait_for(|state| matches!(state, ConnectionState::Closed { .. }))
+        .await?;
+    assert!(!service.peer().is_connected());
+    assert_eq!(
+        service.peer().connection_state(),
+        ConnectionState::Closed {
+            reason: QuitReason::Closed
+        }
+    );
+    assert_eq!(service.waiting().await, QuitReason::Closed);
+    Ok(())
+}
+
+/// A transport which fails to send anything, with an error of the kind
+struct Broken(ErrorKind);
+
+impl Sink<ServerJsonRpcMessage> for Broken {
+    type Error = std::io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, _: ServerJsonRpcMessage) -> Result<(), Self::Error> {
+        Err(std::io::Error::new(self.0, "broken"))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_quit_when_peer_disconnected() -> anyhow::Result<()> {
+    let (_client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (Broken(ErrorKind::BrokenPipe), server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+    assert!(service.peer().notify_tool_list_changed().await.is_err());
+    // the service quits at once, though the input stream is still open
+    assert_eq!(service.waiting().await, QuitReason::PeerDisconnected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quit_with_transport_error() -> anyhow::Result<()> {
+    let (client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (Broken(ErrorKind::Other), server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+    // a send failure doesn't end the session by itself
+    assert!(service.peer().notify_tool_list_changed().await.is_err());
+    assert!(service.peer().is_connected());
+
+    drop(client_tx);
+    match service.waiting().await {
+        QuitReason::TransportError(error) => assert_eq!(error.source.to_string(), "broken"),
+        reason => panic!("unexpected quit reason {reason:?}"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quit_with_read_error() -> anyhow::Result<()> {
+    // the stream stays open after the error, the service quits on the error itself
+    let server_rx =
+        stream::iter([Err(std::io::Error::other("unreadable"))]).chain(stream::pending());
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        TryStreamSink(Broken(ErrorKind::Other), server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+    let QuitReason::TransportError(error) = service.waiting().await else {
+        panic!("expect a transport error");
+    };
+    assert_eq!(error.direction, WireDirection::Incoming);
+    assert_eq!(
+        error.to_string(),
+        "failed to receive the next message: unreadable"
+    );
+
+    let server_rx = stream::iter([Err(std::io::Error::from(ErrorKind::ConnectionReset))])
+        .chain(stream::pending());
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        TryStreamSink(Broken(ErrorKind::Other), server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+    assert_eq!(service.waiting().await, QuitReason::PeerDisconnected);
+    Ok(())
+}
+
+/// A reader failing at once
+struct Unreadable;
+
+impl tokio::io::AsyncRead for Unreadable {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Err(std::io::Error::other("unreadable")))
+    }
+}
+
+#[tokio::test]
+async fn test_quit_with_io_read_error() -> anyhow::Result<()> {
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (Unreadable, tokio::io::sink()),
+        ClientInfo::default(),
+    )
+    .await?;
+    let QuitReason::TransportError(error) = service.waiting().await else {
+        panic!("expect a transport error");
+    };
+    assert_eq!(error.direction, WireDirection::Incoming);
+    assert_eq!(error.source.to_string(), "unreadable");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transport_error_report() -> anyhow::Result<()> {
+    let (client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly_with_config(
+        ServerHandlerService::new(Server),
+        (Broken(ErrorKind::Other), server_rx),
+        ClientInfo::default(),
+        ServeConfig::default().with_backtrace(true),
+    )
+    .await?;
+    assert!(service.peer().notify_tool_list_changed().await.is_err());
+
+    drop(client_tx);
+    let QuitReason::TransportError(error) = service.waiting().await else {
+        panic!("expect a transport error");
+    };
+    // what failed to be sent, and over which transport
+    assert_eq!(
+        error.message,
+        "notification `notifications/tools/list_changed`"
+    );
+    assert!(error.transport.contains("Broken"), "{}", error.transport);
+    assert_eq!(
+        error.to_string(),
+        "failed to send notification `notifications/tools/list_changed`: broken"
+    );
+    assert_eq!(
+        std::error::Error::source(error.as_ref()).map(ToString::to_string),
+        Some("broken".to_owned())
+    );
+    assert_eq!(
+        error.backtrace.status(),
+        std::backtrace::BacktraceStatus::Captured
+    );
+    let report = format!("{error:#}");
+    assert!(report.contains("  transport: "), "{report}");
+    assert!(report.contains("  caused by: broken"), "{report}");
+    assert!(report.contains("  backtrace:"), "{report}");
+    Ok(())
+}
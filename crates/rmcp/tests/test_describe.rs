@@ -0,0 +1,125 @@
+mod common;
+
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandlerService, RoleServer, ServerHandler, ServiceError,
+    model::{
+        Cursor, ErrorCode, ListPromptsResult, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo, Tool,
+    },
+    service::{RequestContext, RunningService},
+};
+
+/// Lists its tools one per page, fails to list its prompts, and has no resources
+#[derive(Debug, Clone)]
+pub struct Partial;
+
+const TOOLS: [&str; 3] = ["sum", "sub", "echo"];
+
+impl ServerHandler for Partial {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("a partial server".into()),
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_tools()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        let page = request.cursor.map_or(0, |cursor| cursor.parse().unwrap());
+        let tool = Tool::new(TOOLS[page], "a tool", Arc::new(Default::default()));
+        Ok(ListToolsResult {
+            next_cursor: (page + 1 < TOOLS.len()).then(|| Cursor::new((page + 1).to_string())),
+            tools: vec![tool],
+            meta: None,
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _: PaginatedRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::Error> {
+        Err(rmcp::Error::internal_error("prompts unavailable", None))
+    }
+}
+
+/// Lists its tools on pages whose cursors loop back, `1`, `2`, and `1` again
+#[derive(Debug, Clone)]
+pub struct Looping;
+
+impl ServerHandler for Looping {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        let next = match request.cursor.as_deref() {
+            Some("1") => "2",
+            _ => "1",
+        };
+        Ok(ListToolsResult {
+            next_cursor: Some(Cursor::new(next.to_owned())),
+            tools: vec![Tool::new("echo", "a tool", Arc::new(Default::default()))],
+            meta: None,
+        })
+    }
+}
+
+async fn connect<S: ServerHandler>(
+    server: S,
+) -> anyhow::Result<RunningService<ClientHandlerService>> {
+    let (_, client) = common::connect(server).await?;
+    Ok(client)
+}
+
+#[tokio::test]
+async fn test_describe_partial_failure() -> anyhow::Result<()> {
+    let client = connect(Partial).await?;
+    let description = client.peer().describe().await;
+    assert_eq!(
+        description.info.instructions.as_deref(),
+        Some("a partial server")
+    );
+    let tools = description.tools?;
+    let names = tools
+        .iter()
+        .map(|tool| tool.name.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, TOOLS);
+    let Err(ServiceError::McpError(error)) = description.prompts else {
+        panic!("expect the prompts to fail");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    // not advertised, so not fetched
+    assert!(description.resources?.is_empty());
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_describe_repeated_cursor() -> anyhow::Result<()> {
+    let client = connect(Looping).await?;
+    let description = client.peer().describe().await;
+    assert!(matches!(
+        description.tools,
+        Err(ServiceError::UnexpectedResponse)
+    ));
+    client.cancel().await?;
+    Ok(())
+}
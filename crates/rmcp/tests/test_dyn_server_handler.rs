@@ -0,0 +1,47 @@
+mod common;
+
+use rmcp::{
+    ServerHandler,
+    handler::server::DynServerHandler,
+    model::{Implementation, ServerInfo},
+};
+
+#[derive(Debug, Clone)]
+pub struct Named(&'static str);
+
+impl ServerHandler for Named {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            server_info: Implementation {
+                name: self.0.to_string(),
+                version: "0.1.0".to_string(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Empty;
+
+impl ServerHandler for Empty {}
+
+#[tokio::test]
+async fn test_dyn_server_handler() -> anyhow::Result<()> {
+    let handlers: Vec<Box<dyn DynServerHandler>> = vec![Box::new(Named("named")), Box::new(Empty)];
+    let names = handlers
+        .iter()
+        .map(|handler| handler.as_ref().get_info().server_info.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names[0], "named");
+    assert_eq!(names[1], ServerInfo::default().server_info.name);
+
+    let handler = handlers[0].clone();
+    let (_server, client) = common::connect(handler).await?;
+
+    assert_eq!(client.peer().peer_info().server_info.name, "named");
+    let tools = client.peer().list_tools(Default::default()).await?;
+    assert!(tools.tools.is_empty());
+    client.cancel().await?;
+    Ok(())
+}
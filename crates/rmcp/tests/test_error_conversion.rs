@@ -0,0 +1,119 @@
+mod common;
+
+use anyhow::Context;
+use rmcp::{
+    ServerHandler, ServiceError,
+    handler::server::sanitize::ErrorSanitizer,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct FileReader {
+    sanitizer: Option<ErrorSanitizer>,
+}
+
+#[tool(tool_box)]
+impl FileReader {
+    #[tool(description = "Read a file with std")]
+    fn read_std(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "the path of the file")]
+        path: String,
+    ) -> Result<String, rmcp::Error> {
+        Ok(std::fs::read_to_string(path)?)
+    }
+
+    #[tool(description = "Read a file with anyhow")]
+    fn read_anyhow(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "the path of the file")]
+        path: String,
+    ) -> anyhow::Result<String> {
+        std::fs::read_to_string(&path).with_context(|| format!("fail to read {path}"))
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for FileReader {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    fn error_sanitizer(&self) -> Option<&ErrorSanitizer> {
+        self.sanitizer.as_ref()
+    }
+}
+
+async fn call(server: FileReader, tool: &str, path: &str) -> anyhow::Result<rmcp::Error> {
+    let (_server, client) = common::connect(server).await?;
+    let error = client
+        .peer()
+        .call_tool(CallToolRequestParam {
+            name: tool.to_owned().into(),
+            arguments: json!({ "path": path }).as_object().cloned(),
+            meta: None,
+        })
+        .await
+        .expect_err("the file is missing");
+    client.cancel().await?;
+    match error {
+        ServiceError::McpError(error) => Ok(error),
+        error => anyhow::bail!("expect a mcp error, got {error:?}"),
+    }
+}
+
+#[test]
+fn test_from_errors() {
+    let error = rmcp::Error::from(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "no such file",
+    ));
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert_eq!(error.message, "no such file");
+
+    // the sources are kept
+    let error = rmcp::Error::from(
+        anyhow::Error::from(std::io::Error::other("disk full")).context("fail to save"),
+    );
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert_eq!(error.message, "fail to save: disk full");
+}
+
+#[tokio::test]
+async fn test_tool_errors() -> anyhow::Result<()> {
+    let path = "/nonexistent/secret.txt";
+    let error = call(FileReader::default(), "read_std", path).await?;
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+
+    let error = call(FileReader::default(), "read_anyhow", path).await?;
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert!(
+        error
+            .message
+            .starts_with("fail to read /nonexistent/secret.txt: "),
+        "{}",
+        error.message
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_redacted_errors() -> anyhow::Result<()> {
+    let server = FileReader {
+        sanitizer: Some(ErrorSanitizer::default()),
+    };
+    let path = "/nonexistent/secret.txt";
+    for tool in ["read_std", "read_anyhow"] {
+        let error = call(server.clone(), tool, path).await?;
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(error.message, ErrorSanitizer::GENERIC_MESSAGE);
+    }
+    Ok(())
+}
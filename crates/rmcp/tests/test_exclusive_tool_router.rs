@@ -0,0 +1,64 @@
+mod common;
+
+use rmcp::{
+    handler::server::tool::{ExclusiveToolRouter, ToolCallContext},
+    model::CallToolRequestParam,
+    tool,
+};
+
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: i32,
+}
+
+#[tool(tool_box)]
+impl Counter {
+    #[tool(description = "Increment the counter by 1")]
+    async fn increment(&mut self) -> String {
+        self.value += 1;
+        self.value.to_string()
+    }
+
+    #[tool(description = "Get the current counter value")]
+    fn get_value(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+fn request(name: &'static str) -> CallToolRequestParam {
+    CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+        meta: None,
+    }
+}
+
+#[tokio::test]
+async fn test_exclusive_tool_router() {
+    let router = ExclusiveToolRouter::new(Counter::default(), Counter::tool_box());
+    assert_eq!(router.list().len(), 2);
+    let calls = (0..10).map(|_| {
+        let router = router.clone();
+        tokio::spawn(async move {
+            router
+                .call(request("increment"), common::request_context())
+                .await
+        })
+    });
+    for call in calls {
+        call.await.expect("join").expect("call increment");
+    }
+    assert_eq!(router.state().lock().await.value, 10);
+    let result = router
+        .call(request("get_value"), common::request_context())
+        .await
+        .expect("call get_value");
+    assert_eq!(result.content[0].as_text().expect("text").text, "10");
+}
+
+#[tokio::test]
+async fn test_mut_tool_requires_exclusive_context() {
+    let counter = Counter::default();
+    let context = ToolCallContext::new(&counter, request("increment"), common::request_context());
+    assert!(Counter::tool_box().call(context).await.is_err());
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Error as McpError, Peer, RoleClient, RoleServer,
+    ServerHandler,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, NumberOrString, ProgressNotification,
+        ProgressNotificationParam,
+    },
+    service::{RequestContext, RunningService, ServeConfig},
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Reports the progress of its tools, `partial` stops at 1 of 3 and `complete` reaches 3 of 3,
+/// `queued` too but without waiting for the notifications to be sent
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let token = request
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get("progressToken"))
+            .cloned()
+            .map(serde_json::from_value::<NumberOrString>)
+            .transpose()
+            .map_err(|error| McpError::invalid_params(error.to_string(), None))?;
+        let last = if request.name == "complete" { 3 } else { 1 };
+        if let (Some(token), "queued") = (&token, request.name.as_ref()) {
+            for progress in 1..=3 {
+                let notification = ProgressNotification::new(token.clone(), progress, Some(3));
+                context
+                    .peer
+                    .try_send_notification(notification.into())
+                    .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+            }
+        } else if let Some(token) = token {
+            for progress in 1..=last {
+                context
+                    .peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token: token.clone(),
+                        progress,
+                        total: Some(3),
+                        message: None,
+                    })
+                    .await
+                    .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+            }
+        }
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+/// Forwards the progress notifications
+#[derive(Debug, Clone)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    progress: UnboundedSender<ProgressNotificationParam>,
+}
+
+impl ClientHandler for Client {
+    async fn on_progress(&self, params: ProgressNotificationParam) {
+        let _ = self.progress.send(params);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    config: ServeConfig,
+) -> anyhow::Result<(
+    RunningService<ClientHandlerService<Client>>,
+    UnboundedReceiver<ProgressNotificationParam>,
+)> {
+    let (progress, progress_rx) = unbounded_channel();
+    let client = Client {
+        peer: None,
+        progress,
+    };
+    let (_, client) = common::serve_with_config(Server, client, config).await?;
+    Ok((client, progress_rx))
+}
+
+async fn call(
+    client: &RunningService<ClientHandlerService<Client>>,
+    name: &'static str,
+    token: i64,
+) -> anyhow::Result<()> {
+    client
+        .call_tool(
+            CallToolRequestParam::new(name).with_progress_token(NumberOrString::Number(token)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Receive `count` progress notifications and no more, sorted by token and progress
+async fn received(
+    progress_rx: &mut UnboundedReceiver<ProgressNotificationParam>,
+    count: usize,
+) -> anyhow::Result<Vec<(NumberOrString, u32, Option<u32>)>> {
+    let mut received = Vec::new();
+    while received.len() < count {
+        let params = tokio::time::timeout(Duration::from_secs(5), progress_rx.recv())
+            .await?
+            .expect("a progress notification");
+        received.push((params.progress_token, params.progress, params.total));
+    }
+    // and nothing more
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(progress_rx.try_recv().is_err());
+    received.sort_by_key(|(token, progress, _)| (token.to_string(), *progress));
+    Ok(received)
+}
+
+#[tokio::test]
+async fn test_final_progress() -> anyhow::Result<()> {
+    let (client, mut progress_rx) =
+        connect(ServeConfig::default().with_final_progress(true)).await?;
+    call(&client, "partial", 1).await?;
+    call(&client, "complete", 2).await?;
+    // without a progress token, there's no progress
+    client
+        .call_tool(CallToolRequestParam::new("partial"))
+        .await?;
+
+    let token = |id| NumberOrString::Number(id);
+    assert_eq!(
+        received(&mut progress_rx, 5).await?,
+        [
+            // completed by the serve loop
+            (token(1), 1, Some(3)),
+            (token(1), 3, Some(3)),
+            // completed by the tool itself
+            (token(2), 1, Some(3)),
+            (token(2), 2, Some(3)),
+            (token(2), 3, Some(3)),
+        ]
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_final_progress_by_default() -> anyhow::Result<()> {
+    let (client, mut progress_rx) = connect(ServeConfig::default()).await?;
+    call(&client, "partial", 1).await?;
+    assert_eq!(
+        received(&mut progress_rx, 1).await?,
+        [(NumberOrString::Number(1), 1, Some(3))]
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_queued_progress_before_the_response() -> anyhow::Result<()> {
+    let (client, mut progress_rx) =
+        connect(ServeConfig::default().with_final_progress(true)).await?;
+    for token in 1..=20 {
+        call(&client, "queued", token).await?;
+    }
+    // the tool completed its progress itself every time, the serve loop saw it before the response
+    let received = received(&mut progress_rx, 60).await?;
+    assert_eq!(
+        received
+            .iter()
+            .filter(|(_, progress, _)| *progress == 3)
+            .count(),
+        20
+    );
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,231 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    RoleServer, ServerHandler,
+    handler::server::{
+        idempotency::IdempotencyCache,
+        tool::{
+            FromToolCallContextPart, ToolBox, ToolBoxItem, ToolCallContext, cached_schema_for_type,
+        },
+    },
+    model::{
+        CallToolRequestParam, CallToolResult, ClientInfo, Content, EmptyObject, NumberOrString,
+        Tool, ToolAnnotations,
+    },
+    service::{Peer, RequestContext},
+    tool,
+};
+
+#[derive(Debug, Default)]
+pub struct Server {
+    calls: AtomicU32,
+}
+
+impl Server {
+    fn count(&self) -> CallToolResult {
+        let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        CallToolResult::success(vec![Content::text(calls.to_string())])
+    }
+}
+
+fn tool_box() -> ToolBox<Server> {
+    let schema = cached_schema_for_type::<EmptyObject>();
+    let mut tool_box = ToolBox::new();
+    tool_box.add(ToolBoxItem::new(
+        Tool::new("idempotent_count", "count calls", schema.clone()).with_annotations(
+            ToolAnnotations {
+                idempotent_hint: Some(true),
+                ..Default::default()
+            },
+        ),
+        |context: ToolCallContext<'_, Server>| {
+            Box::pin(async move {
+                let (server, _) = <&Server>::from_tool_call_context_part(context)?;
+                Ok(server.count())
+            })
+        },
+    ));
+    tool_box.add(ToolBoxItem::new(
+        Tool::new("slow_count", "count calls", schema.clone()).with_annotations(ToolAnnotations {
+            idempotent_hint: Some(true),
+            ..Default::default()
+        }),
+        |context: ToolCallContext<'_, Server>| {
+            Box::pin(async move {
+                let (server, _) = <&Server>::from_tool_call_context_part(context)?;
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(server.count())
+            })
+        },
+    ));
+    tool_box.add(ToolBoxItem::new(
+        Tool::new("count", "count calls", schema),
+        |context: ToolCallContext<'_, Server>| {
+            Box::pin(async move {
+                let (server, _) = <&Server>::from_tool_call_context_part(context)?;
+                Ok(server.count())
+            })
+        },
+    ));
+    tool_box
+}
+
+/// Call a tool in the session of `peer`, each call has its own request id
+async fn call(
+    server: &Server,
+    tool_box: &ToolBox<Server>,
+    cache: &IdempotencyCache,
+    peer: &Peer<RoleServer>,
+    name: &'static str,
+    idempotency_key: Option<&str>,
+) -> String {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+    let mut request = CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+        meta: None,
+    };
+    if let Some(key) = idempotency_key {
+        request = request.with_idempotency_key(key);
+    }
+    let request_context = RequestContext {
+        id: NumberOrString::Number(NEXT_ID.fetch_add(1, Ordering::SeqCst).into()),
+        peer: peer.clone(),
+        ..common::request_context()
+    };
+    let context = ToolCallContext::new(server, request, request_context);
+    let result = tool_box
+        .call_with_idempotency_cache(context, cache)
+        .await
+        .expect("call tool");
+    result.content[0].as_text().expect("text").text.clone()
+}
+
+#[tokio::test]
+async fn test_idempotent_call_is_cached() {
+    let server = Server::default();
+    let tool_box = tool_box();
+    let cache = IdempotencyCache::default();
+    let peer = common::server_peer(ClientInfo::default());
+    let call = |name, key| call(&server, &tool_box, &cache, &peer, name, key);
+
+    assert_eq!(call("idempotent_count", Some("a")).await, "1");
+    assert_eq!(call("idempotent_count", Some("a")).await, "1");
+    assert_eq!(cache.len(), 1);
+    // a new key is executed
+    assert_eq!(call("idempotent_count", Some("b")).await, "2");
+    // calls without a key are never cached
+    assert_eq!(call("idempotent_count", None).await, "3");
+    assert_eq!(call("idempotent_count", None).await, "4");
+    // tools which are not idempotent are never cached
+    assert_eq!(call("count", Some("c")).await, "5");
+    assert_eq!(call("count", Some("c")).await, "6");
+    assert_eq!(cache.len(), 2);
+}
+
+#[tokio::test]
+async fn test_idempotency_key_is_scoped_to_the_session() {
+    let server = Server::default();
+    let tool_box = tool_box();
+    let cache = IdempotencyCache::default();
+    let (first, second) = (
+        common::server_peer(ClientInfo::default()),
+        common::server_peer(ClientInfo::default()),
+    );
+
+    let call = |peer, key| call(&server, &tool_box, &cache, peer, "idempotent_count", key);
+    assert_eq!(call(&first, Some("a")).await, "1");
+    assert_eq!(call(&second, Some("a")).await, "2");
+    assert_eq!(call(&first, Some("a")).await, "1");
+    assert_eq!(call(&second, Some("a")).await, "2");
+    assert_eq!(cache.len(), 2);
+}
+
+#[tokio::test]
+async fn test_idempotency_cache_eviction() {
+    let server = Server::default();
+    let tool_box = tool_box();
+    let peer = common::server_peer(ClientInfo::default());
+
+    let cache = IdempotencyCache::new(Duration::ZERO, 16);
+    let call_expired = |key| call(&server, &tool_box, &cache, &peer, "idempotent_count", key);
+    assert_eq!(call_expired(Some("a")).await, "1");
+    assert_eq!(call_expired(Some("a")).await, "2");
+    cache.evict_expired();
+    assert!(cache.is_empty());
+
+    let cache = IdempotencyCache::new(Duration::from_secs(60), 1);
+    let call = |key| call(&server, &tool_box, &cache, &peer, "idempotent_count", key);
+    call(Some("a")).await;
+    call(Some("b")).await;
+    assert_eq!(cache.len(), 1);
+    assert_eq!(call(Some("b")).await, "4");
+    assert_eq!(call(Some("a")).await, "5");
+}
+
+#[tokio::test]
+async fn test_running_duplicate_waits_for_the_result() {
+    let server = Server::default();
+    let tool_box = tool_box();
+    let cache = IdempotencyCache::default();
+    let peer = common::server_peer(ClientInfo::default());
+    let call = |key| call(&server, &tool_box, &cache, &peer, "slow_count", key);
+
+    let (first, second) = tokio::join!(call(Some("a")), call(Some("a")));
+    assert_eq!((first.as_str(), second.as_str()), ("1", "1"));
+    assert_eq!(server.calls.load(Ordering::SeqCst), 1);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    calls: Arc<AtomicU32>,
+}
+
+#[tool(tool_box)]
+impl Counter {
+    #[tool(description = "Count the calls", annotations(idempotent_hint = true))]
+    async fn count(&self) -> String {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        (self.calls.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Counter {}
+
+#[tokio::test]
+async fn test_tool_macro_calls_are_deduplicated() -> anyhow::Result<()> {
+    let (_server, client) = common::connect(Counter::default()).await?;
+    let tools = client.peer().list_tools(Default::default()).await?.tools;
+    let annotations = tools[0].annotations.as_ref().expect("annotations");
+    assert_eq!(annotations.idempotent_hint, Some(true));
+
+    let call = |key| {
+        client.peer().call_tool(
+            CallToolRequestParam {
+                name: "count".into(),
+                arguments: None,
+                meta: None,
+            }
+            .with_idempotency_key(key),
+        )
+    };
+    let text = |result: CallToolResult| result.content[0].as_text().expect("text").text.clone();
+    let (first, second) = tokio::join!(call("a"), call("a"));
+    assert_eq!(
+        (text(first?), text(second?)),
+        ("1".to_owned(), "1".to_owned())
+    );
+    assert_eq!(text(call("a").await?), "1");
+    assert_eq!(text(call("b").await?), "2");
+    client.cancel().await?;
+    Ok(())
+}
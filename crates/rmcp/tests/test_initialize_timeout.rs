@@ -0,0 +1,165 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::{SinkExt, StreamExt, channel::mpsc};
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    backoff::ExponentialBackoff,
+    model::{
+        ClientJsonRpcMessage, ClientRequest, JsonRpcRequest, ServerInfo, ServerJsonRpcMessage,
+        ServerMessage, ServerResult,
+    },
+    serve_client_with_options, serve_server,
+    service::{ClientInitializeOptions, InitializeTimeout},
+};
+
+/// A server slow to start, it answers the first initialize request after `delay` and counts the others
+fn slow_server(
+    delay: Option<Duration>,
+) -> (
+    mpsc::Sender<ClientJsonRpcMessage>,
+    mpsc::Receiver<ServerJsonRpcMessage>,
+    Arc<AtomicUsize>,
+) {
+    let (client_tx, mut server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let (server_tx, client_rx) = mpsc::channel(16);
+    let initialize_requests = Arc::new(AtomicUsize::new(0));
+    let count = initialize_requests.clone();
+    tokio::spawn(async move {
+        while let Some(message) = server_rx.next().await {
+            let ClientJsonRpcMessage::Request(JsonRpcRequest {
+                id,
+                request: ClientRequest::InitializeRequest(_),
+                ..
+            }) = message
+            else {
+                continue;
+            };
+            let (Some(delay), 0) = (delay, count.fetch_add(1, Ordering::SeqCst)) else {
+                continue;
+            };
+            let mut server_tx = server_tx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let response = ServerMessage::Response(
+                    ServerResult::InitializeResult(ServerInfo::default()),
+                    id,
+                );
+                let _ = server_tx.send(response.into_json_rpc_message()).await;
+            });
+        }
+    });
+    (client_tx, client_rx, initialize_requests)
+}
+
+#[tokio::test]
+async fn test_initialize_timeout() -> anyhow::Result<()> {
+    let (client_tx, client_rx, initialize_requests) = slow_server(None);
+    let options = ClientInitializeOptions::default()
+        .with_timeout(Duration::from_millis(50))
+        .with_retries(1);
+    let error = serve_client_with_options(
+        ClientHandlerService::new(None),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        options,
+    )
+    .await
+    .expect_err("the server never answers");
+    assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+    let timeout = error
+        .get_ref()
+        .and_then(|error| error.downcast_ref::<InitializeTimeout>())
+        .expect("an initialize timeout");
+    assert_eq!(
+        *timeout,
+        InitializeTimeout {
+            timeout: Duration::from_millis(50),
+            attempts: 2,
+        }
+    );
+    assert_eq!(initialize_requests.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_initialize_retry() -> anyhow::Result<()> {
+    // the first attempt times out, and the response is accepted during the second one
+    let (client_tx, client_rx, initialize_requests) = slow_server(Some(Duration::from_millis(150)));
+    let options = ClientInitializeOptions::default()
+        .with_timeout(Duration::from_millis(100))
+        .with_retries(2);
+    let client = serve_client_with_options(
+        ClientHandlerService::new(None),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        options,
+    )
+    .await?;
+    assert_eq!(client.peer().peer_info(), &ServerInfo::default());
+    // the response to the first request, the second one is ignored
+    assert_eq!(initialize_requests.load(Ordering::SeqCst), 2);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_initialize_retry_backoff() -> anyhow::Result<()> {
+    let (client_tx, client_rx, initialize_requests) = slow_server(None);
+    let backoff = ExponentialBackoff::new(Duration::from_millis(100), Duration::from_secs(1))
+        .with_jitter(0.0);
+    let options = ClientInitializeOptions::default()
+        .with_timeout(Duration::from_millis(50))
+        .with_retries(2)
+        .with_backoff(backoff);
+    let start = tokio::time::Instant::now();
+    let error = serve_client_with_options(
+        ClientHandlerService::new(None),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        options,
+    )
+    .await
+    .expect_err("the server never answers");
+    // 3 timeouts, the retries wait 100ms and 200ms longer
+    assert!(start.elapsed() >= Duration::from_millis(450));
+    assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+    assert_eq!(initialize_requests.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_initialize_retry_server_starting_late() -> anyhow::Result<()> {
+    #[derive(Debug, Clone, Default)]
+    pub struct Server;
+    impl ServerHandler for Server {}
+
+    // the server reads the transport only after the first attempt timed out,
+    // it answers both initialize requests before reading the initialized notification
+    let (client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let (server_tx, client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let server = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        serve_server(
+            ServerHandlerService::new(Server),
+            (server_tx.sink_map_err(std::io::Error::other), server_rx),
+        )
+        .await
+    });
+    let options = ClientInitializeOptions::default()
+        .with_timeout(Duration::from_millis(100))
+        .with_retries(2);
+    let client = serve_client_with_options(
+        ClientHandlerService::new(None),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        options,
+    )
+    .await?;
+    let server = server.await??;
+    assert_eq!(client.peer().peer_info(), &Server.get_info());
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
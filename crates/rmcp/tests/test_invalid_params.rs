@@ -0,0 +1,75 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServiceError,
+    model::{ClientRequest, ErrorCode, UnknownRequest},
+    service::RunningService,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+async fn connect() -> anyhow::Result<RunningService<ClientHandlerService>> {
+    let (_, client) = common::connect(Server).await?;
+    Ok(client)
+}
+
+fn error_code(result: Result<serde_json::Value, ServiceError>) -> ErrorCode {
+    match result {
+        Err(ServiceError::McpError(error)) => error.code,
+        result => panic!("expect an error, got {result:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_read_resource_without_uri() -> anyhow::Result<()> {
+    let client = connect().await?;
+    let result = client
+        .peer()
+        .request("resources/read", json!({}).as_object().cloned())
+        .await;
+    assert_eq!(error_code(result), ErrorCode::INVALID_PARAMS);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_prompt_with_malformed_arguments() -> anyhow::Result<()> {
+    let client = connect().await?;
+    let result = client
+        .peer()
+        .request(
+            "prompts/get",
+            json!({ "name": "greeting", "arguments": ["not", "an", "object"] })
+                .as_object()
+                .cloned(),
+        )
+        .await;
+    assert_eq!(error_code(result), ErrorCode::INVALID_PARAMS);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_method_not_found() -> anyhow::Result<()> {
+    let client = connect().await?;
+    let result = client.peer().request("experimental/echo", None).await;
+    assert_eq!(error_code(result), ErrorCode::METHOD_NOT_FOUND);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_invalid_params_error() {
+    let request = UnknownRequest::new(
+        "tools/call",
+        json!({ "arguments": {} }).as_object().cloned(),
+    );
+    let error = ClientRequest::invalid_params_error(&request).expect("a tools/call error");
+    assert!(error.to_string().contains("name"), "{error}");
+    let request = UnknownRequest::new("experimental/echo", None);
+    assert!(ClientRequest::invalid_params_error(&request).is_none());
+}
@@ -0,0 +1,85 @@
+use futures::{SinkExt, channel::mpsc};
+use rmcp::{
+    ClientHandlerService, ServiceError,
+    model::{ClientJsonRpcMessage, ClientRequest, PingRequest, ServerInfo, ServerJsonRpcMessage},
+    service::{PeerRequestOptions, RunningService, ServeConfig, serve_directly_with_config},
+};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+    })
+}
+
+/// A client of a server which never answers
+async fn connect(
+    config: ServeConfig,
+) -> anyhow::Result<(
+    RunningService<ClientHandlerService>,
+    mpsc::Receiver<ClientJsonRpcMessage>,
+    mpsc::Sender<ServerJsonRpcMessage>,
+)> {
+    let (client_tx, server_rx) = mpsc::channel(16);
+    let (server_tx, client_rx) = mpsc::channel(16);
+    let client = serve_directly_with_config(
+        ClientHandlerService::simple(),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+        ServerInfo::default(),
+        config,
+    )
+    .await?;
+    Ok((client, server_rx, server_tx))
+}
+
+#[tokio::test]
+async fn test_dropped_request_is_leaked() -> anyhow::Result<()> {
+    let config = ServeConfig::default().with_panic_on_leaked_requests(true);
+    let (client, _server_rx, _server_tx) = connect(config).await?;
+    let mut handle = client
+        .peer()
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    handle.await_sent().await?;
+    drop(handle);
+    let error = client.cancel().await.expect_err("the leak panics");
+    assert!(error.is_panic());
+    let message = error.into_panic();
+    let message = message
+        .downcast_ref::<String>()
+        .expect("a formatted message");
+    assert!(message.contains("1 request(s) leaked"), "{message}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_leaked_request_is_logged_by_default() -> anyhow::Result<()> {
+    let (client, _server_rx, _server_tx) = connect(ServeConfig::default()).await?;
+    let mut handle = client
+        .peer()
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    handle.await_sent().await?;
+    drop(handle);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_awaited_and_cancelled_requests_are_not_leaked() -> anyhow::Result<()> {
+    let config = ServeConfig::default().with_panic_on_leaked_requests(true);
+    let (client, _server_rx, _server_tx) = connect(config).await?;
+    let peer = client.peer().clone();
+    let cancelled = peer
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    cancelled.cancel(None).await?;
+    let pending = peer
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    client.cancel().await?;
+    assert!(matches!(
+        pending.await_response().await,
+        Err(ServiceError::Transport(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, ServerHandler, ServerHandlerService,
+    ServiceError,
+    model::{ServerCapabilities, ServerInfo},
+    service::RunningService,
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+#[derive(Debug, Clone)]
+struct Server {
+    capabilities: ServerCapabilities,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: self.capabilities.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Forwards the list changed notifications
+#[derive(Debug, Clone)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    changes: UnboundedSender<&'static str>,
+}
+
+impl ClientHandler for Client {
+    async fn on_tool_list_changed(&self) {
+        let _ = self.changes.send("tools");
+    }
+
+    async fn on_prompt_list_changed(&self) {
+        let _ = self.changes.send("prompts");
+    }
+
+    async fn on_resource_list_changed(&self) {
+        let _ = self.changes.send("resources");
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    capabilities: ServerCapabilities,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Client>>,
+    UnboundedReceiver<&'static str>,
+)> {
+    let (changes, changes_rx) = unbounded_channel();
+    let (server, client) = common::serve(
+        Server { capabilities },
+        Client {
+            peer: None,
+            changes,
+        },
+    )
+    .await?;
+    Ok((server, client, changes_rx))
+}
+
+#[tokio::test]
+async fn test_list_changed_notified() -> anyhow::Result<()> {
+    let capabilities = ServerCapabilities::builder()
+        .enable_prompts()
+        .enable_prompts_list_changed()
+        .enable_resources()
+        .enable_resources_list_changed()
+        .enable_tools()
+        .enable_tool_list_changed()
+        .build();
+    let (server, client, mut changes) = connect(capabilities).await?;
+    server.peer().notify_tools_changed().await?;
+    server.peer().notify_prompts_changed().await?;
+    server.peer().notify_resources_changed().await?;
+    // the notifications are handled concurrently
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.extend(changes.recv().await);
+    }
+    received.sort();
+    assert_eq!(received, ["prompts", "resources", "tools"]);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_changed_not_advertised() -> anyhow::Result<()> {
+    // the tools are advertised, but not their changes
+    let capabilities = ServerCapabilities::builder().enable_tools().build();
+    let (server, client, mut changes) = connect(capabilities).await?;
+    let peer = server.peer();
+    for result in [
+        peer.notify_tools_changed().await,
+        peer.notify_prompts_changed().await,
+        peer.notify_resources_changed().await,
+    ] {
+        assert!(matches!(
+            result,
+            Err(ServiceError::CapabilityNotSupported { .. })
+        ));
+    }
+    assert!(matches!(
+        peer.notify_tools_changed().await,
+        Err(ServiceError::CapabilityNotSupported {
+            capability: "tools.listChanged"
+        })
+    ));
+
+    // the unchecked notification is still sent
+    peer.notify_tool_list_changed().await?;
+    assert_eq!(changes.recv().await, Some("tools"));
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use rmcp::{
+    ClientHandler, Peer, RoleClient, ServerHandler,
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Forwards the log messages
+#[derive(Debug, Clone)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    messages: UnboundedSender<LoggingMessageNotificationParam>,
+}
+
+impl ClientHandler for Client {
+    async fn on_logging_message(&self, params: LoggingMessageNotificationParam) {
+        let _ = self.messages.send(params);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct QueryEvent {
+    query: String,
+    elapsed_ms: u64,
+}
+
+#[tokio::test]
+async fn test_log_messages() -> anyhow::Result<()> {
+    let (messages, mut messages_rx) = unbounded_channel();
+    let (server, client) = common::serve(
+        Server,
+        Client {
+            peer: None,
+            messages,
+        },
+    )
+    .await?;
+
+    server
+        .peer()
+        .log_text(LoggingLevel::Info, Some("db"), "connected")
+        .await?;
+    let event = QueryEvent {
+        query: "select 1".to_owned(),
+        elapsed_ms: 3,
+    };
+    server
+        .peer()
+        .log_structured(LoggingLevel::Warning, None, &event)
+        .await?;
+
+    let text = messages_rx.recv().await.expect("a text message");
+    assert_eq!(text.level, LoggingLevel::Info);
+    assert_eq!(text.logger.as_deref(), Some("db"));
+    assert_eq!(text.text_data(), Some("connected"));
+
+    let structured = messages_rx.recv().await.expect("a structured message");
+    assert_eq!(structured.level, LoggingLevel::Warning);
+    assert_eq!(structured.logger, None);
+    assert_eq!(structured.text_data(), None);
+    assert_eq!(structured.data_as::<QueryEvent>()?, event);
+    assert!(text.data_as::<QueryEvent>().is_err());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandlerService, RoleServer, ServerHandler, ServerHandlerService,
+    model::{
+        ClientInfo, Implementation, ListToolsResult, PaginatedRequestParam, ServerCapabilities,
+        ServerInfo, Tool,
+    },
+    serve_client_no_init, serve_server,
+    service::RequestContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _: PaginatedRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: vec![Tool::new("echo", "Echo", Arc::new(Default::default()))],
+            meta: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_manual_initialize() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = common::transports();
+    let server = tokio::spawn(serve_server(
+        ServerHandlerService::new(Server),
+        server_transport,
+    ));
+    let client = serve_client_no_init(ClientHandlerService::new(None), client_transport).await?;
+    // nothing is known about the server yet
+    assert_eq!(client.peer().peer_info(), &ServerInfo::default());
+
+    // e.g. a proxy forwards the info of its own client
+    let client_info = ClientInfo {
+        client_info: Implementation {
+            name: "forwarded".into(),
+            version: "1.0.0".into(),
+        },
+        ..Default::default()
+    };
+    let server_info = client.initialize(client_info).await?;
+    assert_eq!(server_info, Server.get_info());
+    // the cached info is updated, so the capabilities are known
+    assert_eq!(client.peer().peer_info(), &server_info);
+    assert!(client.peer().peer_info().capabilities.tools.is_some());
+    client.notify_initialized().await?;
+
+    let server = server.await??;
+    assert_eq!(server.peer().peer_info().client_info.name, "forwarded");
+    let tools = client.list_tools(Default::default()).await?;
+    assert_eq!(tools.tools[0].name, "echo");
+    client.cancel().await?;
+    Ok(())
+}
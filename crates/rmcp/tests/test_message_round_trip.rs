@@ -0,0 +1,46 @@
+use std::{fmt::Debug, fs, path::Path};
+
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+fn message_id(json: &[u8]) -> Option<Value> {
+    serde_json::from_slice::<Value>(json)
+        .ok()?
+        .get("id")
+        .cloned()
+}
+
+/// The property of the fuzz targets, checked against their seed corpus
+fn assert_corpus_round_trip<T>(target: &str)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../fuzz/corpus")
+        .join(target);
+    let mut count = 0;
+    for entry in fs::read_dir(corpus).expect("read corpus") {
+        let path = entry.expect("read corpus entry").path();
+        let data = fs::read(&path).expect("read message");
+        let message: T =
+            serde_json::from_slice(&data).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        let json = serde_json::to_vec(&message).expect("serialize a parsed message");
+        let parsed: T = serde_json::from_slice(&json).expect("parse a serialized message");
+        assert_eq!(parsed, message, "{}", path.display());
+        assert_eq!(serde_json::to_vec(&parsed).expect("serialize"), json);
+        assert_eq!(message_id(&json), message_id(&data), "{}", path.display());
+        count += 1;
+    }
+    assert!(count > 0, "empty corpus");
+}
+
+#[test]
+fn test_client_message_corpus_round_trip() {
+    assert_corpus_round_trip::<ClientJsonRpcMessage>("client_message");
+}
+
+#[test]
+fn test_server_message_corpus_round_trip() {
+    assert_corpus_round_trip::<ServerJsonRpcMessage>("server_message");
+}
@@ -0,0 +1,113 @@
+use rmcp::{
+    ServiceError,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientRequest, Content, ErrorCode, PingRequest,
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerResult,
+    },
+    test_util::MockServer,
+};
+
+fn sum(request: CallToolRequestParam) -> Result<CallToolResult, rmcp::Error> {
+    let arguments = request.arguments.unwrap_or_default();
+    let sum = ["a", "b"]
+        .iter()
+        .filter_map(|key| arguments.get(*key)?.as_i64())
+        .sum::<i64>();
+    Ok(CallToolResult::success(vec![Content::text(
+        sum.to_string(),
+    )]))
+}
+
+#[tokio::test]
+async fn test_scripted_tool_call() -> anyhow::Result<()> {
+    let mock = MockServer::new().on_call_tool("sum", sum).connect().await?;
+    let tools = mock.client.list_tools(Default::default()).await?;
+    assert_eq!(tools.tools.len(), 1);
+    assert_eq!(tools.tools[0].name, "sum");
+
+    let result = mock
+        .client
+        .call_tool(CallToolRequestParam {
+            name: "sum".into(),
+            arguments: serde_json::json!({ "a": 1, "b": 2 }).as_object().cloned(),
+            meta: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("3")
+    );
+
+    let call = mock.assert_tool_called("sum");
+    assert_eq!(call.arguments.unwrap()["a"], 1);
+    mock.assert_request_count("tools/call", 1);
+    mock.assert_requested("tools/list");
+    assert_eq!(mock.requests().len(), 2);
+    mock.client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unmatched_is_method_not_found() -> anyhow::Result<()> {
+    let mock = MockServer::new().on_call_tool("sum", sum).connect().await?;
+    let error = mock
+        .client
+        .call_tool(CallToolRequestParam {
+            name: "product".into(),
+            arguments: None,
+            meta: None,
+        })
+        .await
+        .expect_err("no rule for product");
+    let ServiceError::McpError(error) = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+    assert!(mock.tool_calls("sum").is_empty());
+    assert_eq!(mock.tool_calls("product").len(), 1);
+
+    // ping is answered by default
+    mock.client
+        .send_request(ClientRequest::PingRequest(PingRequest {
+            method: Default::default(),
+        }))
+        .await?;
+    mock.client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rules_in_order() -> anyhow::Result<()> {
+    let mock = MockServer::new()
+        .on_read_resource("str:///a", |request| {
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text("a", request.uri)],
+                meta: None,
+            })
+        })
+        .on("resources/read", |_| {
+            Err(rmcp::Error::resource_not_found("not scripted", None))
+        })
+        .on_match(|_| true, |_| Ok(ServerResult::empty(())))
+        .connect()
+        .await?;
+    let read = |uri: &str| {
+        mock.client
+            .read_resource(ReadResourceRequestParam::new(uri))
+    };
+    assert!(read("str:///a").await.is_ok());
+    assert!(matches!(
+        read("str:///b").await,
+        Err(ServiceError::McpError(error)) if error.code == ErrorCode::RESOURCE_NOT_FOUND
+    ));
+    mock.assert_request_count("resources/read", 2);
+    // the catch-all rule overrides the listing of the tools
+    assert!(matches!(
+        mock.client.list_tools(Default::default()).await,
+        Err(ServiceError::UnexpectedResponse)
+    ));
+    // the initialized notification is part of the handshake
+    assert!(mock.notifications().is_empty());
+    mock.client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use futures::{StreamExt, channel::mpsc};
+use rmcp::{
+    ServerHandler, ServerHandlerService,
+    model::{
+        ClientInfo, ClientJsonRpcMessage, ServerJsonRpcMessage, ServerNotification,
+        ToolListChangedNotification,
+    },
+    service::serve_directly,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_sent_notification_is_acknowledged() -> anyhow::Result<()> {
+    let (server_tx, mut client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let (_client_tx, server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let service = serve_directly(
+        ServerHandlerService::new(Server),
+        (server_tx, server_rx),
+        ClientInfo::default(),
+    )
+    .await?;
+
+    // the notification is written, so the sender must not see a disconnection
+    service
+        .peer()
+        .send_notification(ServerNotification::ToolListChangedNotification(
+            ToolListChangedNotification {
+                method: Default::default(),
+            },
+        ))
+        .await?;
+    let message = client_rx.next().await.expect("notification");
+    assert!(matches!(message, ServerJsonRpcMessage::Notification(_)));
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use rmcp::{ServerHandler, ServerHandlerService, serve_server};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_malformed_request_is_answered_with_a_parse_error() -> anyhow::Result<()> {
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let server = tokio::spawn(serve_server(ServerHandlerService::new(Server), server_io));
+    let (reader, mut writer) = tokio::io::split(client_io);
+    let mut lines = BufReader::new(reader).lines();
+    let mut read = async || -> anyhow::Result<Value> {
+        let line = lines.next_line().await?.expect("a line");
+        Ok(serde_json::from_str(&line)?)
+    };
+
+    let initialize = json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": { "name": "raw", "version": "0.0.0" },
+        },
+    });
+    writer
+        .write_all(format!("{initialize}\n").as_bytes())
+        .await?;
+    assert_eq!(read().await?["id"], 0);
+    writer
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n")
+        .await?;
+    let _server = server.await??;
+
+    // not UTF-8 in a string, the id can still be read
+    writer
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"\xc3\x28\"}\n")
+        .await?;
+    let response = read().await?;
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["error"]["code"], -32700);
+
+    // without a readable id, the line is skipped
+    writer.write_all(b"{\"id\":2,\"method\n").await?;
+    // and the connection is still served
+    writer
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"ping\"}\n")
+        .await?;
+    let response = read().await?;
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["result"], json!({}));
+    Ok(())
+}
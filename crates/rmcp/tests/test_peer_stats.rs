@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandlerService, RoleServer, ServerHandler, ServerHandlerService,
+    model::{CallToolRequestParam, CallToolResult, ClientRequest, Request},
+    service::{PeerRequestOptions, RequestContext, RunningService},
+};
+use tokio::sync::{Notify, mpsc::UnboundedSender};
+
+/// Its tool only answers once released
+#[derive(Debug, Clone)]
+struct Server {
+    started: UnboundedSender<()>,
+    release: Arc<Notify>,
+}
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let _ = self.started.send(());
+        self.release.notified().await;
+        Ok(CallToolResult::success(vec![]))
+    }
+}
+
+async fn connect(
+    server: Server,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService>,
+)> {
+    common::connect(server).await
+}
+
+#[tokio::test]
+async fn test_stats_after_round_trips() -> anyhow::Result<()> {
+    let (started, _started_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (server, client) = connect(Server {
+        started,
+        release: Default::default(),
+    })
+    .await?;
+    let stats = client.stats();
+    assert_eq!(stats.messages_sent, 0);
+    assert_eq!(stats.last_activity, None);
+    assert_eq!(stats.last_ping_rtt, None);
+
+    for _ in 0..3 {
+        client.ping().await?;
+    }
+    let stats = client.stats();
+    assert_eq!(stats.messages_sent, 3);
+    assert_eq!(stats.messages_received, 3);
+    assert_eq!(stats.requests_in_flight, 0);
+    assert!(stats.last_activity.is_some());
+    assert!(stats.last_ping_rtt.is_some());
+
+    let stats = server.stats();
+    assert_eq!(stats.messages_sent, 3);
+    assert_eq!(stats.messages_received, 3);
+    assert_eq!(stats.requests_handling, 0);
+    // the server hasn't pinged
+    assert_eq!(stats.last_ping_rtt, None);
+
+    // a notification counts as a message
+    server.ping().await?;
+    client.notify_roots_list_changed().await?;
+    assert_eq!(client.stats().messages_sent, 5);
+    assert!(server.stats().last_ping_rtt.is_some());
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stats_requests_in_flight() -> anyhow::Result<()> {
+    let (started, mut started_rx) = tokio::sync::mpsc::unbounded_channel();
+    let release = Arc::new(Notify::new());
+    let (server, client) = connect(Server {
+        started,
+        release: release.clone(),
+    })
+    .await?;
+    let mut handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(Request {
+                method: Default::default(),
+                params: CallToolRequestParam {
+                    name: "wait".into(),
+                    arguments: None,
+                    meta: None,
+                },
+            }),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    handle.await_sent().await?;
+    started_rx.recv().await;
+    assert_eq!(client.stats().requests_in_flight, 1);
+    assert_eq!(server.stats().requests_handling, 1);
+    let before = client.stats().last_activity;
+
+    release.notify_one();
+    handle.await_response().await?;
+    assert_eq!(client.stats().requests_in_flight, 0);
+    assert_eq!(server.stats().requests_handling, 0);
+    assert!(client.stats().last_activity > before);
+    client.cancel().await?;
+    Ok(())
+}
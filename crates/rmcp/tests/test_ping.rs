@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use futures::{SinkExt, channel::mpsc};
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServerHandlerService, ServiceError,
+    model::PingRequestMethod,
+    service::{PeerRequestOptions, RequestContext, serve_directly},
+};
+
+#[derive(Debug, Clone, Default)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Fails the pings if they ever reach it
+#[derive(Debug, Clone, Default)]
+struct NoPingServer;
+
+impl ServerHandler for NoPingServer {
+    async fn ping(&self, _context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        Err(McpError::method_not_found::<PingRequestMethod>())
+    }
+}
+
+#[tokio::test]
+async fn test_ping_round_trip() -> anyhow::Result<()> {
+    let (server, client) = common::connect(Server).await?;
+
+    client.peer().ping().await?;
+    server.peer().ping().await?;
+    assert!(client.peer().stats().last_ping_rtt.is_some());
+    assert!(server.peer().stats().last_ping_rtt.is_some());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ping_answered_by_serve_loop() -> anyhow::Result<()> {
+    let (server, client) = common::connect(NoPingServer).await?;
+
+    client.peer().ping().await?;
+    let response = client.peer().request("ping", None).await?;
+    assert_eq!(response, serde_json::json!({}));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ping_timeout() -> anyhow::Result<()> {
+    // the client never answers
+    let (to_client, _client_rx) = mpsc::channel(16);
+    let server = serve_directly(
+        ServerHandlerService::new(Server),
+        (
+            to_client.sink_map_err(std::io::Error::other),
+            futures::stream::pending(),
+        ),
+        Default::default(),
+    )
+    .await?;
+    let peer = server.peer().clone();
+
+    peer.set_request_timeout(Some(Duration::from_millis(50)));
+    assert_eq!(peer.request_timeout(), Some(Duration::from_millis(50)));
+    let error = peer.ping().await.expect_err("no answer");
+    assert!(
+        matches!(error, ServiceError::Timeout { timeout } if timeout == Duration::from_millis(50)),
+        "{error}"
+    );
+
+    // the timeout of a request overrides the one of the peer
+    let handle = peer
+        .send_cancellable_request(
+            rmcp::model::ServerRequest::PingRequest(rmcp::model::PingRequest {
+                method: Default::default(),
+            }),
+            PeerRequestOptions::no_options().with_timeout(Duration::from_millis(10)),
+        )
+        .await?;
+    assert!(matches!(
+        handle.await_response().await,
+        Err(ServiceError::Timeout { timeout }) if timeout == Duration::from_millis(10)
+    ));
+
+    server.cancel().await?;
+    Ok(())
+}
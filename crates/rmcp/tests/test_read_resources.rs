@@ -0,0 +1,80 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceError,
+    model::{ReadResourceRequestParam, ReadResourceResult, ResourceContents},
+    service::RequestContext,
+};
+use tokio::sync::Barrier;
+
+/// Answers a read once three reads are in flight
+#[derive(Debug, Clone)]
+struct Server {
+    barrier: Arc<Barrier>,
+    reads: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for Server {
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        if request.uri == "file:///missing" {
+            return Err(rmcp::Error::resource_not_found("no such file", None));
+        }
+        self.barrier.wait().await;
+        let name = request.uri.trim_start_matches("file:///").to_owned();
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(name, request.uri)],
+            meta: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_read_resources() -> anyhow::Result<()> {
+    let reads = Arc::new(AtomicUsize::new(0));
+    let (server, client) = common::connect(Server {
+        barrier: Arc::new(Barrier::new(3)),
+        reads: reads.clone(),
+    })
+    .await?;
+
+    // the reads are only answered when the three are in flight at once
+    let results = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.peer().read_resources([
+            "file:///a",
+            "file:///b",
+            "file:///a",
+            "file:///c",
+            "file:///missing",
+        ]),
+    )
+    .await?;
+    assert_eq!(results.len(), 4);
+    assert_eq!(reads.load(Ordering::SeqCst), 4);
+    for name in ["a", "b", "c"] {
+        let uri = format!("file:///{name}");
+        let result = results[&uri].as_ref().expect("a read resource");
+        assert_eq!(result.contents, [ResourceContents::text(name, uri)]);
+    }
+    assert!(matches!(
+        results["file:///missing"],
+        Err(ServiceError::McpError(_))
+    ));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, ServerHandler, ServerHandlerService,
+    ServiceError,
+    handler::client::{LimitPolicy, RequestLimit},
+    model::{
+        Content, CreateMessageRequestParam, CreateMessageResult, LoggingLevel,
+        LoggingMessageNotificationParam, Role, SamplingMessage,
+    },
+    service::{RequestContext, RunningService},
+};
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Clone, Default)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Answers a sampling once the test opens the gate
+#[derive(Debug, Clone)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    limit: RequestLimit,
+    gate: Arc<Semaphore>,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+    logs: Arc<AtomicUsize>,
+}
+
+impl Client {
+    fn new(limit: RequestLimit) -> Self {
+        Self {
+            peer: None,
+            limit,
+            gate: Arc::new(Semaphore::new(0)),
+            running: Default::default(),
+            max_running: Default::default(),
+            logs: Default::default(),
+        }
+    }
+}
+
+impl ClientHandler for Client {
+    fn can_create_message(&self) -> bool {
+        true
+    }
+
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::Error> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        self.gate.acquire().await.unwrap().forget();
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(CreateMessageResult {
+            model: "gate".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("done"),
+            },
+            meta: None,
+        })
+    }
+
+    fn request_limit(&self) -> Option<&RequestLimit> {
+        Some(&self.limit)
+    }
+
+    async fn on_logging_message(&self, _params: LoggingMessageNotificationParam) {
+        self.logs.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    client: Client,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Client>>,
+)> {
+    common::serve(Server, client).await
+}
+
+fn param() -> CreateMessageRequestParam {
+    CreateMessageRequestParam {
+        messages: vec![SamplingMessage {
+            role: Role::User,
+            content: Content::text("wait"),
+        }],
+        model_preferences: None,
+        system_prompt: None,
+        include_context: None,
+        temperature: None,
+        max_tokens: 16,
+        stop_sequences: None,
+        metadata: None,
+        meta: None,
+    }
+}
+
+async fn wait_in_flight(limit: &RequestLimit, count: usize) -> anyhow::Result<()> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while limit.in_flight() < count {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reject_over_limit() -> anyhow::Result<()> {
+    let client = Client::new(RequestLimit::new(2, LimitPolicy::Reject));
+    let (server, running_client) = connect(client.clone()).await?;
+
+    let pending = (0..2)
+        .map(|_| {
+            let peer = server.peer().clone();
+            tokio::spawn(async move { peer.create_message(param()).await })
+        })
+        .collect::<Vec<_>>();
+    wait_in_flight(&client.limit, 2).await?;
+
+    let error = server.create_message(param()).await.unwrap_err();
+    let ServiceError::McpError(error) = error else {
+        panic!("expected a busy error, got {error:?}");
+    };
+    assert_eq!(error.code, RequestLimit::BUSY);
+
+    // neither the pings nor the notifications count against the limit
+    server.ping().await?;
+    server
+        .notify_logging_message(LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            logger: None,
+            data: serde_json::json!("still there"),
+        })
+        .await?;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.logs.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await?;
+
+    client.gate.add_permits(2);
+    for pending in pending {
+        let result = pending.await??;
+        assert_eq!(result.message.content.as_text().unwrap().text, "done");
+    }
+    assert_eq!(client.limit.in_flight(), 0);
+    assert_eq!(client.max_running.load(Ordering::SeqCst), 2);
+    running_client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_queue_over_limit() -> anyhow::Result<()> {
+    let client = Client::new(RequestLimit::new(1, LimitPolicy::Queue));
+    let (server, running_client) = connect(client.clone()).await?;
+
+    let pending = (0..3)
+        .map(|_| {
+            let peer = server.peer().clone();
+            tokio::spawn(async move { peer.create_message(param()).await })
+        })
+        .collect::<Vec<_>>();
+    wait_in_flight(&client.limit, 1).await?;
+    server.ping().await?;
+    assert_eq!(client.running.load(Ordering::SeqCst), 1);
+
+    client.gate.add_permits(3);
+    for pending in pending {
+        pending.await??;
+    }
+    assert_eq!(client.max_running.load(Ordering::SeqCst), 1);
+    running_client.cancel().await?;
+    Ok(())
+}
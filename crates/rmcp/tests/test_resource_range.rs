@@ -0,0 +1,94 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, Peer, RoleClient, RoleServer, ServerHandler, ServerHandlerService,
+    model::{
+        ByteRange, ContentRange, ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+    },
+    service::{RequestContext, RunningService},
+};
+
+const RESOURCE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+/// Serves [`RESOURCE`], honoring the range if `ranges` is set
+#[derive(Debug, Clone, Default)]
+pub struct Server {
+    ranges: bool,
+}
+
+impl ServerHandler for Server {
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        match request.range()? {
+            Some(range) if self.ranges => {
+                ReadResourceResult::blob_range(RESOURCE, request.uri, range)
+            }
+            _ => Ok(ReadResourceResult {
+                contents: vec![ResourceContents::blob(RESOURCE, request.uri)],
+                meta: None,
+            }),
+        }
+    }
+}
+
+async fn connect(
+    server: Server,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Option<Peer<RoleClient>>>>,
+)> {
+    common::connect(server).await
+}
+
+fn read(range: ByteRange) -> ReadResourceRequestParam {
+    ReadResourceRequestParam::new("file:///fox.txt").with_range(range)
+}
+
+fn bytes(result: &ReadResourceResult) -> Vec<u8> {
+    result.contents[0].decode_blob().unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_read_range() -> anyhow::Result<()> {
+    let (_server, client) = connect(Server { ranges: true }).await?;
+
+    let result = client.read_resource(read(ByteRange::new(4, 5))).await?;
+    assert_eq!(bytes(&result), b"quick");
+    assert_eq!(
+        result.content_range()?,
+        Some(ContentRange {
+            offset: 4,
+            length: 5,
+            total: RESOURCE.len() as u64,
+        })
+    );
+
+    // the range is cut at the end of the resource
+    let result = client.read_resource(read(ByteRange::new(40, 10))).await?;
+    assert_eq!(bytes(&result), b"dog");
+    assert_eq!(
+        result.content_range()?.unwrap().end(),
+        RESOURCE.len() as u64
+    );
+
+    let error = client
+        .read_resource(read(ByteRange::from_offset(100)))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("after the end of the resource"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_ignored() -> anyhow::Result<()> {
+    let (_server, client) = connect(Server { ranges: false }).await?;
+    let result = client.read_resource(read(ByteRange::new(4, 5))).await?;
+    assert_eq!(bytes(&result), RESOURCE);
+    assert_eq!(result.content_range()?, None);
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, RoleServer, ServerHandler,
+    ServerHandlerService, ServiceError,
+    handler::client::ResourceRefetcher,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo, SubscribeRequestParam,
+        UnsubscribeRequestParam,
+    },
+    service::{RequestContext, RunningService},
+};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Counts the reads, a resource reads as the number of reads so far, the first read is slow if `slow_first_read`
+#[derive(Debug, Clone, Default)]
+struct Counting {
+    reads: Arc<AtomicUsize>,
+    slow_first_read: bool,
+}
+
+impl ServerHandler for Counting {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let reads = self.reads.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.slow_first_read && reads == 1 {
+            tokio::time::sleep(DEBOUNCE * 3).await;
+        }
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(reads.to_string(), request.uri)],
+            meta: None,
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        _: SubscribeRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::Error> {
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        _: UnsubscribeRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    refetcher: ResourceRefetcher,
+    updates: Arc<AtomicUsize>,
+}
+
+impl ClientHandler for Client {
+    fn resource_refetcher(&self) -> Option<&ResourceRefetcher> {
+        Some(&self.refetcher)
+    }
+
+    async fn on_resource_updated(&self, _: ResourceUpdatedNotificationParam) {
+        self.updates.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+struct Connection {
+    server: RunningService<ServerHandlerService<Counting>>,
+    client: RunningService<ClientHandlerService<Client>>,
+    refetcher: ResourceRefetcher,
+    updates: Arc<AtomicUsize>,
+    reads: Arc<AtomicUsize>,
+}
+
+async fn connect() -> anyhow::Result<Connection> {
+    connect_to(Counting::default()).await
+}
+
+async fn connect_to(counting: Counting) -> anyhow::Result<Connection> {
+    let reads = counting.reads.clone();
+    let handler = Client {
+        refetcher: ResourceRefetcher::new(DEBOUNCE),
+        ..Default::default()
+    };
+    let refetcher = handler.refetcher.clone();
+    let updates = handler.updates.clone();
+    let (server, client) = common::serve(counting, handler).await?;
+    Ok(Connection {
+        server,
+        client,
+        refetcher,
+        updates,
+        reads,
+    })
+}
+
+impl Connection {
+    async fn subscribe_refetching(
+        &self,
+        uri: &str,
+    ) -> anyhow::Result<UnboundedReceiver<Result<ReadResourceResult, ServiceError>>> {
+        let (tx, rx) = unbounded_channel();
+        self.refetcher
+            .subscribe(self.client.peer(), uri, move |result| {
+                let _ = tx.send(result);
+            })
+            .await?;
+        Ok(rx)
+    }
+
+    async fn update(&self, uri: &str) -> anyhow::Result<()> {
+        self.server
+            .peer()
+            .notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.into() })
+            .await?;
+        Ok(())
+    }
+}
+
+fn text(result: Result<ReadResourceResult, ServiceError>) -> String {
+    match result.expect("read resource").contents.remove(0) {
+        ResourceContents::TextResourceContents { text, .. } => text,
+        contents => panic!("unexpected contents {contents:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_refetch_debounced() -> anyhow::Result<()> {
+    let connection = connect().await?;
+    let mut results = connection.subscribe_refetching("str:///log").await?;
+    for _ in 0..5 {
+        connection.update("str:///log").await?;
+    }
+    let result = tokio::time::timeout(DEBOUNCE * 5, results.recv()).await?;
+    assert_eq!(text(result.expect("a refetch")), "1");
+    tokio::time::sleep(DEBOUNCE * 2).await;
+    assert!(results.try_recv().is_err(), "the updates are collapsed");
+
+    // a later update is fetched again
+    connection.update("str:///log").await?;
+    let result = tokio::time::timeout(DEBOUNCE * 5, results.recv()).await?;
+    assert_eq!(text(result.expect("a refetch")), "2");
+    assert_eq!(connection.updates.load(Ordering::SeqCst), 0);
+    connection.client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refetch_opt_in() -> anyhow::Result<()> {
+    let connection = connect().await?;
+    connection
+        .client
+        .peer()
+        .subscribe(SubscribeRequestParam {
+            uri: "str:///plain".into(),
+        })
+        .await?;
+    connection.update("str:///plain").await?;
+    tokio::time::sleep(DEBOUNCE * 2).await;
+    assert_eq!(connection.updates.load(Ordering::SeqCst), 1);
+    assert_eq!(connection.reads.load(Ordering::SeqCst), 0);
+    assert!(!connection.refetcher.is_subscribed("str:///plain"));
+    connection.client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refetch_unsubscribe_drops_pending() -> anyhow::Result<()> {
+    let connection = connect().await?;
+    let mut results = connection.subscribe_refetching("str:///log").await?;
+    connection.update("str:///log").await?;
+    // let the client handle the notification before unsubscribing
+    tokio::time::sleep(DEBOUNCE / 4).await;
+    connection.refetcher.unsubscribe("str:///log").await?;
+    tokio::time::sleep(DEBOUNCE * 2).await;
+    assert!(results.try_recv().is_err());
+    assert_eq!(connection.reads.load(Ordering::SeqCst), 0);
+    connection.client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refetch_drops_superseded_read() -> anyhow::Result<()> {
+    let connection = connect_to(Counting {
+        slow_first_read: true,
+        ..Default::default()
+    })
+    .await?;
+    let mut results = connection.subscribe_refetching("str:///log").await?;
+    connection.update("str:///log").await?;
+    // the second read starts while the first one is still running, and completes first
+    tokio::time::sleep(DEBOUNCE * 3 / 2).await;
+    connection.update("str:///log").await?;
+    let result = tokio::time::timeout(DEBOUNCE * 5, results.recv()).await?;
+    assert_eq!(text(result.expect("a refetch")), "2");
+    tokio::time::sleep(DEBOUNCE * 4).await;
+    assert!(results.try_recv().is_err(), "the first read is stale");
+    assert_eq!(connection.reads.load(Ordering::SeqCst), 2);
+    connection.client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, ServerHandler, ServerHandlerService,
+    ServiceError,
+    handler::client::Roots,
+    model::{ErrorCode, ListRootsResult, Root},
+    service::{RequestContext, RunningService},
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Lists its roots with the `list_roots` hook
+#[derive(Debug, Clone)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    roots: Vec<Root>,
+}
+
+impl ClientHandler for Client {
+    fn can_list_roots(&self) -> bool {
+        true
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, rmcp::Error> {
+        Ok(ListRootsResult {
+            roots: self.roots.clone(),
+            meta: None,
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    roots: Vec<Root>,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Client>>,
+)> {
+    common::serve(Server, Client { peer: None, roots }).await
+}
+
+#[tokio::test]
+async fn test_list_roots_hook() -> anyhow::Result<()> {
+    let roots = vec![
+        Root::new("file:///workspace/app").with_name("app"),
+        Root::new("file:///workspace/lib"),
+    ];
+    let (server, client) = connect(roots.clone()).await?;
+    assert!(server.peer_info().capabilities.roots.is_some());
+    assert_eq!(server.list_roots().await?.roots, roots);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_roots_not_file_url() -> anyhow::Result<()> {
+    let (server, client) = connect(vec![
+        Root::new("file:///workspace"),
+        Root::new("https://example.com/repo"),
+    ])
+    .await?;
+    let error = server
+        .list_roots()
+        .await
+        .expect_err("a root isn't a file URL");
+    assert!(matches!(
+        error,
+        ServiceError::McpError(error) if error.code == ErrorCode::INTERNAL_ERROR
+    ));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_roots_rejects_not_file_url() -> anyhow::Result<()> {
+    assert!(Roots::new([Root::new("relative/path")]).is_err());
+
+    let (_server, client) = connect(Vec::new()).await?;
+    let roots = Roots::new([Root::new("file:///workspace")])?;
+    let result = roots
+        .update(client.peer(), [Root::new("ftp://example.com")])
+        .await;
+    assert!(matches!(result, Err(ServiceError::McpError(_))));
+    // the roots are left unchanged
+    assert_eq!(roots.list().roots, [Root::new("file:///workspace")]);
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, ServerHandler, ServerHandlerService,
+    ServiceError,
+    model::{
+        Content, CreateMessageRequest, CreateMessageRequestParam, CreateMessageResult, ErrorCode,
+        Role, SamplingMessage, ServerRequest,
+    },
+    service::{RequestContext, RunningService},
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+/// Answers with the text of the last message in upper case
+#[derive(Debug, Clone, Default)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+    sampling: bool,
+    calls: Arc<AtomicUsize>,
+}
+
+impl ClientHandler for Client {
+    fn can_create_message(&self) -> bool {
+        self.sampling
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let text = params
+            .messages
+            .last()
+            .and_then(|message| Some(message.content.as_text()?.text.to_uppercase()))
+            .unwrap_or_default();
+        Ok(CreateMessageResult {
+            model: "upper".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text(text),
+            },
+            meta: None,
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect<H: ClientHandler>(
+    client: H,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<H>>,
+)> {
+    common::serve(Server, client).await
+}
+
+fn param(messages: &[&str]) -> CreateMessageRequestParam {
+    CreateMessageRequestParam {
+        messages: messages
+            .iter()
+            .map(|text| SamplingMessage {
+                role: Role::User,
+                content: Content::text(*text),
+            })
+            .collect(),
+        model_preferences: None,
+        system_prompt: None,
+        include_context: None,
+        temperature: None,
+        max_tokens: 64,
+        stop_sequences: None,
+        metadata: None,
+        meta: None,
+    }
+}
+
+#[tokio::test]
+async fn test_create_message() -> anyhow::Result<()> {
+    let (server, client) = connect(Client {
+        sampling: true,
+        ..Default::default()
+    })
+    .await?;
+    let result = server.create_message(param(&["hi", "hello"])).await?;
+    assert_eq!(result.model, "upper");
+    assert_eq!(result.message.role, Role::Assistant);
+    assert_eq!(
+        result
+            .message
+            .content
+            .as_text()
+            .map(|text| text.text.as_str()),
+        Some("HELLO")
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+/// The requests with no messages, and with `max_tokens` 0
+fn invalid_params() -> [CreateMessageRequestParam; 2] {
+    let no_tokens = CreateMessageRequestParam {
+        max_tokens: 0,
+        ..param(&["hi"])
+    };
+    [param(&[]), no_tokens]
+}
+
+fn is_invalid_params(error: ServiceError) -> bool {
+    matches!(error, ServiceError::McpError(error) if error.code == ErrorCode::INVALID_PARAMS)
+}
+
+#[tokio::test]
+async fn test_create_message_invalid_params() -> anyhow::Result<()> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (server, client) = connect(Client {
+        sampling: true,
+        calls: calls.clone(),
+        ..Default::default()
+    })
+    .await?;
+    let sent = server.peer().stats().messages_sent;
+    for params in invalid_params() {
+        let error = server
+            .create_message(params)
+            .await
+            .expect_err("the server rejects it");
+        assert!(is_invalid_params(error));
+    }
+    assert_eq!(server.peer().stats().messages_sent, sent);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    client.cancel().await?;
+    Ok(())
+}
+
+/// A server which doesn't check the params before sending them
+#[tokio::test]
+async fn test_client_rejects_invalid_params() -> anyhow::Result<()> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (server, client) = connect(Client {
+        sampling: true,
+        calls: calls.clone(),
+        ..Default::default()
+    })
+    .await?;
+    for params in invalid_params() {
+        let error = server
+            .send_request(ServerRequest::CreateMessageRequest(CreateMessageRequest {
+                method: Default::default(),
+                params,
+            }))
+            .await
+            .expect_err("the client rejects it");
+        assert!(is_invalid_params(error));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_message_not_supported() -> anyhow::Result<()> {
+    // the default handler doesn't answer sampling
+    let (server, client) = connect(None).await?;
+    assert!(server.peer_info().capabilities.sampling.is_none());
+    let error = server
+        .create_message(param(&["hi"]))
+        .await
+        .expect_err("sampling isn't supported");
+    assert!(matches!(
+        error,
+        ServiceError::McpError(error) if error.code == ErrorCode::METHOD_NOT_FOUND
+    ));
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, ServerHandler, ServerHandlerService,
+    model::{
+        Content, CreateMessageRequestParam, CreateMessageResult, NumberOrString,
+        ProgressNotificationParam, RequestMeta, Role, SamplingMessage,
+    },
+    service::{RequestContext, RunningService},
+};
+
+/// Collects the partial text streamed by the client
+#[derive(Debug, Clone, Default)]
+struct Server {
+    partials: Arc<Mutex<Vec<ProgressNotificationParam>>>,
+}
+
+impl ServerHandler for Server {
+    async fn on_progress(&self, notification: ProgressNotificationParam) {
+        self.partials.lock().unwrap().push(notification);
+    }
+}
+
+/// Streams the words of its answer, if the server asks for it
+#[derive(Debug, Clone, Default)]
+struct Client {
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl ClientHandler for Client {
+    fn can_create_message(&self) -> bool {
+        true
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::Error> {
+        let words = ["Hello", " streaming", " world"];
+        if let Some(mut partial) = context.partial_message_stream(&params) {
+            for word in words {
+                partial
+                    .stream_partial(word)
+                    .await
+                    .map_err(|error| rmcp::Error::internal_error(error.to_string(), None))?;
+            }
+        }
+        Ok(CreateMessageResult {
+            model: "words".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text(words.concat()),
+            },
+            meta: None,
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+async fn connect(
+    server: Server,
+) -> anyhow::Result<(
+    RunningService<ServerHandlerService<Server>>,
+    RunningService<ClientHandlerService<Client>>,
+)> {
+    common::serve(server, Client::default()).await
+}
+
+fn param(meta: Option<RequestMeta>) -> CreateMessageRequestParam {
+    CreateMessageRequestParam {
+        messages: vec![SamplingMessage {
+            role: Role::User,
+            content: Content::text("say hello"),
+        }],
+        model_preferences: None,
+        system_prompt: None,
+        include_context: None,
+        temperature: None,
+        max_tokens: 64,
+        stop_sequences: None,
+        metadata: None,
+        meta,
+    }
+}
+
+#[tokio::test]
+async fn test_stream_partial() -> anyhow::Result<()> {
+    let handler = Server::default();
+    let (server, client) = connect(handler.clone()).await?;
+    let token = NumberOrString::String("sampling-1".into());
+    let result = server
+        .create_message(param(Some(RequestMeta::new(token.clone()))))
+        .await?;
+    assert_eq!(
+        result.message.content.as_text().unwrap().text,
+        "Hello streaming world"
+    );
+
+    // the notifications are handled concurrently, they may arrive after the response
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while handler.partials.lock().unwrap().len() < 3 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await?;
+    let mut partials = handler.partials.lock().unwrap().clone();
+    partials.sort_by_key(|partial| partial.progress);
+    assert!(
+        partials
+            .iter()
+            .all(|partial| partial.progress_token == token)
+    );
+    assert_eq!(
+        partials
+            .iter()
+            .map(|partial| (partial.progress, partial.message.as_deref().unwrap()))
+            .collect::<Vec<_>>(),
+        [(1, "Hello"), (2, " streaming"), (3, " world")]
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_progress_token() -> anyhow::Result<()> {
+    let handler = Server::default();
+    let (server, client) = connect(handler.clone()).await?;
+    let result = server.create_message(param(None)).await?;
+    assert_eq!(
+        result.message.content.as_text().unwrap().text,
+        "Hello streaming world"
+    );
+    // a ping is answered after the notifications sent before it are received
+    server.ping().await?;
+    assert!(handler.partials.lock().unwrap().is_empty());
+    client.cancel().await?;
+    Ok(())
+}
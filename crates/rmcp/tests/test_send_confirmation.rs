@@ -0,0 +1,102 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{Sink, SinkExt, StreamExt, channel::mpsc};
+use rmcp::{
+    ClientHandlerService, ServiceError,
+    model::{ClientJsonRpcMessage, ClientRequest, PingRequest, ServerJsonRpcMessage},
+    serve_client_no_init,
+    service::PeerRequestOptions,
+};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_request_sent() -> anyhow::Result<()> {
+    let (client_tx, mut server_rx) = mpsc::channel::<ClientJsonRpcMessage>(16);
+    let (_server_tx, client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let client = serve_client_no_init(
+        ClientHandlerService::new(None),
+        (client_tx.sink_map_err(std::io::Error::other), client_rx),
+    )
+    .await?;
+    let mut handle = client
+        .peer()
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    // sent without a response
+    handle.await_sent().await?;
+    handle.await_sent().await?;
+    let message = server_rx.next().await.expect("the ping");
+    assert!(matches!(
+        message,
+        ClientJsonRpcMessage::Request(ref request) if request.id == handle.id
+    ));
+
+    client.peer().notify_initialized().await?;
+    assert!(matches!(
+        server_rx.next().await,
+        Some(ClientJsonRpcMessage::Notification(_))
+    ));
+    client.cancel().await?;
+    Ok(())
+}
+
+/// A transport which fails to send anything
+struct Broken;
+
+impl Sink<ClientJsonRpcMessage> for Broken {
+    type Error = std::io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, _: ClientJsonRpcMessage) -> Result<(), Self::Error> {
+        // not a broken connection, which would end the session
+        Err(std::io::Error::other("broken"))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn test_send_failure() -> anyhow::Result<()> {
+    let (_server_tx, client_rx) = mpsc::channel::<ServerJsonRpcMessage>(16);
+    let client = serve_client_no_init(ClientHandlerService::new(None), (Broken, client_rx)).await?;
+
+    let error = client.peer().notify_initialized().await;
+    assert!(
+        matches!(error, Err(ServiceError::Transport(_))),
+        "{error:?}"
+    );
+
+    let mut handle = client
+        .peer()
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    let error = handle.await_sent().await;
+    assert!(
+        matches!(error, Err(ServiceError::Transport(_))),
+        "{error:?}"
+    );
+    let error = handle.await_response().await;
+    assert!(
+        matches!(error, Err(ServiceError::Transport(_))),
+        "{error:?}"
+    );
+    client.cancel().await?;
+    Ok(())
+}
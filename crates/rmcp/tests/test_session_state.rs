@@ -0,0 +1,157 @@
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ClientHandlerService, RoleClient, RoleServer, ServerHandler, ServerHandlerService,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ErrorData, ServerCapabilities, ServerInfo,
+    },
+    service::{Peer, RequestContext, RunningService},
+};
+use serde_json::json;
+
+/// The user logged in on a connection
+#[derive(Debug, Clone)]
+struct User(String);
+
+/// Counts the sessions whose state is dropped
+struct DropGuard(Arc<AtomicUsize>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// One handler shared by all the connections
+#[derive(Debug, Clone, Default)]
+struct LoginServer {
+    logins: Arc<AtomicUsize>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for LoginServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let session = context.session();
+        match request.name.as_ref() {
+            "login" => {
+                let name = request
+                    .arguments
+                    .as_ref()
+                    .and_then(|arguments| arguments.get("name")?.as_str())
+                    .ok_or_else(|| ErrorData::invalid_params("missing name", None))?;
+                session.insert(User(name.to_owned()));
+                session.get_or_insert_with(|| Arc::new(DropGuard(self.dropped.clone())));
+                self.logins.fetch_add(1, Ordering::SeqCst);
+                Ok(CallToolResult::success(vec![]))
+            }
+            "whoami" => {
+                let User(name) = session
+                    .get::<User>()
+                    .ok_or_else(|| ErrorData::invalid_request("login first", None))?;
+                Ok(CallToolResult::success(vec![Content::text(name)]))
+            }
+            _ => Err(ErrorData::invalid_params("no such tool", None)),
+        }
+    }
+}
+
+async fn connect(
+    server: LoginServer,
+) -> (
+    RunningService<ClientHandlerService<Option<Peer<RoleClient>>>>,
+    RunningService<ServerHandlerService<LoginServer>>,
+) {
+    let (server, client) = common::connect(server).await.expect("connected");
+    (client, server)
+}
+
+async fn call(
+    client: &RunningService<ClientHandlerService<Option<Peer<RoleClient>>>>,
+    name: &'static str,
+    arguments: serde_json::Value,
+) -> Result<CallToolResult, rmcp::ServiceError> {
+    client
+        .peer()
+        .call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: arguments.as_object().cloned(),
+            meta: None,
+        })
+        .await
+}
+
+fn text(result: &CallToolResult) -> &str {
+    &result.content[0].as_text().expect("text content").text
+}
+
+#[tokio::test]
+async fn test_state_per_connection() -> anyhow::Result<()> {
+    let handler = LoginServer::default();
+    let (alice, _alice_server) = connect(handler.clone()).await;
+    let (bob, _bob_server) = connect(handler.clone()).await;
+
+    call(&alice, "whoami", json!({}))
+        .await
+        .expect_err("not logged in yet");
+    call(&alice, "login", json!({ "name": "alice" })).await?;
+    call(&bob, "login", json!({ "name": "bob" })).await?;
+    assert_eq!(text(&call(&alice, "whoami", json!({})).await?), "alice");
+    assert_eq!(text(&call(&bob, "whoami", json!({})).await?), "bob");
+    assert_eq!(handler.logins.load(Ordering::SeqCst), 2);
+
+    alice.cancel().await?;
+    bob.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_state_dropped_on_disconnect() -> anyhow::Result<()> {
+    let handler = LoginServer::default();
+    let (client, server) = connect(handler.clone()).await;
+    call(&client, "login", json!({ "name": "alice" })).await?;
+    let session = server.peer().session().clone();
+    assert!(session.contains::<User>());
+    assert_eq!(handler.dropped.load(Ordering::SeqCst), 0);
+
+    client.cancel().await?;
+    server.waiting().await;
+    assert_eq!(handler.dropped.load(Ordering::SeqCst), 1);
+    assert!(!session.contains::<User>());
+    Ok(())
+}
+
+/// The client has its own session too
+#[tokio::test]
+async fn test_client_session() -> anyhow::Result<()> {
+    let (client, _server) = connect(LoginServer::default()).await;
+    let session = client.peer().session();
+    assert_eq!(
+        session.insert(User("alice".into())).map(|User(name)| name),
+        None
+    );
+    let previous = session.insert(User("bob".into()));
+    assert_eq!(previous.map(|User(name)| name), Some("alice".to_owned()));
+    assert_eq!(
+        session.remove::<User>().map(|User(name)| name),
+        Some("bob".to_owned())
+    );
+    assert!(!session.contains::<User>());
+    client.cancel().await?;
+    Ok(())
+}
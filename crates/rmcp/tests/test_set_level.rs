@@ -0,0 +1,89 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServiceError,
+    handler::server::logging::TracingLevelReload,
+    model::{ErrorCode, LoggingLevel, ServerCapabilities, ServerInfo, SetLevelRequestParam},
+    service::RunningService,
+};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{Registry, reload};
+
+#[derive(Debug, Clone)]
+struct Server {
+    logging: bool,
+    reload: TracingLevelReload<LevelFilter, Registry>,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        let capabilities = if self.logging {
+            ServerCapabilities::builder().enable_logging().build()
+        } else {
+            ServerCapabilities::default()
+        };
+        ServerInfo {
+            capabilities,
+            ..Default::default()
+        }
+    }
+
+    async fn on_set_level(&self, level: LoggingLevel) {
+        self.reload.reload(level).expect("the subscriber is alive");
+    }
+}
+
+async fn connect(
+    logging: bool,
+) -> anyhow::Result<(
+    RunningService<ClientHandlerService>,
+    reload::Layer<LevelFilter, Registry>,
+)> {
+    // the handle only reloads while the layer is alive
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    let server = Server {
+        logging,
+        reload: TracingLevelReload::new(handle),
+    };
+    let (_, client) = common::connect(server).await?;
+    Ok((client, filter))
+}
+
+#[tokio::test]
+async fn test_set_level_reloads_filter() -> anyhow::Result<()> {
+    let (client, filter) = connect(true).await?;
+    let handle = filter.handle();
+    client
+        .set_level(SetLevelRequestParam {
+            level: LoggingLevel::Debug,
+        })
+        .await?;
+    assert_eq!(handle.clone_current(), Some(LevelFilter::DEBUG));
+    client
+        .set_level(SetLevelRequestParam {
+            level: LoggingLevel::Warning,
+        })
+        .await?;
+    assert_eq!(handle.clone_current(), Some(LevelFilter::WARN));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_level_without_logging() -> anyhow::Result<()> {
+    let (client, filter) = connect(false).await?;
+    let handle = filter.handle();
+    let error = client
+        .set_level(SetLevelRequestParam {
+            level: LoggingLevel::Debug,
+        })
+        .await
+        .expect_err("logging isn't advertised");
+    assert!(matches!(
+        error,
+        ServiceError::McpError(error) if error.code == ErrorCode::METHOD_NOT_FOUND
+    ));
+    assert_eq!(handle.clone_current(), Some(LevelFilter::INFO));
+    client.cancel().await?;
+    Ok(())
+}
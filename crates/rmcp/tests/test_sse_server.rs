@@ -0,0 +1,417 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{Router, routing::get};
+use rmcp::{
+    ClientHandlerService, ServerHandler,
+    model::{CallToolRequestParam, ServerCapabilities, ServerInfo},
+    serve_client, serve_client_resumed, tool,
+    transport::{
+        sse::{SseSession, SseTransport, SseTransportError},
+        sse_server::{SseServer, SseServerConfig},
+    },
+};
+
+/// A handler per session, each counting from 0
+#[derive(Debug, Clone, Default)]
+struct Counter {
+    count: Arc<AtomicUsize>,
+}
+
+#[tool(tool_box)]
+impl Counter {
+    #[tool(description = "Increment the counter of the session")]
+    fn increment(&self) -> String {
+        (self.count.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Counter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The sessions end soon after their client leaves
+fn config() -> SseServerConfig {
+    SseServerConfig {
+        resume_timeout: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
+
+/// Serve the counter at `/mcp` of an app with another route
+async fn serve(mcp: &SseServer) -> anyhow::Result<String> {
+    let app = Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .nest("/mcp", mcp.router(Counter::default));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok(format!("http://{address}"))
+}
+
+async fn increment(client: &rmcp::Peer<rmcp::RoleClient>) -> anyhow::Result<String> {
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "increment".into(),
+            arguments: None,
+            meta: None,
+        })
+        .await?;
+    Ok(result.content[0].as_text().expect("text").text.clone())
+}
+
+/// Wait for the sessions to be closed, they're removed when the server sees the stream closed
+async fn wait_for_sessions(mcp: &SseServer, count: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while mcp.session_count() != count {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} sessions instead of {count}", mcp.session_count()));
+}
+
+#[tokio::test]
+async fn test_sessions() -> anyhow::Result<()> {
+    let mcp = SseServer::new(config());
+    let url = serve(&mcp).await?;
+
+    let first = serve_client(
+        ClientHandlerService::simple(),
+        SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?,
+    )
+    .await?;
+    let second = serve_client(
+        ClientHandlerService::simple(),
+        SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?,
+    )
+    .await?;
+    assert_eq!(mcp.session_count(), 2);
+
+    let tools = first.list_tools(Default::default()).await?.tools;
+    assert_eq!(tools.len(), 1);
+    assert_eq!(increment(first.peer()).await?, "1");
+    assert_eq!(increment(first.peer()).await?, "2");
+    // each session has its own handler
+    assert_eq!(increment(second.peer()).await?, "1");
+
+    // the app's other routes are still served
+    let health = reqwest::get(format!("{url}/health")).await?.text().await?;
+    assert_eq!(health, "ok");
+
+    first.cancel().await?;
+    wait_for_sessions(&mcp, 1).await;
+    second.cancel().await?;
+    wait_for_sessions(&mcp, 0).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_session() -> anyhow::Result<()> {
+    let mcp = SseServer::new(config());
+    let url = serve(&mcp).await?;
+    let response = reqwest::Client::new()
+        .post(format!("{url}/mcp/sse?sessionId=unknown"))
+        .json(&serde_json::json!({ "jsonrpc": "2.0", "method": "ping", "id": 1 }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown() -> anyhow::Result<()> {
+    let mcp = SseServer::new(config());
+    let url = serve(&mcp).await?;
+    let client = serve_client(
+        ClientHandlerService::simple(),
+        SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?,
+    )
+    .await?;
+    assert_eq!(mcp.session_count(), 1);
+
+    mcp.cancel();
+    wait_for_sessions(&mcp, 0).await;
+    // the client sees the stream closed
+    let quit_reason = tokio::time::timeout(Duration::from_secs(5), client.waiting()).await?;
+    assert!(
+        matches!(quit_reason, rmcp::service::QuitReason::Closed),
+        "{quit_reason:?}"
+    );
+    Ok(())
+}
+
+/// The events of an SSE stream read by hand, to resume it
+struct EventReader {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+#[derive(Debug)]
+struct RawEvent {
+    id: Option<u64>,
+    event: String,
+    data: String,
+}
+
+impl EventReader {
+    async fn open(request: reqwest::RequestBuilder) -> anyhow::Result<Self> {
+        let response = request.send().await?.error_for_status()?;
+        Ok(Self {
+            response,
+            buffer: String::new(),
+        })
+    }
+
+    async fn next(&mut self) -> anyhow::Result<RawEvent> {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block = self.buffer[..end].to_owned();
+                self.buffer.drain(..end + 2);
+                let mut event = RawEvent {
+                    id: None,
+                    event: String::new(),
+                    data: String::new(),
+                };
+                for line in block.lines() {
+                    match line.split_once(':') {
+                        Some(("id", id)) => event.id = Some(id.trim().parse()?),
+                        Some(("event", value)) => event.event = value.trim().to_owned(),
+                        Some(("data", value)) => event.data = value.trim().to_owned(),
+                        _ => {}
+                    }
+                }
+                if !event.data.is_empty() {
+                    return Ok(event);
+                }
+                continue;
+            }
+            let chunk = tokio::time::timeout(Duration::from_secs(5), self.response.chunk())
+                .await??
+                .ok_or_else(|| anyhow::anyhow!("stream closed"))?;
+            self.buffer.push_str(std::str::from_utf8(&chunk)?);
+        }
+    }
+}
+
+/// Open a session by hand, and initialize it, the events of the initialize response and the pings are numbered
+async fn open_session(url: &str) -> anyhow::Result<(EventReader, String)> {
+    let http = reqwest::Client::new();
+    let mut events = EventReader::open(http.get(format!("{url}/mcp/sse"))).await?;
+    let endpoint = events.next().await?;
+    assert_eq!(endpoint.event, "endpoint");
+    assert_eq!(endpoint.id, None);
+    let post_url = format!("{url}/mcp/sse{}", endpoint.data);
+    let initialize = serde_json::json!({
+        "jsonrpc": "2.0", "id": 0, "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": { "name": "test", "version": "0" },
+        },
+    });
+    http.post(&post_url)
+        .json(&initialize)
+        .send()
+        .await?
+        .error_for_status()?;
+    let response = events.next().await?;
+    assert_eq!(response.id, Some(1));
+    let initialized =
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    http.post(&post_url)
+        .json(&initialized)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok((events, post_url))
+}
+
+async fn post_ping(post_url: &str, id: u32) -> anyhow::Result<()> {
+    let ping = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "ping" });
+    reqwest::Client::new()
+        .post(post_url)
+        .json(&ping)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume() -> anyhow::Result<()> {
+    let mcp = SseServer::new(SseServerConfig {
+        resume_timeout: Duration::from_secs(5),
+        ..Default::default()
+    });
+    let url = serve(&mcp).await?;
+    let (mut events, post_url) = open_session(&url).await?;
+    post_ping(&post_url, 1).await?;
+    assert_eq!(events.next().await?.id, Some(2));
+    // the stream breaks, and the server answers while it's closed
+    drop(events);
+    post_ping(&post_url, 2).await?;
+    post_ping(&post_url, 3).await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(mcp.session_count(), 1);
+
+    // resume after the first ping: its response isn't sent again
+    let mut events = EventReader::open(
+        reqwest::Client::new()
+            .get(&post_url)
+            .header("Last-Event-ID", "2"),
+    )
+    .await?;
+    for (id, request_id) in [(3, 2), (4, 3)] {
+        let event = events.next().await?;
+        assert_eq!((event.id, event.event.as_str()), (Some(id), "message"));
+        let message: serde_json::Value = serde_json::from_str(&event.data)?;
+        assert_eq!(message["id"], request_id);
+    }
+    // then the new events
+    post_ping(&post_url, 4).await?;
+    assert_eq!(events.next().await?.id, Some(5));
+
+    // an unknown session has no stream to resume
+    let unknown = reqwest::Client::new()
+        .get(format!("{url}/mcp/sse?sessionId=unknown"))
+        .send()
+        .await?;
+    assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_buffer_bound() -> anyhow::Result<()> {
+    let mcp = SseServer::new(SseServerConfig {
+        replay_buffer: 2,
+        resume_timeout: Duration::from_secs(5),
+        ..Default::default()
+    });
+    let url = serve(&mcp).await?;
+    let (mut events, post_url) = open_session(&url).await?;
+    for id in 1..=3 {
+        post_ping(&post_url, id).await?;
+        events.next().await?;
+    }
+    drop(events);
+
+    // the events 1 and 2 are dropped, only 3 and 4 are kept
+    let resume = |last_event_id: &'static str| {
+        reqwest::Client::new()
+            .get(&post_url)
+            .header("Last-Event-ID", last_event_id)
+            .send()
+    };
+    assert_eq!(resume("1").await?.status(), reqwest::StatusCode::GONE);
+    assert_eq!(
+        resume("x").await?.status(),
+        reqwest::StatusCode::BAD_REQUEST
+    );
+    let mut events = EventReader::open(
+        reqwest::Client::new()
+            .get(&post_url)
+            .header("Last-Event-ID", "2"),
+    )
+    .await?;
+    assert_eq!(events.next().await?.id, Some(3));
+    assert_eq!(events.next().await?.id, Some(4));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_after_restart() -> anyhow::Result<()> {
+    let mcp = SseServer::new(SseServerConfig {
+        resume_timeout: Duration::from_secs(5),
+        ..Default::default()
+    });
+    let url = serve(&mcp).await?;
+    let path = std::env::temp_dir().join(format!("rmcp-sse-session-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(SseSession::load(&path)?, None);
+
+    let transport = SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?;
+    let tracker = transport.session_tracker();
+    let client = serve_client(ClientHandlerService::simple(), transport).await?;
+    assert_eq!(increment(client.peer()).await?, "1");
+    assert_eq!(increment(client.peer()).await?, "2");
+    let session = tracker.session(client.peer_info().clone());
+    // the initialize request and the two calls
+    assert_eq!(session.next_request_id, 3);
+    assert_eq!(session.last_event_id.as_deref(), Some("3"));
+    session.save(&path)?;
+    // the process "restarts"
+    client.cancel().await?;
+
+    let session = SseSession::load(&path)?.expect("a saved session");
+    std::fs::remove_file(&path)?;
+    let transport = SseTransport::builder(format!("{url}/mcp/sse"))
+        .resume(&session)
+        .start()
+        .await?;
+    let tracker = transport.session_tracker();
+    let client = serve_client_resumed(
+        ClientHandlerService::simple(),
+        transport,
+        session.server_info.clone(),
+        session.next_request_id,
+    )
+    .await?;
+    // the same session, with the same handler
+    assert_eq!(mcp.session_count(), 1);
+    assert_eq!(increment(client.peer()).await?, "3");
+    let resumed = tracker.session(client.peer_info().clone());
+    assert_eq!(resumed.endpoint, session.endpoint);
+    assert_eq!(resumed.next_request_id, 4);
+    assert_eq!(resumed.last_event_id.as_deref(), Some("4"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_closed_session() -> anyhow::Result<()> {
+    let mcp = SseServer::new(config());
+    let url = serve(&mcp).await?;
+    let transport = SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?;
+    let tracker = transport.session_tracker();
+    let client = serve_client(ClientHandlerService::simple(), transport).await?;
+    let session = tracker.session(client.peer_info().clone());
+    client.cancel().await?;
+    // the restart is longer than the resume timeout
+    wait_for_sessions(&mcp, 0).await;
+
+    let Err(error) = SseTransport::builder(format!("{url}/mcp/sse"))
+        .resume(&session)
+        .start()
+        .await
+    else {
+        panic!("the session is closed");
+    };
+    assert!(
+        matches!(
+            error,
+            SseTransportError::SessionNotResumable(reqwest::StatusCode::NOT_FOUND)
+        ),
+        "{error}"
+    );
+    // a new session instead
+    let client = serve_client(
+        ClientHandlerService::simple(),
+        SseTransport::start(format!("{url}/mcp/sse"), Default::default()).await?,
+    )
+    .await?;
+    assert_eq!(increment(client.peer()).await?, "1");
+    client.cancel().await?;
+    Ok(())
+}
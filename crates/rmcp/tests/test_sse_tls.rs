@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use futures::SinkExt;
+use rmcp::{
+    model::ClientJsonRpcMessage,
+    transport::sse::{Certificate, SseTlsConfig, SseTransport, SseTransportError},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        self,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    },
+};
+
+/// The test CA, and the certificate of localhost and 127.0.0.1 it issued, valid until 2125
+const CA: &[u8] = include_bytes!("fixtures/tls/ca.pem");
+const CERT: &[u8] = include_bytes!("fixtures/tls/localhost.pem");
+const KEY: &[u8] = include_bytes!("fixtures/tls/localhost.key");
+
+/// Read the head and the body of a http request, and return the head, `None` once the connection is closed
+async fn read_request(stream: &mut (impl AsyncRead + Unpin)) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0; 1];
+        if stream.read(&mut byte).await? == 0 {
+            return Ok(None);
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head).into_owned();
+    let length = head
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or_default();
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+    Ok(Some(head))
+}
+
+/// A legacy SSE server over TLS, with the certificate of localhost
+async fn serve_tls() -> anyhow::Result<(String, tokio::task::JoinHandle<std::io::Result<()>>)> {
+    let certs = CertificateDer::pem_slice_iter(CERT).collect::<Result<Vec<_>, _>>()?;
+    let key = PrivateKeyDer::from_pem_slice(KEY)?;
+    let config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("https://localhost:{}/sse", listener.local_addr()?.port());
+    let server = tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                // the handshake fails when the client doesn't trust the certificate
+                let mut stream = acceptor.accept(stream).await?;
+                while let Some(head) = read_request(&mut stream).await? {
+                    let response: &[u8] = if head.starts_with("GET") {
+                        b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\nevent: endpoint\ndata: /message\n\n"
+                    } else {
+                        b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n"
+                    };
+                    stream.write_all(response).await?;
+                    stream.flush().await?;
+                }
+                std::io::Result::Ok(())
+            });
+        }
+    });
+    Ok((url, server))
+}
+
+fn ping() -> ClientJsonRpcMessage {
+    serde_json::from_value(serde_json::json!({
+        "jsonrpc": "2.0", "method": "ping", "id": 1
+    }))
+    .expect("a ping")
+}
+
+/// Open the SSE stream and post a message, over TLS
+async fn connect(url: &str, tls: SseTlsConfig) -> Result<(), SseTransportError> {
+    let mut transport = SseTransport::builder(url).tls(tls).start().await?;
+    transport.send(ping()).await
+}
+
+#[tokio::test]
+async fn test_untrusted_certificate() -> anyhow::Result<()> {
+    let (url, server) = serve_tls().await?;
+    let error = connect(&url, SseTlsConfig::default())
+        .await
+        .expect_err("the test CA isn't trusted");
+    assert!(
+        matches!(&error, SseTransportError::Reqwest(error) if error.is_connect()),
+        "{error}"
+    );
+    server.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_root_certificate() -> anyhow::Result<()> {
+    let (url, server) = serve_tls().await?;
+    let tls = SseTlsConfig::default()
+        .with_root_certificate(Certificate::from_pem(CA)?)
+        .without_built_in_roots();
+    connect(&url, tls).await?;
+
+    // pinned to another CA
+    let other = Certificate::from_pem(CERT)?;
+    let tls = SseTlsConfig::default()
+        .with_root_certificate(other)
+        .without_built_in_roots();
+    connect(&url, tls).await.expect_err("not issued by the pin");
+    server.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_accept_invalid_certs() -> anyhow::Result<()> {
+    let (url, server) = serve_tls().await?;
+    connect(&url, SseTlsConfig::default().danger_accept_invalid_certs()).await?;
+    server.abort();
+    Ok(())
+}
+
+#[cfg(feature = "transport-sse-rustls")]
+#[tokio::test]
+async fn test_rustls_config() -> anyhow::Result<()> {
+    let (url, server) = serve_tls().await?;
+    let mut roots = rustls::RootCertStore::empty();
+    for certificate in CertificateDer::pem_slice_iter(CA) {
+        roots.add(certificate?)?;
+    }
+    let config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    connect(&url, SseTlsConfig::default().with_rustls_config(config)).await?;
+    server.abort();
+    Ok(())
+}
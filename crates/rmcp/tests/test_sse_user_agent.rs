@@ -0,0 +1,90 @@
+use futures::SinkExt;
+use rmcp::{
+    model::{ClientJsonRpcMessage, Implementation},
+    transport::sse::SseTransport,
+};
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Read the head of a http request, and return the value of its `User-Agent` header
+async fn read_user_agent(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0; 1];
+        if stream.read(&mut byte).await? == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    Ok(head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("user-agent")
+            .then(|| value.trim().to_string())
+    }))
+}
+
+/// A fake SSE server, it returns the user agents of the SSE request and the first POST request
+async fn serve(listener: TcpListener) -> std::io::Result<(Option<String>, Option<String>)> {
+    let (mut sse, _) = listener.accept().await?;
+    let sse_user_agent = read_user_agent(&mut sse).await?;
+    sse.write_all(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncache-control: no-cache\r\n\r\nevent: endpoint\ndata: /message?sessionId=1\n\n",
+    )
+    .await?;
+    let (mut post, _) = listener.accept().await?;
+    let post_user_agent = read_user_agent(&mut post).await?;
+    post.write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n")
+        .await?;
+    Ok((sse_user_agent, post_user_agent))
+}
+
+fn notification() -> ClientJsonRpcMessage {
+    serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/initialized"
+    }))
+    .expect("valid notification")
+}
+
+#[tokio::test]
+async fn test_sse_default_user_agent() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/sse", listener.local_addr()?);
+    let server = tokio::spawn(serve(listener));
+
+    let mut transport = SseTransport::builder(url)
+        .client_info(Implementation {
+            name: "my-client".to_string(),
+            version: "1.2.3".to_string(),
+        })
+        .start()
+        .await?;
+    transport.send(notification()).await?;
+
+    let (sse_user_agent, post_user_agent) = server.await??;
+    assert_eq!(sse_user_agent.as_deref(), Some("my-client/1.2.3"));
+    assert_eq!(post_user_agent.as_deref(), Some("my-client/1.2.3"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sse_custom_user_agent() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/sse", listener.local_addr()?);
+    let server = tokio::spawn(serve(listener));
+
+    let mut transport = SseTransport::builder(url)
+        .user_agent("analytics-bot/0.1")
+        .start()
+        .await?;
+    transport.send(notification()).await?;
+
+    let (sse_user_agent, post_user_agent) = server.await??;
+    assert_eq!(sse_user_agent.as_deref(), Some("analytics-bot/0.1"));
+    assert_eq!(post_user_agent.as_deref(), Some("analytics-bot/0.1"));
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use rmcp::{
+    RoleServer,
+    handler::server::tool::{Structured, ToolBox, ToolBoxItem, ToolCallContext},
+    model::{
+        CallToolRequestParam, CallToolResult, ClientInfo, ErrorCode, JsonObject, NumberOrString,
+        Tool,
+    },
+    schemars,
+    service::{AtomicU32RequestIdProvider, Peer, RequestContext},
+    tool,
+};
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct Weather {
+    pub city: String,
+    pub temperature: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Get the weather of a city")]
+    fn get_weather(&self, #[tool(param)] city: String) -> Result<Structured<Weather>, String> {
+        if city.is_empty() {
+            return Err("city is required".to_string());
+        }
+        Ok(Structured(Weather {
+            city,
+            temperature: 21.5,
+        }))
+    }
+}
+
+fn call_context<'a, S>(service: &'a S, name: &'static str, city: &str) -> ToolCallContext<'a, S> {
+    let (peer, _) = Peer::new(
+        Arc::new(AtomicU32RequestIdProvider::default()),
+        ClientInfo::default(),
+    );
+    ToolCallContext::new(
+        service,
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: json!({ "city": city }).as_object().cloned(),
+            meta: None,
+        },
+        RequestContext::<RoleServer> {
+            ct: CancellationToken::new(),
+            id: NumberOrString::Number(0),
+            peer,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_structured_output() {
+    let tool = Server::get_weather_tool_attr();
+    let output_schema = tool.output_schema.expect("output schema");
+    assert_eq!(output_schema["type"], "object");
+    assert_eq!(output_schema["required"], json!(["city", "temperature"]));
+
+    let result = Server::tool_box()
+        .call(call_context(&Server, "get_weather", "Paris"))
+        .await
+        .expect("call get_weather");
+    let expected = json!({ "city": "Paris", "temperature": 21.5 });
+    assert_eq!(result.structured_content, Some(expected.clone()));
+    let text = &result.content[0].as_text().expect("text content").text;
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(text).unwrap(),
+        expected
+    );
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["structuredContent"], expected);
+
+    // an error result doesn't need structured content
+    let result = Server::tool_box()
+        .call(call_context(&Server, "get_weather", ""))
+        .await
+        .expect("call get_weather");
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(result.structured_content, None);
+}
+
+#[tokio::test]
+async fn test_structured_output_validation() {
+    let output_schema: JsonObject = serde_json::from_value(json!({
+        "type": "object",
+        "properties": { "temperature": { "type": "number" } },
+        "required": ["temperature"]
+    }))
+    .unwrap();
+    let mut tool_box = ToolBox::<()>::new();
+    tool_box.add(ToolBoxItem::new(
+        Tool::new("invalid", "", JsonObject::new()).with_output_schema(output_schema.clone()),
+        |_| Box::pin(async { Ok(CallToolResult::structured(json!({ "temperature": "hot" }))) }),
+    ));
+    tool_box.add(ToolBoxItem::new(
+        Tool::new("unstructured", "", JsonObject::new()).with_output_schema(output_schema),
+        |_| Box::pin(async { Ok(CallToolResult::success(vec![])) }),
+    ));
+
+    for name in ["invalid", "unstructured"] {
+        let error = tool_box
+            .call(call_context(&(), name, "Paris"))
+            .await
+            .expect_err("invalid output");
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(error.data.expect("error data")["tool"], name);
+    }
+}
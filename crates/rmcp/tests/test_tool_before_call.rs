@@ -0,0 +1,88 @@
+mod common;
+
+use rmcp::{
+    RoleServer,
+    handler::server::tool::ToolCallContext,
+    model::{CallToolRequestParam, ClientInfo, ErrorCode, Implementation},
+    service::RequestContext,
+    tool,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box, before_call = Self::authorize)]
+impl Server {
+    #[tool(description = "Say hello to the client")]
+    fn say_hello(&self) -> String {
+        "hello".to_string()
+    }
+
+    #[tool(description = "Delete everything")]
+    fn delete_all(&self) -> String {
+        "deleted".to_string()
+    }
+
+    /// Only the trusted client can call the destructive tools
+    fn authorize(
+        request: &CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<(), rmcp::Error> {
+        let client = &context.peer.peer_info().client_info;
+        if request.name == "delete_all" && client.name != "trusted" {
+            return Err(rmcp::Error::new(
+                ErrorCode(-32001),
+                "unauthorized",
+                Some(json!({ "tool": request.name, "client": client.name })),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn request_context(client_name: &str) -> RequestContext<RoleServer> {
+    let peer = common::server_peer(ClientInfo {
+        client_info: Implementation {
+            name: client_name.to_string(),
+            version: "1.0.0".to_string(),
+        },
+        ..Default::default()
+    });
+    RequestContext {
+        peer,
+        ..common::request_context()
+    }
+}
+
+async fn call(name: &'static str, client_name: &str) -> Result<String, rmcp::Error> {
+    let context = ToolCallContext::new(
+        &Server,
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: None,
+            meta: None,
+        },
+        request_context(client_name),
+    );
+    let result = Server::tool_box().call(context).await?;
+    Ok(result.content[0]
+        .as_text()
+        .expect("text content")
+        .text
+        .clone())
+}
+
+#[tokio::test]
+async fn test_before_call_rejects_unauthorized_call() {
+    assert_eq!(call("say_hello", "guest").await.unwrap(), "hello");
+    assert_eq!(call("delete_all", "trusted").await.unwrap(), "deleted");
+
+    let error = call("delete_all", "guest").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode(-32001));
+    assert_eq!(error.message, "unauthorized");
+    assert_eq!(
+        error.data,
+        Some(json!({ "tool": "delete_all", "client": "guest" }))
+    );
+}
@@ -0,0 +1,93 @@
+use rmcp::{ServerHandler, model::ListToolsResult, schemars, tool};
+use serde_json::json;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SumRequest {
+    #[schemars(description = "the left hand side number")]
+    pub a: i32,
+    pub b: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Calculator;
+
+#[tool(tool_box)]
+impl Calculator {
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, #[tool(aggr)] SumRequest { a, b }: SumRequest) -> String {
+        (a + b).to_string()
+    }
+
+    #[tool(description = "Calculate the difference of two numbers")]
+    fn sub(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "the left hand side number")]
+        a: i32,
+        #[tool(param)] b: i32,
+    ) -> String {
+        (a - b).to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Calculator {}
+
+#[derive(Debug, Clone)]
+pub struct Greeter;
+
+#[tool(tool_box = greetings)]
+impl Greeter {
+    #[tool(description = "Say hello")]
+    fn hello(&self) -> String {
+        "hello".to_owned()
+    }
+}
+
+#[tool(tool_box = greetings)]
+impl ServerHandler for Greeter {}
+
+#[test]
+fn test_calculator_catalog() {
+    let tools = Calculator::tool_catalog();
+    let names = tools
+        .iter()
+        .map(|tool| tool.name.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["sum", "sub"]);
+
+    let sum = &tools[0];
+    assert_eq!(sum.description, "Calculate the sum of two numbers");
+    let schema = serde_json::to_value(&*sum.input_schema).unwrap();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["a", "b"]));
+    assert_eq!(
+        schema["properties"]["a"]["description"],
+        "the left hand side number"
+    );
+    assert_eq!(schema["properties"]["b"]["type"], "integer");
+
+    let sub = &tools[1];
+    let schema = serde_json::to_value(&*sub.input_schema).unwrap();
+    assert_eq!(schema["required"], json!(["a", "b"]));
+    assert_eq!(
+        schema["properties"]["a"]["description"],
+        "the left hand side number"
+    );
+
+    // the catalog is what `tools/list` returns
+    let catalog = ListToolsResult {
+        tools,
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&catalog).unwrap();
+    assert_eq!(json["tools"][1]["name"], "sub");
+    assert!(json["tools"][1]["inputSchema"].is_object());
+}
+
+#[test]
+fn test_named_tool_box_catalog() {
+    let tools = Greeter::greetings_catalog();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "hello");
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use std::time::Duration;
+
+use rmcp::{
+    error::Cancelled,
+    handler::server::tool::{ToolCallContext, ToolContext},
+    model::{
+        CallToolRequestParam, ClientCapabilities, ClientInfo, Content, Implementation,
+        NumberOrString,
+    },
+    service::RequestContext,
+    tool,
+};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Describe the calling client")]
+    fn whoami(&self, context: ToolContext) -> String {
+        let client = context.client_info();
+        format!(
+            "{} {} (request {}, roots: {})",
+            client.name,
+            client.version,
+            context.id,
+            context.client_capabilities().roots.is_some()
+        )
+    }
+
+    #[tool(description = "The id of the request, as JSON")]
+    fn request_id(&self, context: ToolContext) -> Result<Content, rmcp::Error> {
+        Content::json(context.request_id())
+    }
+
+    #[tool(description = "Wait for some seconds")]
+    async fn wait(
+        &self,
+        #[tool(param)] seconds: u64,
+        context: ToolContext,
+    ) -> Result<String, rmcp::Error> {
+        context.sleep(Duration::from_secs(seconds)).await?;
+        Ok(format!("waited {seconds}s"))
+    }
+}
+
+fn wait_context(seconds: u64, ct: CancellationToken) -> ToolCallContext<'static, Server> {
+    ToolCallContext::new(
+        &Server,
+        CallToolRequestParam {
+            name: "wait".into(),
+            arguments: serde_json::json!({ "seconds": seconds })
+                .as_object()
+                .cloned(),
+            meta: None,
+        },
+        RequestContext {
+            ct,
+            id: NumberOrString::Number(1),
+            ..common::request_context()
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_tool_context() {
+    let peer = common::server_peer(ClientInfo {
+        capabilities: ClientCapabilities::builder().enable_roots().build(),
+        client_info: Implementation {
+            name: "inspector".to_string(),
+            version: "0.1.0".to_string(),
+        },
+        ..Default::default()
+    });
+    let context = ToolCallContext::new(
+        &Server,
+        CallToolRequestParam {
+            name: "whoami".into(),
+            arguments: None,
+            meta: None,
+        },
+        RequestContext {
+            id: NumberOrString::Number(7),
+            peer,
+            ..common::request_context()
+        },
+    );
+    let result = Server::tool_box().call(context).await.unwrap();
+    assert_eq!(
+        result.content[0].as_text().expect("text content").text,
+        "inspector 0.1.0 (request 7, roots: true)"
+    );
+}
+
+#[tokio::test]
+async fn test_request_id() {
+    for (id, json) in [
+        (NumberOrString::Number(7), "7"),
+        (NumberOrString::String("7".into()), "\"7\""),
+    ] {
+        let context = ToolCallContext::new(
+            &Server,
+            CallToolRequestParam::new("request_id"),
+            RequestContext {
+                id,
+                ..common::request_context()
+            },
+        );
+        let result = Server::tool_box().call(context).await.unwrap();
+        assert_eq!(result.content[0].as_text().unwrap().text, json);
+    }
+}
+
+#[tokio::test]
+async fn test_sleep() {
+    let result = Server::tool_box()
+        .call(wait_context(0, CancellationToken::new()))
+        .await
+        .unwrap();
+    assert_eq!(result.content[0].as_text().unwrap().text, "waited 0s");
+}
+
+#[tokio::test]
+async fn test_sleep_cancelled() {
+    let ct = CancellationToken::new();
+    let call = tokio::spawn(Server::tool_box().call(wait_context(3600, ct.clone())));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    ct.cancel();
+    let error = tokio::time::timeout(Duration::from_secs(5), call)
+        .await
+        .expect("the sleep returns once cancelled")
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error, Cancelled.into());
+}
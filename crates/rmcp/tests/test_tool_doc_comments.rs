@@ -0,0 +1,69 @@
+mod common;
+
+use rmcp::{ServerHandler, schemars, tool};
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ConvertRequest {
+    /// The temperature in degrees Celsius
+    pub celsius: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Converter;
+
+#[tool(tool_box)]
+impl Converter {
+    /// Convert a temperature to Fahrenheit.
+    ///
+    /// The result is rounded to one decimal.
+    #[tool]
+    fn to_fahrenheit(&self, #[tool(aggr)] ConvertRequest { celsius }: ConvertRequest) -> String {
+        format!("{:.1}", celsius * 9.0 / 5.0 + 32.0)
+    }
+
+    /// Convert a temperature to Kelvin
+    #[tool]
+    fn to_kelvin(
+        &self,
+        /// The temperature in degrees Celsius
+        #[tool(param)]
+        celsius: f64,
+    ) -> String {
+        format!("{:.2}", celsius + 273.15)
+    }
+
+    /// Not the description of the tool
+    #[tool(description = "Round a temperature")]
+    fn round(&self, #[tool(param)] value: f64) -> String {
+        value.round().to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Converter {}
+
+#[tokio::test]
+async fn test_doc_comment_descriptions() -> anyhow::Result<()> {
+    let (server, client) = common::connect(Converter).await?;
+
+    let tools = client.peer().list_tools(Default::default()).await?.tools;
+    assert_eq!(
+        tools[0].description,
+        "Convert a temperature to Fahrenheit.\n\nThe result is rounded to one decimal."
+    );
+    assert_eq!(
+        tools[0].input_schema["properties"]["celsius"]["description"],
+        "The temperature in degrees Celsius"
+    );
+    assert_eq!(tools[1].description, "Convert a temperature to Kelvin");
+    assert_eq!(
+        tools[1].input_schema["properties"]["celsius"]["description"],
+        "The temperature in degrees Celsius"
+    );
+    // an explicit description wins over the doc comment
+    assert_eq!(tools[2].description, "Round a temperature");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use std::collections::HashMap;
+
+use rmcp::{
+    ServerHandler, ServiceError,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct FileReader {
+    files: HashMap<String, String>,
+}
+
+#[tool(tool_box)]
+impl FileReader {
+    #[tool(description = "Read the content of a file")]
+    fn read_file(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "the path of the file")]
+        path: String,
+    ) -> Result<String, rmcp::Error> {
+        self.files.get(&path).cloned().ok_or_else(|| {
+            rmcp::Error::resource_not_found("file not found", Some(json!({ "path": path })))
+        })
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for FileReader {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_tool_error_code() -> anyhow::Result<()> {
+    let (_server, client) = common::connect(FileReader {
+        files: HashMap::from([("hello.txt".to_string(), "hello".to_string())]),
+    })
+    .await?;
+
+    let read_file = |path: &str| CallToolRequestParam {
+        name: "read_file".into(),
+        arguments: json!({ "path": path }).as_object().cloned(),
+        meta: None,
+    };
+    let result = client.peer().call_tool(read_file("hello.txt")).await?;
+    assert_eq!(result.is_error, Some(false));
+    let text = result.content[0].as_text().expect("text content");
+    assert_eq!(text.text, "hello");
+
+    let error = client
+        .peer()
+        .call_tool(read_file("missing.txt"))
+        .await
+        .expect_err("file not found");
+    let ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert_eq!(error.message, "file not found");
+    assert_eq!(error.data, Some(json!({ "path": "missing.txt" })));
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ClientHandler, ClientHandlerService, RoleClient, RoleServer, ServerHandler, ServiceError,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientInfo, Content, ErrorCode, ErrorData,
+        Implementation, ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo,
+        Tool,
+    },
+    service::{Peer, RequestContext, RunningService},
+};
+
+/// A server whose `drop_database` tool is only for the admin console
+#[derive(Debug, Clone, Default)]
+struct AdminServer {
+    dropped: Arc<AtomicUsize>,
+    listed: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for AdminServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        self.listed.fetch_add(1, Ordering::SeqCst);
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: ["status", "drop_database"]
+                .into_iter()
+                .map(|name| Tool::new(name, "a tool", Arc::new(Default::default())))
+                .collect(),
+            meta: None,
+        })
+    }
+
+    fn filter_tool(&self, name: &str, context: &RequestContext<RoleServer>) -> bool {
+        name != "drop_database" || context.peer.peer_info().client_info.name == "admin-console"
+    }
+
+    fn post_process_tool(&self, tool: &mut Tool) {
+        tool.description = format!("{} ({})", tool.description, tool.name).into();
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if request.name == "drop_database" {
+            self.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            request.name.to_string(),
+        )]))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NamedClient {
+    name: &'static str,
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl ClientHandler for NamedClient {
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            client_info: Implementation {
+                name: self.name.into(),
+                version: "1.0.0".into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+async fn connect(
+    server: AdminServer,
+    name: &'static str,
+) -> RunningService<ClientHandlerService<NamedClient>> {
+    let (_, client) = common::serve(server, NamedClient { name, peer: None })
+        .await
+        .expect("connected");
+    client
+}
+
+fn call(name: &'static str) -> CallToolRequestParam {
+    CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+        meta: None,
+    }
+}
+
+async fn tool_names(client: &RunningService<ClientHandlerService<NamedClient>>) -> Vec<String> {
+    let tools = client
+        .peer()
+        .list_tools(Default::default())
+        .await
+        .expect("tools listed");
+    tools
+        .tools
+        .into_iter()
+        .map(|tool| tool.name.to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_hidden_tool() -> anyhow::Result<()> {
+    let server = AdminServer::default();
+    let client = connect(server.clone(), "chat-app").await;
+    assert_eq!(tool_names(&client).await, ["status"]);
+    let tools = client.peer().list_tools(Default::default()).await?;
+    assert_eq!(tools.tools[0].description, "a tool (status)");
+
+    let result = client.peer().call_tool(call("status")).await?;
+    assert_eq!(result.is_error, Some(false));
+    let error = client
+        .peer()
+        .call_tool(call("drop_database"))
+        .await
+        .expect_err("the tool is hidden");
+    let ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert_eq!(error.message, "tool not found");
+    assert_eq!(server.dropped.load(Ordering::SeqCst), 0);
+    // the calls don't list the tools
+    assert_eq!(server.listed.load(Ordering::SeqCst), 2);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_visible_tool() -> anyhow::Result<()> {
+    let server = AdminServer::default();
+    let client = connect(server.clone(), "admin-console").await;
+    assert_eq!(tool_names(&client).await, ["status", "drop_database"]);
+
+    client.peer().call_tool(call("drop_database")).await?;
+    assert_eq!(server.dropped.load(Ordering::SeqCst), 1);
+    client.cancel().await?;
+    Ok(())
+}
@@ -1,4 +1,12 @@
-use rmcp::{ServerHandler, handler::server::tool::ToolCallContext, tool};
+use rmcp::{
+    ServerHandler,
+    handler::server::tool::{
+        IntoCallToolResult, SchemaDraft, SchemaOptions, ToolCallContext,
+        schema_for_type_with_draft, schema_for_type_with_options,
+    },
+    model::Content,
+    tool,
+};
 use schemars::JsonSchema;
 
 use serde::{Deserialize, Serialize};
@@ -27,19 +35,30 @@ pub struct Server {}
 
 impl Server {
     /// This tool is used to get the weather of a city.
-    #[tool(name = "get-weather", description = "Get the weather of a city.", vis = )]
+    #[tool(
+        name = "get-weather",
+        title = "Get Weather",
+        description = "Get the weather of a city.",
+        vis =
+    )]
     pub async fn get_weather(&self, #[tool(param)] city: String) -> String {
         drop(city);
         "rain".to_string()
     }
     #[tool(description = "Empty Parameter")]
     async fn empty_param(&self) {}
+    #[tool(description = "Get the forecast of the week.")]
+    async fn get_forecast(&self) -> Vec<Content> {
+        vec!["rain".into(), Content::text("sun")]
+    }
 }
 
 #[tokio::test]
 async fn test_tool_macros() {
     let server = Server::default();
-    let _attr = Server::get_weather_tool_attr();
+    let attr = Server::get_weather_tool_attr();
+    assert_eq!(attr.name, "get-weather");
+    assert_eq!(attr.display_name(), "Get Weather");
     let _get_weather_call_fn = Server::get_weather_tool_call;
     let _get_weather_fn = Server::get_weather;
     server.get_weather("harbin".into()).await;
@@ -51,6 +70,147 @@ async fn test_tool_macros_with_empty_param() {
     println!("{_attr:?}");
     assert_eq!(_attr.input_schema.get("type").unwrap(), "object");
     assert!(_attr.input_schema.get("properties").is_none());
+    assert!(_attr.title.is_none());
+    assert_eq!(_attr.display_name(), "empty_param");
+}
+
+#[tokio::test]
+async fn test_tool_returning_contents() {
+    let _attr = Server::get_forecast_tool_attr();
+    let result = Server::default()
+        .get_forecast()
+        .await
+        .into_call_tool_result()
+        .unwrap();
+    assert_eq!(
+        result.content,
+        [Content::text("rain"), Content::text("sun")]
+    );
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetForecastRequest {
+    pub days: u32,
+    pub weather: GetWeatherRequest,
+}
+
+#[test]
+fn test_tool_schema_draft() {
+    let attr = Server::get_weather_tool_attr();
+    assert_eq!(
+        attr.input_schema.get("$schema").unwrap(),
+        SchemaDraft::Draft07.meta_schema()
+    );
+
+    let schema = schema_for_type_with_draft::<GetForecastRequest>(SchemaDraft::Draft07);
+    assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+    assert_eq!(
+        schema["properties"]["weather"]["$ref"],
+        "#/definitions/GetWeatherRequest"
+    );
+    assert!(schema["definitions"].get("GetWeatherRequest").is_some());
+
+    let schema = schema_for_type_with_draft::<GetForecastRequest>(SchemaDraft::Draft2020_12);
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(
+        schema["properties"]["weather"]["$ref"],
+        "#/$defs/GetWeatherRequest"
+    );
+    assert!(schema["$defs"].get("GetWeatherRequest").is_some());
+    assert!(schema.get("definitions").is_none());
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Operand {
+    pub value: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct NestedSumRequest {
+    pub a: Operand,
+    pub b: Operand,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Expression {
+    pub value: i32,
+    pub children: Vec<Expression>,
+}
+
+#[test]
+fn test_tool_schema_inline_subschemas() {
+    let referenced = SchemaOptions::default();
+    let inlined = SchemaOptions {
+        inline_subschemas: true,
+        ..Default::default()
+    };
+    let schema = schema_for_type_with_options::<NestedSumRequest>(referenced);
+    assert_eq!(schema["properties"]["a"]["$ref"], "#/definitions/Operand");
+
+    let schema = schema_for_type_with_options::<NestedSumRequest>(inlined);
+    assert!(schema.get("definitions").is_none());
+    for operand in ["a", "b"] {
+        let operand = &schema["properties"][operand];
+        assert!(operand.get("$ref").is_none());
+        assert_eq!(operand["type"], "object");
+        assert_eq!(operand["properties"]["value"]["type"], "integer");
+    }
+
+    // the cyclic position keeps a reference
+    let schema = schema_for_type_with_options::<Expression>(SchemaOptions {
+        draft: SchemaDraft::Draft2020_12,
+        inline_subschemas: true,
+    });
+    assert_eq!(
+        schema["properties"]["children"]["items"]["$ref"],
+        "#/$defs/Expression"
+    );
+    assert!(schema["$defs"].get("Expression").is_some());
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ForecastServer {}
+
+#[tool(
+    tool_box,
+    schema_draft = SchemaDraft::Draft2020_12,
+    inline_subschemas = true
+)]
+impl ForecastServer {
+    #[tool(description = "Get the forecast of a city.")]
+    async fn get_forecast(&self, #[tool(aggr)] request: GetForecastRequest) -> String {
+        drop(request);
+        "rain".to_string()
+    }
+}
+
+#[test]
+fn test_tool_box_schema_options() {
+    let tools = ForecastServer::tool_catalog();
+    let schema = &tools[0].input_schema;
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["properties"]["weather"]["type"], "object");
+    assert!(schema.get("$defs").is_none());
+
+    // the tool box passes its options, the tool attribute alone uses the defaults
+    let options = SchemaOptions {
+        draft: SchemaDraft::Draft2020_12,
+        inline_subschemas: true,
+    };
+    let tool = ForecastServer::get_forecast_tool_attr_with_options(options);
+    assert_eq!(&tool.input_schema, schema);
+    let schema = ForecastServer::get_forecast_tool_attr().input_schema;
+    assert_eq!(schema["$schema"], SchemaDraft::Draft07.meta_schema());
+    assert_eq!(
+        schema["properties"]["weather"]["$ref"],
+        "#/definitions/GetWeatherRequest"
+    );
 }
 
 impl GetWeatherRequest {}
@@ -0,0 +1,101 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, ServerHandler,
+    model::{CallToolResult, JsonObject, ServerCapabilities, ServerInfo},
+    schemars,
+    service::RunningService,
+    tool,
+};
+use serde_json::json;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GreetRequest {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Take no argument")]
+    fn ping(&self) -> String {
+        "pong".to_owned()
+    }
+
+    #[tool(description = "Greet someone, or everyone")]
+    fn greet(&self, #[tool(aggr)] request: GreetRequest) -> String {
+        format!("hello {}", request.name.as_deref().unwrap_or("everyone"))
+    }
+
+    #[tool(description = "Greet someone, or everyone, with a single parameter")]
+    fn greet_param(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "who to greet")]
+        name: Option<String>,
+    ) -> String {
+        format!("hello {}", name.as_deref().unwrap_or("everyone"))
+    }
+
+    #[tool(description = "Count the arguments")]
+    fn count(&self, arguments: JsonObject) -> String {
+        arguments.len().to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+async fn connect() -> anyhow::Result<RunningService<ClientHandlerService>> {
+    let (_, client) = common::connect(Server).await?;
+    Ok(client)
+}
+
+/// Call the tool with the raw `params`, as a JS client would send them
+async fn call(
+    client: &RunningService<ClientHandlerService>,
+    params: serde_json::Value,
+) -> anyhow::Result<String> {
+    let result = client
+        .peer()
+        .request("tools/call", params.as_object().cloned())
+        .await?;
+    let result: CallToolResult = serde_json::from_value(result)?;
+    Ok(result.content[0].as_text().expect("text").text.clone())
+}
+
+#[tokio::test]
+async fn test_null_arguments() -> anyhow::Result<()> {
+    let client = connect().await?;
+    for (name, expected) in [
+        ("ping", "pong"),
+        ("greet", "hello everyone"),
+        ("greet_param", "hello everyone"),
+        ("count", "0"),
+    ] {
+        // absent, `null` and empty arguments are all the same
+        for params in [
+            json!({ "name": name }),
+            json!({ "name": name, "arguments": null }),
+            json!({ "name": name, "arguments": {} }),
+        ] {
+            assert_eq!(call(&client, params.clone()).await?, expected, "{params}");
+        }
+    }
+    // as is a `null` parameter
+    let params = json!({ "name": "greet_param", "arguments": { "name": null } });
+    assert_eq!(call(&client, params).await?, "hello everyone");
+    let params = json!({ "name": "greet_param", "arguments": { "name": "Ada" } });
+    assert_eq!(call(&client, params).await?, "hello Ada");
+    client.cancel().await?;
+    Ok(())
+}
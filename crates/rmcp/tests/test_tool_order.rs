@@ -0,0 +1,133 @@
+use std::{borrow::Cow, sync::Arc};
+
+use rmcp::{
+    handler::server::tool::{SchemaDraft, ToolBox, ToolBoxItem, ToolOrder},
+    model::{CallToolResult, Tool},
+    tool, tool_box,
+};
+
+#[derive(Debug, Clone)]
+pub struct Declared;
+
+#[tool(tool_box)]
+impl Declared {
+    #[tool(description = "z")]
+    fn zulu(&self) -> String {
+        "zulu".into()
+    }
+
+    #[tool(description = "a")]
+    fn alpha(&self) -> String {
+        "alpha".into()
+    }
+
+    #[tool(description = "m")]
+    fn mike(&self) -> String {
+        "mike".into()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sorted;
+
+#[tool(tool_box, order = ToolOrder::Alphabetical)]
+impl Sorted {
+    #[tool(description = "z")]
+    fn zulu(&self) -> String {
+        "zulu".into()
+    }
+
+    #[tool(description = "a")]
+    fn alpha(&self) -> String {
+        "alpha".into()
+    }
+
+    #[tool(description = "m")]
+    fn mike(&self) -> String {
+        "mike".into()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Manual;
+
+impl Manual {
+    #[tool(description = "z")]
+    fn zulu(&self) -> String {
+        "zulu".into()
+    }
+
+    #[tool(description = "a")]
+    fn alpha(&self) -> String {
+        "alpha".into()
+    }
+
+    // the options are accepted in any order
+    tool_box!(Manual { zulu, alpha } tool_box, inline_subschemas = true, order = ToolOrder::Alphabetical, schema_draft = SchemaDraft::Draft2020_12,);
+}
+
+fn names(tools: Vec<Tool>) -> Vec<String> {
+    tools
+        .into_iter()
+        .map(|tool| tool.name.to_string())
+        .collect()
+}
+
+fn item(name: impl Into<Cow<'static, str>>) -> ToolBoxItem<()> {
+    ToolBoxItem::new(
+        Tool::new(name, "a tool", Arc::new(Default::default())),
+        |_| Box::pin(async { Ok(CallToolResult::success(vec![])) }),
+    )
+}
+
+#[test]
+fn test_macro_order() {
+    assert_eq!(
+        names(Declared::tool_box().list()),
+        ["zulu", "alpha", "mike"]
+    );
+    assert_eq!(Sorted::tool_box().order(), ToolOrder::Alphabetical);
+    assert_eq!(names(Sorted::tool_box().list()), ["alpha", "mike", "zulu"]);
+}
+
+#[test]
+fn test_tool_box_macro_options_in_any_order() {
+    let tools = Manual::tool_box().list();
+    assert_eq!(names(tools.clone()), ["alpha", "zulu"]);
+    assert_eq!(
+        tools[0].input_schema["$schema"],
+        SchemaDraft::Draft2020_12.meta_schema()
+    );
+}
+
+/// The map is a `HashMap`, whose iteration order changes from one instance to another
+#[test]
+fn test_stable_order() {
+    let names = (1..=20).map(|i| format!("tool_{i}")).collect::<Vec<_>>();
+    for _ in 0..10 {
+        let mut tool_box = ToolBox::new();
+        for name in &names {
+            tool_box.add(item(name.clone()));
+        }
+        assert_eq!(self::names(tool_box.list()), names);
+
+        tool_box.set_order(ToolOrder::Alphabetical);
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(self::names(tool_box.list()), sorted);
+    }
+}
+
+#[test]
+fn test_replaced_and_inserted_tools() {
+    let mut tool_box = ToolBox::new();
+    for name in ["b", "a", "c"] {
+        tool_box.add(item(name));
+    }
+    // a replaced tool keeps its place
+    tool_box.add(item("a"));
+    let inserted = item("0");
+    tool_box.map.insert(inserted.attr.name.clone(), inserted);
+    tool_box.map.remove("c");
+    assert_eq!(names(tool_box.list()), ["b", "a", "0"]);
+}
@@ -0,0 +1,102 @@
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    handler::server::tool::ToolContext,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, Cursor, ServerCapabilities, ServerInfo,
+    },
+    serve_client, serve_server, tool,
+};
+
+/// Lists 250 numbers, by pages of 100 when the client asks for a page
+#[derive(Debug, Clone, Default)]
+struct Server;
+
+fn numbers() -> Vec<Content> {
+    (0..250).map(|i| Content::text(i.to_string())).collect()
+}
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "List the numbers, by pages")]
+    fn list(&self, context: ToolContext) -> Result<CallToolResult, rmcp::Error> {
+        match context.cursor()? {
+            Some(cursor) => CallToolResult::paginate(numbers(), &cursor, 100),
+            None => Ok(CallToolResult::success(numbers())),
+        }
+    }
+
+    #[tool(description = "List the numbers, all at once")]
+    fn list_unpaged(&self) -> Result<CallToolResult, rmcp::Error> {
+        Ok(CallToolResult::success(numbers()))
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+async fn connect() -> anyhow::Result<rmcp::service::RunningService<ClientHandlerService>> {
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(serve_server(ServerHandlerService::new(Server), server_io));
+    Ok(serve_client(ClientHandlerService::simple(), client_io).await?)
+}
+
+fn texts(result: &CallToolResult) -> Vec<String> {
+    result
+        .content
+        .iter()
+        .map(|content| content.as_text().expect("text").text.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pages() -> anyhow::Result<()> {
+    let client = connect().await?;
+    let first = client
+        .call_tool(CallToolRequestParam::new("list").with_cursor(Cursor::default()))
+        .await?;
+    assert_eq!(first.content.len(), 100);
+    let cursor = first.next_cursor()?.expect("a second page");
+    let second = client
+        .call_tool(CallToolRequestParam::new("list").with_cursor(cursor))
+        .await?;
+    assert_eq!(texts(&second)[0], "100");
+    assert!(second.next_cursor()?.is_some());
+
+    // a client which doesn't ask for a page gets all the content
+    let all = client.call_tool(CallToolRequestParam::new("list")).await?;
+    assert_eq!(all.content.len(), 250);
+    assert_eq!(all.next_cursor()?, None);
+
+    // an invalid cursor is rejected
+    client
+        .call_tool(CallToolRequestParam::new("list").with_cursor(Cursor::new("1000")))
+        .await
+        .expect_err("invalid cursor");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_all_pages() -> anyhow::Result<()> {
+    let client = connect().await?;
+    let expected = (0..250).map(|i| i.to_string()).collect::<Vec<_>>();
+    let paged = client
+        .call_tool_all_pages(CallToolRequestParam::new("list"))
+        .await?;
+    assert_eq!(texts(&paged), expected);
+    assert_eq!(paged.next_cursor()?, None);
+    // a tool which doesn't page its content returns it at once
+    let unpaged = client
+        .call_tool_all_pages(CallToolRequestParam::new("list_unpaged"))
+        .await?;
+    assert_eq!(texts(&unpaged), expected);
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use rmcp::{
+    handler::server::tool::{ToolCallContext, ToolContext},
+    model::{CallToolRequestParam, ErrorCode, JsonObject},
+    schemars, tool,
+};
+use serde_json::json;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct LogRequest {
+    pub message: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StrictLogRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Log a message, and report the unknown fields")]
+    fn log(&self, #[tool(aggr)] request: LogRequest, context: ToolContext) -> String {
+        let arguments = context.raw_arguments().expect("arguments");
+        let mut extra = arguments
+            .keys()
+            .filter(|key| *key != "message")
+            .cloned()
+            .collect::<Vec<_>>();
+        extra.sort();
+        format!("{}; extra: {}", request.message, extra.join(","))
+    }
+
+    #[tool(description = "Log a message, with the raw arguments as a parameter")]
+    fn log_raw(&self, #[tool(aggr)] request: LogRequest, arguments: JsonObject) -> String {
+        format!("{}; {} arguments", request.message, arguments.len())
+    }
+
+    #[tool(description = "Log a message, rejecting unknown fields")]
+    fn log_strict(&self, #[tool(aggr)] request: StrictLogRequest) -> String {
+        request.message
+    }
+}
+
+async fn call(name: &'static str) -> Result<String, rmcp::Error> {
+    let context = ToolCallContext::new(
+        &Server,
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: json!({ "message": "hello", "level": "debug", "trace": 1 })
+                .as_object()
+                .cloned(),
+            meta: None,
+        },
+        common::request_context(),
+    );
+    let result = Server::tool_box().call(context).await?;
+    Ok(result.content[0]
+        .as_text()
+        .expect("text content")
+        .text
+        .clone())
+}
+
+#[tokio::test]
+async fn test_tool_raw_arguments() -> anyhow::Result<()> {
+    assert_eq!(call("log").await?, "hello; extra: level,trace");
+    assert_eq!(call("log_raw").await?, "hello; 3 arguments");
+    let error = call("log_strict").await.expect_err("unknown fields");
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("unknown field"), "{}", error.message);
+    Ok(())
+}
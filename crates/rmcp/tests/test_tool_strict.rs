@@ -0,0 +1,86 @@
+mod common;
+
+use rmcp::{
+    handler::server::tool::ToolCallContext,
+    model::{CallToolRequestParam, ErrorCode},
+    schemars, tool,
+};
+use serde_json::json;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SumRequest {
+    #[serde(default)]
+    pub a: i32,
+    #[serde(default)]
+    pub b: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Calculator;
+
+#[tool(tool_box)]
+impl Calculator {
+    #[tool(description = "Calculate the sum of two numbers")]
+    fn sum(&self, #[tool(aggr)] SumRequest { a, b }: SumRequest) -> String {
+        (a + b).to_string()
+    }
+
+    #[tool(strict, description = "Calculate the sum of two numbers")]
+    fn strict_sum(&self, #[tool(aggr)] SumRequest { a, b }: SumRequest) -> String {
+        (a + b).to_string()
+    }
+
+    #[tool(description = "Calculate the difference of two numbers", strict)]
+    fn strict_sub(&self, #[tool(param)] a: i32, #[tool(param)] b: i32) -> String {
+        (a - b).to_string()
+    }
+
+    #[tool(strict)]
+    fn strict_zero(&self) -> String {
+        "0".to_string()
+    }
+}
+
+async fn call(name: &'static str, arguments: serde_json::Value) -> Result<String, rmcp::Error> {
+    let context = ToolCallContext::new(
+        &Calculator,
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: arguments.as_object().cloned(),
+            meta: None,
+        },
+        common::request_context(),
+    );
+    let result = Calculator::tool_box().call(context).await?;
+    Ok(result.content[0]
+        .as_text()
+        .expect("text content")
+        .text
+        .clone())
+}
+
+#[tokio::test]
+async fn test_tool_strict() -> anyhow::Result<()> {
+    // the typo is silently ignored by default
+    assert_eq!(call("sum", json!({ "aa": 1, "b": 2 })).await?, "2");
+
+    assert_eq!(call("strict_sum", json!({ "a": 1, "b": 2 })).await?, "3");
+    assert_eq!(call("strict_sub", json!({ "a": 3, "b": 2 })).await?, "1");
+    assert_eq!(call("strict_zero", json!({})).await?, "0");
+
+    for (name, arguments) in [
+        ("strict_sum", json!({ "aa": 1, "b": 2 })),
+        ("strict_sub", json!({ "a": 3, "b": 2, "aa": 1 })),
+        ("strict_zero", json!({ "aa": 1 })),
+    ] {
+        let error = call(name, arguments).await.expect_err("unknown field");
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert!(
+            error.message.contains("unknown field `aa`"),
+            "{}",
+            error.message
+        );
+        assert_eq!(error.data, Some(json!({ "field": "aa" })));
+    }
+    Ok(())
+}
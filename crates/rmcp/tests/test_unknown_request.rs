@@ -0,0 +1,177 @@
+mod common;
+
+use rmcp::{
+    ClientHandlerService, Peer, RoleClient, RoleServer, ServerHandler, ServerHandlerService,
+    ServiceError,
+    model::{
+        ClientRequest, ErrorCode, ServerResult, UnknownNotification, UnknownRequest, UnknownResult,
+    },
+    serve_client, serve_server,
+    service::{RequestContext, RunningService, Service},
+};
+use serde_json::json;
+
+/// The upstream server, which knows the experimental methods
+#[derive(Debug, Clone, Default)]
+pub struct Upstream;
+
+impl ServerHandler for Upstream {
+    async fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, rmcp::Error> {
+        match request.method.as_str() {
+            "experimental/echo" => Ok(ServerResult::UnknownResult(UnknownResult(
+                request.params.unwrap_or_default(),
+            ))),
+            _ => Err(rmcp::Error::new(
+                ErrorCode(-32001),
+                "upstream refused",
+                Some(json!({ "method": request.method })),
+            )),
+        }
+    }
+}
+
+/// A relay which doesn't know the experimental methods and passes them through
+#[derive(Debug, Clone)]
+pub struct Proxy {
+    upstream: Peer<RoleClient>,
+}
+
+impl ServerHandler for Proxy {
+    async fn unknown_request(
+        &self,
+        request: UnknownRequest,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, rmcp::Error> {
+        match self
+            .upstream
+            .send_request(ClientRequest::Unknown(request))
+            .await
+        {
+            Ok(result) => Ok(result),
+            Err(ServiceError::McpError(error)) => Err(error),
+            Err(error) => Err(rmcp::Error::internal_error(error.to_string(), None)),
+        }
+    }
+}
+
+async fn connect<S: Service<Role = RoleServer>>(
+    server: S,
+) -> anyhow::Result<RunningService<ClientHandlerService>> {
+    let (server_transport, client_transport) = common::transports();
+    let server = tokio::spawn(serve_server(server, server_transport));
+    let client = serve_client(ClientHandlerService::new(None), client_transport).await?;
+    // the server keeps running in the background after its handle is dropped
+    server.await??;
+    Ok(client)
+}
+
+#[tokio::test]
+async fn test_unknown_request_pass_through() -> anyhow::Result<()> {
+    let upstream = connect(ServerHandlerService::new(Upstream)).await?;
+    let client = connect(ServerHandlerService::new(Proxy {
+        upstream: upstream.peer().clone(),
+    }))
+    .await?;
+
+    let params = json!({ "message": "hello", "nested": { "value": 1 } });
+    let result = client
+        .send_request(ClientRequest::Unknown(UnknownRequest::new(
+            "experimental/echo",
+            params.as_object().cloned(),
+        )))
+        .await?;
+    let ServerResult::UnknownResult(UnknownResult(result)) = result else {
+        panic!("expect an unknown result, got {result:?}");
+    };
+    assert_eq!(serde_json::Value::Object(result), params);
+
+    // the error originated by the upstream is passed through untouched
+    let error = client
+        .send_request(ClientRequest::Unknown(UnknownRequest::new(
+            "experimental/refused",
+            None,
+        )))
+        .await
+        .expect_err("upstream refused");
+    let ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode(-32001));
+    assert_eq!(error.message, "upstream refused");
+    assert_eq!(
+        error.data,
+        Some(json!({ "method": "experimental/refused" }))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_request_method_not_found() -> anyhow::Result<()> {
+    #[derive(Debug, Clone, Default)]
+    pub struct Server;
+    impl ServerHandler for Server {}
+
+    let client = connect(ServerHandlerService::new(Server)).await?;
+    let error = client
+        .send_request(ClientRequest::Unknown(UnknownRequest::new(
+            "experimental/echo",
+            None,
+        )))
+        .await
+        .expect_err("method not found");
+    let ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_request_and_notify() -> anyhow::Result<()> {
+    /// Echo the experimental requests, and forward the experimental notifications to the test
+    #[derive(Debug, Clone)]
+    pub struct Server {
+        notifications: tokio::sync::mpsc::UnboundedSender<UnknownNotification>,
+    }
+    impl ServerHandler for Server {
+        async fn unknown_request(
+            &self,
+            request: UnknownRequest,
+            context: RequestContext<RoleServer>,
+        ) -> Result<ServerResult, rmcp::Error> {
+            Upstream.unknown_request(request, context).await
+        }
+        async fn on_unknown_notification(&self, notification: UnknownNotification) {
+            let _ = self.notifications.send(notification);
+        }
+    }
+
+    let (notifications, mut received) = tokio::sync::mpsc::unbounded_channel();
+    let client = connect(ServerHandlerService::new(Server { notifications })).await?;
+
+    let params = json!({ "message": "hello" });
+    let result = client
+        .request("experimental/echo", params.as_object().cloned())
+        .await?;
+    assert_eq!(result, params);
+    let error = client
+        .request("experimental/refused", None)
+        .await
+        .expect_err("refused");
+    let ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    assert_eq!(error.code, ErrorCode(-32001));
+
+    client
+        .notify("notifications/experimental", params.as_object().cloned())
+        .await?;
+    let notification = received.recv().await.expect("notification");
+    assert_eq!(notification.method, "notifications/experimental");
+    assert_eq!(notification.params, params.as_object().cloned());
+    Ok(())
+}
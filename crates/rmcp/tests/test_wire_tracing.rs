@@ -0,0 +1,89 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandlerService, RoleClient, ServerHandler, ServerHandlerService,
+    model::CallToolRequestParam,
+    serve_client_with_options, serve_server,
+    service::{ClientInitializeOptions, Peer, ServeConfig, WIRE_TRACING_TARGET},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+/// Collect the formatted events in memory
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_wire_tracing() -> anyhow::Result<()> {
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_max_level(tracing::Level::INFO)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (server_transport, client_transport) = common::transports();
+    let server = tokio::spawn(serve_server(
+        ServerHandlerService::new(Server),
+        server_transport,
+    ));
+    let options = ClientInitializeOptions::default()
+        .with_serve_config(ServeConfig::default().with_wire_tracing(true));
+    let client = serve_client_with_options(
+        ClientHandlerService::new(None::<Peer<RoleClient>>),
+        client_transport,
+        options,
+    )
+    .await?;
+    let _server = server.await??;
+
+    client.peer().list_tools(Default::default()).await?;
+    let error = client
+        .peer()
+        .call_tool(CallToolRequestParam {
+            name: "missing".into(),
+            arguments: None,
+            meta: None,
+        })
+        .await
+        .expect_err("no tools");
+    let rmcp::ServiceError::McpError(error) = error else {
+        panic!("expect a mcp error, got {error:?}");
+    };
+    client.cancel().await?;
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone())?;
+    let wire_lines = logs
+        .lines()
+        .filter(|line| line.contains(WIRE_TRACING_TARGET))
+        .collect::<Vec<_>>();
+    let find = |method: &str, message: &str| {
+        wire_lines
+            .iter()
+            .find(|line| line.contains(&format!("method=\"{method}\"")) && line.contains(message))
+            .unwrap_or_else(|| panic!("no {message} for {method} in:\n{logs}"))
+    };
+    assert!(find("tools/list", "send request").contains("id=1"));
+    assert!(find("tools/list", "receive response").contains("elapsed="));
+    assert!(find("tools/call", "send request").contains("id=2"));
+    let error_line = find("tools/call", "receive error");
+    assert!(error_line.contains(&format!("code={}", error.code.0)));
+    Ok(())
+}
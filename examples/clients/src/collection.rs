@@ -44,6 +44,7 @@ async fn main() -> Result<()> {
             .call_tool(CallToolRequestParam {
                 name: "git_status".into(),
                 arguments: serde_json::json!({ "repo_path": "." }).as_object().cloned(),
+                meta: None,
             })
             .await?;
     }
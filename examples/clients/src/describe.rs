@@ -0,0 +1,62 @@
+use anyhow::Result;
+use rmcp::{ClientHandlerService, serve_client, transport::child_process::TokioChildProcess};
+use tokio::process::Command;
+
+/// Print a card of everything the stdio server example exposes
+#[tokio::main]
+async fn main() -> Result<()> {
+    let service = serve_client(
+        ClientHandlerService::simple(),
+        TokioChildProcess::new(Command::new("cargo").args([
+            "run",
+            "-p",
+            "mcp-server-examples",
+            "--example",
+            "std_io",
+        ]))?,
+    )
+    .await?;
+
+    let description = service.describe().await;
+    let info = &description.info;
+    println!(
+        "{} {} (protocol {})",
+        info.server_info.name, info.server_info.version, info.protocol_version
+    );
+    if let Some(instructions) = &info.instructions {
+        println!("{instructions}");
+    }
+    match &description.tools {
+        Ok(tools) => {
+            println!("\nTools:");
+            for tool in tools {
+                println!("  {}: {}", tool.name, tool.description);
+            }
+        }
+        Err(e) => println!("\nTools unavailable: {e}"),
+    }
+    match &description.prompts {
+        Ok(prompts) => {
+            println!("\nPrompts:");
+            for prompt in prompts {
+                println!(
+                    "  {}: {}",
+                    prompt.name,
+                    prompt.description.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        Err(e) => println!("\nPrompts unavailable: {e}"),
+    }
+    match &description.resources {
+        Ok(resources) => {
+            println!("\nResources:");
+            for resource in resources {
+                println!("  {} ({})", resource.name, resource.uri);
+            }
+        }
+        Err(e) => println!("\nResources unavailable: {e}"),
+    }
+    service.cancel().await?;
+    Ok(())
+}
@@ -50,6 +50,7 @@ async fn main() -> Result<()> {
         .call_tool(CallToolRequestParam {
             name: "echo".into(),
             arguments: Some(object!({ "message": "hi from rmcp" })),
+            meta: None,
         })
         .await?;
     tracing::info!("Tool result for echo: {tool_result:#?}");
@@ -59,6 +60,7 @@ async fn main() -> Result<()> {
         .call_tool(CallToolRequestParam {
             name: "longRunningOperation".into(),
             arguments: Some(object!({ "duration": 3, "steps": 1 })),
+            meta: None,
         })
         .await?;
     tracing::info!("Tool result for longRunningOperation: {tool_result:#?}");
@@ -71,9 +73,7 @@ async fn main() -> Result<()> {
 
     // Read resource
     let resource = service
-        .read_resource(ReadResourceRequestParam {
-            uri: "test://static/resource/3".into(),
-        })
+        .read_resource(ReadResourceRequestParam::new("test://static/resource/3"))
         .await?;
     tracing::info!("Resource: {resource:#?}");
 
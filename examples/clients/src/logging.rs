@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    handler::client::LoggingClientHandler,
+    model::{
+        LoggingLevel, NumberOrString, ProgressNotificationParam, ServerCapabilities, ServerInfo,
+    },
+    serve_client, serve_server,
+};
+use tracing_subscriber::EnvFilter;
+
+/// A server which only pushes notifications
+#[derive(Debug, Clone)]
+struct Indexer;
+
+impl ServerHandler for Indexer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_logging()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Serve the indexer in memory, and log what it pushes without writing a client handler
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
+        .init();
+
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(serve_server(ServerHandlerService::new(Indexer), server_io));
+    let client = serve_client(
+        ClientHandlerService::new(LoggingClientHandler::default()),
+        client_io,
+    )
+    .await?;
+    let server = server.await??;
+
+    let peer = server.peer();
+    peer.log_text(LoggingLevel::Info, Some("indexer"), "indexing started")
+        .await?;
+    for progress in 1..=3 {
+        peer.notify_progress(ProgressNotificationParam {
+            progress_token: NumberOrString::Number(1),
+            progress,
+            total: Some(3),
+            message: Some(format!("file {progress} of 3")),
+        })
+        .await?;
+    }
+    peer.log_structured(
+        LoggingLevel::Warning,
+        Some("indexer"),
+        serde_json::json!({ "skipped": ["image.bin"] }),
+    )
+    .await?;
+    peer.notify_tool_list_changed().await?;
+
+    // the notifications are handled concurrently, let the client log them
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
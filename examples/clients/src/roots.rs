@@ -0,0 +1,111 @@
+use anyhow::Result;
+use rmcp::{
+    ClientHandler, ClientHandlerService, Peer, RoleClient, RoleServer, ServerHandler,
+    ServerHandlerService, handler::client::Roots, model::Root, serve_client, serve_server,
+};
+
+/// A client exposing the directories of its workspace as roots
+#[derive(Debug, Clone)]
+struct WorkspaceClient {
+    peer: Option<Peer<RoleClient>>,
+    roots: Roots,
+}
+
+impl ClientHandler for WorkspaceClient {
+    fn roots(&self) -> Option<&Roots> {
+        Some(&self.roots)
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+/// A server listing the roots of the client each time they change
+#[derive(Debug, Clone, Default)]
+struct RootsServer {
+    peer: Option<Peer<RoleServer>>,
+}
+
+impl ServerHandler for RootsServer {
+    async fn on_roots_list_changed(&self) {
+        let Some(peer) = &self.peer else {
+            return;
+        };
+        match peer.list_roots().await {
+            Ok(result) => {
+                for root in result.roots {
+                    println!("root changed: {} {:?}", root.uri, root.name);
+                }
+            }
+            Err(error) => tracing::error!(%error, "fail to list the roots"),
+        }
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        self.peer = Some(peer);
+    }
+}
+
+fn file_url(path: &std::path::Path) -> String {
+    format!("file://{}", path.display())
+}
+
+/// cargo run -p mcp-client-examples --example roots
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
+    let workspace = std::env::current_dir()?;
+    let roots = Roots::new([
+        Root::new(file_url(&workspace)).with_name("workspace"),
+        Root::new(file_url(&workspace.join("examples"))).with_name("examples"),
+    ])?;
+
+    // the server runs in process, connected to the client by a pipe
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(serve_server(
+        ServerHandlerService::new(RootsServer::default()),
+        server_io,
+    ));
+    let client = serve_client(
+        ClientHandlerService::new(WorkspaceClient {
+            peer: None,
+            roots: roots.clone(),
+        }),
+        client_io,
+    )
+    .await?;
+    let server = server.await??;
+
+    println!(
+        "the client advertises {:?}",
+        server.peer_info().capabilities
+    );
+    for root in server.list_roots().await?.roots {
+        println!("root: {} {:?}", root.uri, root.name);
+    }
+
+    // narrow the workspace, the server lists the roots again
+    roots
+        .update(
+            client.peer(),
+            [Root::new(file_url(&workspace.join("crates"))).with_name("crates")],
+        )
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    client.cancel().await?;
+    Ok(())
+}
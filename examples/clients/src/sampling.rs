@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use rmcp::{
+    ClientHandler, ClientHandlerService, Error as McpError, Peer, RoleClient, RoleServer,
+    ServerHandler, ServerHandlerService,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, CreateMessageRequestParam,
+        CreateMessageResult, ListToolsResult, PaginatedRequestParam, Role, SamplingMessage,
+        ServerCapabilities, ServerInfo, Tool,
+    },
+    serve_client, serve_server,
+    service::RequestContext,
+};
+use serde_json::json;
+
+/// Stands for a LLM: it answers with the last message reversed
+#[derive(Debug, Clone, Default)]
+struct MockLlm;
+
+impl MockLlm {
+    fn complete(&self, messages: &[SamplingMessage]) -> String {
+        messages
+            .last()
+            .and_then(|message| message.content.as_text())
+            .map(|text| text.text.chars().rev().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A client routing the sampling requests of the server to the LLM
+#[derive(Debug, Clone, Default)]
+struct SamplingClient {
+    peer: Option<Peer<RoleClient>>,
+    llm: MockLlm,
+}
+
+impl ClientHandler for SamplingClient {
+    fn can_create_message(&self) -> bool {
+        true
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        tracing::info!(messages = params.messages.len(), "sampling requested");
+        Ok(CreateMessageResult {
+            model: "mock-llm".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text(self.llm.complete(&params.messages)),
+            },
+            meta: None,
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+/// A server whose `ask` tool asks the LLM of the client
+#[derive(Debug, Clone)]
+struct AskServer;
+
+impl ServerHandler for AskServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let schema = json!({
+            "type": "object",
+            "properties": { "question": { "type": "string" } },
+            "required": ["question"]
+        });
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: vec![Tool::new(
+                "ask",
+                "Ask the LLM of the client",
+                Arc::new(schema.as_object().cloned().unwrap_or_default()),
+            )],
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let question = request
+            .arguments
+            .as_ref()
+            .and_then(|arguments| arguments.get("question")?.as_str())
+            .ok_or_else(|| McpError::invalid_params("missing question", None))?;
+        let answer = context
+            .peer
+            .create_message(CreateMessageRequestParam {
+                messages: vec![SamplingMessage {
+                    role: Role::User,
+                    content: Content::text(question),
+                }],
+                model_preferences: None,
+                system_prompt: Some("Answer briefly".into()),
+                include_context: None,
+                temperature: None,
+                max_tokens: 256,
+                stop_sequences: None,
+                metadata: None,
+                meta: None,
+            })
+            .await
+            .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+        Ok(CallToolResult::success(vec![answer.message.content]))
+    }
+}
+
+/// cargo run -p mcp-client-examples --example sampling
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
+    // the server runs in process, connected to the client by a pipe
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = serve_server(ServerHandlerService::new(AskServer), server_io).await?;
+        server.waiting().await;
+        anyhow::Ok(())
+    });
+
+    let client = serve_client(
+        ClientHandlerService::new(SamplingClient::default()),
+        client_io,
+    )
+    .await?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "ask".into(),
+            arguments: json!({ "question": "hello sampling" }).as_object().cloned(),
+            meta: None,
+        })
+        .await?;
+    for content in &result.content {
+        if let Some(text) = content.as_text() {
+            println!("{}", text.text);
+        }
+    }
+    client.cancel().await?;
+    Ok(())
+}
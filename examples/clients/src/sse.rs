@@ -16,7 +16,8 @@ async fn main() -> Result<()> {
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
-    let transport = SseTransport::start("http://localhost:8000/sse", Default::default()).await?;
+    let transport =
+        SseTransport::start("http://localhost:8000/mcp/sse", Default::default()).await?;
 
     let client = serve_client(ClientHandlerService::simple(), transport)
         .await
@@ -37,6 +38,7 @@ async fn main() -> Result<()> {
         .call_tool(CallToolRequestParam {
             name: "git_status".into(),
             arguments: serde_json::json!({ "repo_path": "." }).as_object().cloned(),
+            meta: None,
         })
         .await?;
     tracing::info!("Tool result: {tool_result:#?}");
@@ -0,0 +1,59 @@
+//! Measure the latency of a burst of tool calls over SSE, against the axum server example.
+//!
+//! Run `cargo run --example sse_burst` for the default HTTP version, and `cargo run --example sse_burst -- --http1`
+//! to compare with HTTP/1.1. Each call is sent once the previous one is answered, so the time of a call is its latency.
+use std::time::Instant;
+
+use anyhow::Result;
+use rmcp::{
+    ClientHandlerService,
+    model::CallToolRequestParam,
+    serve_client,
+    transport::sse::{SseHttpVersion, SseTransport},
+};
+
+const CALLS: usize = 100;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let http_version = if std::env::args().any(|arg| arg == "--http1") {
+        SseHttpVersion::Http1Only
+    } else {
+        SseHttpVersion::Auto
+    };
+    let transport = SseTransport::builder("http://localhost:8000/mcp/sse")
+        .http_version(http_version)
+        .start()
+        .await?;
+    let client = serve_client(ClientHandlerService::simple(), transport).await?;
+
+    let mut latencies = Vec::with_capacity(CALLS);
+    let start = Instant::now();
+    for _ in 0..CALLS {
+        let call = Instant::now();
+        client
+            .call_tool(CallToolRequestParam {
+                name: "increment".into(),
+                arguments: None,
+                meta: None,
+            })
+            .await?;
+        latencies.push(call.elapsed());
+    }
+    let total = start.elapsed();
+    // the first call may open a connection
+    let first = latencies[0];
+    latencies.sort();
+    let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    println!("{http_version:?}: {CALLS} calls in {total:?}");
+    println!(
+        "latency p50 {:?}, p90 {:?}, p99 {:?}, mean {:?}",
+        percentile(50),
+        percentile(90),
+        percentile(99),
+        total / CALLS as u32,
+    );
+    println!("first call {first:?}");
+    client.cancel().await?;
+    Ok(())
+}
@@ -37,6 +37,7 @@ async fn main() -> Result<()> {
         .call_tool(CallToolRequestParam {
             name: "git_status".into(),
             arguments: serde_json::json!({ "repo_path": "." }).as_object().cloned(),
+            meta: None,
         })
         .await?;
     tracing::info!("Tool result: {tool_result:#?}");
@@ -1,5 +1,6 @@
 use rmcp::{
     ServerHandler,
+    handler::server::tool::ToolBox,
     model::{ServerCapabilities, ServerInfo},
     schemars, tool,
 };
@@ -33,6 +34,13 @@ impl Calculator {
     }
 }
 
+impl Calculator {
+    /// The calculator tools, to compose them with other tool sets
+    pub fn tools() -> &'static ToolBox<Self> {
+        Self::tool_box()
+    }
+}
+
 #[tool(tool_box)]
 impl ServerHandler for Calculator {
     fn get_info(&self) -> ServerInfo {
@@ -0,0 +1,36 @@
+use rmcp::{
+    handler::server::composite::{CompositeHandler, ToolNameCollision},
+    model::{ServerCapabilities, ServerInfo},
+    tool,
+};
+
+use super::calculator::Calculator;
+
+/// A second tool set, living in its own module
+#[derive(Debug, Clone)]
+pub struct TextTools;
+
+#[tool(tool_box)]
+impl TextTools {
+    #[tool(description = "Convert a text to uppercase")]
+    fn uppercase(&self, #[tool(param)] text: String) -> String {
+        text.to_uppercase()
+    }
+
+    #[tool(description = "Count the characters of a text")]
+    fn length(&self, #[tool(param)] text: String) -> String {
+        text.chars().count().to_string()
+    }
+}
+
+/// A server with both the calculator tools and the text tools
+pub fn calculator_with_text_tools() -> Result<CompositeHandler, ToolNameCollision> {
+    CompositeHandler::new()
+        .with_info(ServerInfo {
+            instructions: Some("A calculator, and some text tools".into()),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        })
+        .with_tool_box(Calculator, Calculator::tools())?
+        .with_tool_box(TextTools, TextTools::tool_box())
+}
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use rmcp::{
-    Error as McpError, RoleServer, ServerHandler, const_string, model::*, schemars,
-    service::RequestContext, tool,
+    Error as McpError, RoleServer, ServerHandler, const_string, handler::server::tool::ToolContext,
+    model::*, schemars, service::RequestContext, tool,
 };
 
 use serde_json::json;
@@ -62,6 +62,16 @@ impl Counter {
         Ok(CallToolResult::success(vec![Content::text("hello")]))
     }
 
+    #[tool(description = "Greet the calling client by its name")]
+    fn greet_client(&self, context: ToolContext) -> Result<CallToolResult, McpError> {
+        let client = context.client_info();
+        tracing::info!(client = %client.name, version = %client.version, "greeting client");
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "hello, {}",
+            client.name
+        ))]))
+    }
+
     #[tool(description = "Repeat what you say")]
     fn echo(
         &self,
@@ -94,7 +104,7 @@ impl ServerHandler for Counter {
                 .enable_tools()
                 .build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some("This server provides a counter tool that can increment and decrement values. The counter starts at 0 and can be modified using the 'increment' and 'decrement' tools. Use 'get_value' to check the current count.".to_string()),
+            instructions: Some("This server provides a counter tool that can increment and decrement values. The counter starts at 0 and can be modified using the 'increment' and 'decrement' tools. Use 'get_value' to check the current count.".to_string()), meta: None,
         }
     }
 
@@ -109,12 +119,13 @@ impl ServerHandler for Counter {
                 self._create_resource_text("memo://insights", "memo-name"),
             ],
             next_cursor: None,
+            meta: None,
         })
     }
 
     async fn read_resource(
         &self,
-        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        ReadResourceRequestParam { uri, .. }: ReadResourceRequestParam,
         _: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, McpError> {
         match uri.as_str() {
@@ -122,12 +133,14 @@ impl ServerHandler for Counter {
                 let cwd = "/Users/to/some/path/";
                 Ok(ReadResourceResult {
                     contents: vec![ResourceContents::text(cwd, uri)],
+                    meta: None,
                 })
             }
             "memo://insights" => {
                 let memo = "Business Intelligence Memo\n\nAnalysis has revealed 5 key insights ...";
                 Ok(ReadResourceResult {
                     contents: vec![ResourceContents::text(memo, uri)],
+                    meta: None,
                 })
             }
             _ => Err(McpError::resource_not_found(
@@ -146,15 +159,19 @@ impl ServerHandler for Counter {
     ) -> Result<ListPromptsResult, McpError> {
         Ok(ListPromptsResult {
             next_cursor: None,
-            prompts: vec![Prompt::new(
-                "example_prompt",
-                Some("This is an example prompt that takes one required agrument, message"),
-                Some(vec![PromptArgument {
-                    name: "message".to_string(),
-                    description: Some("A message to put in the prompt".to_string()),
-                    required: Some(true),
-                }]),
-            )],
+            prompts: vec![
+                Prompt::new(
+                    "example_prompt",
+                    Some("This is an example prompt that takes one required agrument, message"),
+                    None,
+                )
+                .with_argument(
+                    PromptArgument::new("message")
+                        .with_description("A message to put in the prompt")
+                        .with_required(true),
+                ),
+            ],
+            meta: None,
         })
     }
 
@@ -170,8 +187,9 @@ impl ServerHandler for Counter {
                     description: None,
                     messages: vec![PromptMessage {
                         role: PromptMessageRole::User,
-                        content: PromptMessageContent::text(prompt),
+                        content: Content::text(prompt),
                     }],
+                    meta: None,
                 })
             }
             _ => Err(McpError::invalid_params("prompt not found", None)),
@@ -186,6 +204,7 @@ impl ServerHandler for Counter {
         Ok(ListResourceTemplatesResult {
             next_cursor: None,
             resource_templates: Vec::new(),
+            meta: None,
         })
     }
 }
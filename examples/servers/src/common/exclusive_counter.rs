@@ -0,0 +1,87 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, handler::server::tool::ExclusiveToolRouter,
+    model::*, service::RequestContext, tool,
+};
+
+/// The counter state, tools can take `&mut self` because the router serializes all the calls.
+#[derive(Debug, Default)]
+pub struct CounterState {
+    value: i32,
+}
+
+#[tool(tool_box)]
+impl CounterState {
+    #[tool(description = "Increment the counter by 1")]
+    fn increment(&mut self) -> String {
+        self.value += 1;
+        self.value.to_string()
+    }
+
+    #[tool(description = "Decrement the counter by 1")]
+    fn decrement(&mut self) -> String {
+        self.value -= 1;
+        self.value.to_string()
+    }
+
+    #[tool(description = "Set the counter to a value")]
+    fn set(
+        &mut self,
+        #[tool(param)]
+        #[schemars(description = "the new value")]
+        value: i32,
+    ) -> String {
+        self.value = value;
+        self.value.to_string()
+    }
+
+    #[tool(description = "Get the current counter value")]
+    fn get_value(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+#[derive(Clone)]
+pub struct ExclusiveCounter {
+    router: ExclusiveToolRouter<CounterState>,
+}
+
+impl ExclusiveCounter {
+    pub fn new() -> Self {
+        Self {
+            router: ExclusiveToolRouter::new(CounterState::default(), CounterState::tool_box()),
+        }
+    }
+}
+
+impl ServerHandler for ExclusiveCounter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some(
+                "A counter whose tools mutate the state directly, all the calls are serialized."
+                    .into(),
+            ),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: self.router.list(),
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.router.call(request, context).await
+    }
+}
@@ -1,2 +1,20 @@
 pub mod calculator;
+pub mod composite;
 pub mod counter;
+pub mod exclusive_counter;
+pub mod plugin_router;
+
+use rmcp::handler::server::DynServerHandler;
+
+/// The server of an example by name, e.g. from its first argument, `counter` by default
+pub fn server(name: Option<&str>) -> anyhow::Result<Box<dyn DynServerHandler>> {
+    match name.unwrap_or("counter") {
+        "counter" => Ok(Box::new(counter::Counter::new())),
+        "exclusive_counter" => Ok(Box::new(exclusive_counter::ExclusiveCounter::new())),
+        "plugins" => Ok(Box::new(plugin_router::PluginRouter::new())),
+        "composite" => Ok(Box::new(composite::calculator_with_text_tools()?)),
+        name => Err(anyhow::anyhow!(
+            "unknown server `{name}`, expect `counter`, `exclusive_counter`, `plugins` or `composite`"
+        )),
+    }
+}
@@ -0,0 +1,88 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler,
+    handler::server::DynServerHandler,
+    model::{
+        CallToolRequestParam, CallToolResult, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+
+use super::{calculator::Calculator, counter::Counter};
+
+/// A server made of plugins, the tools of a plugin are exposed as `{plugin}.{tool}`
+#[derive(Clone)]
+pub struct PluginRouter {
+    plugins: Vec<(String, Box<dyn DynServerHandler>)>,
+}
+
+impl PluginRouter {
+    pub fn new() -> Self {
+        Self {
+            plugins: vec![
+                ("calculator".to_string(), Box::new(Calculator)),
+                ("counter".to_string(), Box::new(Counter::new())),
+            ],
+        }
+    }
+
+    /// Find the plugin of a tool, and the name of the tool in the plugin
+    fn route<'a>(&self, name: &'a str) -> Option<(&dyn DynServerHandler, &'a str)> {
+        let (key, tool) = name.split_once('.')?;
+        self.plugins
+            .iter()
+            .find(|(plugin_key, _)| plugin_key == key)
+            .map(|(_, plugin)| (plugin.as_ref(), tool))
+    }
+}
+
+impl ServerHandler for PluginRouter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("The tools of all the plugins, prefixed by the plugin name".into()),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: PaginatedRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let mut tools = Vec::new();
+        for (key, plugin) in &self.plugins {
+            let plugin = plugin.as_ref();
+            let result = plugin.list_tools(request.clone(), context.clone()).await?;
+            tools.extend(result.tools.into_iter().map(|mut tool| {
+                tool.name = format!("{key}.{name}", name = tool.name).into();
+                tool
+            }));
+        }
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools,
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        CallToolRequestParam {
+            name,
+            arguments,
+            meta,
+        }: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let (plugin, tool) = self
+            .route(&name)
+            .ok_or_else(|| McpError::invalid_params("tool not found", None))?;
+        let request = CallToolRequestParam {
+            name: tool.to_string().into(),
+            arguments,
+            meta,
+        };
+        plugin.call_tool(request, context).await
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServerHandlerService,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, JsonObject, ListToolsResult,
+        PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+    },
+    serve_server,
+    service::RequestContext,
+};
+use serde_json::json;
+use tracing_subscriber::{self, EnvFilter};
+
+/// A server whose tools are registered at runtime with the `register` tool,
+/// each registration tells the client to fetch the tools again with `notifications/tools/list_changed`
+#[derive(Clone, Default)]
+struct DynamicTools {
+    /// The registered tools, with their reply
+    tools: Arc<RwLock<Vec<(Tool, String)>>>,
+}
+
+fn schema(value: serde_json::Value) -> Arc<JsonObject> {
+    match value {
+        serde_json::Value::Object(object) => Arc::new(object),
+        _ => unreachable!("a schema is an object"),
+    }
+}
+
+impl DynamicTools {
+    fn register_tool() -> Tool {
+        Tool::new(
+            "register",
+            "Register a tool which replies with a fixed text",
+            schema(json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "reply": { "type": "string" }
+                },
+                "required": ["name", "reply"]
+            })),
+        )
+    }
+
+    async fn register(
+        &self,
+        arguments: Option<JsonObject>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let argument = |key: &str| {
+            arguments
+                .as_ref()
+                .and_then(|arguments| arguments.get(key)?.as_str())
+                .map(str::to_owned)
+                .ok_or_else(|| McpError::invalid_params(format!("missing {key}"), None))
+        };
+        let (name, reply) = (argument("name")?, argument("reply")?);
+        {
+            let mut tools = self.tools.write().expect("tools lock poisoned");
+            tools.retain(|(tool, _)| tool.name != name);
+            let description = format!("Reply {reply}");
+            tools.push((
+                Tool::new(
+                    name.clone(),
+                    description,
+                    schema(json!({ "type": "object" })),
+                ),
+                reply,
+            ));
+        }
+        context
+            .peer
+            .notify_tools_changed()
+            .await
+            .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+        tracing::info!(%name, "tool registered");
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "registered {name}"
+        ))]))
+    }
+}
+
+impl ServerHandler for DynamicTools {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("Call register to add a tool".into()),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let tools = self.tools.read().expect("tools lock poisoned");
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: std::iter::once(Self::register_tool())
+                .chain(tools.iter().map(|(tool, _)| tool.clone()))
+                .collect(),
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if request.name == "register" {
+            return self.register(request.arguments, context).await;
+        }
+        let tools = self.tools.read().expect("tools lock poisoned");
+        let (_, reply) = tools
+            .iter()
+            .find(|(tool, _)| tool.name == request.name)
+            .ok_or_else(|| McpError::invalid_params(format!("no tool {}", request.name), None))?;
+        Ok(CallToolResult::success(vec![Content::text(reply.clone())]))
+    }
+}
+
+/// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example dynamic_tools
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
+    let service = serve_server(
+        ServerHandlerService::new(DynamicTools::default()),
+        (tokio::io::stdin(), tokio::io::stdout()),
+    )
+    .await
+    .inspect_err(|e| {
+        tracing::error!("serving error: {:?}", e);
+    })?;
+
+    let quit_reason = service.waiting().await;
+    tracing::info!(?quit_reason, "server stopped");
+    Ok(())
+}
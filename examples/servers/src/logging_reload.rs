@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServerHandlerService,
+    handler::server::logging::TracingLevelReload,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ListToolsResult, LoggingLevel,
+        PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+    },
+    serve_server,
+    service::RequestContext,
+};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{Registry, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt};
+
+/// A server whose tracing filter follows the level set by the client with `logging/setLevel`
+#[derive(Clone)]
+struct LoggingServer {
+    reload: TracingLevelReload<LevelFilter, Registry>,
+}
+
+impl ServerHandler for LoggingServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some(
+                "Set the level, then call emit to see which events are logged".into(),
+            ),
+            capabilities: ServerCapabilities::builder()
+                .enable_logging()
+                .enable_tools()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn on_set_level(&self, level: LoggingLevel) {
+        match self.reload.reload(level) {
+            Ok(()) => tracing::warn!(?level, "tracing level changed"),
+            Err(error) => tracing::error!(%error, "fail to reload the tracing filter"),
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: vec![Tool::new(
+                "emit",
+                "Emit an event at each tracing level",
+                Arc::new(serde_json::Map::new()),
+            )],
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if request.name != "emit" {
+            return Err(McpError::invalid_params(
+                format!("no tool {}", request.name),
+                None,
+            ));
+        }
+        tracing::debug!("a debug event");
+        tracing::info!("an info event");
+        tracing::warn!("a warning event");
+        tracing::error!("an error event");
+        Ok(CallToolResult::success(vec![Content::text(
+            "emitted, see the server logs",
+        )]))
+    }
+}
+
+/// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example logging_reload
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr).with_ansi(false))
+        .init();
+
+    let service = serve_server(
+        ServerHandlerService::new(LoggingServer {
+            reload: TracingLevelReload::new(handle),
+        }),
+        (tokio::io::stdin(), tokio::io::stdout()),
+    )
+    .await
+    .inspect_err(|e| {
+        tracing::error!("serving error: {:?}", e);
+    })?;
+
+    let quit_reason = service.waiting().await;
+    tracing::info!(?quit_reason, "server stopped");
+    Ok(())
+}
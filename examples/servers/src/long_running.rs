@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use rmcp::{
+    ClientHandlerService, Error as McpError, ServerHandler, ServerHandlerService,
+    handler::server::tool::ToolContext,
+    model::{CallToolRequest, CallToolRequestParam, ClientRequest, ServerCapabilities, ServerInfo},
+    serve_client, serve_server,
+    service::PeerRequestOptions,
+    tool,
+};
+use tracing_subscriber::{self, EnvFilter};
+
+/// A server with a long running tool, which stops as soon as the client cancels the call
+#[derive(Debug, Clone, Default)]
+struct Countdown;
+
+#[tool(tool_box)]
+impl Countdown {
+    #[tool(description = "Count down to zero, one second at a time")]
+    async fn countdown(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The number of seconds to count down from")]
+        seconds: u32,
+        context: ToolContext,
+    ) -> Result<String, McpError> {
+        // the id of the call correlates the logs of concurrent countdowns
+        let request_id = context.request_id().to_string();
+        for remaining in (1..=seconds).rev() {
+            tracing::info!(request_id, remaining, "counting down");
+            if let Err(cancelled) = context.sleep(Duration::from_secs(1)).await {
+                tracing::info!(request_id, remaining, "countdown cancelled");
+                return Err(cancelled.into());
+            }
+        }
+        Ok("liftoff".into())
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Countdown {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("Call countdown, then cancel it".into()),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Serve the countdown in memory, call it with a client and cancel the call after a few seconds
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .with_writer(std::io::stderr)
+        .init();
+
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(serve_server(
+        ServerHandlerService::new(Countdown),
+        server_io,
+    ));
+    let client = serve_client(ClientHandlerService::simple(), client_io).await?;
+    let server = server.await??;
+
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "countdown".into(),
+            arguments: serde_json::json!({ "seconds": 10 }).as_object().cloned(),
+            meta: None,
+        },
+    });
+    let call = client
+        .peer()
+        .send_cancellable_request(request, PeerRequestOptions::no_options())
+        .await?;
+    tokio::time::sleep(Duration::from_millis(3500)).await;
+    call.cancel(Some("the user got bored".into())).await?;
+
+    // let the server log the cancellation before shutting down
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.cancel().await?;
+    server.waiting().await;
+    Ok(())
+}
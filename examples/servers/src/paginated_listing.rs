@@ -0,0 +1,88 @@
+use anyhow::Result;
+use rmcp::{
+    ClientHandlerService, Error as McpError, ServerHandler, ServerHandlerService,
+    handler::server::tool::ToolContext,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, Cursor, ServerCapabilities, ServerInfo,
+    },
+    serve_client, serve_server, tool,
+};
+use tracing_subscriber::{self, EnvFilter};
+
+const PAGE_SIZE: usize = 500;
+
+/// A server with a tool listing thousands of files, a page at a time for the clients asking for pages.
+///
+/// The paging is an rmcp extension behind the `experimental` feature, the other clients get the whole listing.
+#[derive(Debug, Clone)]
+struct Listing {
+    files: usize,
+}
+
+#[tool(tool_box)]
+impl Listing {
+    #[tool(description = "List the files of the archive")]
+    fn list_files(&self, context: ToolContext) -> Result<CallToolResult, McpError> {
+        let files = (0..self.files)
+            .map(|i| Content::text(format!("archive/file-{i:05}.txt")))
+            .collect();
+        match context.cursor()? {
+            Some(cursor) => CallToolResult::paginate(files, &cursor, PAGE_SIZE),
+            None => Ok(CallToolResult::success(files)),
+        }
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Listing {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("Call list_files, with a cursor in its _meta to page it".into()),
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Serve the listing in memory, and read it page by page, then all at once with `call_tool_all_pages`
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .with_writer(std::io::stderr)
+        .init();
+
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let server = tokio::spawn(serve_server(
+        ServerHandlerService::new(Listing { files: 2_345 }),
+        server_io,
+    ));
+    let client = serve_client(ClientHandlerService::simple(), client_io).await?;
+    let server = server.await??;
+
+    let mut cursor = Cursor::default();
+    loop {
+        let page = client
+            .call_tool(CallToolRequestParam::new("list_files").with_cursor(cursor))
+            .await?;
+        let first = page.content.first().and_then(|content| content.as_text());
+        tracing::info!(
+            files = page.content.len(),
+            first = first.map(|text| text.text.as_str()),
+            "page"
+        );
+        match page.next_cursor()? {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+
+    let all = client
+        .call_tool_all_pages(CallToolRequestParam::new("list_files"))
+        .await?;
+    tracing::info!(files = all.content.len(), "all the pages");
+
+    client.cancel().await?;
+    server.waiting().await;
+    Ok(())
+}
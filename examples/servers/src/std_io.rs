@@ -1,10 +1,11 @@
 use anyhow::Result;
-use common::counter::Counter;
-use rmcp::{ServerHandlerService, serve_server};
+use rmcp::{ServerHandlerService, serve_server, service::QuitReason};
 
 use tracing_subscriber::{self, EnvFilter};
 mod common;
-/// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example std_io
+/// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example std_io [server]
+///
+/// The server is one of [`common::server`], the counter by default.
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize the tracing subscriber with file and stdout logging
@@ -16,9 +17,9 @@ async fn main() -> Result<()> {
 
     tracing::info!("Starting MCP server");
 
-    // Create an instance of our counter router
+    let server = common::server(std::env::args().nth(1).as_deref())?;
     let service = serve_server(
-        ServerHandlerService::new(Counter::new()),
+        ServerHandlerService::new(server),
         (tokio::io::stdin(), tokio::io::stdout()),
     )
     .await
@@ -26,6 +27,10 @@ async fn main() -> Result<()> {
         tracing::error!("serving error: {:?}", e);
     })?;
 
-    service.waiting().await?;
-    Ok(())
+    // exit with an error when the session didn't end cleanly, so that a supervisor restarts the server
+    match service.waiting().await {
+        QuitReason::Closed | QuitReason::Cancelled => Ok(()),
+        QuitReason::PeerDisconnected => Err(anyhow::anyhow!("the client is disconnected")),
+        QuitReason::TransportError(error) => Err(anyhow::anyhow!("transport error: {error}")),
+    }
 }
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use rmcp::{
+    ClientHandlerService, ServerHandler, ServerHandlerService,
+    model::{ServerCapabilities, ServerInfo, Tool},
+    serve_client, serve_server, tool,
+};
+use serde_json::json;
+
+/// A server adding examples to the generated input schema of its tool
+#[derive(Debug, Clone, Default)]
+struct Weather;
+
+#[tool(tool_box)]
+impl Weather {
+    #[tool(description = "Get the weather of a city")]
+    fn get_weather(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the city")]
+        city: String,
+    ) -> String {
+        format!("It's sunny in {city}")
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Weather {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    fn post_process_tool(&self, tool: &mut Tool) {
+        if tool.name == "get_weather" {
+            Arc::make_mut(&mut tool.input_schema).insert(
+                "examples".into(),
+                json!([{ "city": "Paris" }, { "city": "Tokyo" }]),
+            );
+        }
+    }
+}
+
+/// Serve the weather in memory and print the tools a client lists
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(serve_server(ServerHandlerService::new(Weather), server_io));
+    let client = serve_client(ClientHandlerService::simple(), client_io).await?;
+    let server = server.await??;
+
+    let tools = client.peer().list_tools(Default::default()).await?.tools;
+    println!("{}", serde_json::to_string_pretty(&tools)?);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
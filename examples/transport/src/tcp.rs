@@ -1,5 +1,7 @@
 use common::calculator::Calculator;
-use rmcp::{ClientHandlerService, ServerHandlerService, serve_client, serve_server};
+use rmcp::{
+    ClientHandlerService, ServerHandlerService, serve_client, serve_server, service::QuitReason,
+};
 
 mod common;
 #[tokio::main]
@@ -15,7 +17,10 @@ async fn server() -> anyhow::Result<()> {
         tokio::spawn(async move {
             let service = ServerHandlerService::new(Calculator);
             let server = serve_server(service, stream).await?;
-            server.waiting().await?;
+            match server.waiting().await {
+                QuitReason::Closed => {}
+                reason => println!("session ended: {reason:?}"),
+            }
             anyhow::Ok(())
         });
     }
@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rmcp::model::ServerJsonRpcMessage;
+use serde_json::Value;
+
+fn message_id(json: &[u8]) -> Option<Value> {
+    serde_json::from_slice::<Value>(json)
+        .ok()?
+        .get("id")
+        .cloned()
+}
+
+// a parsed message is serialized to a message which parses to itself, and keeps its id
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = serde_json::from_slice::<ServerJsonRpcMessage>(data) else {
+        return;
+    };
+    let json = serde_json::to_vec(&message).expect("serialize a parsed message");
+    let parsed: ServerJsonRpcMessage =
+        serde_json::from_slice(&json).expect("parse a serialized message");
+    assert_eq!(parsed, message);
+    assert_eq!(serde_json::to_vec(&parsed).expect("serialize"), json);
+    // a `null` id is not a valid one, such a message is read as a notification
+    let id = message_id(data).filter(|id| !id.is_null());
+    assert_eq!(message_id(&json), id);
+});